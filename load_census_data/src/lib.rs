@@ -24,11 +24,14 @@ extern crate enum_map;
 
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
+use std::fs::File;
+use std::io::BufReader;
 use std::path::Path;
 use std::string::String;
 
 use log::{debug, info, trace, warn};
 use rand::{Rng, RngCore};
+use rayon::prelude::*;
 
 use crate::nomis_download::{build_table_request_string, DataFetcher};
 use crate::parsing_error::DataLoadingError;
@@ -46,6 +49,13 @@ pub mod parse_table;
 pub mod parsing_error;
 pub mod tables;
 
+/// The default capacity, in bytes, of the buffered reader `read_generic_table_from_disk` wraps
+/// around each table file - large enough that bulk tables aren't read one OS page at a time
+pub const DEFAULT_CSV_READER_BUFFER_SIZE: usize = 8 * 1024 * 1024;
+
+/// The number of rows handed to each rayon worker when deserializing a table in parallel
+const CSV_PARSE_CHUNK_SIZE: usize = 10_000;
+
 /// This is a container for all the Records relating to one Output Area for All Census Tables
 pub struct CensusDataEntry<'a> {
     pub output_area_code: String,
@@ -95,6 +105,9 @@ pub struct CensusData {
     pub workplace_density: EmploymentDensities,
     /// Residential Area -> Workplace Area -> Count
     pub residents_workplace: HashMap<String, WorkplaceResidentialRecord>,
+    /// Output area codes whose occupation table record was imputed, rather than directly observed,
+    /// by `impute_missing_occupation_data`
+    pub imputed_areas: HashSet<String>,
 }
 
 /// Initialization
@@ -138,7 +151,7 @@ impl CensusData {
     /// Attempts to load the given table from a file on disk
     ///
     /// If the file doesn't exist and data_fetcher exists, will attempt to download the table from the NOMIS api
-    async fn fetch_generic_table<U: 'static + PreProcessingTable, T: TableEntry<U>>(
+    async fn fetch_generic_table<U: 'static + PreProcessingTable + Send, T: TableEntry<U>>(
         census_directory: &str,
         region_code: &str,
         table_name: CensusTableNames,
@@ -163,7 +176,7 @@ impl CensusData {
         }
         CensusData::read_generic_table_from_disk::<T, U>(&filename, false)
     }
-    pub fn read_table_and_generate_filename<U: 'static + PreProcessingTable, T: TableEntry<U>>(
+    pub fn read_table_and_generate_filename<U: 'static + PreProcessingTable + Send, T: TableEntry<U>>(
         census_directory: &str,
         region_code: &str,
         table_name: CensusTableNames,
@@ -189,26 +202,81 @@ impl CensusData {
     }
 
     /// This loads a census data table from disk
-    pub fn read_generic_table_from_disk<T: TableEntry<U>, U: 'static + PreProcessingTable>(
+    pub fn read_generic_table_from_disk<T: TableEntry<U>, U: 'static + PreProcessingTable + Send>(
+        table_name: &str,
+        is_bulk: bool,
+    ) -> Result<HashMap<String, T>, DataLoadingError> {
+        CensusData::read_generic_table_from_disk_with_buffer_size::<T, U>(
+            table_name,
+            is_bulk,
+            DEFAULT_CSV_READER_BUFFER_SIZE,
+        )
+    }
+
+    /// This loads a census data table from disk, as `read_generic_table_from_disk`, but with a
+    /// configurable buffer size for the underlying file reader
+    ///
+    /// Once the header row has been read, the remaining rows are deserialized in chunks across a
+    /// rayon thread pool, rather than one row at a time on the calling thread
+    ///
+    /// This applies to non-bulk tables only: when `is_bulk` is set, the file is opened but no rows
+    /// are parsed, and an empty map is returned
+    pub fn read_generic_table_from_disk_with_buffer_size<
+        T: TableEntry<U>,
+        U: 'static + PreProcessingTable + Send,
+    >(
         table_name: &str,
         is_bulk: bool,
+        buffer_size: usize,
     ) -> Result<HashMap<String, T>, DataLoadingError> {
         info!("Reading census table: '{}' from disk", table_name);
-        let mut reader =
-            csv::Reader::from_path(table_name).map_err(|e| DataLoadingError::IOError {
-                source: Box::new(e),
-                context: format!("Failed to create csv reader for file: {}", table_name),
-            })?;
+        let file = File::open(table_name).map_err(|e| DataLoadingError::IOError {
+            source: Box::new(e),
+            context: format!("Failed to open csv file: {}", table_name),
+        })?;
+        let mut reader = csv::Reader::from_reader(BufReader::with_capacity(buffer_size, file));
         if is_bulk {
             Ok(HashMap::new())
         } else {
-            let data = reader
-                .deserialize()
-                .collect::<Result<Vec<U>, csv::Error>>()
+            let headers = reader
+                .headers()
                 .map_err(|e| DataLoadingError::IOError {
                     source: Box::new(e),
-                    context: format!("Failed to parse csv file: {}", table_name),
+                    context: format!("Failed to read csv headers for file: {}", table_name),
+                })?
+                .clone();
+            let rows = reader
+                .records()
+                .collect::<Result<Vec<csv::StringRecord>, csv::Error>>()
+                .map_err(|e| DataLoadingError::IOError {
+                    source: Box::new(e),
+                    context: format!("Failed to read csv file: {}", table_name),
                 })?;
+            let data = rows
+                .par_chunks(CSV_PARSE_CHUNK_SIZE)
+                .enumerate()
+                .map(|(chunk_index, chunk)| {
+                    chunk
+                        .iter()
+                        .enumerate()
+                        .map(|(row_index, record)| {
+                            record.deserialize::<U>(Some(&headers)).map_err(|e| {
+                                DataLoadingError::IOError {
+                                    source: Box::new(e),
+                                    context: format!(
+                                        "Failed to parse row {} of csv file: {}",
+                                        chunk_index * CSV_PARSE_CHUNK_SIZE + row_index + 2,
+                                        table_name
+                                    ),
+                                }
+                            })
+                        })
+                        .collect::<Result<Vec<U>, DataLoadingError>>()
+                })
+                .collect::<Result<Vec<Vec<U>>, DataLoadingError>>()?
+                .into_iter()
+                .flatten()
+                .collect::<Vec<U>>();
             debug!("Loaded table into pre processing");
             let data = T::generate(data)?;
             Ok(data)
@@ -358,6 +426,7 @@ impl CensusData {
             occupation_counts,
             workplace_density: EmploymentDensities {},
             residents_workplace,
+            imputed_areas: HashSet::new(),
         };
         census_data.filter_incomplete_output_areas();
         Ok(census_data)
@@ -390,6 +459,47 @@ impl CensusData {
         Ok(())
     }
 
+    /// Fills in a plausible `OccupationCountRecord` for any Output Area that has every other table
+    /// but is missing its occupation data, by copying the record of the Output Area with the
+    /// lexicographically smallest code that does have one, instead of `filter_incomplete_output_areas`
+    /// dropping the area entirely. The donor is the same for every missing area; it is neither the
+    /// geographically nearest area nor a regional average
+    ///
+    /// Call this before `filter_incomplete_output_areas` to retain these areas, at the cost of their
+    /// occupation distribution being an approximation rather than an observed value. Imputed areas
+    /// are recorded in `imputed_areas` so downstream consumers can audit or down-weight them
+    pub fn impute_missing_occupation_data(&mut self) {
+        let donor_code = match self.occupation_counts.keys().min() {
+            Some(code) => code.clone(),
+            None => return,
+        };
+        let donor = self
+            .occupation_counts
+            .get(&donor_code)
+            .expect("Donor Output Area must have an occupation record")
+            .clone();
+
+        let missing_areas: Vec<String> = self
+            .population_counts
+            .keys()
+            .filter(|area| {
+                self.age_counts.contains_key(*area)
+                    && self.residents_workplace.contains_key(*area)
+                    && !self.occupation_counts.contains_key(*area)
+            })
+            .cloned()
+            .collect();
+
+        for area in missing_areas {
+            debug!(
+                "Imputing occupation data for Output Area {} from {}",
+                area, donor_code
+            );
+            self.occupation_counts.insert(area.clone(), donor.clone());
+            self.imputed_areas.insert(area);
+        }
+    }
+
     pub fn filter_incomplete_output_areas(&mut self) {
         info!("Removing incomplete Output Areas");
         // Filter out areas
@@ -444,8 +554,46 @@ impl CensusData {
         self.valid_areas = valid_areas;
         debug!("There are {} complete output areas", self.valid_areas.len());
     }
+
+    /// Checks that enough of `valid_areas` are also present in `shapefile_codes` to rule out the
+    /// census tables and the output-area shapefile coming from different census vintages, which
+    /// use incompatible output area codes and would otherwise leave `SimulatorBuilder` building
+    /// from a near-empty, silently filtered, population
+    ///
+    /// Returns a `LowOverlap` error naming the observed overlap percentage if it falls below
+    /// `MINIMUM_SHAPEFILE_OVERLAP_PERCENTAGE`
+    pub fn check_shapefile_vintage(
+        &self,
+        shapefile_codes: &HashSet<String>,
+    ) -> Result<(), DataLoadingError> {
+        if self.valid_areas.is_empty() {
+            return Ok(());
+        }
+        let overlapping = self
+            .valid_areas
+            .iter()
+            .filter(|code| shapefile_codes.contains(*code))
+            .count();
+        let overlap_percentage = overlapping as f64 / self.valid_areas.len() as f64;
+        if overlap_percentage < MINIMUM_SHAPEFILE_OVERLAP_PERCENTAGE {
+            return Err(DataLoadingError::ValueParsingError {
+                source: crate::parsing_error::ParseErrorType::LowOverlap {
+                    context: "Output area codes barely overlap between the census tables and the \
+                    shapefile - this usually means they are from different census vintages"
+                        .to_string(),
+                    overlap_percentage: overlap_percentage * 100.0,
+                },
+            });
+        }
+        Ok(())
+    }
 }
 
+/// Below this fraction of `CensusData::valid_areas` also present in the output-area shapefile,
+/// `check_shapefile_vintage` treats the mismatch as a vintage incompatibility rather than a
+/// handful of genuinely missing areas
+const MINIMUM_SHAPEFILE_OVERLAP_PERCENTAGE: f64 = 0.5;
+
 impl CensusData {
     /// Attempts to retrieve all records relating to the given output area code
     ///
@@ -465,9 +613,19 @@ impl CensusData {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::{HashMap, HashSet};
+
     use rand::thread_rng;
 
     use crate::CensusData;
+    use crate::parsing_error::{DataLoadingError, ParseErrorType};
+    use crate::tables::age_structure::AgePopulationRecord;
+    use crate::tables::occupation_count::{OccupationCountRecord, RawOccupationType};
+    use crate::tables::population_and_density_per_output_area::{
+        PopulationRecord, PreProcessingPopulationDensityRecord,
+    };
+    use crate::tables::resides_vs_workplace::WorkplaceResidentialRecord;
+    use crate::tables::TableEntry;
 
     #[test]
     fn test_workplace_area_distrubution() {
@@ -480,4 +638,148 @@ mod tests {
             println!("{}", area_data.get_random_workplace_area(&mut rng).unwrap())
         }
     }
+
+    /// The chunked, rayon-parallelised parse in `read_generic_table_from_disk_with_buffer_size`
+    /// should produce the same per output area records as deserializing the same CSV sequentially
+    #[test]
+    fn parallel_table_parsing_matches_sequential_parsing_of_the_same_csv() {
+        let filename = std::env::temp_dir()
+            .join(format!("parallel_population_table_test_{}.csv", std::process::id()))
+            .to_str()
+            .expect("Non-UTF8 temp path")
+            .to_string();
+        let mut csv_contents = String::from(
+            "GEOGRAPHY_NAME,GEOGRAPHY_TYPE,RURAL_URBAN_NAME,CELL_NAME,MEASURES_NAME,OBS_VALUE,OBS_STATUS,RECORD_OFFSET,RECORD_COUNT\n",
+        );
+        for area_index in 0..5 {
+            let area = format!("E0000{}", area_index);
+            csv_contents.push_str(&format!(
+                "{area},OA,Total,Area (Hectares),Value,12.5,,0,9\n"
+            ));
+            csv_contents.push_str(&format!(
+                "{area},OA,Total,Density (number of persons per hectare),Value,40.0,,0,9\n"
+            ));
+            csv_contents.push_str(&format!(
+                "{area},OA,Total,All usual residents,Value,500,,0,9\n"
+            ));
+        }
+        std::fs::write(&filename, &csv_contents).expect("Failed to write fixture CSV");
+
+        let parallel = CensusData::read_generic_table_from_disk_with_buffer_size::<
+            PopulationRecord,
+            PreProcessingPopulationDensityRecord,
+        >(&filename, false, 64)
+            .expect("Failed to parse fixture CSV");
+
+        let mut reader = csv::Reader::from_path(&filename).expect("Failed to open fixture CSV");
+        let records = reader
+            .deserialize()
+            .collect::<Result<Vec<PreProcessingPopulationDensityRecord>, csv::Error>>()
+            .expect("Failed to sequentially parse fixture CSV");
+        let sequential =
+            PopulationRecord::generate(records).expect("Failed to build sequential records");
+
+        std::fs::remove_file(&filename).ok();
+
+        assert_eq!(parallel.len(), sequential.len());
+        for (area, expected) in &sequential {
+            let actual = parallel.get(area).expect("Area missing from parallel parse");
+            assert_eq!(actual.population_size, expected.population_size);
+            assert_eq!(actual.area_size, expected.area_size);
+            assert_eq!(actual.density, expected.density);
+        }
+    }
+
+    fn census_data_with_valid_areas(valid_areas: HashSet<String>) -> CensusData {
+        CensusData {
+            valid_areas,
+            age_counts: Default::default(),
+            population_counts: Default::default(),
+            occupation_counts: Default::default(),
+            workplace_density: crate::tables::employment_densities::EmploymentDensities {},
+            residents_workplace: Default::default(),
+            imputed_areas: Default::default(),
+        }
+    }
+
+    /// A shapefile using a different census vintage's output area codes should be rejected with a
+    /// `LowOverlap` error, rather than silently proceeding with an almost-empty simulation
+    #[test]
+    fn mismatched_vintage_output_area_codes_raise_a_low_overlap_error() {
+        let census_data = census_data_with_valid_areas(
+            (0..100).map(|index| format!("E0000{}", index)).collect(),
+        );
+        let shapefile_codes: HashSet<String> =
+            (0..100).map(|index| format!("E9999{}", index)).collect();
+
+        let error = census_data
+            .check_shapefile_vintage(&shapefile_codes)
+            .expect_err("Mismatched output area codes should be rejected");
+        assert!(matches!(
+            error,
+            DataLoadingError::ValueParsingError {
+                source: ParseErrorType::LowOverlap { .. }
+            }
+        ));
+    }
+
+    #[test]
+    fn matching_output_area_codes_pass_the_vintage_check() {
+        let valid_areas: HashSet<String> =
+            (0..100).map(|index| format!("E0000{}", index)).collect();
+        let census_data = census_data_with_valid_areas(valid_areas.clone());
+
+        assert!(census_data.check_shapefile_vintage(&valid_areas).is_ok());
+    }
+
+    fn population_record() -> PopulationRecord {
+        PopulationRecord {
+            area_size: 1.0,
+            density: 1.0,
+            population_counts: Default::default(),
+            population_size: 100,
+        }
+    }
+
+    fn workplace_record() -> WorkplaceResidentialRecord {
+        WorkplaceResidentialRecord {
+            workplace_count: Default::default(),
+            total_workplace_count: 0,
+        }
+    }
+
+    /// An Output Area missing only its occupation table should be imputed from another Output
+    /// Area's occupation record, flagged in `imputed_areas`, and retained in `valid_areas` instead
+    /// of being dropped by `filter_incomplete_output_areas`
+    #[test]
+    fn imputing_missing_occupation_data_retains_the_area_and_flags_it() {
+        let mut census_data = CensusData {
+            valid_areas: Default::default(),
+            age_counts: HashMap::from([
+                ("complete".to_string(), AgePopulationRecord::new([10; 101], 1010).unwrap()),
+                ("missing_occupation".to_string(), AgePopulationRecord::new([10; 101], 1010).unwrap()),
+            ]),
+            population_counts: HashMap::from([
+                ("complete".to_string(), population_record()),
+                ("missing_occupation".to_string(), population_record()),
+            ]),
+            occupation_counts: HashMap::from([(
+                "complete".to_string(),
+                OccupationCountRecord::new(vec![RawOccupationType::Managers], vec![10]).unwrap(),
+            )]),
+            workplace_density: crate::tables::employment_densities::EmploymentDensities {},
+            residents_workplace: HashMap::from([
+                ("complete".to_string(), workplace_record()),
+                ("missing_occupation".to_string(), workplace_record()),
+            ]),
+            imputed_areas: Default::default(),
+        };
+
+        census_data.impute_missing_occupation_data();
+        assert!(census_data.occupation_counts.contains_key("missing_occupation"));
+        assert!(census_data.imputed_areas.contains("missing_occupation"));
+
+        census_data.filter_incomplete_output_areas();
+        assert!(census_data.valid_areas.contains("missing_occupation"));
+    }
 }
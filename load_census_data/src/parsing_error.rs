@@ -65,6 +65,12 @@ pub enum ParseErrorType {
         max_size: String,
         actual_size: String,
     },
+    /// Too few of the keys in one collection are also present in another, suggesting the two data
+    /// sources use incompatible identifiers (e.g. output areas from different census vintages)
+    LowOverlap {
+        context: String,
+        overlap_percentage: f64,
+    },
 }
 
 impl Display for ParseErrorType {
@@ -117,6 +123,16 @@ impl Display for ParseErrorType {
                     context, max_size, actual_size
                 )
             }
+            ParseErrorType::LowOverlap {
+                context,
+                overlap_percentage,
+            } => {
+                write!(
+                    f,
+                    "Low Overlap Error: {},\tObserved Overlap: {:.1}%",
+                    context, overlap_percentage
+                )
+            }
         }
     }
 }
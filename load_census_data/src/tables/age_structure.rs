@@ -59,6 +59,25 @@ impl AgePopulationRecord {
     pub fn get_random_age(&mut self, rng: &mut dyn RngCore) -> u16 {
         self.age_weighting.sample(rng) as u16
     }
+
+    /// Builds a record directly from already-tallied age counts, rather than from raw CSV rows, for
+    /// callers (e.g. tests, imputation) that have a population breakdown but no CSV rows to parse
+    pub fn new(
+        population_counts: [u16; 101],
+        population_size: u16,
+    ) -> Result<AgePopulationRecord, DataLoadingError> {
+        Ok(AgePopulationRecord {
+            age_weighting: WeightedIndex::new(&population_counts).map_err(|e| {
+                DataLoadingError::ValueParsingError {
+                    source: ParseErrorType::MathError {
+                        context: format!("Failed to build age weighting: {}", e),
+                    },
+                }
+            })?,
+            population_counts,
+            population_size,
+        })
+    }
 }
 
 impl TableEntry<PreProcessingAgePopulationRecord> for AgePopulationRecord {}
@@ -121,6 +121,29 @@ impl OccupationCountRecord {
     pub fn get_random_occupation(&mut self, rng: &mut dyn RngCore) -> RawOccupationType {
         self.occupations[self.occupation_weighting.sample(rng)]
     }
+
+    /// Builds a record directly from already-tallied occupation counts, rather than from raw CSV
+    /// rows, for callers (e.g. tests, imputation) that have a population breakdown but no
+    /// `PreProcessingOccupationCountRecord`s to parse
+    pub fn new(
+        occupations: Vec<RawOccupationType>,
+        occupation_population: Vec<u32>,
+    ) -> Result<OccupationCountRecord, DataLoadingError> {
+        let total_range = occupation_population.iter().sum();
+        let occupation_weighting = WeightedIndex::new(&occupation_population).map_err(|e| {
+            DataLoadingError::ValueParsingError {
+                source: ParseErrorType::MathError {
+                    context: format!("Failed to build occupation weighting: {}", e),
+                },
+            }
+        })?;
+        Ok(OccupationCountRecord {
+            occupations,
+            occupation_population,
+            occupation_weighting,
+            total_range,
+        })
+    }
 }
 
 impl TableEntry<PreProcessingOccupationCountRecord> for OccupationCountRecord {}
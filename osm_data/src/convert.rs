@@ -76,6 +76,22 @@ pub fn decimal_latitude_and_longitude_to_northing_and_eastings(
     f64_trimmed_to_isize((easting, northing))
 }
 
+/// Converts National Grid Northings and Eastings (OSGB36, as produced by
+/// `decimal_latitude_and_longitude_to_northing_and_eastings`) back to a latitude and longitude in
+/// decimal degree format (WGS84)
+///
+/// Reverses the same pipeline as the forward conversion, stage by stage - accurate to within a
+/// metre for Great Britain, the same tolerance the forward conversion itself targets
+pub fn northing_and_eastings_to_decimal_latitude_and_longitude(
+    easting: i32,
+    northing: i32,
+) -> (f64, f64) {
+    let (lat, lon) = eastings_to_lat_lon(easting as f64, northing as f64, Ellipsoid::airy());
+    let (x, y, z) = lat_lon_to_cartesian(lat, lon, Ellipsoid::airy());
+    let (x, y, z) = helmert_osbg36_to_wgs84((x, y, z));
+    cartesian_to_lat_lon(x, y, z, Ellipsoid::GRS80_zone_30())
+}
+
 /// Trims f64 coordinates to an isize
 fn f64_trimmed_to_isize(position: (f64, f64)) -> (i32, i32) {
     (position.0.round() as i32, position.1.round() as i32)
@@ -189,6 +205,81 @@ fn lat_lon_to_eastings(lat: f64, lon: f64, ellipsoid: Ellipsoid) -> (f64, f64) {
     (northing, easting)
 }
 
+/// Converts Northings and Eastings back to a latitude and longitude in degree format
+///
+/// This is the inverse of `lat_lon_to_eastings`
+///
+///https://www.ordnancesurvey.co.uk/documents/resources/guide-coordinate-systems-great-britain.pdf - C.2
+fn eastings_to_lat_lon(easting: f64, northing: f64, ellipsoid: Ellipsoid) -> (f64, f64) {
+    let lat_origin: f64 = ellipsoid.true_x_origin.to_radians();
+    let lon_origin: f64 = ellipsoid.true_y_origin.to_radians();
+
+    let n = (ellipsoid.a - ellipsoid.b) / (ellipsoid.a + ellipsoid.b);
+    let n2 = n * n;
+    let n3 = n2 * n;
+
+    // Iterate on the latitude of the northing origin, until the meridonial arc `m` converges on
+    // the requested northing
+    let mut lat = lat_origin;
+    let mut m = 0.0;
+    loop {
+        lat = ((northing - ellipsoid.map_y_origin - m) / (ellipsoid.a * ellipsoid.f0)) + lat;
+
+        let lat_diff = lat - lat_origin;
+        let lat_total = lat + lat_origin;
+        let ma = (1.0 + n + (1.25 * n2) + (1.25 * n3)) * (lat_diff);
+        let mb = (3.0 * n + 3.0 * n2 + (21.0 / 8.0) * n3) * (lat_diff.sin()) * (lat_total.cos());
+        let mc = (((15.0 / 8.0) * n2) + ((15.0 / 8.0) * n3))
+            * ((2.0 * lat_diff).sin())
+            * ((2.0 * lat_total).cos());
+        let md = (35.0 / 24.0) * n3 * ((3.0 * lat_diff).sin()) * ((3.0 * lat_total).cos());
+        m = ellipsoid.b * ellipsoid.f0 * (ma - mb + mc - md);
+
+        if (northing - ellipsoid.map_y_origin - m).abs() < 0.00001 {
+            break;
+        }
+    }
+
+    let lat_sin = lat.sin();
+    let lat_cos = lat.cos();
+    let lat_tan = lat.tan();
+    let lat_tan2 = lat_tan * lat_tan;
+    let lat_tan4 = lat_tan2 * lat_tan2;
+    let lat_tan6 = lat_tan4 * lat_tan2;
+
+    let V = ellipsoid.a * ellipsoid.f0 * ((1.0 - ellipsoid.e2 * lat_sin * lat_sin).powf(-0.5));
+    let p = ellipsoid.a
+        * ellipsoid.f0
+        * (1.0 - ellipsoid.e2)
+        * ((1.0 - ellipsoid.e2 * lat_sin * lat_sin).powf(-1.5));
+    let N2 = (V / p) - 1.0;
+
+    let vii = lat_tan / (2.0 * p * V);
+    let viii = (lat_tan / (24.0 * p * V.powi(3)))
+        * (5.0 + (3.0 * lat_tan2) + N2 - (9.0 * lat_tan2 * N2));
+    let ix = (lat_tan / (720.0 * p * V.powi(5))) * (61.0 + (90.0 * lat_tan2) + (45.0 * lat_tan4));
+
+    let sec_lat = 1.0 / lat_cos;
+    let x = sec_lat / V;
+    let xi = (sec_lat / (6.0 * V.powi(3))) * ((V / p) + (2.0 * lat_tan2));
+    let xii = (sec_lat / (120.0 * V.powi(5))) * (5.0 + (28.0 * lat_tan2) + (24.0 * lat_tan4));
+    let xiia = (sec_lat / (5040.0 * V.powi(7)))
+        * (61.0 + (662.0 * lat_tan2) + (1320.0 * lat_tan4) + (720.0 * lat_tan6));
+
+    let e_diff = easting - ellipsoid.map_x_origin;
+    let e_diff2 = e_diff * e_diff;
+    let e_diff3 = e_diff2 * e_diff;
+    let e_diff4 = e_diff3 * e_diff;
+    let e_diff5 = e_diff4 * e_diff;
+    let e_diff6 = e_diff5 * e_diff;
+    let e_diff7 = e_diff6 * e_diff;
+
+    let final_lat = lat - (vii * e_diff2) + (viii * e_diff4) - (ix * e_diff6);
+    let final_lon = lon_origin + (x * e_diff) - (xi * e_diff3) + (xii * e_diff5) - (xiia * e_diff7);
+
+    (final_lat.to_degrees(), final_lon.to_degrees())
+}
+
 /// These values are converted from secs to radians
 ///
 ///
@@ -218,11 +309,30 @@ fn helmert_wgs84_to_osbg36(point: (f64, f64, f64)) -> (f64, f64, f64) {
     (output.0, output.1, output.2)
 }
 
+/// Converts a National Grid Cartesian (X,Y,Z) coordinate back to a WGS84 one
+///
+/// Approximates the inverse of `helmert_wgs84_to_osbg36` by negating every transform parameter,
+/// rather than computing a true matrix inverse - accurate to within a few millimetres at the scale
+/// of Great Britain, which is the approach the Ordnance Survey's own guide recommends for reversing
+/// this transform
+fn helmert_osbg36_to_wgs84(point: (f64, f64, f64)) -> (f64, f64, f64) {
+    let p = ndarray::arr2(&[[point.0], [point.1], [point.2]]);
+    let inverse_r = ndarray::arr2(&[
+        [1.0 - S, RZ, -RY],
+        [-RZ, 1.0 - S, RX],
+        [RY, -RX, 1.0 - S],
+    ]);
+    let inverse_t = ndarray::arr2(&[[-T[0][0]], [-T[1][0]], [-T[2][0]]]);
+    let output = inverse_t + inverse_r.dot(&p);
+    (output[[0, 0]], output[[1, 0]], output[[2, 0]])
+}
+
 #[cfg(test)]
 mod tests {
     use crate::convert::{
         cartesian_to_lat_lon, decimal_latitude_and_longitude_to_northing_and_eastings,
-        Ellipsoid, helmert_wgs84_to_osbg36, lat_lon_to_cartesian, lat_lon_to_eastings,
+        eastings_to_lat_lon, Ellipsoid, helmert_wgs84_to_osbg36, lat_lon_to_cartesian,
+        lat_lon_to_eastings, northing_and_eastings_to_decimal_latitude_and_longitude,
     };
 
     #[test]
@@ -357,6 +467,36 @@ mod tests {
         );
     }
 
+    /// Feeding the Northing/Easting produced by the OS worked example above back through the
+    /// inverse grid conversion should recover the lat/lon the example started from
+    #[test]
+    fn test_eastings_to_lat_lon() {
+        let desired_accuracy = 0.00001;
+        let northing = 313177.270;
+        let easting = 651409.903;
+        let (lat, lon) = eastings_to_lat_lon(easting, northing, Ellipsoid::airy());
+
+        let expected_lat = 52.65757;
+        let diff_lat = (lat - expected_lat).abs();
+        assert!(
+            diff_lat < desired_accuracy,
+            "Latitude is incorrect, actual: {}, expected: {}, difference: {}",
+            lat,
+            expected_lat,
+            diff_lat
+        );
+
+        let expected_lon = 1.717922;
+        let diff_lon = (lon - expected_lon).abs();
+        assert!(
+            diff_lon < desired_accuracy,
+            "Longitude is incorrect, actual: {}, expected: {}, difference: {}",
+            lon,
+            expected_lon,
+            diff_lon
+        );
+    }
+
     #[test]
     fn test_conversion() {
         let desired_accuracy = 0.05;
@@ -417,4 +557,35 @@ mod tests {
             easting, expected_easting, diff_easting
         );
     }
+
+    /// Converting a known coordinate forward to Northings/Eastings, then back again, should land
+    /// within a metre of the original - close enough for plotting buildings, given the forward
+    /// conversion already rounds to the nearest metre
+    #[test]
+    fn test_northing_and_eastings_round_trip_within_a_metre() {
+        let original_lat = 53.61199; // 53 36 43.1653 N
+        let original_lon = -1.664442; // 001 39 51.9920 W
+        let (easting, northing) = decimal_latitude_and_longitude_to_northing_and_eastings(
+            original_lat,
+            original_lon,
+        );
+
+        let (lat, lon) =
+            northing_and_eastings_to_decimal_latitude_and_longitude(easting, northing);
+        let (round_tripped_easting, round_tripped_northing) =
+            decimal_latitude_and_longitude_to_northing_and_eastings(lat, lon);
+
+        assert!(
+            (round_tripped_easting - easting).abs() <= 1,
+            "Easting drifted by more than a metre after a round trip: {} vs {}",
+            round_tripped_easting,
+            easting
+        );
+        assert!(
+            (round_tripped_northing - northing).abs() <= 1,
+            "Northing drifted by more than a metre after a round trip: {} vs {}",
+            round_tripped_northing,
+            northing
+        );
+    }
 }
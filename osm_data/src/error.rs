@@ -52,6 +52,13 @@ pub enum OSMError {
     ShapeFileError {
         source: shapefile::Error,
     },
+    /// The grid size used to build the OSM Voronoi diagrams doesn't match the grid size used for
+    /// the output-area polygon lookup - they must agree for coordinates to scale consistently
+    /// between the two
+    GridSizeMismatch {
+        osm_grid_size: i32,
+        polygon_grid_size: i32,
+    },
 }
 
 impl From<osmpbf::Error> for OSMError {
@@ -115,6 +122,12 @@ impl Display for OSMError {
                     context
                 )
             }
+            OSMError::GridSizeMismatch {
+                osm_grid_size,
+                polygon_grid_size,
+            } => {
+                write!(f, "\nAn error occurred loading OSM data\n:\tType: GridSizeMismatch\n\tOSM/Voronoi grid size: {}\n\tOutput area polygon grid size: {}\n\tThese must match, or lookups between them will be scaled inconsistently", osm_grid_size, polygon_grid_size)
+            }
         }
     }
 }
@@ -136,6 +149,7 @@ impl std::error::Error for OSMError {
             OSMError::MissingKey { .. } => None,
             OSMError::IsEmpty { .. } => None,
             OSMError::ShapeFileError { ref source } => Some(source),
+            OSMError::GridSizeMismatch { .. } => None,
         }
     }
 }
@@ -19,11 +19,11 @@
  */
 //! Used to load in building types and locations from an OSM file
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-use std::convert::TryFrom;
 use std::fs::read;
 
 use geo::area::Area;
 use geo::centroid::Centroid;
+use geo::contains::Contains;
 use geo_types::{CoordFloat, CoordNum, Point, Polygon};
 use log::{debug, error, info, warn};
 use osmpbf::{DenseNode, DenseTagIter, TagIter};
@@ -177,8 +177,20 @@ pub enum TagClassifiedBuilding {
     Unknown,
 }
 
-impl<'a> From<HashMap<&'a str, &'a str>> for TagClassifiedBuilding {
-    fn from(tags: HashMap<&'a str, &'a str>) -> Self {
+impl TagClassifiedBuilding {
+    /// Classifies a set of OSM tags the same way as `From<HashMap<&str, &str>>`, except that a
+    /// `building=` tag whose value isn't one of the ones explicitly handled is classified as
+    /// `unrecognised_building_tag_default` instead of unconditionally falling back to
+    /// `WorkPlace` - the best default for these varies by region, e.g. a dataset tagging rural
+    /// outbuildings with an obscure `building=` value would overcount workplaces if they were
+    /// always assumed to be one
+    ///
+    /// A `building=` tag is still required to reach this fallback at all - buildings with no
+    /// `building` tag remain `Unknown` regardless of this default
+    pub fn from_tags_with_unrecognised_default<'a>(
+        tags: HashMap<&'a str, &'a str>,
+        unrecognised_building_tag_default: TagClassifiedBuilding,
+    ) -> Self {
         if let Some(amenity) = tags.get("amenity") {
             match *amenity {
                 "school" => return TagClassifiedBuilding::School,
@@ -199,14 +211,22 @@ impl<'a> From<HashMap<&'a str, &'a str>> for TagClassifiedBuilding {
                 }
                 "school" => TagClassifiedBuilding::School,
                 "hospital" => TagClassifiedBuilding::Hospital,
-                // Unknown buildings can be workplaces?
-                _ => TagClassifiedBuilding::WorkPlace,
+                _ => unrecognised_building_tag_default,
             };
         }
         TagClassifiedBuilding::Unknown
     }
 }
 
+impl<'a> From<HashMap<&'a str, &'a str>> for TagClassifiedBuilding {
+    fn from(tags: HashMap<&'a str, &'a str>) -> Self {
+        TagClassifiedBuilding::from_tags_with_unrecognised_default(
+            tags,
+            TagClassifiedBuilding::WorkPlace,
+        )
+    }
+}
+
 impl<'a> From<TagIter<'a>> for TagClassifiedBuilding {
     fn from(tags: TagIter<'a>) -> Self {
         TagClassifiedBuilding::from(tags.collect::<HashMap<&'a str, &'a str>>())
@@ -219,10 +239,61 @@ impl<'a> From<DenseTagIter<'a>> for TagClassifiedBuilding {
     }
 }
 
+/// A hint, derived from the specific OSM `building=` tag value, of how densely a [`TagClassifiedBuilding::Household`]
+/// building is occupied, relative to a single detached house of the same footprint
+///
+/// `TagClassifiedBuilding` collapses `house`, `apartments`, `terrace`, etc, all down to `Household`, losing
+/// this distinction, so it's preserved separately on [`RawBuilding`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum HouseholdBuildingType {
+    Detached,
+    SemiDetached,
+    Terrace,
+    Apartments,
+    /// Farm, hut, static caravan, cabin, residential, or any other household tag without a more
+    /// specific density hint
+    Other,
+}
+
+impl HouseholdBuildingType {
+    /// A rough multiplier for how many households a building of this type can contain, relative to
+    /// a single detached house of the same footprint
+    pub fn household_density_multiplier(&self) -> u32 {
+        match self {
+            HouseholdBuildingType::Detached => 1,
+            HouseholdBuildingType::SemiDetached => 2,
+            HouseholdBuildingType::Terrace => 3,
+            HouseholdBuildingType::Apartments => 6,
+            HouseholdBuildingType::Other => 1,
+        }
+    }
+}
+
+impl From<&str> for HouseholdBuildingType {
+    fn from(building_tag: &str) -> Self {
+        match building_tag {
+            "detached" => HouseholdBuildingType::Detached,
+            "semidetached_house" => HouseholdBuildingType::SemiDetached,
+            "terrace" => HouseholdBuildingType::Terrace,
+            "apartments" => HouseholdBuildingType::Apartments,
+            _ => HouseholdBuildingType::Other,
+        }
+    }
+}
+
+fn household_building_type_from_tags<'a>(
+    tags: impl Iterator<Item=(&'a str, &'a str)>,
+) -> Option<HouseholdBuildingType> {
+    let tags: HashMap<&'a str, &'a str> = tags.collect();
+    tags.get("building").map(|value| HouseholdBuildingType::from(*value))
+}
+
 /// A wrapper for an Open Street Map Way
 struct RawOSMWay {
     _id: i64,
     classification: TagClassifiedBuilding,
+    /// The specific `building=` tag value, when `classification` is a `Household`
+    household_building_type: Option<HouseholdBuildingType>,
     /// The set of [`RawOSMNode`] that make up this `OSM Way`
     node_ids: Vec<i64>,
 }
@@ -231,6 +302,8 @@ struct RawOSMWay {
 struct RawOSMNode {
     id: i64,
     classification: TagClassifiedBuilding,
+    /// The specific `building=` tag value, when `classification` is a `Household`
+    household_building_type: Option<HouseholdBuildingType>,
     location: Point<i32>,
 }
 
@@ -240,6 +313,8 @@ struct RawOSMNode {
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct RawBuilding {
     classification: TagClassifiedBuilding,
+    /// The specific `building=` tag value, when `classification` is a `Household`
+    household_building_type: Option<HouseholdBuildingType>,
     /// The approximate center of this building
     center: Point<i32>,
     /// The ID in the global hashmap, containing the outline of this buildings
@@ -254,12 +329,31 @@ impl RawBuilding {
         classification: TagClassifiedBuilding,
         boundary: &Polygon<i32>,
         boundary_id: BuildingBoundaryID,
+    ) -> Option<RawBuilding> {
+        RawBuilding::new_with_household_type(classification, None, boundary, boundary_id)
+    }
+    /// Generates a new RawBuilding, additionally recording the specific OSM `building=` tag value,
+    /// for `Household`s where it's known
+    pub fn new_with_household_type(
+        classification: TagClassifiedBuilding,
+        household_building_type: Option<HouseholdBuildingType>,
+        boundary: &Polygon<i32>,
+        boundary_id: BuildingBoundaryID,
     ) -> Option<RawBuilding> {
         let float_boundary: geo_types::Polygon<f64> = convert_polygon_to_float(boundary);
         // Can't find center with integer points
-        let size = float_boundary.unsigned_area().round() as i32;
+        //
+        // Interior rings (e.g. courtyards, atria) aren't occupiable floor space, so their area is
+        // subtracted from the exterior footprint rather than counting it towards capacity
+        let interior_area: f64 = float_boundary
+            .interiors()
+            .iter()
+            .map(|ring| ring.unsigned_area())
+            .sum();
+        let size = (float_boundary.exterior().unsigned_area() - interior_area).round() as i32;
         Some(RawBuilding {
             classification,
+            household_building_type,
             center: float_boundary
                 .centroid()
                 .map(|p| geo_types::Point::from((p.x().round() as i32, (p.y().round()) as i32)))?,
@@ -281,12 +375,48 @@ impl RawBuilding {
     pub fn boundary_id(&self) -> BuildingBoundaryID {
         self.boundary_id
     }
+    /// The specific OSM `building=` tag value, when this is a `Household`, used to weight how many
+    /// households the building can contain (e.g. an apartment block vs a detached house)
+    pub fn household_building_type(&self) -> Option<HouseholdBuildingType> {
+        self.household_building_type
+    }
+    /// A single unit offset deterministically derived from `boundary_id`, used by
+    /// `jitter_coincident_centroids` to separate buildings that would otherwise share a `center`
+    fn jitter_offset(&self) -> (i32, i32) {
+        let bytes = self.boundary_id.id.as_bytes();
+        let dx = if bytes[0] % 2 == 0 { 1 } else { -1 };
+        let dy = if bytes[1] % 2 == 0 { 1 } else { -1 };
+        (dx, dy)
+    }
 }
 
-impl<'a> TryFrom<DenseNode<'a>> for RawOSMNode {
-    type Error = ();
+/// Multiple `RawBuilding`s derived from OSM nodes at identical coordinates (e.g. several points
+/// stacked on top of each other) would otherwise end up with identical `center`s, which collide
+/// as Voronoi seeds, and as keys into `citizens_per_raw_school`, silently merging what should be
+/// distinct buildings
+///
+/// This nudges every building but the first to reach a given coordinate by a unit, in a direction
+/// deterministically derived from its `boundary_id` (see `RawBuilding::jitter_offset`) - repeating
+/// as needed until it reaches a coordinate no earlier building in `buildings` has claimed, so
+/// re-parsing the same file always produces the same jittered positions
+fn jitter_coincident_centroids(buildings: &mut [RawBuilding]) {
+    let mut occupied_centers: HashSet<Point<i32>> = HashSet::with_capacity(buildings.len());
+    for building in buildings.iter_mut() {
+        let (dx, dy) = building.jitter_offset();
+        while !occupied_centers.insert(building.center) {
+            building.center =
+                Point::from((building.center.x() + dx, building.center.y() + dy));
+        }
+    }
+}
 
-    fn try_from(node: DenseNode<'a>) -> Result<Self, Self::Error> {
+impl RawOSMNode {
+    /// Builds a `RawOSMNode` from a `DenseNode`, classifying its tags with
+    /// `unrecognised_building_tag_default` - see `TagClassifiedBuilding::from_tags_with_unrecognised_default`
+    fn from_dense_node(
+        node: DenseNode,
+        unrecognised_building_tag_default: TagClassifiedBuilding,
+    ) -> Result<Self, ()> {
         let visible = node.info().map(|info| info.visible()).unwrap_or(true);
         if visible {
             // TODO Change this
@@ -322,7 +452,11 @@ impl<'a> TryFrom<DenseNode<'a>> for RawOSMNode {
             let position: Point<i32> = position.into();
             return Ok(RawOSMNode {
                 id: node.id,
-                classification: TagClassifiedBuilding::from(node.tags()),
+                classification: TagClassifiedBuilding::from_tags_with_unrecognised_default(
+                    node.tags().collect(),
+                    unrecognised_building_tag_default,
+                ),
+                household_building_type: household_building_type_from_tags(node.tags()),
                 location: position,
             });
         }
@@ -357,6 +491,22 @@ pub fn merge_iterators<T, U: Extend<T> + IntoIterator<Item=T>>(
     }
 }
 
+/// Checks that the OSM Voronoi diagrams and the output-area polygon lookup were built with the
+/// same grid size, returning a descriptive error if not
+///
+/// `osm_grid_size` and `polygon_grid_size` must agree, or coordinates scaled against one won't
+/// line up with lookups against the other - this surfaces as a confusing out-of-bounds panic deep
+/// inside whichever lookup ends up scaled differently, rather than a clear error at startup
+pub fn validate_grid_sizes(osm_grid_size: i32, polygon_grid_size: i32) -> Result<(), OSMError> {
+    if osm_grid_size != polygon_grid_size {
+        return Err(OSMError::GridSizeMismatch {
+            osm_grid_size,
+            polygon_grid_size,
+        });
+    }
+    Ok(())
+}
+
 /// The container for the processed OSM Data, with Voronoi Diagrams
 #[derive(Serialize, Deserialize)]
 pub struct OSMRawBuildings {
@@ -367,6 +517,10 @@ pub struct OSMRawBuildings {
     pub building_locations: HashMap<TagClassifiedBuilding, Vec<RawBuilding>>,
     #[serde(skip_serializing, deserialize_with = "deserialize_to_none")]
     building_voronoi: Option<HashMap<TagClassifiedBuilding, Voronoi>>,
+    /// The grid size the Voronoi diagrams were last built with, set by `construct_voronoi_diagrams`
+    /// - `None` until `build_osm_data` or `from_building_locations` has run
+    #[serde(skip_serializing, deserialize_with = "deserialize_to_none")]
+    grid_size: Option<i32>,
 }
 
 fn deserialize_to_none<'de, D, T>(_deserializer: D) -> Result<Option<T>, D::Error>
@@ -382,6 +536,12 @@ impl OSMRawBuildings {
             .as_ref()
             .expect("Voronoi diagrams are not built!")
     }
+    /// The grid size the Voronoi diagrams were last built with - see `validate_grid_sizes`
+    ///
+    /// `None` until `build_osm_data` or `from_building_locations` has run
+    pub fn grid_size(&self) -> Option<i32> {
+        self.grid_size
+    }
     fn from(
         building_boundaries: HashMap<BuildingBoundaryID, Polygon<i32>>,
         building_locations: HashMap<TagClassifiedBuilding, Vec<RawBuilding>>,
@@ -390,8 +550,23 @@ impl OSMRawBuildings {
             building_boundaries,
             building_locations,
             building_voronoi: None,
+            grid_size: None,
         }
     }
+    /// Builds an `OSMRawBuildings` directly from already-classified building locations, rather than
+    /// parsing a raw `.osm.pbf` file
+    ///
+    /// Useful for synthetic/test data, or any other source of building locations that bypasses the
+    /// normal OSM parsing pipeline
+    pub fn from_building_locations(
+        building_boundaries: HashMap<BuildingBoundaryID, Polygon<i32>>,
+        building_locations: HashMap<TagClassifiedBuilding, Vec<RawBuilding>>,
+        grid_size: i32,
+    ) -> OSMRawBuildings {
+        let mut osm_data = OSMRawBuildings::from(building_boundaries, building_locations);
+        osm_data.construct_voronoi_diagrams(grid_size);
+        osm_data
+    }
     fn read_cached_osm_data(cache_filename: String) -> Result<OSMRawBuildings, OSMError> {
         debug!("Reading cached parsing data from: {}", cache_filename);
         let bytes = read(&cache_filename).map_err(|e| OSMError::IOError {
@@ -406,9 +581,14 @@ impl OSMRawBuildings {
     fn load_and_write_cache(
         raw_filename: String,
         cache_filename: String,
+        unrecognised_building_tag_default: TagClassifiedBuilding,
+        jitter_coincident_building_centroids: bool,
     ) -> Result<OSMRawBuildings, OSMError> {
         debug!("Parsing data from raw OSM file");
-        let mut building_locations = OSMRawBuildings::read_buildings_from_osm(raw_filename)?;
+        let mut building_locations = OSMRawBuildings::read_buildings_from_osm(
+            raw_filename,
+            unrecognised_building_tag_default,
+        )?;
 
         debug!("Removing duplicate buildings...");
         for building_class in BUILDINGS_TO_REMOVE_DUPLICATES {
@@ -456,6 +636,13 @@ impl OSMRawBuildings {
             classified_buildings_to_remove_duplicates
                 .retain(|building| to_remove_list.contains(&building.center));
         }
+
+        if jitter_coincident_building_centroids {
+            debug!("Jittering coincident building centroids...");
+            for buildings in building_locations.building_locations.values_mut() {
+                jitter_coincident_centroids(buildings);
+            }
+        }
         debug!("Saving cache to file");
 
         std::fs::write(
@@ -479,12 +666,22 @@ impl OSMRawBuildings {
     /// * `cache_filename` - The file to store parsed osm data
     /// * `use_cache` - If true, stores the results of loading the OSM file to the `cache_filename` file, otherwise skips parsing the OSM file, and uses the cache instead
     /// * `visualise_building_boundaries` - If true, generates images representing the Voronoi diagrams for each building type
+    /// * `unrecognised_building_tag_default` - The classification given to a building with a
+    ///   `building=` tag whose value isn't explicitly handled - see
+    ///   `TagClassifiedBuilding::from_tags_with_unrecognised_default`. Only applies to a fresh
+    ///   parse; a cache hit reuses whatever default was used when the cache was written
+    /// * `jitter_coincident_building_centroids` - If true, buildings that end up sharing an exact
+    ///   `center` point after duplicate removal are nudged apart - see
+    ///   `jitter_coincident_centroids`. Only applies to a fresh parse; a cache hit reuses whatever
+    ///   positions were written to the cache
     pub fn build_osm_data(
         filename: String,
         cache_filename: String,
         use_cache: bool,
         visualise_building_boundaries: bool,
         grid_size: i32,
+        unrecognised_building_tag_default: TagClassifiedBuilding,
+        jitter_coincident_building_centroids: bool,
     ) -> Result<OSMRawBuildings, OSMError> {
         info!("Building OSM Data...");
         debug!("Starting to read data from file");
@@ -497,11 +694,21 @@ impl OSMRawBuildings {
                 Ok(data) => data,
                 Err(e) => {
                     error!("Loading cached OSM data failed: {}", e);
-                    OSMRawBuildings::load_and_write_cache(filename, cache_filename)?
+                    OSMRawBuildings::load_and_write_cache(
+                        filename,
+                        cache_filename,
+                        unrecognised_building_tag_default,
+                        jitter_coincident_building_centroids,
+                    )?
                 }
             }
         } else {
-            OSMRawBuildings::load_and_write_cache(filename, cache_filename)?
+            OSMRawBuildings::load_and_write_cache(
+                filename,
+                cache_filename,
+                unrecognised_building_tag_default,
+                jitter_coincident_building_centroids,
+            )?
         };
 
         debug!("Loaded OSM data");
@@ -521,7 +728,10 @@ impl OSMRawBuildings {
         Ok(osm_data)
     }
 
-    fn read_buildings_from_osm(filename: String) -> Result<OSMRawBuildings, OSMError> {
+    fn read_buildings_from_osm(
+        filename: String,
+        unrecognised_building_tag_default: TagClassifiedBuilding,
+    ) -> Result<OSMRawBuildings, OSMError> {
         use osmpbf::{Element, ElementReader};
         info!("Reading OSM data from file: {}", filename);
         let reader = ElementReader::from_path(filename)?;
@@ -536,18 +746,24 @@ impl OSMRawBuildings {
                             // Then if a valid building time,instantiate a new Hashmap to be merged
                             (
                                 None,
-                                RawOSMNode::try_from(node).ok().map(|node| {
-                                    let mut map = BTreeMap::new();
-                                    map.insert(node.id, node);
-                                    map
-                                }),
+                                RawOSMNode::from_dense_node(node, unrecognised_building_tag_default)
+                                    .ok()
+                                    .map(|node| {
+                                        let mut map = BTreeMap::new();
+                                        map.insert(node.id, node);
+                                        map
+                                    }),
                             )
                         }
                         //Discard all other OSM elements (Like roads)
                         Element::Way(way) => {
                             let parsed = RawOSMWay {
                                 _id: way.id(),
-                                classification: TagClassifiedBuilding::from(way.tags()),
+                                classification: TagClassifiedBuilding::from_tags_with_unrecognised_default(
+                                    way.tags().collect(),
+                                    unrecognised_building_tag_default,
+                                ),
+                                household_building_type: household_building_type_from_tags(way.tags()),
                                 node_ids: way.refs().collect(),
                             };
                             (Some(vec![parsed]), None)
@@ -595,9 +811,12 @@ impl OSMRawBuildings {
             let building_boundary_id = BuildingBoundaryID::default();
             let mut building_exists = false;
             for classification in building_classification {
-                if let Some(building) =
-                RawBuilding::new(classification, &building_shape, building_boundary_id)
-                {
+                if let Some(building) = RawBuilding::new_with_household_type(
+                    classification,
+                    way.household_building_type,
+                    &building_shape,
+                    building_boundary_id,
+                ) {
                     let building_entry = buildings.entry(classification).or_default();
                     building_entry.push(building);
                     building_exists = true;
@@ -631,9 +850,12 @@ impl OSMRawBuildings {
                         .into(),
                     vec![],
                 );
-                if let Some(building) =
-                RawBuilding::new(node.classification, &building_shape, building_boundary_id)
-                {
+                if let Some(building) = RawBuilding::new_with_household_type(
+                    node.classification,
+                    node.household_building_type,
+                    &building_shape,
+                    building_boundary_id,
+                ) {
                     let building_entry = buildings.entry(node.classification).or_default();
                     building_entry.push(building);
                     if let Some(b) =
@@ -676,6 +898,7 @@ impl OSMRawBuildings {
     ///
     /// This constructs a polygon map, for each building, where each point inside a polygon means that building is the closest one
     fn construct_voronoi_diagrams(&mut self, grid_size: i32) {
+        self.grid_size = Some(grid_size);
         let voronoi: HashMap<TagClassifiedBuilding, Voronoi> = self
             .building_locations
             .par_iter()
@@ -706,13 +929,124 @@ impl OSMRawBuildings {
             .collect();
         self.building_voronoi = Some(voronoi)
     }
+    /// Returns the buildings whose centre point falls within `bounds`, grouped by classification
+    ///
+    /// Useful for pulling out a sub-region (e.g. a city centre) for focused analysis or zoomed-in
+    /// visualisation, without re-parsing the whole OSM file
+    pub fn buildings_in_bounds(
+        &self,
+        bounds: geo_types::Rect<i32>,
+    ) -> HashMap<TagClassifiedBuilding, Vec<RawBuilding>> {
+        self.building_locations
+            .iter()
+            .filter_map(|(building_type, locations)| {
+                let in_bounds: Vec<RawBuilding> = locations
+                    .iter()
+                    .filter(|building| bounds.contains(&building.center()))
+                    .cloned()
+                    .collect();
+                if in_bounds.is_empty() {
+                    None
+                } else {
+                    Some((*building_type, in_bounds))
+                }
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{OSM_CACHE_FILENAME, OSM_FILENAME, OSMRawBuildings};
+    use std::collections::HashMap;
+
+    use crate::{BuildingBoundaryID, HouseholdBuildingType, jitter_coincident_centroids, OSM_CACHE_FILENAME, OSM_FILENAME, OSMRawBuildings, RawBuilding, TagClassifiedBuilding, validate_grid_sizes};
+    use crate::error::OSMError;
     use crate::voronoi_generator::find_seed_bounds;
 
+    /// An `apartments` building of the same footprint as a `detached` house should be assigned more
+    /// households, since its density multiplier is higher
+    #[test]
+    fn apartments_host_more_households_than_detached() {
+        let apartments = HouseholdBuildingType::from("apartments");
+        let detached = HouseholdBuildingType::from("detached");
+        assert!(apartments.household_density_multiplier() > detached.household_density_multiplier());
+    }
+
+    /// A `building=` tag whose value isn't one of the ones explicitly handled should fall back to
+    /// whatever `unrecognised_building_tag_default` is configured, rather than always `WorkPlace`
+    #[test]
+    fn unrecognised_building_tag_falls_back_to_the_configured_default() {
+        let tags = HashMap::from([("building", "construction")]);
+        assert_eq!(
+            TagClassifiedBuilding::from_tags_with_unrecognised_default(
+                tags.clone(),
+                TagClassifiedBuilding::Household,
+            ),
+            TagClassifiedBuilding::Household
+        );
+        assert_eq!(
+            TagClassifiedBuilding::from_tags_with_unrecognised_default(
+                tags,
+                TagClassifiedBuilding::Unknown,
+            ),
+            TagClassifiedBuilding::Unknown
+        );
+    }
+
+    /// Two buildings parsed at the exact same coordinate (e.g. stacked OSM nodes) should end up
+    /// with distinct `center`s after jittering, rather than colliding as the same Voronoi seed /
+    /// `citizens_per_raw_school` key
+    #[test]
+    fn coincident_building_centroids_remain_distinct_after_jitter() {
+        let boundary = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+            vec![],
+        );
+        let mut buildings = vec![
+            RawBuilding::new(
+                TagClassifiedBuilding::School,
+                &boundary,
+                BuildingBoundaryID::default(),
+            )
+                .expect("Failed to build test RawBuilding"),
+            RawBuilding::new(
+                TagClassifiedBuilding::School,
+                &boundary,
+                BuildingBoundaryID::default(),
+            )
+                .expect("Failed to build test RawBuilding"),
+        ];
+        assert_eq!(buildings[0].center(), buildings[1].center());
+
+        jitter_coincident_centroids(&mut buildings);
+
+        assert_ne!(buildings[0].center(), buildings[1].center());
+    }
+
+    /// A mismatch between the OSM/Voronoi grid size and the output-area polygon grid size should
+    /// produce a descriptive `GridSizeMismatch` error, naming both sizes, rather than being left to
+    /// surface as a confusing out-of-bounds panic later on
+    #[test]
+    fn mismatched_grid_sizes_produce_a_descriptive_error() {
+        let error = validate_grid_sizes(50000, 20000).expect_err("Mismatched grid sizes should error");
+        match error {
+            OSMError::GridSizeMismatch {
+                osm_grid_size,
+                polygon_grid_size,
+            } => {
+                assert_eq!(osm_grid_size, 50000);
+                assert_eq!(polygon_grid_size, 20000);
+            }
+            other => panic!("Expected a GridSizeMismatch error, got: {:?}", other),
+        }
+    }
+
+    /// Matching grid sizes should validate without error
+    #[test]
+    fn matching_grid_sizes_validate_successfully() {
+        assert!(validate_grid_sizes(50000, 50000).is_ok());
+    }
+
     #[test]
     pub fn check_x_y_range() {
         let census_directory = "../data/".to_string();
@@ -722,6 +1056,8 @@ mod tests {
             false,
             false,
             50000,
+            TagClassifiedBuilding::WorkPlace,
+            true,
         );
         //assert!(osm_buildings.is_ok());
         let osm_buildings = osm_buildings.unwrap();
@@ -743,4 +1079,58 @@ mod tests {
         println!("Width: {:?}", width);
         assert!(width < height);
     }
+
+    /// A building with a courtyard (interior ring) should have the courtyard's area subtracted from
+    /// its footprint, rather than counting it as occupiable floor space
+    #[test]
+    fn donut_shaped_building_excludes_the_hole_from_its_size() {
+        let exterior = geo_types::LineString::from(vec![(0, 0), (100, 0), (100, 100), (0, 100), (0, 0)]);
+        let hole = geo_types::LineString::from(vec![(20, 20), (80, 20), (80, 80), (20, 80), (20, 20)]);
+        let boundary = geo_types::Polygon::new(exterior, vec![hole]);
+
+        let building = RawBuilding::new(TagClassifiedBuilding::Shop, &boundary, BuildingBoundaryID::default())
+            .expect("Failed to build RawBuilding");
+
+        assert_eq!(building.size(), 100 * 100 - 60 * 60);
+    }
+
+    fn square_building_at(classification: TagClassifiedBuilding, center: (i32, i32)) -> RawBuilding {
+        let (x, y) = center;
+        let boundary = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![
+                (x - 5, y - 5),
+                (x + 5, y - 5),
+                (x + 5, y + 5),
+                (x - 5, y + 5),
+                (x - 5, y - 5),
+            ]),
+            vec![],
+        );
+        RawBuilding::new(classification, &boundary, BuildingBoundaryID::default())
+            .expect("Failed to build RawBuilding")
+    }
+
+    /// `buildings_in_bounds` should only return buildings whose centre point falls inside the
+    /// given box, regardless of their classification
+    #[test]
+    fn buildings_in_bounds_only_returns_buildings_with_centres_inside_the_box() {
+        let inside_shop = square_building_at(TagClassifiedBuilding::Shop, (10, 10));
+        let inside_school = square_building_at(TagClassifiedBuilding::School, (20, 20));
+        let outside_shop = square_building_at(TagClassifiedBuilding::Shop, (1000, 1000));
+
+        let mut building_locations = std::collections::HashMap::new();
+        building_locations.insert(TagClassifiedBuilding::Shop, vec![inside_shop, outside_shop]);
+        building_locations.insert(TagClassifiedBuilding::School, vec![inside_school]);
+
+        let osm_buildings =
+            OSMRawBuildings::from_building_locations(std::collections::HashMap::new(), building_locations, 100);
+
+        let bounds = geo_types::Rect::new((0, 0), (100, 100));
+        let in_bounds = osm_buildings.buildings_in_bounds(bounds);
+
+        let shops = in_bounds.get(&TagClassifiedBuilding::Shop).expect("Expected a Shop inside the bounds");
+        assert_eq!(shops.iter().map(|b| b.center()).collect::<Vec<_>>(), vec![inside_shop.center()]);
+        let schools = in_bounds.get(&TagClassifiedBuilding::School).expect("Expected a School inside the bounds");
+        assert_eq!(schools.iter().map(|b| b.center()).collect::<Vec<_>>(), vec![inside_school.center()]);
+    }
 }
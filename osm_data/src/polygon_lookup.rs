@@ -43,9 +43,9 @@ use std::fmt::{Debug, Display};
 use std::hash::Hash;
 use std::time::Instant;
 
-use geo::prelude::BoundingRect;
+use geo::prelude::{BoundingRect, Intersects};
 use geo_types::{CoordNum, LineString};
-use log::{debug, info, trace};
+use log::{debug, info, trace, warn};
 use num_traits::PrimInt;
 use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use shapefile::dbase::FieldValue;
@@ -53,9 +53,77 @@ use shapefile::Shape;
 
 use crate::convert::decimal_latitude_and_longitude_to_northing_and_eastings;
 use crate::OSMError;
-use crate::quadtree::QuadTree;
+use crate::quadtree::{MAX_DEPTH, MIN_BOUNDARY_SIZE, QuadTree};
 use crate::voronoi_generator::Scaling;
 
+/// A report of how many rings were reoriented or dropped while repairing a polygon, returned by
+/// [`validate_and_repair_rings`]
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct RingRepairReport {
+    pub reoriented_exterior: bool,
+    pub reoriented_interiors: usize,
+    pub dropped_interiors: usize,
+}
+
+impl RingRepairReport {
+    /// Whether any ring was changed from its original state
+    pub fn is_repaired(&self) -> bool {
+        self.reoriented_exterior || self.reoriented_interiors > 0 || self.dropped_interiors > 0
+    }
+}
+
+/// Twice the signed area of a ring, via the shoelace formula\
+/// Positive for a counter-clockwise ring, negative for a clockwise ring
+fn signed_area_x2(ring: &LineString<i32>) -> i64 {
+    ring.0
+        .windows(2)
+        .map(|pair| {
+            let (a, b) = (pair[0], pair[1]);
+            a.x as i64 * b.y as i64 - b.x as i64 * a.y as i64
+        })
+        .sum()
+}
+
+/// Ensures `polygon`'s exterior ring winds counter-clockwise and its interior rings wind clockwise, as
+/// `geo`'s algorithms expect, dropping any interior ring too degenerate (fewer than 3 distinct points, or
+/// zero area) to have a meaningful winding
+///
+/// Real-world shapefiles sometimes violate this convention, or contain self-intersecting or degenerate
+/// rings, which previously caused panics or misassigned holes further down the pipeline
+pub fn validate_and_repair_rings(
+    polygon: geo_types::Polygon<i32>,
+) -> (geo_types::Polygon<i32>, RingRepairReport) {
+    let mut report = RingRepairReport::default();
+    let (exterior, interiors) = polygon.into_inner();
+    let exterior = if signed_area_x2(&exterior) < 0 {
+        report.reoriented_exterior = true;
+        exterior.0.into_iter().rev().collect()
+    } else {
+        exterior
+    };
+    let interiors = interiors
+        .into_iter()
+        .filter_map(|interior| {
+            if interior.0.len() < 4 {
+                report.dropped_interiors += 1;
+                return None;
+            }
+            let area = signed_area_x2(&interior);
+            if area == 0 {
+                report.dropped_interiors += 1;
+                return None;
+            }
+            if area > 0 {
+                report.reoriented_interiors += 1;
+                Some(interior.0.into_iter().rev().collect())
+            } else {
+                Some(interior)
+            }
+        })
+        .collect();
+    (geo_types::Polygon::new(exterior, interiors), report)
+}
+
 /// Converts a geo type Polygon to a quadtree Area (using the Polygon Bounding Box)
 #[inline]
 fn geo_polygon_to_quad_area<T: CoordNum + PrimInt + Display + PartialOrd + Default>(
@@ -97,8 +165,23 @@ impl<T: Debug + Clone + Eq + Ord + Hash> PolygonContainer<T> {
         grid_size: i32,
     ) -> Result<PolygonContainer<T>, OSMError> {
         trace!("Building new Polygon Container of size: {}", grid_size);
-        // Build Quadtree, with Coords of isize and values of seed points
-        let mut lookup = QuadTree::with_size(grid_size, grid_size, 10, 50);
+        let required_depth =
+            ((grid_size as f64 / MIN_BOUNDARY_SIZE as f64).max(1.0).log2().ceil()) as u8;
+        if MAX_DEPTH < required_depth {
+            let max_safe_grid_size = MIN_BOUNDARY_SIZE as i64 * 2i64.pow(MAX_DEPTH as u32);
+            return Err(OSMError::OutOfBounds {
+                context: format!(
+                    "Grid size {} would require a quadtree depth of {}, exceeding the maximum safe depth of {} and risking a stack overflow",
+                    grid_size, required_depth, MAX_DEPTH
+                ),
+                max_size: max_safe_grid_size.to_string(),
+                actual_size: grid_size.to_string(),
+            });
+        }
+        // Build Quadtree, with Coords of isize and values of seed points - `initial_depth` has to be
+        // derived from `grid_size` the same way `required_depth` above is, otherwise the check above
+        // guards a recursion depth this call doesn't actually produce
+        let mut lookup = QuadTree::with_size(grid_size, grid_size, required_depth, 50);
         let mut added = 0;
         for (id, polygon) in &polygons {
             let bounds = match polygon
@@ -245,14 +328,85 @@ impl<T: Debug + Clone + Eq + Ord + Hash> PolygonContainer<T> {
         });*/
         Ok(results)
     }
+    /// Finds pairs of polygons in this container that genuinely overlap, so a shapefile that
+    /// accidentally contains overlapping Output Areas can be identified and fixed - otherwise a
+    /// building inside the overlap resolves to an arbitrary one of them (see
+    /// `find_polygons_containing_polygon`), rather than consistently to just one
+    ///
+    /// Each overlapping pair is reported once, in the order the container's IDs naturally sort in.
+    /// Only pairs whose bounding boxes already intersect (found via the quadtree) are checked
+    /// against the true polygon geometry, so this stays cheap even for a large container
+    pub fn find_overlapping_polygons(&self) -> Vec<(T, T)> {
+        let mut already_reported = std::collections::HashSet::new();
+        let mut overlaps = Vec::new();
+        for (id, polygon) in &self.polygons {
+            let bounds = match geo_polygon_to_quad_area(polygon) {
+                Ok(bounds) => self.scaling.scale_rect(bounds, self.grid_size),
+                Err(_) => continue,
+            };
+            for candidate_id in self.lookup.get_items(bounds) {
+                if candidate_id == id {
+                    continue;
+                }
+                let pair = if id < candidate_id {
+                    (id.clone(), candidate_id.clone())
+                } else {
+                    (candidate_id.clone(), id.clone())
+                };
+                if !already_reported.insert(pair.clone()) {
+                    continue;
+                }
+                if let Some(candidate_polygon) = self.polygons.get(candidate_id) {
+                    if polygon.intersects(candidate_polygon) {
+                        overlaps.push(pair);
+                    }
+                }
+            }
+        }
+        overlaps
+    }
+}
+
+/// The coordinate system a shapefile's points are recorded in
+///
+/// Misidentifying this silently produces garbage coordinates - either un-converted lat/lon values
+/// treated as eastings/northings, or already-projected eastings/northings run back through the
+/// lat/lon conversion a second time - so callers must state which one a given file uses
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CoordinateReferenceSystem {
+    /// Coordinates are decimal latitude/longitude (e.g. WGS84), and need converting to British
+    /// National Grid eastings/northings via [`decimal_latitude_and_longitude_to_northing_and_eastings`]
+    DecimalLatLon,
+    /// Coordinates are already British National Grid eastings/northings, and should be used as-is
+    BritishNationalGrid,
+}
+
+/// Converts a single shapefile point to a British National Grid `Coordinate`, according to the
+/// coordinate system it was recorded in
+fn convert_shapefile_point(
+    point: &shapefile::Point,
+    crs: CoordinateReferenceSystem,
+) -> geo_types::Coordinate<i32> {
+    match crs {
+        CoordinateReferenceSystem::DecimalLatLon => geo_types::Coordinate::from(
+            decimal_latitude_and_longitude_to_northing_and_eastings(point.y, point.x),
+        ),
+        CoordinateReferenceSystem::BritishNationalGrid => {
+            geo_types::Coordinate::from((point.x.round() as i32, point.y.round() as i32))
+        }
+    }
 }
 
 //impl<T: Debug + Clone + Eq + Ord + Hash> PolygonContainer<T> {
 impl PolygonContainer<String> {
     /// Generates the polygons for each output area contained in the given file
+    ///
+    /// `crs` must match the coordinate system `filename`'s points are recorded in - see
+    /// [`CoordinateReferenceSystem`]
     pub fn load_polygons_from_file(
         filename: &str,
         grid_size: i32,
+        crs: CoordinateReferenceSystem,
     ) -> Result<PolygonContainer<String>, OSMError> {
         let mut reader = shapefile::Reader::from_path(filename).map_err(|e| OSMError::IOError {
             source: Box::new(e),
@@ -277,18 +431,7 @@ impl PolygonContainer<String> {
                     rings = polygon.rings()[0]
                         .points()
                         .iter()
-                        .map(|p| {
-                            // TODO Reenable this if using old system
-                            /*
-                            geo_types::Coordinate::from((
-                                p.x.round() as isize,
-                                p.y.round() as isize,
-                            ))*/
-                            geo_types::Coordinate::from(decimal_latitude_and_longitude_to_northing_and_eastings(
-                                p.y,
-                                p.x,
-                            ))
-                        })
+                        .map(|p| convert_shapefile_point(p, crs))
                         .collect();
                     interior_ring = Vec::new();
                 } else {
@@ -299,18 +442,7 @@ impl PolygonContainer<String> {
                             LineString::from(
                                 r.points()
                                     .iter()
-                                    .map(|p| {
-                                        geo_types::Coordinate::from(decimal_latitude_and_longitude_to_northing_and_eastings(
-                                            p.y,
-                                            p.x,
-                                        ))
-                                        // TODO Reenable this if using old system
-                                        /*
-                                        geo_types::Coordinate::from((
-                                            p.x.round() as isize,
-                                            p.y.round() as isize,
-                                        ))*/
-                                    })
+                                    .map(|p| convert_shapefile_point(p, crs))
                                     .collect::<Vec<geo_types::Coordinate<i32>>>(),
                             )
                         })
@@ -328,6 +460,7 @@ impl PolygonContainer<String> {
                     source: format!("Unexpected shape type: {}", shape.shapetype().to_string())
                 });
             };
+            let (polygon, repair_report) = validate_and_repair_rings(polygon);
 
             // Retrieve the area code:
             let code_record =
@@ -354,11 +487,34 @@ impl PolygonContainer<String> {
                 }
             }
 
-            Ok((code, polygon))
-        }).collect::<Result<HashMap<String, geo_types::Polygon<i32>>, OSMError>>()?;
+            Ok((code, polygon, repair_report))
+        }).collect::<Result<Vec<(String, geo_types::Polygon<i32>, RingRepairReport)>, OSMError>>()?;
+        let repaired_count = data.iter().filter(|(_, _, report)| report.is_repaired()).count();
+        if repaired_count > 0 {
+            info!(
+                "Repaired ring winding/degeneracy on {} of {} polygons while loading {}",
+                repaired_count,
+                data.len(),
+                filename
+            );
+        }
+        let data: HashMap<String, geo_types::Polygon<i32>> = data
+            .into_iter()
+            .map(|(code, polygon, _report)| (code, polygon))
+            .collect();
         info!("Finished loading map data in {:?}", start_time.elapsed());
         let scaling = Scaling::yorkshire_national_grid(grid_size);
-        PolygonContainer::new(data, scaling, grid_size)
+        let container = PolygonContainer::new(data, scaling, grid_size)?;
+        let overlaps = container.find_overlapping_polygons();
+        if !overlaps.is_empty() {
+            warn!(
+                "{} found {} overlapping Output Area polygon pairs, e.g. {:?} - Citizens/buildings inside an overlap will resolve to an arbitrary one of the overlapping areas",
+                filename,
+                overlaps.len(),
+                overlaps.first()
+            );
+        }
+        Ok(container)
     }
     /*    pub fn remove_polygon(&mut self, output_area_id: T) {
         let poly=self.polygons.remove(&output_area_id).unwrap();
@@ -366,3 +522,123 @@ impl PolygonContainer<String> {
         self.lookup.delete(geo_polygon_to_quad_area(&poly).unwrap())
     }*/
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use geo_types::{LineString, Polygon};
+
+    use crate::OSMError;
+    use crate::polygon_lookup::{convert_shapefile_point, validate_and_repair_rings, CoordinateReferenceSystem, PolygonContainer};
+    use crate::voronoi_generator::Scaling;
+
+    #[test]
+    fn clockwise_exterior_ring_is_reoriented_counter_clockwise() {
+        // Wound clockwise, rather than the counter-clockwise `geo` expects
+        let clockwise_square = Polygon::new(
+            LineString::from(vec![(0, 0), (0, 10), (10, 10), (10, 0), (0, 0)]),
+            vec![],
+        );
+        let (repaired, report) = validate_and_repair_rings(clockwise_square);
+        assert!(report.reoriented_exterior);
+        assert_eq!(
+            repaired.exterior(),
+            &LineString::from(vec![(0, 0), (10, 0), (10, 10), (0, 10), (0, 0)])
+        );
+    }
+
+    /// A point recorded as decimal lat/lon should be run through the National Grid conversion
+    #[test]
+    fn decimal_lat_lon_point_is_converted_to_national_grid() {
+        // Same fixture as `convert::test_decimal_latitude_and_longitude_to_northing_and_eastings`
+        let point = shapefile::Point::new(-1.664442, 53.61199);
+        let coordinate = convert_shapefile_point(&point, CoordinateReferenceSystem::DecimalLatLon);
+        assert_eq!(coordinate, geo_types::Coordinate::from((422297, 412878)));
+    }
+
+    /// A point already recorded in British National Grid eastings/northings should be used as-is,
+    /// rather than being run back through the lat/lon conversion a second time
+    #[test]
+    fn british_national_grid_point_is_not_double_converted() {
+        let point = shapefile::Point::new(422297.0, 412878.0);
+        let coordinate =
+            convert_shapefile_point(&point, CoordinateReferenceSystem::BritishNationalGrid);
+        assert_eq!(coordinate, geo_types::Coordinate::from((422297, 412878)));
+
+        let double_converted =
+            convert_shapefile_point(&point, CoordinateReferenceSystem::DecimalLatLon);
+        assert_ne!(coordinate, double_converted);
+    }
+
+    /// A grid size whose implied quadtree depth exceeds `MAX_DEPTH` should be rejected with a
+    /// descriptive error, rather than risking a stack overflow while recursively subdividing it
+    #[test]
+    fn oversized_grid_returns_an_error_instead_of_overflowing() {
+        let polygons = HashMap::<u32, geo_types::Polygon<i32>>::new();
+        let grid_size = 200_000_000;
+        let result = PolygonContainer::new(
+            polygons,
+            Scaling::yorkshire_national_grid(grid_size),
+            grid_size,
+        );
+        assert!(matches!(result, Err(OSMError::OutOfBounds { .. })));
+    }
+
+    /// The `QuadTree` built for a larger `grid_size` should actually recurse deeper than one built
+    /// for a smaller `grid_size` - otherwise the depth check above is guarding a recursion depth the
+    /// constructor never actually produces
+    #[test]
+    fn larger_grid_size_produces_a_deeper_quadtree() {
+        let small_grid_size = 1_000;
+        let small = PolygonContainer::<u32>::new(
+            HashMap::new(),
+            Scaling::yorkshire_national_grid(small_grid_size),
+            small_grid_size,
+        )
+            .expect("Failed to build a small PolygonContainer");
+
+        let large_grid_size = 2_000_000;
+        let large = PolygonContainer::<u32>::new(
+            HashMap::new(),
+            Scaling::yorkshire_national_grid(large_grid_size),
+            large_grid_size,
+        )
+            .expect("Failed to build a large PolygonContainer");
+
+        assert!(
+            large.lookup.max_depth() > small.lookup.max_depth(),
+            "A PolygonContainer built from a larger grid_size ({}) should recurse deeper ({}) than \
+            one built from a smaller grid_size ({}, depth {})",
+            large_grid_size,
+            large.lookup.max_depth(),
+            small_grid_size,
+            small.lookup.max_depth()
+        );
+    }
+
+    /// Two Output Area polygons that deliberately overlap should be reported as a pair, while a
+    /// third, disjoint polygon shouldn't be reported against either of them
+    #[test]
+    fn overlapping_polygons_are_reported() {
+        let mut polygons = HashMap::new();
+        polygons.insert(
+            "a".to_string(),
+            Polygon::new(LineString::from(vec![(0, 0), (10, 0), (10, 10), (0, 10), (0, 0)]), vec![]),
+        );
+        polygons.insert(
+            "b".to_string(),
+            Polygon::new(LineString::from(vec![(5, 5), (15, 5), (15, 15), (5, 15), (5, 5)]), vec![]),
+        );
+        polygons.insert(
+            "c".to_string(),
+            Polygon::new(LineString::from(vec![(50, 50), (60, 50), (60, 60), (50, 60), (50, 50)]), vec![]),
+        );
+        let container =
+            PolygonContainer::new(polygons, Scaling::yorkshire_national_grid(100), 100)
+                .expect("Failed to build a test polygon container");
+
+        let overlaps = container.find_overlapping_polygons();
+        assert_eq!(overlaps, vec![("a".to_string(), "b".to_string())]);
+    }
+}
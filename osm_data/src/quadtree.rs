@@ -31,6 +31,13 @@ use serde::ser::SerializeSeq;
 
 pub const MIN_BOUNDARY_SIZE: usize = 100;
 
+/// The maximum recursion depth a quadtree is allowed to be built to
+///
+/// `with_boundary` recurses once per depth level, so a grid size whose implied depth exceeds this
+/// risks a stack overflow rather than a graceful error - callers should validate their requested
+/// grid size against this before building a `QuadTree`
+pub const MAX_DEPTH: u8 = 20;
+
 /// Center point for a rect ([`geo_types::rect::Rect::center()`] for [`geo_types::CoordNum`], as geo_types only implement it for [`geo_types::CoordFloat`]
 pub fn center<T: geo_types::CoordNum>(rect: geo_types::Rect<T>) -> Coordinate<T> {
     let two = T::one() + T::one();
@@ -55,7 +62,24 @@ pub fn compare_geo_coord_nums<T: geo_types::CoordNum>(a: T, b: T) -> Ordering {
 mod tests {
     use std::cmp::Ordering;
 
-    use crate::quadtree::{compare_geo_coord_nums, coord_num_abs};
+    use crate::quadtree::{compare_geo_coord_nums, coord_num_abs, QuadTree};
+
+    /// `with_size` should actually recurse to (at most) the `initial_depth` it's given - a tree
+    /// built with a larger `initial_depth` over a boundary big enough to support it should reach a
+    /// greater `max_depth` than one built with a smaller `initial_depth`
+    #[test]
+    fn with_size_recurses_to_the_requested_initial_depth() {
+        let shallow = QuadTree::<u32, i32>::with_size(1_000_000, 1_000_000, 2, 50);
+        let deep = QuadTree::<u32, i32>::with_size(1_000_000, 1_000_000, 8, 50);
+
+        assert!(
+            deep.max_depth() > shallow.max_depth(),
+            "A tree built with a larger initial_depth ({}) should recurse deeper than one built \
+            with a smaller initial_depth ({})",
+            deep.max_depth(),
+            shallow.max_depth()
+        );
+    }
 
     #[test]
     fn abs_test() {
@@ -459,6 +483,17 @@ impl<'a, T: Clone + Eq + Hash, U: CoordNum + Display> QuadTree<T, U> {
     pub fn contains(&self, other: &geo_types::Rect<U>) -> bool {
         self.boundary.intersects(other)
     }
+    /// The deepest level of recursion actually reached by this tree, so callers (and tests) can
+    /// confirm a requested `initial_depth` produced the structure they expected, rather than being
+    /// silently capped somewhere else
+    pub fn max_depth(&self) -> u8 {
+        match &self.child {
+            Child::Items { .. } => self.depth,
+            Child::Quad { children } => {
+                children.iter().map(|child| child.max_depth()).max().unwrap_or(self.depth)
+            }
+        }
+    }
     /// Returns the top [`MAX_ITEMS_RETURNED`] closest items to the bounding box
     pub fn get_multiple_items(&'a self, bounding_box: geo_types::Rect<U>) -> Vec<(&T, U)> {
         //Box<dyn Iterator<Item=(&T, U)> + 'a> {
@@ -30,7 +30,10 @@ use rand::thread_rng;
 use load_census_data::{CensusData, OSM_CACHE_FILENAME, OSM_FILENAME};
 use load_census_data::osm_parsing::{OSMRawBuildings, RawBuilding, TagClassifiedBuilding};
 use load_census_data::tables::CensusTableNames;
-use osm_data::polygon_lookup::PolygonContainer;
+use load_census_data::tables::population_and_density_per_output_area::{
+    PopulationRecord, PreProcessingPopulationDensityRecord,
+};
+use osm_data::polygon_lookup::{CoordinateReferenceSystem, PolygonContainer};
 use sim::simulator::Simulator;
 
 struct MyProfiler {}
@@ -67,6 +70,8 @@ fn load_census_data(c: &mut Criterion) {
                 false,
                 false,
                 30000,
+                TagClassifiedBuilding::WorkPlace,
+                true,
             )
         })
     });
@@ -77,6 +82,7 @@ fn load_census_data(c: &mut Criterion) {
             PolygonContainer::load_polygons_from_file(
                 CensusTableNames::OutputAreaMap.get_filename(),
                 30000,
+                CoordinateReferenceSystem::DecimalLatLon,
             )
         })
     });
@@ -98,11 +104,14 @@ fn building_assignment(c: &mut Criterion) {
         false,
         false,
         30000,
+        TagClassifiedBuilding::WorkPlace,
+        true,
     )
         .expect("Failed to load osm data");
     let polygons = PolygonContainer::load_polygons_from_file(
         ("../".to_owned() + CensusTableNames::OutputAreaMap.get_filename()).as_str(),
         30000,
+        CoordinateReferenceSystem::DecimalLatLon,
     )
         .unwrap();
     let mut chosen: HashMap<TagClassifiedBuilding, Vec<RawBuilding>> = HashMap::new();
@@ -138,12 +147,39 @@ fn building_assignment(c: &mut Criterion) {
     group.finish();
 }
 
+fn census_table_parsing(c: &mut Criterion) {
+    let directory = "../data/".to_string();
+    let area = "1946157112TYPE299".to_string();
+    let filename = CensusTableNames::PopulationDensity.get_filename().to_string();
+    let filename = format!("{}tables/{}/{}", directory, area, filename);
+
+    let mut group = c.benchmark_group("census_table_parsing");
+    group.sampling_mode(SamplingMode::Flat);
+    group.sample_size(10);
+
+    // TODO England's bulk tables (KS101/KS608) use a different on disk format which isn't parsed
+    //  yet, so this benchmarks the parallel reader against the smaller per-region table instead
+    for buffer_size in [8 * 1024, 8 * 1024 * 1024] {
+        group.bench_function(format!("buffer size {}", buffer_size), |b| {
+            b.iter(|| {
+                CensusData::read_generic_table_from_disk_with_buffer_size::<
+                    PopulationRecord,
+                    PreProcessingPopulationDensityRecord,
+                >(&filename, false, buffer_size)
+                    .unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
 fn profiled() -> Criterion {
     Criterion::default().with_profiler(MyProfiler {})
 }
 criterion_group! {
     name=benches;
     config=profiled();
-    targets = building_assignment
+    targets = building_assignment, census_table_parsing
 }
 criterion_main!(benches);
@@ -0,0 +1,193 @@
+/*
+ * Epidemic Simulation Using Census Data (ESUCD)
+ * Copyright (c)  2022. Sam Ralph
+ *
+ * This file is part of ESUCD.
+ *
+ * ESUCD is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * ESUCD is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with ESUCD.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+
+use std::fs::File;
+use std::io::BufReader;
+
+use anyhow::Context;
+use serde::Deserialize;
+
+/// One time step's compartment counts, matching the shape `StatisticsRecorder::dump_to_file`
+/// writes to `global_stats.json`
+#[derive(Debug, Clone, Deserialize)]
+struct CompartmentCounts {
+    time_step: u32,
+    susceptible: u32,
+    exposed: u32,
+    infected: u32,
+    recovered: u32,
+    vaccinated: u32,
+    asymptomatic: u32,
+    deceased: u32,
+}
+
+/// The absolute difference between two runs' compartment counts at a single matched time step
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompartmentDivergence {
+    pub time_step: u32,
+    pub susceptible: u32,
+    pub exposed: u32,
+    pub infected: u32,
+    pub recovered: u32,
+    pub vaccinated: u32,
+    pub asymptomatic: u32,
+    pub deceased: u32,
+}
+
+impl CompartmentDivergence {
+    /// The largest single-compartment difference recorded at this time step
+    pub fn max(&self) -> u32 {
+        [
+            self.susceptible,
+            self.exposed,
+            self.infected,
+            self.recovered,
+            self.vaccinated,
+            self.asymptomatic,
+            self.deceased,
+        ]
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// The result of comparing two statistics output directories' `global_stats.json` files
+#[derive(Debug, Clone)]
+pub struct DiffReport {
+    pub per_time_step: Vec<CompartmentDivergence>,
+    pub max_divergence: u32,
+    pub identical_within_tolerance: bool,
+}
+
+fn load_global_stats(directory: &str) -> anyhow::Result<Vec<CompartmentCounts>> {
+    let path = format!("{}global_stats.json", directory);
+    let file = File::open(&path).context(format!("Failed to open '{}'", path))?;
+    serde_json::from_reader(BufReader::new(file)).context(format!("Failed to parse '{}'", path))
+}
+
+/// Compares the `global_stats.json` compartment counts recorded by two statistics output
+/// directories, time step by time step, reporting how far they diverge
+///
+/// Used by the `--diff` execute mode to operationalise regression checking - e.g. confirming a
+/// refactor left a run's trajectory unchanged, or quantifying how far apart two scenario variants
+/// end up. Time steps present in only one directory (a run that stopped early, or hasn't reached
+/// that step yet) are skipped, since there's nothing to compare them against
+pub fn diff_statistics_directories(
+    directory_a: &str,
+    directory_b: &str,
+    tolerance: u32,
+) -> anyhow::Result<DiffReport> {
+    let stats_a = load_global_stats(directory_a)?;
+    let stats_b = load_global_stats(directory_b)?;
+
+    let per_time_step: Vec<CompartmentDivergence> = stats_a
+        .iter()
+        .zip(stats_b.iter())
+        .map(|(a, b)| CompartmentDivergence {
+            time_step: a.time_step,
+            susceptible: a.susceptible.abs_diff(b.susceptible),
+            exposed: a.exposed.abs_diff(b.exposed),
+            infected: a.infected.abs_diff(b.infected),
+            recovered: a.recovered.abs_diff(b.recovered),
+            vaccinated: a.vaccinated.abs_diff(b.vaccinated),
+            asymptomatic: a.asymptomatic.abs_diff(b.asymptomatic),
+            deceased: a.deceased.abs_diff(b.deceased),
+        })
+        .collect();
+    let max_divergence = per_time_step.iter().map(CompartmentDivergence::max).max().unwrap_or(0);
+
+    Ok(DiffReport {
+        per_time_step,
+        max_divergence,
+        identical_within_tolerance: max_divergence <= tolerance,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_global_stats(directory: &std::path::Path, entries: &[(u32, u32, u32)]) {
+        std::fs::create_dir_all(directory).expect("Failed to create temp statistics directory");
+        let stats: Vec<CompartmentCounts> = entries
+            .iter()
+            .map(|(time_step, susceptible, infected)| CompartmentCounts {
+                time_step: *time_step,
+                susceptible: *susceptible,
+                exposed: 0,
+                infected: *infected,
+                recovered: 0,
+                vaccinated: 0,
+                asymptomatic: 0,
+                deceased: 0,
+            })
+            .collect();
+        let file = File::create(directory.join("global_stats.json"))
+            .expect("Failed to create temp global_stats.json");
+        serde_json::to_writer(file, &stats).expect("Failed to write temp global_stats.json");
+    }
+
+    /// Diffing a directory against an exact copy of itself should report zero divergence at every
+    /// time step, and be considered identical even at a tolerance of zero
+    #[test]
+    fn diffing_a_directory_against_itself_reports_zero_divergence() {
+        let directory = std::env::temp_dir().join(format!("diff_test_self_{}", std::process::id()));
+        let directory = directory.to_str().unwrap().to_string() + "/";
+        write_global_stats(std::path::Path::new(&directory), &[(0, 100, 0), (1, 95, 5), (2, 90, 10)]);
+
+        let report = diff_statistics_directories(&directory, &directory, 0)
+            .expect("Failed to diff statistics directories");
+
+        std::fs::remove_dir_all(&directory).ok();
+
+        assert_eq!(report.max_divergence, 0);
+        assert!(report.identical_within_tolerance);
+        assert!(report.per_time_step.iter().all(|entry| entry.max() == 0));
+    }
+
+    /// Diffing against a perturbed copy should report the exact per-time-step divergence
+    /// introduced, and fail the tolerance check once it exceeds the configured tolerance
+    #[test]
+    fn diffing_against_a_perturbed_copy_reports_the_expected_difference() {
+        let directory_a = std::env::temp_dir().join(format!("diff_test_a_{}", std::process::id()));
+        let directory_a = directory_a.to_str().unwrap().to_string() + "/";
+        let directory_b = std::env::temp_dir().join(format!("diff_test_b_{}", std::process::id()));
+        let directory_b = directory_b.to_str().unwrap().to_string() + "/";
+        write_global_stats(std::path::Path::new(&directory_a), &[(0, 100, 0), (1, 95, 5)]);
+        write_global_stats(std::path::Path::new(&directory_b), &[(0, 100, 0), (1, 90, 10)]);
+
+        let report = diff_statistics_directories(&directory_a, &directory_b, 0)
+            .expect("Failed to diff statistics directories");
+        let report_with_tolerance = diff_statistics_directories(&directory_a, &directory_b, 5)
+            .expect("Failed to diff statistics directories");
+
+        std::fs::remove_dir_all(&directory_a).ok();
+        std::fs::remove_dir_all(&directory_b).ok();
+
+        assert_eq!(report.per_time_step[0].max(), 0);
+        assert_eq!(report.per_time_step[1].susceptible, 5);
+        assert_eq!(report.per_time_step[1].infected, 5);
+        assert_eq!(report.max_divergence, 5);
+        assert!(!report.identical_within_tolerance);
+        assert!(report_with_tolerance.identical_within_tolerance);
+    }
+}
@@ -0,0 +1,163 @@
+/*
+ * Epidemic Simulation Using Census Data (ESUCD)
+ * Copyright (c)  2022. Sam Ralph
+ *
+ * This file is part of ESUCD.
+ *
+ * ESUCD is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * ESUCD is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with ESUCD.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+
+use std::path::Path;
+
+use load_census_data::tables::CensusTableNames;
+
+/// One required input file `check_required_files` looked for, and whether it was actually found
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileCheckResult {
+    /// A short label identifying what this file is for, e.g. "Census table: AgeStructure"
+    pub description: String,
+    pub path: String,
+    pub exists: bool,
+}
+
+/// Verifies every file `load_data` would need to load the given region exists and can be opened
+/// for reading, without loading any of them - the census tables, OSM raw buildings file and Output Area shapefile are always
+/// required; the OSM cache file is only checked when `use_cache` is requested, since
+/// `OSMRawBuildings::build_osm_data` otherwise falls back to parsing the raw file regardless of
+/// whether a cache exists
+///
+/// Used by the `--check` execute mode to give fast, actionable feedback about missing data files,
+/// rather than failing minutes into a run once loading actually reaches the missing file
+pub fn check_required_files(
+    area_code: &str,
+    census_directory: &str,
+    osm_filename: &str,
+    osm_cache_filename: &str,
+    shapefile_path: Option<&str>,
+    use_cache: bool,
+) -> Vec<FileCheckResult> {
+    let mut results = Vec::new();
+    let is_bulk = area_code == "England";
+    for table_name in [
+        CensusTableNames::PopulationDensity,
+        CensusTableNames::OccupationCount,
+        CensusTableNames::ResidentialAreaVsWorkplaceArea,
+        CensusTableNames::AgeStructure,
+    ] {
+        let filename =
+            if is_bulk { table_name.get_bulk_filename() } else { table_name.get_filename() };
+        let path = format!("{}tables/{}/{}", census_directory, area_code, filename);
+        results.push(FileCheckResult {
+            description: format!("Census table: {:?}", table_name),
+            exists: is_readable_file(&path),
+            path,
+        });
+    }
+    let shapefile_path = shapefile_path
+        .map(|path| path.to_string())
+        .unwrap_or_else(|| CensusTableNames::OutputAreaMap.get_filename().to_string());
+    results.push(FileCheckResult {
+        description: "Output Area shapefile".to_string(),
+        exists: is_readable_file(&shapefile_path),
+        path: shapefile_path,
+    });
+    let osm_path = census_directory.to_string() + osm_filename;
+    results.push(FileCheckResult {
+        description: "OSM raw buildings file".to_string(),
+        exists: is_readable_file(&osm_path),
+        path: osm_path,
+    });
+    if use_cache {
+        let cache_path = census_directory.to_string() + osm_cache_filename;
+        results.push(FileCheckResult {
+            description: "OSM cache file".to_string(),
+            exists: is_readable_file(&cache_path),
+            path: cache_path,
+        });
+    }
+    results
+}
+
+/// Whether `path` is a regular file that can be opened for reading
+fn is_readable_file(path: &str) -> bool {
+    let path = Path::new(path);
+    path.is_file() && std::fs::File::open(path).is_ok()
+}
+
+/// Returns `Err`, naming every missing file, if any of `results` weren't found - so the `--check`
+/// execute mode can map this straight onto a nonzero exit status
+pub fn all_files_present(results: &[FileCheckResult]) -> Result<(), String> {
+    let missing: Vec<&str> = results
+        .iter()
+        .filter(|result| !result.exists)
+        .map(|result| result.path.as_str())
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Missing required file(s): {}", missing.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// With one required file (the OSM raw buildings file) missing, `check_required_files` should
+    /// report that specific file as missing, and `all_files_present` should turn that into an
+    /// `Err` naming it - which the CLI maps onto a nonzero exit status
+    #[test]
+    fn reports_the_one_specific_file_that_is_missing() {
+        let census_directory =
+            std::env::temp_dir().join(format!("health_check_test_{}", std::process::id()));
+        let tables_directory = census_directory.join("tables").join("testarea");
+        std::fs::create_dir_all(&tables_directory).expect("Failed to create temp tables directory");
+
+        for table_name in [
+            CensusTableNames::PopulationDensity,
+            CensusTableNames::OccupationCount,
+            CensusTableNames::ResidentialAreaVsWorkplaceArea,
+            CensusTableNames::AgeStructure,
+        ] {
+            std::fs::write(tables_directory.join(table_name.get_filename()), "")
+                .expect("Failed to create temp census table");
+        }
+        let shapefile_path = census_directory.join("areas.shp");
+        std::fs::write(&shapefile_path, "").expect("Failed to create temp shapefile");
+        // Deliberately leave the OSM raw buildings file uncreated
+
+        let census_directory_str = census_directory.to_str().unwrap().to_string() + "/";
+        let results = check_required_files(
+            "testarea",
+            &census_directory_str,
+            "raw.osm.pbf",
+            "cache.bin",
+            Some(shapefile_path.to_str().unwrap()),
+            false,
+        );
+
+        std::fs::remove_dir_all(&census_directory).ok();
+
+        let osm_result = results
+            .iter()
+            .find(|result| result.description == "OSM raw buildings file")
+            .expect("Expected an OSM raw buildings file check result");
+        assert!(!osm_result.exists);
+        assert!(results.iter().filter(|result| result.description != "OSM raw buildings file")
+            .all(|result| result.exists));
+
+        let error = all_files_present(&results).expect_err("Expected a missing file to be reported");
+        assert!(error.contains(&osm_result.path));
+    }
+}
@@ -23,8 +23,9 @@ use log::info;
 
 use load_census_data::CensusData;
 use load_census_data::tables::CensusTableNames;
-use osm_data::{OSM_CACHE_FILENAME, OSM_FILENAME, OSMRawBuildings};
-use osm_data::polygon_lookup::PolygonContainer;
+use osm_data::{OSM_CACHE_FILENAME, OSM_FILENAME, OSMRawBuildings, TagClassifiedBuilding};
+use osm_data::polygon_lookup::{CoordinateReferenceSystem, PolygonContainer};
+use sim::disease::{DiseaseModel, SeedingStrategy};
 use sim::simulator::Simulator;
 use sim::simulator_builder::SimulatorBuilder;
 
@@ -73,6 +74,8 @@ pub async fn load_data(
                     use_cache,
                     visualise_building_boundaries,
                     grid_size,
+                    TagClassifiedBuilding::WorkPlace,
+                    true,
                 )
                     .context("Failed to load OSM map")
             };
@@ -85,6 +88,7 @@ pub async fn load_data(
                 PolygonContainer::load_polygons_from_file(
                     CensusTableNames::OutputAreaMap.get_filename(),
                     grid_size,
+                    CoordinateReferenceSystem::DecimalLatLon,
                 )
                     .context("Loading polygons for output areas")
             };
@@ -96,6 +100,16 @@ pub async fn load_data(
         osm_buildings.expect("OSM Buildings Data hasn't been executed!")?,
         output_area_polygons.expect("Output Area Polygons hasn't been executed!")?,
     );
+    osm_data::validate_grid_sizes(
+        osm_buildings
+            .grid_size()
+            .expect("OSM buildings should have their Voronoi diagrams built by now"),
+        output_area_polygons.grid_size,
+    )
+        .context("OSM and output area polygon grids are inconsistent")?;
+    census_data
+        .check_shapefile_vintage(&output_area_polygons.polygons.keys().cloned().collect())
+        .context("Census tables and output area shapefile appear incompatible")?;
     Ok((census_data, osm_buildings, output_area_polygons))
 }
 
@@ -106,8 +120,12 @@ pub async fn load_data_and_init_sim(
     allow_downloads: bool,
     visualise_building_boundaries: bool,
     grid_size: i32,
+    initial_infected_count: Option<u32>,
+    disease_name: Option<String>,
+    building_assignment_cache_dir: Option<String>,
 ) -> anyhow::Result<Simulator> {
     info!("Loading data from disk...");
+    let osm_file_path = census_directory.clone() + OSM_FILENAME;
     let (census_data, osm_buildings, output_area_polygons) = load_data(
         area.to_string(),
         census_directory,
@@ -120,6 +138,20 @@ pub async fn load_data_and_init_sim(
     let mut sim = SimulatorBuilder::new(area, census_data, osm_buildings, output_area_polygons)
         .context("Failed to initialise sim")
         .unwrap();
+    if let Some(cache_dir) = building_assignment_cache_dir {
+        sim.set_building_assignment_cache(
+            cache_dir,
+            vec![osm_file_path, CensusTableNames::OutputAreaMap.get_filename().to_string()],
+        );
+    }
+    if let Some(disease_name) = disease_name {
+        let steps_per_day = sim.disease_model.steps_per_day;
+        sim.disease_model = DiseaseModel::from_name(&disease_name, steps_per_day)
+            .context("Failed to resolve --disease argument")?;
+    }
+    if let Some(initial_infected_count) = initial_infected_count {
+        sim.disease_model.seeding_strategy = SeedingStrategy::Count(initial_infected_count);
+    }
     sim.build().context("Failed to initialise sim").unwrap();
     Ok(Simulator::from(sim))
 }
@@ -33,13 +33,18 @@ use sanitize_filename::{Options, sanitize, sanitize_with_options};
 
 use load_census_data::CensusData;
 use load_census_data::tables::CensusTableNames;
-use osm_data::{OSM_CACHE_FILENAME, OSM_FILENAME, OSMRawBuildings};
+use osm_data::{OSMRawBuildings, TagClassifiedBuilding};
 use visualisation::image_export::DrawingRecord;
 
+use crate::health_check::{all_files_present, check_required_files};
 use crate::load_data::load_data;
 use crate::load_data::load_data_and_init_sim;
+use crate::region_config::{Arguments, RegionConfig};
 
+mod diff;
+mod health_check;
 mod load_data;
+mod region_config;
 mod visualise;
 
 //use visualisation::citizen_connections::{connected_groups, draw_graph};
@@ -72,17 +77,25 @@ async fn main() -> anyhow::Result<()> {
         .usage("run \"area_code\" --directory<data_directory> --mode
             \n    The area code which to use must be specified (area)\
             \n    The directory specifying where to store data must be specified (directory)\
-            \n    There are 4 modes available to choose from:\
+            \n    There are 5 modes available to choose from:\
             \n        Download    ->      Downloads and Verifies the data files for a simulation\
             \n        Resume      ->      Used to resume a table download, it if failed for some reason\
+            \n        Check       ->      Verifies all required data files exist and are readable, without loading any of them\
             \n        Simulate    ->      Starts a text only logging simulation for the given area\
             \n        Render      ->      Starts a simulation with a live view of what is happening via a rendering engine\n")
         .arg(
             Arg::with_name("data_directory")
                 .short("d")
                 .long("directory")
-                .help("The directory data files are located")
-                .required(true)
+                .help("The directory data files are located. May instead be set via --region-config")
+                .require_equals(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("region-config")
+                .long("region-config")
+                .help("Path to a region.toml bundling the area code, census directory, OSM filenames, \
+                shapefile path, grid size, and download permission for a region. CLI args override its values")
                 .require_equals(true)
                 .takes_value(true),
         )
@@ -124,9 +137,8 @@ async fn main() -> anyhow::Result<()> {
         )
         .arg(
             Arg::with_name("area")
-                .help("Specifies the area code to simulate")
-                .takes_value(true)
-                .required(true),
+                .help("Specifies the area code to simulate. May instead be set via --region-config")
+                .takes_value(true),
         )
         .arg(
             Arg::with_name("table")
@@ -151,12 +163,19 @@ async fn main() -> anyhow::Result<()> {
                 .requires_all(&["table", "area"])
                 .conflicts_with_all(&["simulate", "render", "download"]),
         )
+        .arg(
+            Arg::with_name("check")
+                .long("check")
+                .help("Verifies all required data files (census tables, OSM raw/cache, shapefile) \
+                exist and are readable for the given area, without loading any of them")
+                .requires("area")
+                .conflicts_with_all(&["simulate", "render", "download", "resume"]),
+        )
         .arg(Arg::with_name("grid-size")
             .require_equals(true)
             .long("grid-size")
             .takes_value(true)
-            .help("Specifies the size of the Voronoi Lookup Grids")
-            .required(true))
+            .help("Specifies the size of the Voronoi Lookup Grids. May instead be set via --region-config"))
         .arg(
             Arg::with_name("output_name")
                 .long("output_name")
@@ -164,21 +183,108 @@ async fn main() -> anyhow::Result<()> {
                 .takes_value(true)
                 .require_equals(true)
         )
+        .arg(
+            Arg::with_name("initial-infected")
+                .long("initial-infected")
+                .help("Specifies the number of Citizens to infect at the start of the simulation")
+                .takes_value(true)
+                .require_equals(true)
+        )
+        .arg(
+            Arg::with_name("disease")
+                .long("disease")
+                .help("Specifies the disease preset to simulate: covid, influenza or measles (defaults to covid)")
+                .takes_value(true)
+                .require_equals(true)
+        )
+        .arg(
+            Arg::with_name("live")
+                .long("live")
+                .help("During --simulate, serves a live WebP choropleth of the current infection \
+                state over HTTP at the given address (e.g. 127.0.0.1:8080), so the epidemic can be \
+                watched in a browser - requires building with `--features webp`")
+                .takes_value(true)
+                .require_equals(true)
+                .requires("simulate")
+        )
+        .arg(
+            Arg::with_name("building-assignment-cache-dir")
+                .long("building-assignment-cache-dir")
+                .help("Caches the building-to-output-area assignment under this directory, keyed by \
+                the OSM and shapefile inputs, reloading it on later builds of the same inputs instead \
+                of recomputing it")
+                .takes_value(true)
+                .require_equals(true)
+        )
+        .arg(
+            Arg::with_name("diff")
+                .long("diff")
+                .help("Compares two statistics output directories' global_stats.json files, time step \
+                by time step, reporting the per-compartment divergence at each step and the maximum \
+                divergence overall")
+                .takes_value(true)
+                .number_of_values(2)
+                .value_names(&["dir_a", "dir_b"])
+                .conflicts_with_all(&["simulate", "render", "download", "resume", "check", "area"]),
+        )
+        .arg(
+            Arg::with_name("diff-tolerance")
+                .long("diff-tolerance")
+                .require_equals(true)
+                .takes_value(true)
+                .help("The largest per-compartment count difference (inclusive) --diff still treats \
+                as identical (defaults to 0)")
+                .requires("diff"),
+        )
         .get_matches();
 
-    let directory = matches
-        .value_of("data_directory")
-        .expect("Missing data directory argument");
-    let census_directory = directory.to_owned() + "/";
-    let area = matches.value_of("area").expect("Missing area argument");
+    if let Some(mut directories) = matches.values_of("diff") {
+        let directory_a = directories.next().expect("Missing dir_a argument");
+        let directory_b = directories.next().expect("Missing dir_b argument");
+        let tolerance = matches
+            .value_of("diff-tolerance")
+            .map(|value| value.parse().expect("diff-tolerance is not an integer!"))
+            .unwrap_or(0);
+        let report = diff::diff_statistics_directories(directory_a, directory_b, tolerance)
+            .context("Failed to diff statistics output directories")?;
+        for entry in &report.per_time_step {
+            info!(
+                "Time step {: >4}: susceptible={} exposed={} infected={} recovered={} vaccinated={} asymptomatic={} deceased={}",
+                entry.time_step, entry.susceptible, entry.exposed, entry.infected, entry.recovered,
+                entry.vaccinated, entry.asymptomatic, entry.deceased
+            );
+        }
+        info!("Maximum divergence: {}", report.max_divergence);
+        if report.identical_within_tolerance {
+            info!("Directories are identical within tolerance {}", tolerance);
+            return Ok(());
+        }
+        error!("Directories diverge beyond tolerance {}", tolerance);
+        return Err(anyhow::anyhow!(
+            "Statistics directories diverge by up to {}, which exceeds tolerance {}",
+            report.max_divergence,
+            tolerance
+        ));
+    }
+
+    let region_config = matches
+        .value_of("region-config")
+        .map(|filename| RegionConfig::load_from_file(filename).expect("Failed to load region config"));
+    let arguments = Arguments::resolve(&matches, region_config);
+    let census_directory = arguments.census_directory;
+    let area = arguments.area_code.as_str();
     let use_cache = matches.is_present("use-cache");
     let visualise_building_boundaries = matches.is_present("visualise-building-boundaries");
-    let allow_downloads = !matches.is_present("disallow-download");
-    let grid_size = matches
-        .value_of("grid-size")
-        .expect("Missing grid-size argument")
-        .parse()
-        .expect("grid-size is not an integer!");
+    let allow_downloads = arguments.allow_downloads;
+    let grid_size = arguments.grid_size;
+
+    let initial_infected_count = matches
+        .value_of("initial-infected")
+        .map(|value| value.parse().expect("initial-infected is not an integer!"));
+    let disease_name = matches.value_of("disease").map(|value| value.to_string());
+    let building_assignment_cache_dir = matches
+        .value_of("building-assignment-cache-dir")
+        .map(|value| value.to_string());
 
     let mut output_directory = "statistics_output/v1.7/".to_string();
     if let Some(name) = matches.value_of("output_name") {
@@ -209,16 +315,41 @@ async fn main() -> anyhow::Result<()> {
         CensusData::resume_download(&census_directory, area, table, row)
             .await
             .context("Failed to resume download of table")
+    } else if matches.is_present("check") {
+        info!("Checking required data files for area '{}'", area);
+        let results = check_required_files(
+            area,
+            &census_directory,
+            &arguments.osm_filename,
+            &arguments.osm_cache_filename,
+            arguments.shapefile_path.as_deref(),
+            use_cache,
+        );
+        for result in &results {
+            if result.exists {
+                info!("[OK]      {}: {}", result.description, result.path);
+            } else {
+                error!("[MISSING] {}: {}", result.description, result.path);
+            }
+        }
+        if let Err(message) = all_files_present(&results) {
+            error!("{}", message);
+            return Err(anyhow::anyhow!(message));
+        }
+        info!("All required data files are present");
+        Ok(())
     } else if matches.is_present("render") {
         unimplemented!("Cannot use renderer on current Rust version (2018")
     } else if matches.is_present("visualise-buildings") {
         info!("Visualising buildings");
         let osm_buildings = OSMRawBuildings::build_osm_data(
-            census_directory.to_string() + OSM_FILENAME,
-            census_directory + OSM_CACHE_FILENAME,
+            census_directory.to_string() + &arguments.osm_filename,
+            census_directory + &arguments.osm_cache_filename,
             use_cache,
             visualise_building_boundaries,
             grid_size,
+            TagClassifiedBuilding::WorkPlace,
+            true,
         )?
             .building_locations
             .drain()
@@ -239,6 +370,9 @@ async fn main() -> anyhow::Result<()> {
             allow_downloads,
             false,
             grid_size,
+            initial_infected_count,
+            disease_name,
+            building_assignment_cache_dir,
         )
             .await?;
 
@@ -297,12 +431,31 @@ async fn main() -> anyhow::Result<()> {
             allow_downloads,
             visualise_building_boundaries,
             grid_size,
+            initial_infected_count,
+            disease_name,
+            building_assignment_cache_dir,
         )
             .await?;
         info!(
             "Finished loading data and Initialising  simulator in {:.2}",
             total_time.elapsed().as_secs_f64()
         );
+        let live_feed_address = matches.value_of("live").map(|value| value.to_string());
+        #[cfg(feature = "webp")]
+        if let Some(address) = live_feed_address {
+            info!("Serving a live infection feed at http://{}/frame.webp", address);
+            if let Err(e) = visualisation::live_feed::run_with_live_feed(sim, output_directory, &address) {
+                error!("{}", e);
+            }
+            info!("Finished in {:?}", total_time.elapsed());
+            return Ok(());
+        }
+        #[cfg(not(feature = "webp"))]
+        if live_feed_address.is_some() {
+            return Err(anyhow::anyhow!(
+                "--live requires building run with `--features webp`"
+            ));
+        }
         if let Err(e) = sim.simulate(output_directory) {
             error!("{}", e);
             //sim.error_dump_json().expect("Failed to create core dump!");
@@ -0,0 +1,192 @@
+/*
+ * Epidemic Simulation Using Census Data (ESUCD)
+ * Copyright (c)  2022. Sam Ralph
+ *
+ * This file is part of ESUCD.
+ *
+ * ESUCD is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * ESUCD is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with ESUCD.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+
+use anyhow::Context;
+use clap::ArgMatches;
+use serde::Deserialize;
+
+use osm_data::{OSM_CACHE_FILENAME, OSM_FILENAME};
+
+/// The contents of a `region.toml` file, bundling the paths and parameters needed to run a single
+/// region so they don't have to be respecified as CLI args and env vars every run
+///
+/// Every field is optional, so a region file only needs to override the values it cares about -
+/// anything left unset falls back to the CLI argument, and then to the built-in default, in `Arguments::resolve`
+#[derive(Debug, Clone, PartialEq, Deserialize, Default)]
+pub struct RegionConfig {
+    pub area_code: Option<String>,
+    pub census_directory: Option<String>,
+    pub osm_filename: Option<String>,
+    pub osm_cache_filename: Option<String>,
+    pub shapefile_path: Option<String>,
+    pub grid_size: Option<i32>,
+    pub allow_downloads: Option<bool>,
+}
+
+impl RegionConfig {
+    /// Parses a `region.toml` file at the given path
+    pub fn load_from_file(filename: &str) -> anyhow::Result<RegionConfig> {
+        let contents = std::fs::read_to_string(filename)
+            .context(format!("Failed to read region config file: {}", filename))?;
+        toml::from_str(&contents)
+            .context(format!("Failed to parse region config file: {}", filename))
+    }
+}
+
+/// The fully resolved set of paths and parameters needed to run a region, merged from a
+/// `RegionConfig` file and the CLI args, with the CLI args taking priority
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arguments {
+    pub area_code: String,
+    pub census_directory: String,
+    pub osm_filename: String,
+    pub osm_cache_filename: String,
+    pub shapefile_path: Option<String>,
+    pub grid_size: i32,
+    pub allow_downloads: bool,
+}
+
+impl Arguments {
+    /// Merges a `RegionConfig` (if one was loaded) with the CLI `matches`, with any value present
+    /// on the command line overriding the region file, and the region file overriding the defaults
+    pub fn resolve(matches: &ArgMatches, region_config: Option<RegionConfig>) -> Arguments {
+        let region_config = region_config.unwrap_or_default();
+        let area_code = matches
+            .value_of("area")
+            .map(String::from)
+            .or(region_config.area_code)
+            .expect("Missing area argument");
+        let census_directory = matches
+            .value_of("data_directory")
+            .map(|directory| directory.to_owned() + "/")
+            .or(region_config.census_directory)
+            .expect("Missing data directory argument");
+        let grid_size = matches
+            .value_of("grid-size")
+            .map(|value| value.parse().expect("grid-size is not an integer!"))
+            .or(region_config.grid_size)
+            .expect("Missing grid-size argument");
+        let allow_downloads = if matches.is_present("disallow-download") {
+            false
+        } else {
+            region_config.allow_downloads.unwrap_or(true)
+        };
+        Arguments {
+            area_code,
+            census_directory,
+            osm_filename: region_config
+                .osm_filename
+                .unwrap_or_else(|| OSM_FILENAME.to_string()),
+            osm_cache_filename: region_config
+                .osm_cache_filename
+                .unwrap_or_else(|| OSM_CACHE_FILENAME.to_string()),
+            shapefile_path: region_config.shapefile_path,
+            grid_size,
+            allow_downloads,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use clap::{App, Arg};
+
+    use super::*;
+
+    /// Mirrors the subset of `main`'s CLI args that `Arguments::resolve` reads
+    fn test_app<'a, 'b>() -> App<'a, 'b> {
+        App::new("test")
+            .arg(Arg::with_name("data_directory").short("d").takes_value(true))
+            .arg(Arg::with_name("disallow-download").long("disallow-download"))
+            .arg(Arg::with_name("grid-size").long("grid-size").takes_value(true))
+            .arg(Arg::with_name("area").takes_value(true))
+    }
+
+    fn write_temp_region_file(contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("region_config_test_{}.toml", std::process::id()));
+        let mut file = std::fs::File::create(&path).expect("Failed to create temp region file");
+        file.write_all(contents.as_bytes())
+            .expect("Failed to write temp region file");
+        path
+    }
+
+    #[test]
+    fn parses_a_region_file_into_a_matching_region_config() {
+        let path = write_temp_region_file(
+            r#"
+            area_code = "E00000001"
+            census_directory = "data/yorkshire/"
+            osm_filename = "OSM/yorkshire.osm.pbf"
+            osm_cache_filename = "OSM/yorkshire_cache"
+            shapefile_path = "data/yorkshire/areas.shp"
+            grid_size = 100
+            allow_downloads = false
+            "#,
+        );
+        let config = RegionConfig::load_from_file(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(
+            config,
+            RegionConfig {
+                area_code: Some("E00000001".to_string()),
+                census_directory: Some("data/yorkshire/".to_string()),
+                osm_filename: Some("OSM/yorkshire.osm.pbf".to_string()),
+                osm_cache_filename: Some("OSM/yorkshire_cache".to_string()),
+                shapefile_path: Some("data/yorkshire/areas.shp".to_string()),
+                grid_size: Some(100),
+                allow_downloads: Some(false),
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_takes_values_from_the_region_file_when_no_cli_override_is_given() {
+        let matches = test_app().get_matches_from(vec!["test", "E00000002"]);
+        let region_config = RegionConfig {
+            area_code: Some("E00000001".to_string()),
+            census_directory: Some("data/yorkshire/".to_string()),
+            grid_size: Some(100),
+            ..RegionConfig::default()
+        };
+        let arguments = Arguments::resolve(&matches, Some(region_config));
+        // The positional "area" argument was supplied on the CLI, so it wins over the region file
+        assert_eq!(arguments.area_code, "E00000002");
+        // Everything else falls back to the region file, since no CLI override was given
+        assert_eq!(arguments.census_directory, "data/yorkshire/");
+        assert_eq!(arguments.grid_size, 100);
+        assert!(arguments.allow_downloads);
+    }
+
+    #[test]
+    fn cli_disallow_download_overrides_the_region_file() {
+        let matches =
+            test_app().get_matches_from(vec!["test", "--disallow-download", "E00000002"]);
+        let region_config = RegionConfig {
+            census_directory: Some("data/yorkshire/".to_string()),
+            grid_size: Some(100),
+            allow_downloads: Some(true),
+            ..RegionConfig::default()
+        };
+        let arguments = Arguments::resolve(&matches, Some(region_config));
+        assert!(!arguments.allow_downloads);
+    }
+}
@@ -17,31 +17,147 @@
  * along with ESUCD.  If not, see <https://www.gnu.org/licenses/>.
  *
  */
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use enum_map::EnumMap;
 use num_format::Locale;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::models::citizen::OccupationType;
 
 /// The directory to store debug dumps in
 pub const DEBUG_DUMP_DIRECTORY: &str = "debug_dumps/";
 /// If true, will generate the debug dumps
 pub const CREATE_DEBUG_DUMPS: bool = false;
 
+/// If true, `Simulator`'s top level RNG records every word it draws to `RNG_LOG_PATH` via
+/// `crate::rng_log::RecordingRng` - see that module for replaying the log to pin nondeterminism
+/// bugs. Writing a log line per draw has a real performance cost, so this stays off by default
+pub const RNG_LOG_ENABLED: bool = false;
+/// Where `Simulator`'s top level RNG log is written, when `RNG_LOG_ENABLED` is set
+pub const RNG_LOG_PATH: &str = "debug_dumps/rng_log.txt";
+
+/// The default `SeedingStrategy::Count` used for `DiseaseModel::seeding_strategy`, unless overridden
+/// (e.g. via the `--initial-infected` CLI argument)
 pub const STARTING_INFECTED_COUNT: u32 = 10;
 /// The amount of floor space in m^2 per Workplace building
 pub const WORKPLACE_BUILDING_SIZE: u16 = 1000;
 pub const HOUSEHOLD_SIZE: u16 = 4;
 pub const MIN_WORKPLACE_OCCUPANT_COUNT: u32 = 20;
+/// The default `SimulatorBuilder::workplace_building_overcapacity`, unless overridden
+pub const DEFAULT_WORKPLACE_BUILDING_OVERCAPACITY: f64 = 1.1;
+/// The default `SimulatorBuilder::average_class_size`, unless overridden
+pub const DEFAULT_AVERAGE_CLASS_SIZE: f64 = 26.6;
+/// The default `SimulatorBuilder::average_office_size`, unless overridden
+pub const DEFAULT_AVERAGE_OFFICE_SIZE: usize = 12;
+/// The default `SimulatorBuilder::prune_isolated_citizens`, unless overridden
+pub const DEFAULT_PRUNE_ISOLATED_CITIZENS: bool = false;
+/// The default `SimulatorBuilder::population_scale`, unless overridden
+pub const DEFAULT_POPULATION_SCALE: f64 = 1.0;
+/// The default `DiseaseModel::commute_window`, unless overridden - a single time step before work
+/// starts or ends, matching the original (non-configurable) commute behaviour
+pub const DEFAULT_COMMUTE_WINDOW: u32 = 1;
+/// The default `DiseaseModel::mask_adoption_ramp_up_steps`, unless overridden - a week at the
+/// standard hourly (24 steps per day) resolution
+pub const DEFAULT_MASK_ADOPTION_RAMP_UP_STEPS: u32 = 7 * 24;
+/// The default `DiseaseModel::vaccine_efficacy`, unless overridden
+pub const DEFAULT_VACCINE_EFFICACY: f64 = 0.9;
+/// The default `SimulatorBuilder::max_workplace_search_attempts`, unless overridden
+pub const DEFAULT_MAX_WORKPLACE_SEARCH_ATTEMPTS: u32 = 50;
+/// The default `SimulatorBuilder::min_student_age`, unless overridden - the typical age of first
+/// entry into compulsory education in England
+pub const DEFAULT_MIN_STUDENT_AGE: u16 = 5;
+/// The default `DiseaseModel::community_transmission_rate`, unless overridden - zero, so a run
+/// sees no background community transmission unless explicitly configured
+pub const DEFAULT_COMMUNITY_TRANSMISSION_RATE: f64 = 0.0;
+/// The default `SimulatorBuilder::workplace_room_size`, unless overridden - once a Workplace's
+/// occupants exceed this, `Workplace::find_exposures` contains exposures within a room rather than
+/// mixing the whole building
+pub const DEFAULT_WORKPLACE_ROOM_SIZE: u32 = 30;
+/// The default `SimulatorBuilder::statistics_sampling_interval`, unless overridden - records full
+/// compartment counts every time step, matching the original (non-configurable) behaviour
+pub const DEFAULT_STATISTICS_SAMPLING_INTERVAL: u32 = 1;
 
 /// How often to print debug statements
 pub const DEBUG_ITERATION_PRINT: usize = 50;
 
+/// If true, `Simulator::step` calls `validate_invariants` after every time step in debug builds, to
+/// catch citizen/area lookup corruption as soon as it happens rather than as a confusing fingerprint
+/// mismatch many steps later. Has no effect in release builds
+pub const VALIDATE_INVARIANTS_AFTER_STEP: bool = true;
+
+/// If true, `WarningAggregator` logs every individual failure as it is recorded, in addition to
+/// the aggregated summary line - useful when tracking down which specific Citizen/Output Area is
+/// failing, but floods the logs on an England-scale run so it defaults to off
+pub const VERBOSE_BUILD_WARNINGS: bool = false;
+
+/// The number of recent `StateSnapshot`s `Simulator::state_history` retains, so a panic mid-run can
+/// be diagnosed from the trajectory leading up to it - `0` disables history retention entirely
+pub const STATE_HISTORY_CAPACITY: usize = 20;
+
 pub const PUBLIC_TRANSPORT_PERCENTAGE: f64 = 0.2;
 pub const BUS_CAPACITY: u32 = 20;
 pub const MAX_STUDENT_AGE: u16 = 18;
 // TODO Figure out how to get global SystemLocale::default()
 pub const NUMBER_FORMATTING: Locale = Locale::en_GB; // SystemLocale::default().expect("Failed to determine the locale format to use for formatting numbers");
 
+/// The default `DiseaseModel::remote_work_probability`, unless overridden - office-based
+/// occupations are given a non-trivial chance of being deliberately home-based, while occupations
+/// that require physical presence (e.g. skilled trades, machine operatives) are kept at `0.0`
+pub fn default_remote_work_probability() -> EnumMap<OccupationType, f64> {
+    let mut probability = EnumMap::default();
+    probability[OccupationType::Manager] = 0.3;
+    probability[OccupationType::Professional] = 0.35;
+    probability[OccupationType::Technical] = 0.2;
+    probability[OccupationType::Administrative] = 0.25;
+    probability[OccupationType::Sales] = 0.05;
+    probability
+}
+
+/// Builds a Rng seeded purely from `(global_seed, area_index)`, so each Output Area's stochastic
+/// decisions are reproducible regardless of how many threads rayon uses to schedule the work
+pub fn deterministic_area_rng(global_seed: u64, area_index: u32) -> StdRng {
+    let mut hasher = DefaultHasher::new();
+    global_seed.hash(&mut hasher);
+    area_index.hash(&mut hasher);
+    StdRng::seed_from_u64(hasher.finish())
+}
+
 pub fn get_memory_usage() -> anyhow::Result<String> {
     Ok(format!(
         "{:.2} GB",
         (procinfo::pid::statm_self()?.size * page_size::get() / 1024 / 1024) as f64 / 1024.0
     ))
 }
+
+/// Returns how much physical memory is currently free on this machine, in bytes, by reading
+/// `/proc/meminfo` - used to judge whether `SimulatorBuilder::estimated_memory_bytes` will fit
+/// before committing to a full run
+pub fn get_available_system_memory_bytes() -> anyhow::Result<u64> {
+    Ok(procinfo::meminfo()?.free * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::RngCore;
+
+    use crate::config::deterministic_area_rng;
+
+    /// The same seed and area index must always produce the same Rng, regardless of call order,
+    /// so results don't depend on how rayon schedules Output Areas across threads
+    #[test]
+    fn same_seed_and_area_are_deterministic() {
+        let mut first = deterministic_area_rng(42, 7);
+        let mut second = deterministic_area_rng(42, 7);
+        assert_eq!(first.next_u64(), second.next_u64());
+    }
+
+    #[test]
+    fn different_areas_diverge() {
+        let mut area_a = deterministic_area_rng(42, 1);
+        let mut area_b = deterministic_area_rng(42, 2);
+        assert_ne!(area_a.next_u64(), area_b.next_u64());
+    }
+}
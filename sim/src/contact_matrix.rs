@@ -0,0 +1,95 @@
+/*
+ * Epidemic Simulation Using Census Data (ESUCD)
+ * Copyright (c)  2022. Sam Ralph
+ *
+ * This file is part of ESUCD.
+ *
+ * ESUCD is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * ESUCD is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with ESUCD.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+
+use std::path::Path;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+/// The width, in years, of each age bracket used by an [`AgeContactMatrix`]
+pub const AGE_GROUP_WIDTH: u16 = 10;
+
+/// A contact-rate matrix between age groups (e.g. as published by the POLYMOD study), used to weigh how
+/// likely Citizens of differing ages are to mix within a shared building
+///
+/// Age groups are `AGE_GROUP_WIDTH`-year brackets: group 0 is ages 0-9, group 1 is 10-19, etc
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgeContactMatrix {
+    /// `contacts[a][b]` is the relative contact rate between age group `a` and age group `b`
+    contacts: Vec<Vec<f64>>,
+}
+
+impl AgeContactMatrix {
+    /// Builds a contact matrix directly from an already-loaded set of rates, where `contacts[a][b]`
+    /// is the relative contact rate between age group `a` and age group `b`
+    pub fn new(contacts: Vec<Vec<f64>>) -> AgeContactMatrix {
+        AgeContactMatrix { contacts }
+    }
+    /// Returns the age group bracket that the given age falls into
+    pub fn age_group(age: u16) -> usize {
+        (age / AGE_GROUP_WIDTH) as usize
+    }
+    /// Loads a square contact matrix from a headerless CSV file, where `contacts[a][b]` is read from
+    /// row `a`, column `b`
+    pub fn load_from_csv(path: impl AsRef<Path>) -> anyhow::Result<AgeContactMatrix> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_path(path)
+            .context("Failed to open age contact matrix CSV")?;
+        let mut contacts = Vec::new();
+        for record in reader.records() {
+            let record = record.context("Failed to read age contact matrix row")?;
+            let row = record
+                .iter()
+                .map(|value| {
+                    value
+                        .parse::<f64>()
+                        .context("Failed to parse age contact matrix value")
+                })
+                .collect::<anyhow::Result<Vec<f64>>>()?;
+            contacts.push(row);
+        }
+        Ok(AgeContactMatrix { contacts })
+    }
+    /// Returns the relative contact rate between the two given ages, or `0.0` if either age's group
+    /// falls outside the loaded matrix
+    pub fn contact_rate(&self, age_a: u16, age_b: u16) -> f64 {
+        let (a, b) = (Self::age_group(age_a), Self::age_group(age_b));
+        self.contacts
+            .get(a)
+            .and_then(|row| row.get(b))
+            .copied()
+            .unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contact_matrix::AgeContactMatrix;
+
+    #[test]
+    fn contact_rate_looks_up_correct_age_groups() {
+        let matrix = AgeContactMatrix::new(vec![vec![1.0, 0.0], vec![0.0, 1.0]]);
+        assert_eq!(matrix.contact_rate(5, 8), 1.0);
+        assert_eq!(matrix.contact_rate(5, 15), 0.0);
+        // Ages 25 and 35 fall in groups 2 and 3, outside the 2x2 matrix
+        assert_eq!(matrix.contact_rate(25, 35), 0.0);
+    }
+}
@@ -24,12 +24,19 @@ use std::fs::File;
 use std::hash::Hash;
 use std::io::{BufWriter, Write};
 
-use log::error;
-use serde::{Deserialize, Serialize};
+use enum_map::EnumMap;
+use log::{error, warn};
+use rand::distributions::Distribution;
+use rand::{Rng, RngCore};
+use rand_distr::Gamma;
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::to_writer;
 use uuid::Uuid;
 
-use crate::interventions::MaskStatus;
+use crate::config::{default_remote_work_probability, BUS_CAPACITY, DEFAULT_COMMUNITY_TRANSMISSION_RATE, DEFAULT_COMMUTE_WINDOW, DEFAULT_MASK_ADOPTION_RAMP_UP_STEPS, DEFAULT_VACCINE_EFFICACY, PUBLIC_TRANSPORT_PERCENTAGE, STARTING_INFECTED_COUNT};
+use crate::contact_matrix::AgeContactMatrix;
+use crate::interventions::{MaskStatus, VaccinationStrategy};
+use crate::models::citizen::OccupationType;
 use crate::models::ID;
 
 #[derive(PartialEq, Debug, Serialize, Clone)]
@@ -37,36 +44,91 @@ pub enum DiseaseStatus {
     Susceptible,
     /// The amount of steps(hours) the citizen has been exposed for
     Exposed(u16),
-    /// The amount of steps(hours) the citizen has been infected for
-    Infected(u16),
+    Infected {
+        /// The amount of steps(hours) the citizen has been infected for
+        elapsed: u16,
+        /// The amount of steps(hours) this citizen's infectious period lasts, sampled once at the
+        /// moment of infection from `DiseaseModel::infectious_period_dispersion` - see
+        /// `sample_infectious_period`
+        duration: u16,
+    },
     Recovered,
-    Vaccinated,
+    /// The amount of steps(hours) since the citizen was vaccinated, used to calculate waning efficacy
+    Vaccinated(u32),
+    /// The citizen has died from the disease, and is retained only for final-size accounting -
+    /// they take no further part in the simulation
+    Deceased,
+}
+
+/// Draws a per-Citizen infectious period from a Gamma(`dispersion`, `mean_infected_time / dispersion`)
+/// distribution, which has a mean of `mean_infected_time` regardless of `dispersion` - so a low
+/// `dispersion` widens the spread of infectious periods around the mean, while a high `dispersion`
+/// converges towards every Citizen sharing the same fixed `mean_infected_time` duration
+///
+/// Returns `mean_infected_time` unchanged (the old fixed-duration behaviour) when `dispersion` is
+/// `None`, or when it's not a valid Gamma shape
+pub fn sample_infectious_period(
+    mean_infected_time: u16,
+    dispersion: Option<f64>,
+    rng: &mut dyn RngCore,
+) -> u16 {
+    dispersion
+        .and_then(|dispersion| Gamma::new(dispersion, mean_infected_time as f64 / dispersion).ok())
+        .map(|gamma| gamma.sample(rng).round() as u16)
+        .unwrap_or(mean_infected_time)
 }
 
 impl DiseaseStatus {
+    /// `infection_count` is how many times (including this one) the Citizen has been infected -
+    /// used to apply `DiseaseModel::reinfection_death_rate_multiplier` on a second-or-later infection
+    ///
+    /// `age` selects the Citizen's base death rate from `DiseaseModel::age_mortality_curve` when one
+    /// is configured, falling back to the flat `DiseaseModel::death_rate` otherwise
     pub fn execute_time_step(
         status: &DiseaseStatus,
         disease_model: &DiseaseModel,
+        infection_count: u32,
+        age: u16,
+        rng: &mut dyn RngCore,
     ) -> DiseaseStatus {
         match status {
             DiseaseStatus::Susceptible => DiseaseStatus::Susceptible,
             DiseaseStatus::Exposed(time) => {
                 if disease_model.exposed_time <= *time {
-                    DiseaseStatus::Infected(0)
+                    let duration = sample_infectious_period(
+                        disease_model.infected_time,
+                        disease_model.infectious_period_dispersion,
+                        rng,
+                    );
+                    DiseaseStatus::Infected { elapsed: 0, duration }
                 } else {
                     DiseaseStatus::Exposed(time + 1)
                 }
             }
-            DiseaseStatus::Infected(time) => {
-                if disease_model.infected_time <= *time {
-                    DiseaseStatus::Recovered
+            DiseaseStatus::Infected { elapsed, duration } => {
+                if *duration <= *elapsed {
+                    let base_death_rate = match &disease_model.age_mortality_curve {
+                        Some(curve) => curve.ifr_for_age(age),
+                        None => disease_model.death_rate,
+                    };
+                    let death_rate = if infection_count > 1 {
+                        base_death_rate * disease_model.reinfection_death_rate_multiplier
+                    } else {
+                        base_death_rate
+                    };
+                    if rng.gen::<f64>() < death_rate {
+                        DiseaseStatus::Deceased
+                    } else {
+                        DiseaseStatus::Recovered
+                    }
                 } else {
-                    DiseaseStatus::Infected(time + 1)
+                    DiseaseStatus::Infected { elapsed: elapsed + 1, duration: *duration }
                 }
             }
             DiseaseStatus::Recovered => DiseaseStatus::Recovered,
-            // TODO Allow "break through" infections
-            DiseaseStatus::Vaccinated => DiseaseStatus::Vaccinated,
+            // Break through infections are handled separately, in `Citizen::expose`
+            DiseaseStatus::Vaccinated(time) => DiseaseStatus::Vaccinated(time + 1),
+            DiseaseStatus::Deceased => DiseaseStatus::Deceased,
         }
     }
 }
@@ -79,77 +141,685 @@ impl Display for DiseaseStatus {
             DiseaseStatus::Exposed(since) => {
                 write!(f, "Exposed since: {}", since)
             }
-            DiseaseStatus::Infected(since) => {
-                write!(f, "Infected since: {}", since)
+            DiseaseStatus::Infected { elapsed, .. } => {
+                write!(f, "Infected since: {}", elapsed)
             }
             DiseaseStatus::Recovered => {
-                write!(f, "Recovered/Died")
+                write!(f, "Recovered")
+            }
+            DiseaseStatus::Vaccinated(since) => {
+                write!(f, "Vaccinated since: {}", since)
+            }
+            DiseaseStatus::Deceased => {
+                write!(f, "Deceased")
             }
-            DiseaseStatus::Vaccinated => {
-                write!(f, "Vaccinated")
+        }
+    }
+}
+
+
+/// Strategies for determining how many Citizens are infected at the start of the simulation, to
+/// seed the epidemic
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SeedingStrategy {
+    /// Infect this many distinct Citizens, chosen uniformly at random across all Output Areas
+    ///
+    /// An absolute count is meaningless when comparing regions of differing population size -
+    /// see `Fraction` for seeding that scales with the population
+    Count(u32),
+    /// Infect `ceil(fraction * total_population)` Citizens, so the seed scales with the population size
+    ///
+    /// `fraction` is expected to be within `[0.0, 1.0]`; out of range values are clamped by `resolve`
+    Fraction(f64),
+    /// Infect this many distinct Citizens, chosen with probability weighted by their contact degree,
+    /// rather than uniformly at random
+    ///
+    /// Models outbreaks seeded among highly-connected "hub" individuals (e.g. superspreader
+    /// introductions), instead of a uniform-at-random seed - see
+    /// `SimulatorBuilder::apply_initial_infections` for how degree is approximated
+    WeightedByContactDegree(u32),
+}
+
+impl SeedingStrategy {
+    /// Resolves this strategy into an absolute number of Citizens to infect, given the total population size
+    pub fn resolve(&self, population_size: usize) -> u32 {
+        match self {
+            SeedingStrategy::Count(count) => *count,
+            SeedingStrategy::WeightedByContactDegree(count) => *count,
+            SeedingStrategy::Fraction(fraction) => {
+                let fraction = fraction.clamp(0.0, 1.0);
+                (fraction * population_size as f64).ceil() as u32
             }
         }
     }
 }
 
+impl Default for SeedingStrategy {
+    fn default() -> Self {
+        SeedingStrategy::Count(STARTING_INFECTED_COUNT)
+    }
+}
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct DiseaseModel {
     pub exposure_chance: f64,
+    /// The flat Infection Fatality Rate applied to every age, used whenever `age_mortality_curve`
+    /// is `None`
     pub death_rate: f64,
+    /// An optional age-banded Infection Fatality Rate curve, used in place of the flat `death_rate`
+    /// so older and younger Citizens can be given differing fatality risks
+    ///
+    /// Defaults to `None`, meaning every age shares the same `death_rate`
+    pub age_mortality_curve: Option<AgeMortalityCurve>,
+    /// The number of time steps a Citizen remains Exposed for, expressed in units of `steps_per_day`
     pub exposed_time: u16,
+    /// The number of time steps a Citizen remains Infected for, expressed in units of `steps_per_day`
     pub infected_time: u16,
     pub max_time_step: u16,
     /// The amount of people vaccinated per timestamp
     pub vaccination_rate: u16,
+    /// The strategy used to prioritise which eligible Citizens are offered a vaccine first
+    pub vaccination_strategy: VaccinationStrategy,
+    /// The strategy used to determine how many Citizens are infected at the start of the simulation,
+    /// to seed the epidemic
+    ///
+    /// Clamped to the total population size if it resolves higher, so sweeping this value doesn't
+    /// require knowing the population size in advance
+    pub seeding_strategy: SeedingStrategy,
+    /// The probability that a susceptible Citizen is exposed by an untracked, external source (e.g. travel
+    /// into the simulated region) during a single time step, independent of any local contacts
+    pub importation_rate: f64,
+    /// The number of simulation time steps that make up a single day
+    ///
+    /// Schedules (`Citizen::execute_time_step`) and disease periods are both expressed in units of this
+    /// resolution, so it can be lowered for faster, coarser (e.g. day-level) runs, or raised for finer ones
+    pub steps_per_day: u32,
+
+    /// An optional age-group contact matrix, used to weigh how likely Citizens of differing ages are
+    /// to mix within a shared building
+    ///
+    /// Defaults to `None`, meaning Citizens mix uniformly regardless of age
+    pub contact_matrix: Option<AgeContactMatrix>,
 
     // TODO Check if data on mask compliance ratio
     pub mask_percentage: f64,
     pub mask_effectiveness: f64,
+
+    /// The proportion of Citizens whose infection will be asymptomatic, sampled once per Citizen
+    pub asymptomatic_chance: f64,
+    /// How much an asymptomatic Citizen's infected contacts count towards exposing others, relative
+    /// to a symptomatic Citizen's (`1.0`)
+    ///
+    /// Asymptomatic Citizens are not isolated by symptom-triggered interventions, so this is
+    /// typically left at `1.0` unless such an intervention is modelled
+    pub asymptomatic_infectiousness_multiplier: f64,
+
+    /// The proportion of commuters who travel by public transport, rather than by car
+    ///
+    /// Sampled once per Citizen at generation; only public transport commuters generate bus sessions
+    /// in `Simulator::generate_exposures`
+    pub public_transport_percentage: f64,
+
+    /// The maximum number of Citizens who can share a single bus/train vehicle on a given route
+    ///
+    /// A route with more commuters than this is split across several independently-exposing
+    /// vehicles, rather than generating one unrealistically large vehicle - see
+    /// `Simulator::apply_exposures`
+    pub public_transport_capacity: u32,
+
+    /// The dispersion parameter `k` of the Gamma(k, 1/k) distribution individual infectiousness
+    /// multipliers are sampled from at Citizen generation, reproducing the observed overdispersion
+    /// ("superspreading") of secondary cases, where a minority of infected Citizens cause most
+    /// transmission
+    ///
+    /// Defaults to `None`, meaning every Citizen has a uniform infectiousness multiplier of `1.0`.
+    /// Lower values of `k` widen the distribution (more heterogeneity); very high values converge
+    /// towards the uniform default
+    pub superspreading_dispersion: Option<f64>,
+
+    /// The dispersion parameter `k` of the Gamma(k, `infected_time` / k) distribution each Citizen's
+    /// infectious period is independently sampled from at the moment they become infected, so the
+    /// time to recovery/death varies per Citizen instead of being a fixed `infected_time` for everyone
+    ///
+    /// Defaults to `None`, meaning every Citizen's infectious period is exactly `infected_time`,
+    /// matching the old fixed-duration behaviour. Lower values of `k` widen the spread; very high
+    /// values converge towards the fixed-duration default
+    pub infectious_period_dispersion: Option<f64>,
+
+    /// The number of time steps before work starts (and again before it ends) during which a
+    /// public transport commuter boards a bus, expressed in units of `steps_per_day`
+    ///
+    /// Confines `Citizen::on_public_transport` sessions to the morning/evening commute windows
+    /// either side of the working day, so e.g. no exposures are generated from a bus at 3 a.m.
+    pub commute_window: u32,
+
+    /// The number of time steps over which mask adoption ramps from 0 up to `mask_effectiveness`
+    /// after a `MaskStatus` mandate is activated, reflecting that real-world behavioural change
+    /// builds up gradually rather than flipping instantly
+    ///
+    /// A value of `0` applies the full effectiveness immediately, matching the old instant-adoption
+    /// behaviour
+    pub mask_adoption_ramp_up_steps: u32,
+
+    /// The proportion of exposures a freshly vaccinated Citizen is protected against, before any waning
+    ///
+    /// E.g. `0.9` means a vaccinated Citizen's exposure chance is reduced by 90% relative to an
+    /// unvaccinated Citizen's, immediately after vaccination
+    pub vaccine_efficacy: f64,
+    /// The number of time steps over which `vaccine_efficacy` decays linearly down to `0.0`, modelling
+    /// waning immunity, so breakthrough infections become more likely the longer ago a Citizen was
+    /// vaccinated
+    ///
+    /// `None` means immunity never wanes, matching the old permanent-protection behaviour
+    pub vaccine_waning_period: Option<u32>,
+
+    /// The per-step hazard multiplier for background community transmission (e.g. shops, streets),
+    /// independent of a Citizen's assigned buildings or public transport
+    ///
+    /// Each susceptible Citizen's chance of exposure via this route is `community_transmission_rate`
+    /// scaled by their Output Area's current prevalence, so it rises and falls with the local outbreak
+    /// rather than being a flat background rate like `importation_rate`
+    ///
+    /// Defaults to `0.0`, so a run sees no community transmission unless explicitly configured
+    pub community_transmission_rate: f64,
+
+    /// The probability that a Citizen of a given `OccupationType` is deliberately assigned to work
+    /// from home, rather than being assigned a Workplace building, sampled once per Citizen in
+    /// `SimulatorBuilder::build_workplaces`
+    ///
+    /// This is distinct from the Citizens who end up working from home because `build_workplaces`
+    /// couldn't otherwise assign them a Workplace - this field represents genuine remote-work
+    /// policy, so it can be raised by a work-from-home intervention without that being
+    /// indistinguishable from an assignment failure
+    pub remote_work_probability: EnumMap<OccupationType, f64>,
+
+    /// Reduces within-household transmission to/from children, reflecting that children may be
+    /// less susceptible to, and/or less infectious with, some diseases than adults
+    ///
+    /// Applied by `Household::find_exposures`, on top of any age effect `contact_matrix` already
+    /// applies. Defaults to `None`, meaning Household transmission has no age effect
+    pub household_child_transmission: Option<ChildTransmissionModifier>,
+
+    /// Multiplies a Citizen's `infectiousness_multiplier` the moment they're exposed for the
+    /// second-or-later time, reflecting that reinfections are often milder - and so less infectious
+    /// to others - than a Citizen's first infection
+    ///
+    /// Defaults to `1.0`, i.e. no difference between a first infection and a reinfection, unless
+    /// explicitly configured
+    pub reinfection_infectiousness_multiplier: f64,
+    /// Multiplies `death_rate` for a Citizen's second-or-later infection, reflecting that
+    /// reinfections are typically less severe than a Citizen's first infection
+    ///
+    /// Defaults to `1.0`, i.e. no difference in fatality risk between a first infection and a
+    /// reinfection, unless explicitly configured
+    pub reinfection_death_rate_multiplier: f64,
+
+    /// Multiplies the aggregate infectiousness of an infected Citizen's contacts at a
+    /// `BuildingType::Hospital`, reflecting the elevated nosocomial transmission risk healthcare
+    /// workers face from the patients they treat, relative to an equivalent `Workplace` contact
+    ///
+    /// Defaults to `1.0`, i.e. no elevated risk, unless explicitly configured - see
+    /// `Simulator::apply_exposures`
+    pub hospital_transmission_multiplier: f64,
+
+    /// Keeps commuters on a given route grouped into the same vehicles every time they travel,
+    /// rather than reshuffling the route's commuters into fresh, randomly mixed vehicles each time
+    ///
+    /// Real commuters sharing a route tend to repeatedly ride with the same cohort (the same bus at
+    /// the same time every day), giving them correlated, repeated exposure that a full reshuffle
+    /// washes out. Defaults to `false`, matching the old always-reshuffled behaviour - see
+    /// `Simulator::split_route_into_vehicles`
+    pub stable_public_transport_cohorts: bool,
+}
+
+/// Reduces a Household exposure's chance of succeeding when the Citizen being exposed, or the
+/// Citizen transmitting it, is below `child_age_threshold` - see
+/// `DiseaseModel::household_child_transmission`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChildTransmissionModifier {
+    /// Citizens younger than this are treated as children
+    pub child_age_threshold: u16,
+    /// Multiplies a household exposure's chance of succeeding when the Citizen being exposed is a
+    /// child, e.g. `0.5` halves a child's chance of catching the disease from an infected
+    /// household member
+    pub child_susceptibility_multiplier: f64,
+    /// Multiplies a household exposure's chance of succeeding when the representative infected
+    /// Citizen transmitting it is a child, e.g. `0.5` halves the chance a child passes the disease
+    /// on to other household members
+    pub child_infectiousness_multiplier: f64,
+}
+
+impl Default for ChildTransmissionModifier {
+    fn default() -> Self {
+        ChildTransmissionModifier {
+            child_age_threshold: 18,
+            child_susceptibility_multiplier: 1.0,
+            child_infectiousness_multiplier: 1.0,
+        }
+    }
+}
+
+/// An age-banded Infection Fatality Rate (IFR) curve, used by `DiseaseModel::age_mortality_curve`
+/// to vary a Citizen's death rate by age, instead of applying a single flat `DiseaseModel::death_rate`
+/// to every age uniformly
+///
+/// A given age's rate is read from the band with the largest `min_age` not exceeding it; an age
+/// outside the covered range is clamped to the nearest defined band (see `ifr_for_age`), rather
+/// than silently falling back to a rate of `0.0`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AgeMortalityCurve {
+    /// `(min_age, ifr)` pairs, sorted ascending by `min_age`
+    bands: Vec<(u16, f64)>,
+}
+
+/// Deserialized separately from the derive macro so that a config file can't smuggle in an empty
+/// `bands`, which would otherwise panic later in `min_defined_age`/`ifr_for_age` instead of failing
+/// at load time
+impl<'de> Deserialize<'de> for AgeMortalityCurve {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Raw {
+            bands: Vec<(u16, f64)>,
+        }
+        let raw = Raw::deserialize(deserializer)?;
+        if raw.bands.is_empty() {
+            return Err(serde::de::Error::custom(
+                "AgeMortalityCurve requires at least one age band",
+            ));
+        }
+        let mut bands = raw.bands;
+        bands.sort_by_key(|(min_age, _)| *min_age);
+        Ok(AgeMortalityCurve { bands })
+    }
+}
+
+impl AgeMortalityCurve {
+    /// Builds a curve from `(min_age, ifr)` bands, sorting them ascending by `min_age`
+    ///
+    /// Panics if `bands` is empty, since a curve with no bands can't return a rate for any age
+    pub fn new(mut bands: Vec<(u16, f64)>) -> AgeMortalityCurve {
+        assert!(!bands.is_empty(), "AgeMortalityCurve requires at least one age band");
+        bands.sort_by_key(|(min_age, _)| *min_age);
+        AgeMortalityCurve { bands }
+    }
+
+    /// The youngest age this curve has a defined band for
+    pub fn min_defined_age(&self) -> u16 {
+        self.bands.first().expect("AgeMortalityCurve requires at least one age band").0
+    }
+
+    /// The oldest age this curve has a defined band for - ages above this are clamped down to it
+    /// by `ifr_for_age`
+    pub fn max_defined_age(&self) -> u16 {
+        self.bands.last().expect("AgeMortalityCurve requires at least one age band").0
+    }
+
+    /// Returns the Infection Fatality Rate for the given age, clamping to the nearest defined band
+    /// if `age` falls outside the curve's covered range
+    ///
+    /// Warns when clamping down from above `max_defined_age`, since that silently understates risk
+    /// for the oldest Citizens unless the gap is caught - see `validate_covers_population`
+    pub fn ifr_for_age(&self, age: u16) -> f64 {
+        if age > self.max_defined_age() {
+            warn!(
+                "Age {} exceeds the mortality curve's oldest band ({}); clamping to the {} band's IFR",
+                age, self.max_defined_age(), self.max_defined_age()
+            );
+        }
+        let clamped_age = age.clamp(self.min_defined_age(), self.max_defined_age());
+        self.bands
+            .iter()
+            .rev()
+            .find(|(min_age, _)| *min_age <= clamped_age)
+            .map(|(_, ifr)| *ifr)
+            .expect("AgeMortalityCurve requires at least one age band")
+    }
+
+    /// Warns, naming the gap, if this curve's oldest band doesn't reach `max_population_age` -
+    /// intended to be called once a population has been generated, so a curve that stops short of
+    /// the oldest actual Citizen is caught as a configuration issue rather than only surfacing as
+    /// repeated per-Citizen warnings from `ifr_for_age` during the run
+    pub fn validate_covers_population(&self, max_population_age: u16) {
+        if max_population_age > self.max_defined_age() {
+            warn!(
+                "Age mortality curve's oldest band is {}, but the population has Citizens up to age \
+                {} - ages above {} will use the {} band's IFR",
+                self.max_defined_age(), max_population_age, self.max_defined_age(), self.max_defined_age()
+            );
+        }
+    }
 }
 
 impl DiseaseModel {
-    /// Creates a new disease model representative of COVID-19
+    /// Creates a new disease model representative of COVID-19, at the standard hourly (24 steps per day) resolution
     ///
     /// R Rate - 2.5
     /// Death Rate - 0.05
     /// Exposure Time - 4 days
     /// Infected Time - 14 days
     pub fn covid() -> DiseaseModel {
+        DiseaseModel::covid_with_resolution(24)
+    }
+    /// Creates a new disease model representative of COVID-19, at the given `steps_per_day` time resolution
+    ///
+    /// The exposed and infected periods (4 and 14 days respectively) are scaled to the requested resolution,
+    /// so a day-level run (`steps_per_day = 1`) and an hourly run (`steps_per_day = 24`) describe the same epidemic
+    pub fn covid_with_resolution(steps_per_day: u32) -> DiseaseModel {
         DiseaseModel {
             exposure_chance: 0.00055,
             death_rate: 0.2,
-            exposed_time: 4 * 24,
-            infected_time: 14 * 24,
+            age_mortality_curve: None,
+            exposed_time: (4 * steps_per_day) as u16,
+            infected_time: (14 * steps_per_day) as u16,
             max_time_step: 5000,
             vaccination_rate: 85 * 18,
+            vaccination_strategy: VaccinationStrategy::Random,
+            seeding_strategy: SeedingStrategy::Count(STARTING_INFECTED_COUNT),
+            importation_rate: 0.0,
+            steps_per_day,
+            contact_matrix: None,
             mask_percentage: 0.8,
             mask_effectiveness: 0.70,
+            asymptomatic_chance: 0.0,
+            asymptomatic_infectiousness_multiplier: 1.0,
+            public_transport_percentage: PUBLIC_TRANSPORT_PERCENTAGE,
+            public_transport_capacity: BUS_CAPACITY,
+            superspreading_dispersion: None,
+            infectious_period_dispersion: None,
+            commute_window: DEFAULT_COMMUTE_WINDOW,
+            mask_adoption_ramp_up_steps: DEFAULT_MASK_ADOPTION_RAMP_UP_STEPS,
+            vaccine_efficacy: DEFAULT_VACCINE_EFFICACY,
+            vaccine_waning_period: None,
+            community_transmission_rate: DEFAULT_COMMUNITY_TRANSMISSION_RATE,
+            remote_work_probability: default_remote_work_probability(),
+            household_child_transmission: None,
+            reinfection_infectiousness_multiplier: 1.0,
+            reinfection_death_rate_multiplier: 1.0,
+            hospital_transmission_multiplier: 1.0,
+            stable_public_transport_cohorts: false,
+        }
+    }
+    /// Creates a new disease model representative of seasonal influenza, at the standard hourly
+    /// (24 steps per day) resolution
+    ///
+    /// R Rate - 1.3
+    /// Death Rate - 0.001
+    /// Exposure Time - 2 days
+    /// Infected Time - 6 days
+    pub fn influenza() -> DiseaseModel {
+        DiseaseModel::influenza_with_resolution(24)
+    }
+    /// Creates a new disease model representative of seasonal influenza, at the given `steps_per_day`
+    /// time resolution
+    ///
+    /// Much shorter exposed and infected periods than COVID-19, and a lower `exposure_chance`
+    /// calibrated so `approximate_r0` lands around the commonly cited real-world value of ~1.3
+    pub fn influenza_with_resolution(steps_per_day: u32) -> DiseaseModel {
+        DiseaseModel {
+            exposure_chance: 0.00072,
+            death_rate: 0.001,
+            exposed_time: (2 * steps_per_day) as u16,
+            infected_time: (6 * steps_per_day) as u16,
+            ..DiseaseModel::covid_with_resolution(steps_per_day)
         }
     }
+    /// Creates a new disease model representative of measles, at the standard hourly
+    /// (24 steps per day) resolution
+    ///
+    /// R Rate - 15
+    /// Death Rate - 0.002
+    /// Exposure Time - 11 days
+    /// Infected Time - 8 days
+    pub fn measles() -> DiseaseModel {
+        DiseaseModel::measles_with_resolution(24)
+    }
+    /// Creates a new disease model representative of measles, at the given `steps_per_day` time
+    /// resolution
+    ///
+    /// Measles is one of the most contagious known diseases, so `exposure_chance` is calibrated
+    /// well above COVID-19's so `approximate_r0` lands in the commonly cited range of ~12-18
+    pub fn measles_with_resolution(steps_per_day: u32) -> DiseaseModel {
+        DiseaseModel {
+            exposure_chance: 0.00625,
+            death_rate: 0.002,
+            exposed_time: (11 * steps_per_day) as u16,
+            infected_time: (8 * steps_per_day) as u16,
+            ..DiseaseModel::covid_with_resolution(steps_per_day)
+        }
+    }
+    /// Builds the named preset disease model (`"covid"`, `"influenza"` or `"measles"`, case-insensitive),
+    /// at the given `steps_per_day` time resolution
+    ///
+    /// Used to resolve the `--disease` CLI argument into a `DiseaseModel` without the caller needing
+    /// to know about every preset constructor
+    pub fn from_name(name: &str, steps_per_day: u32) -> anyhow::Result<DiseaseModel> {
+        match name.to_lowercase().as_str() {
+            "covid" | "covid-19" | "covid19" => Ok(DiseaseModel::covid_with_resolution(steps_per_day)),
+            "influenza" | "flu" => Ok(DiseaseModel::influenza_with_resolution(steps_per_day)),
+            "measles" => Ok(DiseaseModel::measles_with_resolution(steps_per_day)),
+            _ => Err(anyhow::anyhow!("Unknown disease preset '{}', expected one of: covid, influenza, measles", name)),
+        }
+    }
+    /// The fraction of `mask_effectiveness` currently in effect, ramping linearly from `0.0` to
+    /// `1.0` over `mask_adoption_ramp_up_steps` time steps since the current `MaskStatus` mandate
+    /// was activated
+    pub fn mask_adoption_fraction(&self, steps_since_activation: u32) -> f64 {
+        if self.mask_adoption_ramp_up_steps == 0 {
+            return 1.0;
+        }
+        (steps_since_activation as f64 / self.mask_adoption_ramp_up_steps as f64).min(1.0)
+    }
+    /// The fraction of `vaccine_efficacy` still in effect, given how long ago a Citizen was vaccinated
+    ///
+    /// Decays linearly from `vaccine_efficacy` down to `0.0` over `vaccine_waning_period`, or stays
+    /// at the full `vaccine_efficacy` forever if no waning period is configured
+    pub fn vaccine_effectiveness(&self, steps_since_vaccination: u32) -> f64 {
+        let remaining_fraction = match self.vaccine_waning_period {
+            None => 1.0,
+            Some(0) => 1.0,
+            Some(waning_period) => {
+                1.0 - (steps_since_vaccination as f64 / waning_period as f64).min(1.0)
+            }
+        };
+        self.vaccine_efficacy * remaining_fraction
+    }
     // TODO Redo this function
     pub fn get_exposure_chance(
         &self,
-        is_vaccinated: bool,
+        vaccinated_steps: Option<u32>,
         global_mask_status: &MaskStatus,
         is_on_public_transport_and_mask_compliant: bool,
     ) -> f64 {
         let mut chance = self.exposure_chance
             - match global_mask_status {
             MaskStatus::None(_) => 0.0,
-            MaskStatus::PublicTransport(_) => {
+            MaskStatus::PublicTransport(hour) => {
                 if is_on_public_transport_and_mask_compliant {
-                    self.exposure_chance * self.mask_effectiveness
+                    self.exposure_chance * self.mask_effectiveness * self.mask_adoption_fraction(*hour)
                 } else {
                     0.0
                 }
             }
-            MaskStatus::Everywhere(_) => self.exposure_chance * self.mask_effectiveness,
+            MaskStatus::Everywhere(hour) => {
+                self.exposure_chance * self.mask_effectiveness * self.mask_adoption_fraction(*hour)
+            }
+        };
+        if let Some(steps) = vaccinated_steps {
+            chance -= self.exposure_chance * self.vaccine_effectiveness(steps);
         }
-            - if is_vaccinated { 1.0 } else { 0.0 };
         if chance.is_sign_negative() {
             chance = 0.0;
         }
         chance
     }
+    /// A rough estimate of the basic reproduction number implied by this model's parameters, given
+    /// an assumed average number of contacts a Citizen has per day
+    ///
+    /// This is *not* the R0 actually produced by a simulation run - the real figure also depends on
+    /// the generated population's household/workplace/school sizes, which aren't known to
+    /// `DiseaseModel` - but it's a useful way to sanity-check a preset's `exposure_chance` against
+    /// literature values before running a full simulation
+    pub fn approximate_r0(&self, average_daily_contacts: f64) -> f64 {
+        let infected_days = self.infected_time as f64 / self.steps_per_day as f64;
+        self.exposure_chance * average_daily_contacts * infected_days
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::disease::{sample_infectious_period, AgeMortalityCurve, DiseaseModel};
+    use crate::interventions::MaskStatus;
+    use crate::test_util::variance;
+
+    /// A low dispersion `k` should produce a far more spread out (higher variance) distribution of
+    /// infectious periods than a high `k`, on the same seed, around the same mean `infected_time`
+    #[test]
+    fn low_dispersion_produces_higher_variance_than_high_dispersion() {
+        let sample = |dispersion: f64| -> Vec<f64> {
+            let mut rng = StdRng::seed_from_u64(42);
+            (0..1000)
+                .map(|_| sample_infectious_period(336, Some(dispersion), &mut rng) as f64)
+                .collect()
+        };
+
+        let low_dispersion_samples = sample(0.1);
+        let high_dispersion_samples = sample(100.0);
+
+        assert!(variance(&low_dispersion_samples) > variance(&high_dispersion_samples));
+    }
+
+    /// With no dispersion configured, every sampled infectious period should be exactly
+    /// `mean_infected_time`, leaving the old fixed-duration behaviour unchanged
+    #[test]
+    fn no_dispersion_always_returns_the_fixed_duration() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..10 {
+            assert_eq!(sample_infectious_period(336, None, &mut rng), 336);
+        }
+    }
+
+    /// Day-resolution and hour-resolution models of the same disease should reach Infected/Recovered
+    /// after the same number of days, since their periods are scaled by `steps_per_day`
+    #[test]
+    fn periods_scale_with_steps_per_day() {
+        let hourly = DiseaseModel::covid_with_resolution(24);
+        let daily = DiseaseModel::covid_with_resolution(1);
+        assert_eq!(hourly.exposed_time / hourly.steps_per_day as u16, daily.exposed_time);
+        assert_eq!(hourly.infected_time / hourly.steps_per_day as u16, daily.infected_time);
+    }
+
+    /// After a mask mandate activates, the effective mask fraction should increase monotonically
+    /// over the ramp period, then stay capped at full effectiveness once the ramp completes
+    #[test]
+    fn mask_adoption_fraction_ramps_up_monotonically_then_caps() {
+        let mut disease = DiseaseModel::covid();
+        disease.mask_adoption_ramp_up_steps = 10;
+
+        let fractions: Vec<f64> = (0..=10).map(|hour| disease.mask_adoption_fraction(hour)).collect();
+        for window in fractions.windows(2) {
+            assert!(window[0] <= window[1]);
+        }
+        assert_eq!(fractions[0], 0.0);
+        assert_eq!(*fractions.last().unwrap(), 1.0);
+        assert_eq!(disease.mask_adoption_fraction(20), 1.0);
+    }
+
+    /// A freshly vaccinated Citizen (efficacy 0.9) should face roughly 10% of an unvaccinated
+    /// Citizen's exposure chance, with no mask mandate in effect
+    #[test]
+    fn vaccine_efficacy_reduces_exposure_chance_to_roughly_ten_percent() {
+        let mut disease = DiseaseModel::covid();
+        disease.vaccine_efficacy = 0.9;
+        let mask_status = MaskStatus::None(0);
+
+        let baseline = disease.get_exposure_chance(None, &mask_status, false);
+        let vaccinated = disease.get_exposure_chance(Some(0), &mask_status, false);
+
+        assert!((vaccinated / baseline - 0.1).abs() < 1e-9);
+    }
+
+    /// Waning immunity should increase the effective exposure chance the longer ago a Citizen was
+    /// vaccinated, until the waning period elapses and the Citizen is as exposed as if unvaccinated
+    #[test]
+    fn waning_vaccine_effectiveness_increases_exposure_chance_over_time() {
+        let mut disease = DiseaseModel::covid();
+        disease.vaccine_efficacy = 0.9;
+        disease.vaccine_waning_period = Some(1000);
+        let mask_status = MaskStatus::None(0);
+
+        let chance_at_start = disease.get_exposure_chance(Some(0), &mask_status, false);
+        let chance_midway = disease.get_exposure_chance(Some(500), &mask_status, false);
+        let chance_after_waning = disease.get_exposure_chance(Some(1000), &mask_status, false);
+        let baseline = disease.get_exposure_chance(None, &mask_status, false);
+
+        assert!(chance_at_start < chance_midway);
+        assert!(chance_midway < chance_after_waning);
+        assert_eq!(chance_after_waning, baseline);
+    }
+
+    /// Each preset's `approximate_r0`, at a plausible average daily contact count, should fall
+    /// within the commonly cited real-world range for that disease, and the presets should be
+    /// ordered by contagiousness as expected (measles > COVID-19 > influenza)
+    #[test]
+    fn preset_r0_estimates_fall_within_literature_ranges() {
+        let average_daily_contacts = 300.0;
+
+        let influenza_r0 = DiseaseModel::influenza().approximate_r0(average_daily_contacts);
+        let covid_r0 = DiseaseModel::covid().approximate_r0(average_daily_contacts);
+        let measles_r0 = DiseaseModel::measles().approximate_r0(average_daily_contacts);
+
+        assert!((1.0..2.0).contains(&influenza_r0), "Influenza R0 estimate {} out of range", influenza_r0);
+        assert!((1.5..4.0).contains(&covid_r0), "COVID-19 R0 estimate {} out of range", covid_r0);
+        assert!((12.0..18.0).contains(&measles_r0), "Measles R0 estimate {} out of range", measles_r0);
+
+        assert!(influenza_r0 < covid_r0);
+        assert!(covid_r0 < measles_r0);
+    }
+
+    /// `from_name` should resolve each preset's name (case-insensitively) and reject unknown names
+    #[test]
+    fn from_name_resolves_known_presets_and_rejects_unknown_ones() {
+        assert_eq!(
+            DiseaseModel::from_name("COVID", 24).unwrap().exposure_chance,
+            DiseaseModel::covid().exposure_chance
+        );
+        assert_eq!(
+            DiseaseModel::from_name("flu", 24).unwrap().exposure_chance,
+            DiseaseModel::influenza().exposure_chance
+        );
+        assert_eq!(
+            DiseaseModel::from_name("Measles", 24).unwrap().exposure_chance,
+            DiseaseModel::measles().exposure_chance
+        );
+        assert!(DiseaseModel::from_name("ebola", 24).is_err());
+    }
+
+    /// A curve missing a band for the population's oldest Citizens should clamp their IFR to its
+    /// oldest defined band, rather than silently extrapolating or returning `0.0`
+    #[test]
+    fn ifr_for_age_clamps_to_the_nearest_band_when_age_exceeds_the_curve() {
+        let curve = AgeMortalityCurve::new(vec![(0, 0.001), (40, 0.01), (65, 0.05)]);
+
+        // 90 is older than the curve's oldest defined band (65), so it should clamp down to it,
+        // rather than e.g. returning 0.0 for falling off the end of the bands
+        assert_eq!(curve.ifr_for_age(90), curve.ifr_for_age(65));
+        assert_eq!(curve.ifr_for_age(90), 0.05);
+        // An in-range age should still read its own band normally
+        assert_eq!(curve.ifr_for_age(50), 0.01);
+    }
+
+    /// Deserializing a config with an empty `bands` must fail cleanly rather than producing a
+    /// value that panics later in `min_defined_age`/`ifr_for_age`
+    #[test]
+    fn deserializing_empty_age_mortality_curve_fails() {
+        let result: Result<AgeMortalityCurve, _> = serde_json::from_str(r#"{"bands":[]}"#);
+
+        assert!(result.is_err());
+    }
 }
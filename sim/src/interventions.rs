@@ -19,10 +19,41 @@
  */
 use std::collections::BTreeSet;
 use std::fmt::{Display, Formatter};
+use std::path::Path;
 
+use anyhow::Context;
 use log::info;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
+use crate::models::citizen::Citizen;
+
+/// Strategies for prioritising which eligible Citizens are offered a vaccine first
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum VaccinationStrategy {
+    /// Eligible Citizens are offered a vaccine in no particular order
+    Random,
+    /// Older Citizens are offered a vaccine before younger ones
+    OldestFirst,
+}
+
+impl VaccinationStrategy {
+    /// Scores `citizen`'s priority for receiving a vaccine under this strategy - a higher score
+    /// means the Citizen should be offered a vaccine sooner
+    pub fn priority_score(&self, citizen: &Citizen) -> f64 {
+        match self {
+            VaccinationStrategy::Random => 0.0,
+            VaccinationStrategy::OldestFirst => citizen.age as f64,
+        }
+    }
+}
+
+impl Default for VaccinationStrategy {
+    fn default() -> Self {
+        VaccinationStrategy::Random
+    }
+}
+
+#[derive(Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Serialize, Deserialize)]
 pub enum MaskStatus {
     None(u32),
     PublicTransport(u32),
@@ -60,7 +91,7 @@ impl MaskStatus {
 /// This contains the thresholds of percentage cases to trigger a given intervention
 ///
 /// If none, then the Intervention is never applied
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InterventionThresholds {
     /// The percent of cases to trigger a total lockdown
     lockdown: Option<f64>,
@@ -77,7 +108,79 @@ impl Default for InterventionThresholds {
     }
 }
 
-#[derive(Clone, Debug)]
+/// A named intervention that can be scheduled via an `InterventionCalendar`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CalendarIntervention {
+    Lockdown,
+    Vaccination,
+}
+
+/// A calendar-driven alternative to `InterventionThresholds` - rather than reacting to the current
+/// infected percentage, an intervention can instead be turned on or off on specific simulation
+/// days, so a region's actual policy timeline can be replayed instead of (or alongside) a
+/// threshold rule
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct InterventionCalendar {
+    /// Kept sorted by day, so `active_on` only has to scan forward to find the most recent entry
+    entries: Vec<(u32, CalendarIntervention, bool)>,
+}
+
+impl InterventionCalendar {
+    /// Builds a calendar directly from an already-loaded set of `(day, intervention, active)`
+    /// entries
+    pub fn new(mut entries: Vec<(u32, CalendarIntervention, bool)>) -> InterventionCalendar {
+        entries.sort_by_key(|(day, _, _)| *day);
+        InterventionCalendar { entries }
+    }
+    /// Loads a calendar from a headerless CSV file, with columns `day,intervention,active`, where
+    /// `intervention` is `lockdown`/`vaccination` (case-insensitive) and `active` is `true`/`false`
+    pub fn load_from_csv(path: impl AsRef<Path>) -> anyhow::Result<InterventionCalendar> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_path(path)
+            .context("Failed to open intervention calendar CSV")?;
+        let mut entries = Vec::new();
+        for record in reader.records() {
+            let record = record.context("Failed to read intervention calendar row")?;
+            let day = record
+                .get(0)
+                .context("Missing day column in intervention calendar row")?
+                .parse::<u32>()
+                .context("Failed to parse intervention calendar day")?;
+            let intervention = match record
+                .get(1)
+                .context("Missing intervention column in intervention calendar row")?
+                .to_lowercase()
+                .as_str()
+            {
+                "lockdown" => CalendarIntervention::Lockdown,
+                "vaccination" => CalendarIntervention::Vaccination,
+                other => anyhow::bail!("Unknown intervention calendar entry: '{}'", other),
+            };
+            let active = record
+                .get(2)
+                .context("Missing active column in intervention calendar row")?
+                .parse::<bool>()
+                .context("Failed to parse intervention calendar active flag")?;
+            entries.push((day, intervention, active));
+        }
+        Ok(InterventionCalendar::new(entries))
+    }
+    /// Returns whether `intervention` should be active on `day`, according to the latest scheduled
+    /// entry on or before `day` - `None` if the calendar has no opinion, so the caller should fall
+    /// back to its own threshold rule
+    fn active_on(&self, intervention: &CalendarIntervention, day: u32) -> Option<bool> {
+        self.entries
+            .iter()
+            .filter(|(entry_day, entry_intervention, _)| {
+                *entry_day <= day && entry_intervention == intervention
+            })
+            .last()
+            .map(|(_, _, active)| *active)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InterventionStatus {
     /// The hour at which lockdown was implemented
     lockdown: Option<u32>,
@@ -86,6 +189,16 @@ pub struct InterventionStatus {
     /// The hour at which lockdown was implemented
     pub mask_status: MaskStatus,
     thresholds: InterventionThresholds,
+    /// Scheduled days on which interventions should activate/deactivate, regardless of prevalence -
+    /// combined with `thresholds` as an OR, so either can trigger an intervention
+    calendar: InterventionCalendar,
+    /// Whether schools are currently confining students and staff to their own class ("bubbles"),
+    /// rather than allowing the usual corridor/assembly mixing between classes
+    school_bubbles: bool,
+    /// Whether a Citizen transitioning into `DiseaseStatus::Infected` ("testing positive") should
+    /// pin their whole household - every other Citizen sharing their `household_code` - home for the
+    /// rest of that Citizen's infectious period, rather than only the Citizen themself
+    household_isolation: bool,
 }
 
 impl Default for InterventionStatus {
@@ -95,6 +208,9 @@ impl Default for InterventionStatus {
             vaccination: None,
             mask_status: MaskStatus::None(0),
             thresholds: Default::default(),
+            calendar: Default::default(),
+            school_bubbles: false,
+            household_isolation: false,
         }
     }
 }
@@ -107,36 +223,57 @@ pub enum InterventionsEnabled {
 }
 
 impl InterventionStatus {
-    pub fn update_status(&mut self, percentage_infected: f64) -> BTreeSet<InterventionsEnabled> {
+    /// Replaces the calendar of scheduled intervention days, so a region's actual policy timeline
+    /// can be replayed instead of (or alongside) the threshold rule in `InterventionThresholds`
+    pub fn set_calendar(&mut self, calendar: InterventionCalendar) {
+        self.calendar = calendar;
+    }
+    pub fn update_status(
+        &mut self,
+        percentage_infected: f64,
+        current_day: u32,
+    ) -> BTreeSet<InterventionsEnabled> {
         //debug!("Updating intervention status");
         let mut new_interventions = BTreeSet::new();
         // Lockdown
-        if let Some(threshold) = self.thresholds.lockdown {
+        let calendar_wants_lockdown = self
+            .calendar
+            .active_on(&CalendarIntervention::Lockdown, current_day)
+            .unwrap_or(false);
+        let threshold_wants_lockdown = match self.thresholds.lockdown {
+            Some(threshold) => threshold < percentage_infected,
+            None => false,
+        };
+        if calendar_wants_lockdown || threshold_wants_lockdown {
             // Lockdown is enabled
-            if threshold < percentage_infected {
-                self.lockdown = Some(if let Some(hour) = self.lockdown {
-                    hour + 1
-                } else {
-                    new_interventions.insert(InterventionsEnabled::Lockdown);
-                    0
-                });
-            }
-            // Lockdown is removed
-            else if self.lockdown.is_some() {
-                self.lockdown = None;
-            }
+            self.lockdown = Some(if let Some(hour) = self.lockdown {
+                hour + 1
+            } else {
+                new_interventions.insert(InterventionsEnabled::Lockdown);
+                0
+            });
+        }
+        // Lockdown is removed
+        else if self.lockdown.is_some() {
+            self.lockdown = None;
         }
 
         // Vaccination
-        if let Some(threshold) = self.thresholds.vaccination_threshold {
-            if threshold < percentage_infected {
-                self.vaccination = Some(if let Some(hour) = self.vaccination {
-                    hour + 1
-                } else {
-                    new_interventions.insert(InterventionsEnabled::Vaccination);
-                    0
-                });
-            }
+        let calendar_wants_vaccination = self
+            .calendar
+            .active_on(&CalendarIntervention::Vaccination, current_day)
+            .unwrap_or(false);
+        let threshold_wants_vaccination = match self.thresholds.vaccination_threshold {
+            Some(threshold) => threshold < percentage_infected,
+            None => false,
+        };
+        if calendar_wants_vaccination || threshold_wants_vaccination {
+            self.vaccination = Some(if let Some(hour) = self.vaccination {
+                hour + 1
+            } else {
+                new_interventions.insert(InterventionsEnabled::Vaccination);
+                0
+            });
         }
         //Mask Wearing
         self.mask_status = match &self.mask_status {
@@ -188,4 +325,55 @@ impl InterventionStatus {
     pub fn vaccination_program_started(&self) -> bool {
         self.vaccination.is_some()
     }
+    /// Whether schools should currently restrict exposure to within a student's own class
+    pub fn school_bubbles_enabled(&self) -> bool {
+        self.school_bubbles
+    }
+    /// Turns school bubbling on or off, so a region's policy timeline can enact or lift it
+    pub fn set_school_bubbles_enabled(&mut self, enabled: bool) {
+        self.school_bubbles = enabled;
+    }
+    /// Whether household isolation is currently enabled - see `household_isolation`
+    pub fn household_isolation_enabled(&self) -> bool {
+        self.household_isolation
+    }
+    /// Turns household isolation on or off, so a region's policy timeline can enact or lift it
+    pub fn set_household_isolation_enabled(&mut self, enabled: bool) {
+        self.household_isolation = enabled;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interventions::{CalendarIntervention, InterventionCalendar, InterventionStatus};
+
+    /// A calendar entry scheduling lockdown on day 10 should enable lockdown on that day's steps
+    /// regardless of how low the infected percentage is, since the calendar and threshold rules
+    /// are combined with an OR
+    #[test]
+    fn calendar_enables_lockdown_on_scheduled_day_regardless_of_prevalence() {
+        let mut status = InterventionStatus::default();
+        status.set_calendar(InterventionCalendar::new(vec![(
+            10,
+            CalendarIntervention::Lockdown,
+            true,
+        )]));
+
+        let steps_per_day = 24;
+        for day in 0..10 {
+            for _ in 0..steps_per_day {
+                status.update_status(0.0, day);
+            }
+        }
+        assert!(
+            !status.lockdown_enabled(),
+            "Lockdown shouldn't be active before its scheduled day"
+        );
+
+        status.update_status(0.0, 10);
+        assert!(
+            status.lockdown_enabled(),
+            "Lockdown should be active on its scheduled day, even with zero prevalence"
+        );
+    }
 }
@@ -23,10 +23,18 @@ extern crate log;
 extern crate pretty_env_logger;
 
 pub mod config;
-mod disease;
+pub mod contact_matrix;
+pub mod disease;
 mod error;
 mod interventions;
 pub mod models;
+pub mod rng_log;
 pub mod simulator;
 pub mod simulator_builder;
+mod state_history;
 mod statistics;
+mod surveillance;
+#[cfg(test)]
+mod test_util;
+pub mod time;
+mod warning_aggregator;
@@ -23,14 +23,19 @@ use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter};
 use std::hash::Hash;
 
+use enum_map::Enum;
 use geo::Point;
-use log::error;
+use log::{error, warn};
+use rand::Rng;
+use rand::RngCore;
 use serde::{Deserialize, Serialize, Serializer};
 use uuid::Uuid;
 
 use osm_data::RawBuilding;
 
 use crate::config::MIN_WORKPLACE_OCCUPANT_COUNT;
+use crate::contact_matrix::AgeContactMatrix;
+use crate::disease::ChildTransmissionModifier;
 use crate::error::SimError;
 use crate::models::citizen::{CitizenID, OccupationType};
 use crate::models::get_density_for_occupation;
@@ -42,7 +47,7 @@ pub const MINIMUM_FLOOR_SPACE_SIZE: u32 = 2000;
 /// A wrapper for all building types, for easier use in Hashmaps
 ///
 /// Each element contains
-#[derive(Clone, Debug, Deserialize, Serialize, Hash, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, Hash, Eq, PartialEq, Enum)]
 pub enum BuildingType {
     Household,
     Workplace,
@@ -50,6 +55,10 @@ pub enum BuildingType {
     Restaurant,
     SuperMarket,
     Shop,
+    /// A Workplace staffed by Citizens with the `Caring` occupation (healthcare workers), tagged
+    /// distinctly from `Workplace` so nosocomial transmission can be modelled separately - see
+    /// `DiseaseModel::hospital_transmission_multiplier`
+    Hospital,
 }
 
 /// This is used to represent a building location
@@ -107,6 +116,10 @@ impl BuildingID {
     pub fn building_index(&self) -> usize {
         self.building_index as usize
     }
+    /// Returns the type of this building (Household, Workplace, School, etc.)
+    pub fn building_type(&self) -> &BuildingType {
+        &self.building_type
+    }
 }
 
 impl Display for BuildingID {
@@ -122,12 +135,35 @@ impl Display for BuildingID {
 /// This represents a home for Citizens
 ///
 /// Has an AreaCode for referencing it, and a list of Citizen ID's that live here
+///
+/// `Household`, `Workplace`, `School` and `Shop` are the built-in implementations, but this is a stable
+/// extension point - researchers can implement `Building` on their own type (e.g. a prison, or a
+/// hospital modelled as a venue rather than a `Workplace`) to give it bespoke occupancy and
+/// exposure rules, and push it into `OutputArea::buildings` alongside the built-in types
+///
+/// A custom implementation should:
+/// * Enforce its own capacity (if any) in `add_citizen`, the way `Workplace::add_citizen` rejects
+///   Citizens once `max_occupant_count` is reached
+/// * Implement `find_exposures` with whatever mixing model fits the venue - `filter_by_age_contact`
+///   is reusable for the common case of "every occupant can plausibly contact every infected
+///   occupant, weighted by age", as used by `Household` and `Workplace`
+/// * Be aware that `Serialize for dyn Building` only has downcast arms for `Household`,
+///   `Workplace` and `School` - any other type (`Shop`, or a custom type) dumped via
+///   `Simulator::statistics_recorder` debug dumps returns a serialization error rather than
+///   panicking, unless a downcast arm for it is added there too
 pub trait Building: Display + Debug {
     /// Creates a new building at the given location, with the specified type
     //fn new(building_code: BuildingCode) -> Self;
 
     /// Adds the new citizen to this building
+    ///
+    /// Implementations that enforce a capacity (e.g. `Workplace`) should return
+    /// `Err(SimError::Default { .. })` once full, rather than silently over-filling
     fn add_citizen(&mut self, citizen_id: CitizenID) -> Result<(), SimError>;
+    /// Removes a citizen from this building's occupant list
+    ///
+    /// Returns an error if the Citizen does not occupy this building
+    fn remove_citizen(&mut self, citizen_id: CitizenID) -> Result<(), SimError>;
     /// Returns the AreaCode where this building is located
     fn id(&self) -> &BuildingID;
     /// Returns a list of ids of occupants that are here
@@ -135,10 +171,66 @@ pub trait Building: Display + Debug {
     fn as_any(&self) -> &dyn Any;
     /// Returns the location of the building
     fn get_location(&self) -> geo_types::Point<i32>;
-    /// Returns a list of Citizens that would be exposed, if the given Citizen is infected
-    fn find_exposures(&self, infected_citizens: &Vec<CitizenID>) -> Vec<CitizenID>;
+    /// Returns a boxed deep copy of this building, so `Box<dyn Building + Sync + Send>` can implement `Clone`
+    fn clone_box(&self) -> Box<dyn Building + Sync + Send>;
+    /// Returns a list of Citizens that would be exposed, if the given Citizens are infected
+    ///
+    /// When `contact_matrix` is supplied, candidates are weighted by age-group affinity between
+    /// `infected_citizens` and the building's occupants; otherwise mixing is uniform.
+    /// `child_transmission` is only meaningful to `Household` - other built-in types ignore it.
+    /// `school_bubbles` is only meaningful to `School` - other built-in types ignore it
+    fn find_exposures(
+        &self,
+        infected_citizens: &[CitizenID],
+        citizen_ages: &HashMap<CitizenID, u16>,
+        contact_matrix: Option<&AgeContactMatrix>,
+        child_transmission: Option<&ChildTransmissionModifier>,
+        school_bubbles: bool,
+        rng: &mut dyn RngCore,
+    ) -> Vec<CitizenID>;
 }
 
+/// Filters `candidates` down to those whose contact with at least one of `infected_citizens` is
+/// plausible, weighted by age-group affinity from `contact_matrix`
+///
+/// Returns `candidates` unchanged when no `contact_matrix` is supplied (uniform mixing), or when a
+/// Citizen's age is unknown
+fn filter_by_age_contact(
+    candidates: &[CitizenID],
+    infected_citizens: &[CitizenID],
+    citizen_ages: &HashMap<CitizenID, u16>,
+    contact_matrix: Option<&AgeContactMatrix>,
+    rng: &mut dyn RngCore,
+) -> Vec<CitizenID> {
+    let matrix = match contact_matrix {
+        Some(matrix) => matrix,
+        None => return candidates.to_vec(),
+    };
+    candidates
+        .iter()
+        .filter(|candidate| {
+            let candidate_age = match citizen_ages.get(*candidate) {
+                Some(age) => *age,
+                None => return true,
+            };
+            infected_citizens.iter().any(|infected| match citizen_ages.get(infected) {
+                Some(infected_age) => {
+                    let rate = matrix.contact_rate(candidate_age, *infected_age).clamp(0.0, 1.0);
+                    rate > 0.0 && rng.gen_bool(rate)
+                }
+                None => true,
+            })
+        })
+        .copied()
+        .collect()
+}
+
+/// Dispatches to the concrete `Serialize` impl of `self`, when it is a `Household`, `Workplace` or
+/// `School`
+///
+/// Any other `Building` implementation (`Shop`, or a custom type) isn't recognised here, and falls
+/// back to a serialization error rather than panicking - add a further `downcast_ref` arm for a
+/// type if it needs to appear in debug dumps
 impl Serialize for dyn Building {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
         where
@@ -158,6 +250,12 @@ impl Serialize for dyn Building {
     }
 }
 
+impl Clone for Box<dyn Building + Sync + Send> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub struct Household {
     /// This is unique to the specific output area - ~250 households
@@ -177,12 +275,28 @@ impl Household {
     }
 }
 
+/// Removes the first occurrence of `citizen_id` from `occupants`, erroring if it is not present
+fn remove_occupant(occupants: &mut Vec<CitizenID>, citizen_id: CitizenID) -> Result<(), SimError> {
+    let index = occupants
+        .iter()
+        .position(|occupant| *occupant == citizen_id)
+        .ok_or_else(|| SimError::MissingCitizen {
+            citizen_id: citizen_id.to_string(),
+        })?;
+    occupants.remove(index);
+    Ok(())
+}
+
 impl Building for Household {
     fn add_citizen(&mut self, citizen_id: CitizenID) -> Result<(), SimError> {
         self.occupants.push(citizen_id);
         Ok(())
     }
 
+    fn remove_citizen(&mut self, citizen_id: CitizenID) -> Result<(), SimError> {
+        remove_occupant(&mut self.occupants, citizen_id)
+    }
+
     fn id(&self) -> &BuildingID {
         &self.building_code
     }
@@ -199,8 +313,55 @@ impl Building for Household {
         self.location
     }
 
-    fn find_exposures(&self, infected_citizens: &Vec<CitizenID>) -> Vec<CitizenID> {
-        (*self.occupants).to_owned()
+    fn clone_box(&self) -> Box<dyn Building + Sync + Send> {
+        Box::new(self.clone())
+    }
+
+    fn find_exposures(
+        &self,
+        infected_citizens: &[CitizenID],
+        citizen_ages: &HashMap<CitizenID, u16>,
+        contact_matrix: Option<&AgeContactMatrix>,
+        child_transmission: Option<&ChildTransmissionModifier>,
+        _school_bubbles: bool,
+        rng: &mut dyn RngCore,
+    ) -> Vec<CitizenID> {
+        let candidates =
+            filter_by_age_contact(&self.occupants, infected_citizens, citizen_ages, contact_matrix, rng);
+        let modifier = match child_transmission {
+            Some(modifier) => modifier,
+            None => return candidates,
+        };
+        // The model only tracks aggregate building-level exposure, not pairwise contacts, so the
+        // first infected Citizen present is taken as the representative infector, matching
+        // `Simulator::apply_exposures`'s transmission log
+        let infector_is_child = infected_citizens
+            .first()
+            .and_then(|infector| citizen_ages.get(infector))
+            .map(|age| *age < modifier.child_age_threshold)
+            .unwrap_or(false);
+        let infectiousness_multiplier = if infector_is_child {
+            modifier.child_infectiousness_multiplier
+        } else {
+            1.0
+        };
+        candidates
+            .into_iter()
+            .filter(|candidate| {
+                let exposed_is_child = citizen_ages
+                    .get(candidate)
+                    .map(|age| *age < modifier.child_age_threshold)
+                    .unwrap_or(false);
+                let susceptibility_multiplier = if exposed_is_child {
+                    modifier.child_susceptibility_multiplier
+                } else {
+                    1.0
+                };
+                let combined_multiplier =
+                    (susceptibility_multiplier * infectiousness_multiplier).clamp(0.0, 1.0);
+                combined_multiplier >= 1.0 || rng.gen_bool(combined_multiplier)
+            })
+            .collect()
     }
 }
 
@@ -225,6 +386,15 @@ pub struct Workplace {
     floor_space: u32,
     workplace_occupation_type: OccupationType,
     location: geo_types::Point<i32>,
+    /// The maximum number of occupants sharing a room - see `rooms`
+    room_size: u32,
+    /// Occupants grouped into rooms of up to `room_size`, in the order they were added, so a large
+    /// Workplace's exposures are contained within a room rather than mixing the whole building -
+    /// see `find_exposures`
+    rooms: Vec<Vec<CitizenID>>,
+    /// The room index each occupant currently belongs to
+    #[serde(skip)]
+    occupant_to_room: HashMap<CitizenID, usize>,
 }
 
 impl Workplace {
@@ -232,6 +402,7 @@ impl Workplace {
         building_code: BuildingID,
         raw_building: RawBuilding,
         occupation_type: OccupationType,
+        room_size: u32,
     ) -> Self {
         Workplace {
             building_code,
@@ -239,6 +410,9 @@ impl Workplace {
             floor_space: (raw_building.size() as u32).max(MINIMUM_FLOOR_SPACE_SIZE),
             workplace_occupation_type: occupation_type,
             location: raw_building.center(),
+            room_size,
+            rooms: Vec::new(),
+            occupant_to_room: HashMap::new(),
         }
     }
     fn max_occupant_count(&self) -> u32 {
@@ -248,6 +422,10 @@ impl Workplace {
     pub fn is_at_capacity(&self) -> bool {
         self.max_occupant_count() <= (self.occupants.len() as u32)
     }
+    /// Returns the rooms occupants are split across, so exposures can be contained per-room
+    pub fn rooms(&self) -> &Vec<Vec<CitizenID>> {
+        &self.rooms
+    }
 }
 
 impl Building for Workplace {
@@ -258,6 +436,22 @@ impl Building for Workplace {
             });
         }
         self.occupants.push(citizen_id);
+        let room_capacity = self.room_size.max(1) as usize;
+        match self.rooms.last_mut() {
+            Some(room) if room.len() < room_capacity => room.push(citizen_id),
+            _ => self.rooms.push(vec![citizen_id]),
+        }
+        self.occupant_to_room.insert(citizen_id, self.rooms.len() - 1);
+        Ok(())
+    }
+
+    fn remove_citizen(&mut self, citizen_id: CitizenID) -> Result<(), SimError> {
+        remove_occupant(&mut self.occupants, citizen_id)?;
+        if let Some(room_index) = self.occupant_to_room.remove(&citizen_id) {
+            if let Some(room) = self.rooms.get_mut(room_index) {
+                remove_occupant(room, citizen_id)?;
+            }
+        }
         Ok(())
     }
 
@@ -275,8 +469,36 @@ impl Building for Workplace {
     fn get_location(&self) -> Point<i32> {
         self.location
     }
-    fn find_exposures(&self, infected_citizens: &Vec<CitizenID>) -> Vec<CitizenID> {
-        (*self.occupants).to_owned()
+
+    fn clone_box(&self) -> Box<dyn Building + Sync + Send> {
+        Box::new(self.clone())
+    }
+
+    fn find_exposures(
+        &self,
+        infected_citizens: &[CitizenID],
+        citizen_ages: &HashMap<CitizenID, u16>,
+        contact_matrix: Option<&AgeContactMatrix>,
+        _child_transmission: Option<&ChildTransmissionModifier>,
+        _school_bubbles: bool,
+        rng: &mut dyn RngCore,
+    ) -> Vec<CitizenID> {
+        let mut exposed = Vec::new();
+        for infected_citizen in infected_citizens {
+            match self.occupant_to_room.get(infected_citizen) {
+                Some(room_index) => {
+                    if let Some(room) = self.rooms.get(*room_index) {
+                        exposed.extend(room.iter().copied());
+                    }
+                }
+                None => error!(
+                    "Citizen {} does not belong to this workplace {}!",
+                    infected_citizen,
+                    self.id()
+                ),
+            }
+        }
+        filter_by_age_contact(&exposed, infected_citizens, citizen_ages, contact_matrix, rng)
     }
 }
 
@@ -292,6 +514,84 @@ impl Display for Workplace {
     }
 }
 
+/// A community building (e.g. a shop) that non-working Citizens (retired or unemployed) visit
+/// during the day, generating exposures outside the household, without being a permanent
+/// assignment the way `Household`/`Workplace`/`School` are
+///
+/// Unlike `Workplace`, a `Shop` has no occupancy cap - it only ever holds whichever Citizens are
+/// currently visiting it for the current time step, not a fixed roster
+#[derive(Clone, Debug, Serialize)]
+pub struct Shop {
+    building_code: BuildingID,
+    occupants: Vec<CitizenID>,
+    location: geo_types::Point<i32>,
+}
+
+impl Shop {
+    pub fn new(building_code: BuildingID, raw_building: RawBuilding) -> Self {
+        Shop {
+            building_code,
+            occupants: Vec::new(),
+            location: raw_building.center(),
+        }
+    }
+}
+
+impl Building for Shop {
+    fn add_citizen(&mut self, citizen_id: CitizenID) -> Result<(), SimError> {
+        self.occupants.push(citizen_id);
+        Ok(())
+    }
+
+    fn remove_citizen(&mut self, citizen_id: CitizenID) -> Result<(), SimError> {
+        remove_occupant(&mut self.occupants, citizen_id)
+    }
+
+    fn id(&self) -> &BuildingID {
+        &self.building_code
+    }
+
+    fn occupants(&self) -> Vec<CitizenID> {
+        self.occupants.clone()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self as &dyn Any
+    }
+
+    fn get_location(&self) -> Point<i32> {
+        self.location
+    }
+
+    fn clone_box(&self) -> Box<dyn Building + Sync + Send> {
+        Box::new(self.clone())
+    }
+
+    fn find_exposures(
+        &self,
+        infected_citizens: &[CitizenID],
+        citizen_ages: &HashMap<CitizenID, u16>,
+        contact_matrix: Option<&AgeContactMatrix>,
+        _child_transmission: Option<&ChildTransmissionModifier>,
+        _school_bubbles: bool,
+        rng: &mut dyn RngCore,
+    ) -> Vec<CitizenID> {
+        filter_by_age_contact(&self.occupants, infected_citizens, citizen_ages, contact_matrix, rng)
+    }
+}
+
+impl Display for Shop {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} Building at {}, with {} current visitors",
+            self.building_code,
+            self.building_code,
+            self.occupants.len()
+        )
+    }
+}
+
 #[derive(Serialize, Default, Debug)]
 pub struct SchoolStatistic {
     /// How many students in each class
@@ -302,12 +602,13 @@ pub struct SchoolStatistic {
     classes_per_age_group: Vec<usize>,
     number_of_office_staff: usize,
     number_of_offices: usize,
+    /// How many classes didn't get a teacher of their own, and were instead assigned one already
+    /// teaching another class, because the school doesn't have enough teachers to go around - see
+    /// `School::with_students_and_teachers`
+    shared_teacher_classes: usize,
 }
 
-pub const AVERAGE_CLASS_SIZE: f64 = 26.6;
-const AVERAGE_OFFICE_SIZE: usize = 12;
-
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Class {
     students: Vec<CitizenID>,
     teacher: CitizenID,
@@ -322,13 +623,13 @@ impl Class {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub enum RoomID {
     ClassId { id: usize },
     OfficeId { id: usize },
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Serialize)]
 pub struct School {
     building_code: BuildingID,
     location: geo_types::Point<i32>,
@@ -342,16 +643,24 @@ pub struct School {
 }
 
 impl School {
-    // TODO Return errors instead of panicking!
+    /// Builds a School from its students (grouped by age) and teachers
+    ///
+    /// If there aren't enough teachers for one each per class, classes are shared between teachers
+    /// instead of failing the build - see `SchoolStatistic::shared_teacher_classes`. Returns
+    /// `Err` only if there are no teachers at all, since then no class can be taught
     pub fn with_students_and_teachers(
         building_id: BuildingID,
         building: RawBuilding,
         students: Vec<Vec<CitizenID>>,
         teachers: Vec<CitizenID>,
-    ) -> (School, SchoolStatistic) {
+        average_class_size: f64,
+        average_office_size: usize,
+    ) -> Result<(School, SchoolStatistic), SimError> {
         let mut statistic = SchoolStatistic::default();
-        if teachers.len() < 1 {
-            panic!("Cannot have a school without any teachers!")
+        if teachers.is_empty() {
+            return Err(SimError::InitializationError {
+                message: "Cannot have a school without any teachers!".to_string(),
+            });
         }
         // Remove any empty age groups
         let students: Vec<(usize, Vec<CitizenID>)> = students
@@ -369,29 +678,29 @@ impl School {
             .iter()
             .map(|(_age, student_number)| {
                 if student_number.len() > 0 {
-                    (((student_number.len() as f64) / AVERAGE_CLASS_SIZE).ceil() as usize).max(1)
+                    (((student_number.len() as f64) / average_class_size).ceil() as usize).max(1)
                 } else {
                     0
                 }
             })
             .collect();
 
-        // Check we have enough teachers
         let required_teachers: usize = statistic.classes_per_age_group.iter().sum();
-
-        if teachers.len() < (required_teachers as usize) {
-            panic!(
-                "School does not have enough teachers ({}), requires: ({})",
+        if teachers.len() < required_teachers {
+            warn!(
+                "School {} has {} teachers for {} classes - {} classes will share a teacher with another class",
+                building_id,
                 teachers.len(),
-                required_teachers
+                required_teachers,
+                required_teachers - teachers.len()
             );
         }
 
-        // Allocate students/teachers into classes
+        // Allocate students/teachers into classes, cycling back through `teachers` once every
+        // teacher has a class, so a shortage results in shared classes rather than a failed build
         let mut participant_to_class = HashMap::with_capacity(students.len());
         let mut class_index = 0;
 
-        let mut teachers = teachers.into_iter();
         let mut classes: Vec<Class> = Vec::new();
 
         for ((_age, age_group), class_count) in
@@ -402,7 +711,10 @@ impl School {
             statistic.class_sizes.push(class_size);
             let age_group = age_group.into_iter();
             for class in age_group.as_slice().chunks(class_size) {
-                let teacher = teachers.next().expect("Ran out of teachers!");
+                let teacher = teachers[class_index % teachers.len()];
+                if class_index >= teachers.len() {
+                    statistic.shared_teacher_classes += 1;
+                }
                 for student in class {
                     participant_to_class.insert(*student, RoomID::ClassId { id: class_index });
                 }
@@ -417,11 +729,13 @@ impl School {
             classes.extend(new_classes);
         }
 
-        // Assign any leftover teachers to Offices
+        // Any teachers that weren't needed for a class (only possible when there are more teachers
+        // than classes) are assigned to Offices instead
+        let unneeded_teachers = &teachers[class_index.min(teachers.len())..];
         let mut office_index = 0;
         let mut offices: Vec<Vec<CitizenID>> =
-            Vec::with_capacity(teachers.len() / AVERAGE_OFFICE_SIZE);
-        for misc_staff in teachers.as_slice().chunks(AVERAGE_OFFICE_SIZE) {
+            Vec::with_capacity(unneeded_teachers.len() / average_office_size);
+        for misc_staff in unneeded_teachers.chunks(average_office_size) {
             for staff in misc_staff {
                 participant_to_class.insert(*staff, RoomID::OfficeId { id: office_index });
                 statistic.number_of_office_staff += 1;
@@ -430,7 +744,7 @@ impl School {
             office_index += 1;
             statistic.number_of_offices += 1;
         }
-        (
+        Ok((
             School {
                 building_code: building_id,
                 location: building.center(),
@@ -439,7 +753,7 @@ impl School {
                 occupant_to_class: participant_to_class,
             },
             statistic,
-        )
+        ))
     }
     pub fn classes(&self) -> &Vec<Class> {
         &self.classes
@@ -473,6 +787,10 @@ impl Building for School {
         panic!("Schools can only have citizens added at creation!");
     }
 
+    fn remove_citizen(&mut self, _: CitizenID) -> Result<(), SimError> {
+        panic!("Citizens cannot be removed from a School's class allocation!");
+    }
+
     fn id(&self) -> &BuildingID {
         &self.building_code
     }
@@ -491,7 +809,27 @@ impl Building for School {
     fn get_location(&self) -> Point<i32> {
         self.location
     }
-    fn find_exposures(&self, infected_citizens: &Vec<CitizenID>) -> Vec<CitizenID> {
+
+    fn clone_box(&self) -> Box<dyn Building + Sync + Send> {
+        Box::new(self.clone())
+    }
+
+    fn find_exposures(
+        &self,
+        infected_citizens: &[CitizenID],
+        citizen_ages: &HashMap<CitizenID, u16>,
+        contact_matrix: Option<&AgeContactMatrix>,
+        _child_transmission: Option<&ChildTransmissionModifier>,
+        school_bubbles: bool,
+        rng: &mut dyn RngCore,
+    ) -> Vec<CitizenID> {
+        // Without bubbling, an infected Citizen's exposure isn't contained to their own class -
+        // corridors, assemblies and breaktimes mix the whole school together
+        if !school_bubbles {
+            return filter_by_age_contact(
+                &self.occupants(), infected_citizens, citizen_ages, contact_matrix, rng,
+            );
+        }
         let mut exposed = Vec::new();
         for infected_citizen in infected_citizens {
             let class_index = match self.occupant_to_class.get(infected_citizen) {
@@ -518,28 +856,27 @@ impl Building for School {
                 }
             }
         }
-        exposed
+        filter_by_age_contact(&exposed, infected_citizens, citizen_ages, contact_matrix, rng)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use geo::prelude::Area;
     use geo_types::Geometry::LineString;
     use geo_types::Polygon;
     use strum::IntoEnumIterator;
 
-    use load_census_data::osm_parsing::{
-        BuildingBoundaryID, convert_polygon_to_float, RawBuilding, TagClassifiedBuilding,
-    };
     use load_census_data::tables::employment_densities::EmploymentDensities;
-    use load_census_data::tables::occupation_count::OccupationType;
     use osm_data::{
         BuildingBoundaryID, convert_polygon_to_float, RawBuilding, TagClassifiedBuilding,
     };
 
+    use crate::contact_matrix::AgeContactMatrix;
     use crate::models::building::{
-        Building, BuildingID, BuildingType, MINIMUM_FLOOR_SPACE_SIZE, Workplace,
+        Building, BuildingID, BuildingType, Household, MINIMUM_FLOOR_SPACE_SIZE, School, Workplace,
     };
     use crate::models::citizen::{CitizenID, OccupationType};
     use crate::models::output_area::OutputAreaID;
@@ -564,7 +901,7 @@ mod tests {
         assert_eq!(float.unsigned_area(), MINIMUM_FLOOR_SPACE_SIZE as f64);
         for occupation_type in OccupationType::iter() {
             println!("Testing: {:?}", occupation_type);
-            let mut workplace = Workplace::new(id.clone(), raw, occupation_type);
+            let mut workplace = Workplace::new(id.clone(), raw, occupation_type, 10);
             assert!(
                 EmploymentDensities::get_density_for_occupation(occupation_type)
                     < workplace.floor_space
@@ -592,7 +929,371 @@ mod tests {
             .unwrap();
         let float: Polygon<f64> = convert_polygon_to_float(&building_size);
         assert!(float.unsigned_area() < MINIMUM_FLOOR_SPACE_SIZE as f64);
-        let mut workplace = Workplace::new(id.clone(), raw, OccupationType::All);
+        let mut workplace = Workplace::new(id.clone(), raw, OccupationType::All, 10);
         assert!(MINIMUM_FLOOR_SPACE_SIZE <= workplace.floor_space);
     }
+
+    #[test]
+    fn age_contact_matrix_forbids_cross_age_exposure() {
+        let building_id = BuildingID::new(
+            OutputAreaID::from_code_and_index("area".to_string(), 0),
+            BuildingType::Household,
+            0,
+        );
+        let mut household = Household::new(building_id, geo_types::Point::new(0, 0));
+        let child = CitizenID::from_indexes(0);
+        let adult = CitizenID::from_indexes(1);
+        household.add_citizen(child).unwrap();
+        household.add_citizen(adult).unwrap();
+        let mut ages = HashMap::new();
+        ages.insert(child, 5);
+        ages.insert(adult, 40);
+        // Only allows contact within the same age group
+        let matrix = AgeContactMatrix::new(vec![
+            vec![1.0, 0.0, 0.0, 0.0, 0.0],
+            vec![0.0, 1.0, 0.0, 0.0, 0.0],
+            vec![0.0, 0.0, 1.0, 0.0, 0.0],
+            vec![0.0, 0.0, 0.0, 1.0, 0.0],
+            vec![0.0, 0.0, 0.0, 0.0, 1.0],
+        ]);
+        let exposed = household.find_exposures(
+            &[adult],
+            &ages,
+            Some(&matrix),
+            None,
+            false,
+            &mut rand::thread_rng(),
+        );
+        assert!(!exposed.contains(&child));
+    }
+
+    /// With a very low `child_susceptibility_multiplier`, children in a Household with an infected
+    /// adult should be exposed in far fewer trials than the other adults living with them
+    #[test]
+    fn low_child_susceptibility_multiplier_reduces_how_often_children_are_exposed() {
+        let building_id = BuildingID::new(
+            OutputAreaID::from_code_and_index("area".to_string(), 0),
+            BuildingType::Household,
+            0,
+        );
+        let mut household = Household::new(building_id, geo_types::Point::new(0, 0));
+        let infected_adult = CitizenID::from_indexes(0);
+        let other_adult = CitizenID::from_indexes(1);
+        let child = CitizenID::from_indexes(2);
+        household.add_citizen(infected_adult).unwrap();
+        household.add_citizen(other_adult).unwrap();
+        household.add_citizen(child).unwrap();
+        let mut ages = HashMap::new();
+        ages.insert(infected_adult, 40);
+        ages.insert(other_adult, 45);
+        ages.insert(child, 8);
+
+        let modifier = crate::disease::ChildTransmissionModifier {
+            child_age_threshold: 18,
+            child_susceptibility_multiplier: 0.01,
+            child_infectiousness_multiplier: 1.0,
+        };
+
+        let trials = 1000;
+        let mut rng = rand::thread_rng();
+        let child_exposures = (0..trials)
+            .filter(|_| {
+                household
+                    .find_exposures(&[infected_adult], &ages, None, Some(&modifier), false, &mut rng)
+                    .contains(&child)
+            })
+            .count();
+        let other_adult_exposures = (0..trials)
+            .filter(|_| {
+                household
+                    .find_exposures(&[infected_adult], &ages, None, Some(&modifier), false, &mut rng)
+                    .contains(&other_adult)
+            })
+            .count();
+
+        assert!(
+            child_exposures < other_adult_exposures,
+            "Child was exposed {} times out of {}, the other adult {} times",
+            child_exposures,
+            trials,
+            other_adult_exposures
+        );
+    }
+
+    /// Shrinking `average_class_size` to 10 should proportionally increase both the number of
+    /// classes a single age group is split into, and the number of teachers the school requires
+    #[test]
+    fn smaller_average_class_size_requires_proportionally_more_classes_and_teachers() {
+        let building_size = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(0, 0), (100, 0), (100, 100), (0, 100), (0, 0)]),
+            vec![],
+        );
+        let building_id = BuildingID::new(
+            OutputAreaID::from_code_and_index("area".to_string(), 0),
+            BuildingType::School,
+            0,
+        );
+        let raw_building = RawBuilding::new(
+            TagClassifiedBuilding::School,
+            &building_size,
+            BuildingBoundaryID::default(),
+        )
+            .unwrap();
+        let students: Vec<CitizenID> = (0..100u32).map(CitizenID::from_indexes).collect();
+        let teachers: Vec<CitizenID> = (100..110u32).map(CitizenID::from_indexes).collect();
+
+        let (school, stats) = School::with_students_and_teachers(
+            building_id,
+            raw_building,
+            vec![students],
+            teachers,
+            10.0,
+            12,
+        )
+            .expect("Failed to build a test School");
+        // 100 students at a class size of 10 requires exactly 10 classes, each needing its own teacher
+        assert_eq!(stats.classes_per_age_group, vec![10]);
+        assert_eq!(school.classes().len(), 10);
+    }
+
+    /// With fewer teachers than classes, the School should still build successfully, sharing
+    /// teachers between classes rather than panicking
+    #[test]
+    fn fewer_teachers_than_classes_shares_teachers_instead_of_panicking() {
+        let building_size = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(0, 0), (100, 0), (100, 100), (0, 100), (0, 0)]),
+            vec![],
+        );
+        let building_id = BuildingID::new(
+            OutputAreaID::from_code_and_index("area".to_string(), 0),
+            BuildingType::School,
+            0,
+        );
+        let raw_building = RawBuilding::new(
+            TagClassifiedBuilding::School,
+            &building_size,
+            BuildingBoundaryID::default(),
+        )
+            .unwrap();
+        // 100 students at a class size of 10 requires 10 classes, but only 3 teachers are available
+        let students: Vec<CitizenID> = (0..100u32).map(CitizenID::from_indexes).collect();
+        let teachers: Vec<CitizenID> = (100..103u32).map(CitizenID::from_indexes).collect();
+
+        let (school, stats) = School::with_students_and_teachers(
+            building_id,
+            raw_building,
+            vec![students],
+            teachers,
+            10.0,
+            12,
+        )
+            .expect("A school with too few teachers should share them, not fail to build");
+        assert_eq!(school.classes().len(), 10);
+        assert_eq!(stats.shared_teacher_classes, 7);
+    }
+
+    /// A minimal, non-built-in `Building` implementation, used to check that the trait is a usable
+    /// extension point for custom building types (e.g. a prison, or a hospital modelled as a venue
+    /// rather than a workplace), not just the types defined in this module
+    #[derive(Clone, Debug)]
+    struct CustomVenue {
+        building_code: BuildingID,
+        occupants: Vec<CitizenID>,
+        location: geo_types::Point<i32>,
+    }
+
+    impl std::fmt::Display for CustomVenue {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Custom venue {} with {} occupants", self.building_code, self.occupants.len())
+        }
+    }
+
+    impl Building for CustomVenue {
+        fn add_citizen(&mut self, citizen_id: CitizenID) -> Result<(), crate::error::SimError> {
+            self.occupants.push(citizen_id);
+            Ok(())
+        }
+
+        fn remove_citizen(&mut self, citizen_id: CitizenID) -> Result<(), crate::error::SimError> {
+            super::remove_occupant(&mut self.occupants, citizen_id)
+        }
+
+        fn id(&self) -> &BuildingID {
+            &self.building_code
+        }
+
+        fn occupants(&self) -> Vec<CitizenID> {
+            self.occupants.clone()
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self as &dyn std::any::Any
+        }
+
+        fn get_location(&self) -> geo_types::Point<i32> {
+            self.location
+        }
+
+        fn clone_box(&self) -> Box<dyn Building + Sync + Send> {
+            Box::new(self.clone())
+        }
+
+        fn find_exposures(
+            &self,
+            infected_citizens: &[CitizenID],
+            citizen_ages: &HashMap<CitizenID, u16>,
+            contact_matrix: Option<&AgeContactMatrix>,
+            _child_transmission: Option<&crate::disease::ChildTransmissionModifier>,
+            _school_bubbles: bool,
+            rng: &mut dyn rand::RngCore,
+        ) -> Vec<CitizenID> {
+            super::filter_by_age_contact(&self.occupants, infected_citizens, citizen_ages, contact_matrix, rng)
+        }
+    }
+
+    /// Custom `Building` implementations should be usable anywhere a built-in one is - this
+    /// exercises running an exposure step against one, then cloning it through the boxed
+    /// `Building` trait object, the same way `OutputArea::clone` clones its building list
+    #[test]
+    fn custom_building_implementation_can_find_exposures_and_be_cloned() {
+        let building_id = BuildingID::new(
+            OutputAreaID::from_code_and_index("area".to_string(), 0),
+            BuildingType::Household,
+            0,
+        );
+        let mut venue = CustomVenue {
+            building_code: building_id,
+            occupants: Vec::new(),
+            location: geo_types::Point::new(0, 0),
+        };
+        let resident = CitizenID::from_indexes(0);
+        let visitor = CitizenID::from_indexes(1);
+        venue.add_citizen(resident).unwrap();
+        venue.add_citizen(visitor).unwrap();
+
+        let exposed = venue.find_exposures(&[resident], &HashMap::new(), None, None, false, &mut rand::thread_rng());
+        assert!(exposed.contains(&visitor));
+
+        let boxed: Box<dyn Building + Sync + Send> = Box::new(venue);
+        let cloned = boxed.clone_box();
+        assert_eq!(cloned.occupants(), boxed.occupants());
+    }
+
+    /// Splitting a large, crowded Workplace into rooms should contain each infected occupant's
+    /// exposures to their own room, so the same infected count produces strictly fewer exposures
+    /// than an unsplit, building-wide model of the same occupants
+    #[test]
+    fn room_size_contains_exposures_compared_to_the_building_wide_model() {
+        let building_size = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(0, 0), (1000, 0), (1000, 1000), (0, 1000), (0, 0)]),
+            vec![],
+        );
+        let raw = RawBuilding::new(
+            TagClassifiedBuilding::WorkPlace,
+            &building_size,
+            BuildingBoundaryID::default(),
+        )
+            .unwrap();
+        let occupants: Vec<CitizenID> = (0..100u32).map(CitizenID::from_indexes).collect();
+        let infected = vec![occupants[0], occupants[50]];
+
+        let build_workplace_with_room_size = |room_size: u32| -> Workplace {
+            let id = BuildingID::new(
+                OutputAreaID::from_code_and_index("a".to_string(), 0),
+                BuildingType::Workplace,
+                0,
+            );
+            let mut workplace = Workplace::new(id, raw, OccupationType::Manager, room_size);
+            for occupant in &occupants {
+                workplace
+                    .add_citizen(*occupant)
+                    .expect("Failed to add occupant to test Workplace");
+            }
+            workplace
+        };
+
+        let building_wide = build_workplace_with_room_size(occupants.len() as u32);
+        let split_into_rooms = build_workplace_with_room_size(10);
+
+        let building_wide_exposed: std::collections::HashSet<CitizenID> = building_wide
+            .find_exposures(&infected, &HashMap::new(), None, None, false, &mut rand::thread_rng())
+            .into_iter()
+            .collect();
+        let room_exposed: std::collections::HashSet<CitizenID> = split_into_rooms
+            .find_exposures(&infected, &HashMap::new(), None, None, false, &mut rand::thread_rng())
+            .into_iter()
+            .collect();
+
+        assert_eq!(building_wide_exposed.len(), occupants.len());
+        assert!(
+            room_exposed.len() < building_wide_exposed.len(),
+            "Splitting into rooms of 10 should contain exposures to far fewer than all {} occupants, got {}",
+            occupants.len(),
+            room_exposed.len()
+        );
+    }
+
+    /// With bubbling active, an infected student's exposures should be contained entirely to their
+    /// own class - no students from other classes should appear, compared to the non-bubbled
+    /// baseline where cross-class mixing is expected
+    #[test]
+    fn bubbling_reduces_cross_class_infections_to_zero() {
+        let building_size = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(0, 0), (100, 0), (100, 100), (0, 100), (0, 0)]),
+            vec![],
+        );
+        let building_id = BuildingID::new(
+            OutputAreaID::from_code_and_index("area".to_string(), 0),
+            BuildingType::School,
+            0,
+        );
+        let raw_building = RawBuilding::new(
+            TagClassifiedBuilding::School,
+            &building_size,
+            BuildingBoundaryID::default(),
+        )
+            .unwrap();
+        let students: Vec<CitizenID> = (0..100u32).map(CitizenID::from_indexes).collect();
+        let teachers: Vec<CitizenID> = (100..110u32).map(CitizenID::from_indexes).collect();
+
+        let (school, _stats) = School::with_students_and_teachers(
+            building_id,
+            raw_building,
+            vec![students.clone()],
+            teachers,
+            10.0,
+            12,
+        )
+            .expect("Failed to build a test School");
+
+        // The infected student is in the first class (students 0..10), so any exposed student
+        // from outside that class is a cross-class infection
+        let infected_student = students[0];
+        let first_class: std::collections::HashSet<CitizenID> =
+            school.classes()[0].get_participants().into_iter().collect();
+
+        let bubbled_exposed = school.find_exposures(
+            &[infected_student], &HashMap::new(), None, None, true, &mut rand::thread_rng(),
+        );
+        let cross_class_with_bubbles = bubbled_exposed
+            .iter()
+            .filter(|citizen| !first_class.contains(citizen))
+            .count();
+        assert_eq!(
+            cross_class_with_bubbles, 0,
+            "Bubbling should contain exposures entirely to the infected student's own class"
+        );
+
+        let unbubbled_exposed = school.find_exposures(
+            &[infected_student], &HashMap::new(), None, None, false, &mut rand::thread_rng(),
+        );
+        let cross_class_without_bubbles = unbubbled_exposed
+            .iter()
+            .filter(|citizen| !first_class.contains(citizen))
+            .count();
+        assert!(
+            cross_class_without_bubbles > 0,
+            "Without bubbling, the whole school should mix, exposing students outside the infected student's class"
+        );
+    }
 }
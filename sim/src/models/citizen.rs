@@ -18,7 +18,6 @@
  *
  */
 
-use std::convert::TryFrom;
 use std::fmt::{Debug, Display, Formatter};
 use std::hash::{Hash, Hasher};
 
@@ -27,28 +26,35 @@ use lazy_static::lazy_static;
 use rand::distributions::Distribution;
 use rand::distributions::Uniform;
 use rand::RngCore;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 use uuid::Uuid;
 
 use load_census_data::tables::occupation_count::RawOccupationType;
 
-use crate::config::PUBLIC_TRANSPORT_PERCENTAGE;
 use crate::disease::{DiseaseModel, DiseaseStatus};
 use crate::interventions::MaskStatus;
 use crate::models::building::BuildingID;
 use crate::models::output_area::OutputAreaID;
+use crate::time::DayOfWeek;
 
 lazy_static! {
     /// This is a random uniform distribution, for fast random generation
     static ref RANDOM_DISTRUBUTION: Uniform<f64> =Uniform::new_inclusive(0.0, 1.0);
 }
 /// Calculates the binomial distribution, with at least one success
-fn binomial(probability: f64, n: u8) -> f64 {
-    1.0 - (1.0 - probability).powf(n as f64)
+fn binomial(probability: f64, n: f64) -> f64 {
+    1.0 - (1.0 - probability).powf(n)
 }
 
-#[derive(Debug, Copy, Clone, Serialize)]
+/// Whether `hour` falls within the configured commute `window` immediately before `work_boundary_hour`
+/// (the hour work starts, or the hour it ends), so public transport sessions are confined to the
+/// commute either side of the working day, rather than spanning the whole day
+fn is_in_commute_window(hour: u32, work_boundary_hour: u32, window: u32) -> bool {
+    window > 0 && hour < work_boundary_hour && hour >= work_boundary_hour.saturating_sub(window)
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct CitizenID {
     /// This is a global unique Citizen index
     global_index: u32,
@@ -116,11 +122,21 @@ pub struct Citizen {
     pub household_code: BuildingID,
     /// The place they work at
     pub workplace_code: BuildingID,
+    /// The nearby Shop building this Citizen makes a daily community/shopping trip to, if one was
+    /// found in their Output Area
+    ///
+    /// Only ever visited by Citizens who don't otherwise leave home during the day (`workplace_code
+    /// == household_code`, i.e. retired/unemployed Citizens) - see `execute_time_step`
+    shop_code: Option<BuildingID>,
     occupation: Occupation,
     /// The hour which they go to work
     start_working_hour: u32,
     /// The hour which they leave to work
     end_working_hour: u32,
+    /// The hour a non-working Citizen (see `shop_code`) visits their assigned Shop
+    shopping_trip_hour: u32,
+    /// The hour a non-working Citizen returns home from their Shop visit
+    shopping_return_hour: u32,
     /// The building the Citizen is currently at
     ///
     /// Note that it will be the starting point, if Citizen is using Public Transport
@@ -129,13 +145,37 @@ pub struct Citizen {
     pub disease_status: DiseaseStatus,
     /// Whether this Citizen wears a mask
     pub is_mask_compliant: bool,
+    /// Whether this Citizen's infection (if/when they become infected) will be asymptomatic
+    ///
+    /// Sampled once at creation, rather than at the point of infection, since a Citizen is only
+    /// ever infected once in this simulation
+    pub is_asymptomatic: bool,
+    /// Whether this Citizen commutes by public transport, rather than by car
+    ///
+    /// Sampled once at creation according to the area's configured modal share
     pub uses_public_transport: bool,
     /// The source and destination for a Citizen on Transport this time step
     pub on_public_transport: std::option::Option<(OutputAreaID, OutputAreaID)>,
+    /// How infectious this Citizen is, relative to the baseline (`1.0`), if/when they become infected
+    ///
+    /// Sampled once at creation from `DiseaseModel::superspreading_dispersion`, to model "superspreader"
+    /// heterogeneity - most Citizens stay near the baseline, while a minority drawn from the tail of
+    /// the distribution expose far more contacts than average
+    pub infectiousness_multiplier: f64,
+    /// How many times this Citizen has been exposed and gone on to develop an infection, including
+    /// their current one - incremented by `expose`
+    ///
+    /// This simulation's disease state machine currently treats `DiseaseStatus::Recovered` as
+    /// terminal, so a Citizen is never naturally reinfected, but `expose` still counts correctly if
+    /// a Citizen is driven back to `Susceptible` directly (e.g. once waning immunity is modelled)
+    pub infection_count: u32,
 }
 
 impl Citizen {
     /// Generates a new Citizen with a random ID
+    ///
+    /// `steps_per_day` is used to scale the (9am - 5pm) working day onto the simulation's configured
+    /// time resolution, so a coarser or finer `steps_per_day` still produces a proportionally sized working day
     pub fn new(
         citizen_id: CitizenID,
         household_code: BuildingID,
@@ -143,21 +183,29 @@ impl Citizen {
         age: u16,
         occupation: Occupation,
         is_mask_compliant: bool,
-        rng: &mut dyn RngCore,
+        is_asymptomatic: bool,
+        uses_public_transport: bool,
+        steps_per_day: u32,
     ) -> Citizen {
         Citizen {
             id: citizen_id,
             age,
             household_code: household_code.clone(),
             workplace_code,
+            shop_code: None,
             occupation,
-            start_working_hour: 9,
-            end_working_hour: 17,
+            start_working_hour: (9 * steps_per_day) / 24,
+            end_working_hour: (17 * steps_per_day) / 24,
+            shopping_trip_hour: (13 * steps_per_day) / 24,
+            shopping_return_hour: (14 * steps_per_day) / 24,
             current_building_position: household_code,
             disease_status: DiseaseStatus::Susceptible,
             is_mask_compliant,
-            uses_public_transport: RANDOM_DISTRUBUTION.sample(rng) < PUBLIC_TRANSPORT_PERCENTAGE,
+            is_asymptomatic,
+            uses_public_transport,
             on_public_transport: None,
+            infectiousness_multiplier: 1.0,
+            infection_count: 0,
         }
     }
     /// Returns the ID of this Citizen
@@ -167,35 +215,88 @@ impl Citizen {
 
     pub fn execute_time_step(
         &mut self,
-        current_hour: u32,
+        current_step: u32,
         disease: &DiseaseModel,
         lockdown_enabled: bool,
+        household_isolating: bool,
+        day_of_week: DayOfWeek,
+        rng: &mut dyn RngCore,
     ) -> Option<OutputAreaID> {
         let old_position = self.current_building_position.output_area_code();
-        self.disease_status = DiseaseStatus::execute_time_step(&self.disease_status, disease);
-        if !lockdown_enabled {
-            match current_hour % 24 {
-                // Travelling home to work
-                hour if hour == self.start_working_hour - 1 && self.uses_public_transport => {
+        self.disease_status =
+            DiseaseStatus::execute_time_step(&self.disease_status, disease, self.infection_count, self.age, rng);
+        if self.is_deceased() {
+            // A deceased Citizen is frozen in place for final-size accounting - they never travel
+            // to work or back home again, so they can't be re-added to the move-citizens phase
+            self.on_public_transport = None;
+            return None;
+        }
+        // Essential workers (hospitals, shops, emergency services, ...) attend their workplace
+        // every day, but everyone else's workplace only operates on weekdays
+        let is_working_today = !day_of_week.is_weekend() || self.operates_on_weekends();
+        if !lockdown_enabled && !household_isolating {
+            match current_step % disease.steps_per_day {
+                // Travelling home to work, somewhere in the morning commute window
+                hour if is_working_today
+                    && self.uses_public_transport
+                    && is_in_commute_window(hour, self.start_working_hour, disease.commute_window) =>
+                {
                     self.on_public_transport = Some((
                         self.household_code.output_area_code(),
                         self.workplace_code.output_area_code(),
                     ))
                 }
                 // Starts work
-                hour if hour == self.start_working_hour => {
+                //
+                // Guarded on not already being at the workplace (rather than just the hour) so that
+                // at low `steps_per_day` resolutions - where `start_working_hour` and
+                // `end_working_hour` can collapse onto the same step - this arm and "finish work"
+                // below still alternate a Citizen between household and workplace every matching
+                // step, instead of this arm matching every step and leaving them stuck at work forever
+                hour if is_working_today
+                    && hour == self.start_working_hour
+                    && self.current_building_position != self.workplace_code =>
+                {
                     self.current_building_position = self.workplace_code.clone();
                     self.on_public_transport = None;
                 }
-                // Travelling work to home
-                hour if hour == self.end_working_hour - 1 && self.uses_public_transport => {
+                // Travelling work to home, somewhere in the evening commute window
+                hour if is_working_today
+                    && self.uses_public_transport
+                    && is_in_commute_window(hour, self.end_working_hour, disease.commute_window) =>
+                {
                     self.on_public_transport = Some((
                         self.workplace_code.output_area_code(),
                         self.household_code.output_area_code(),
                     ))
                 }
-                // Finish work, goes home
-                hour if hour == self.end_working_hour => {
+                // Finish work, goes home - guarded on currently being at the workplace, for the same
+                // reason as "starts work" above
+                hour if is_working_today
+                    && hour == self.end_working_hour
+                    && self.current_building_position == self.workplace_code =>
+                {
+                    self.current_building_position = self.household_code.clone();
+                    self.on_public_transport = None;
+                }
+                // A non-working Citizen (retired/unemployed) makes a daily community/shopping trip,
+                // if a nearby Shop was found for their Output Area - guarded on not already being at
+                // the Shop, for the same collapsing-resolution reason as "starts work" above
+                hour if hour == self.shopping_trip_hour
+                    && self.workplace_code == self.household_code
+                    && self.shop_code.is_some()
+                    && self.shop_code != Some(self.current_building_position.clone()) =>
+                {
+                    self.current_building_position =
+                        self.shop_code.clone().expect("Checked Some above");
+                    self.on_public_transport = None;
+                }
+                // Finishes their shopping trip, goes home - guarded on currently being at the Shop,
+                // for the same reason as "finish work" above
+                hour if hour == self.shopping_return_hour
+                    && self.workplace_code == self.household_code
+                    && self.shop_code == Some(self.current_building_position.clone()) =>
+                {
                     self.current_building_position = self.household_code.clone();
                     self.on_public_transport = None;
                 }
@@ -214,34 +315,64 @@ impl Citizen {
             Some(self.current_building_position.output_area_code())
         }
     }
-    /// Registers a new exposure to this citizen
+    /// Calculates the probability of this Citizen being infected by a given exposure
+    ///
+    /// Pure and rng-free, so the probability curve can be asserted against directly in tests, or
+    /// reused anywhere the underlying probability is needed (e.g. dose-response calculations)
+    /// without driving a Citizen's actual disease state
     ///
     /// # Paramaters
-    /// exposure_total: The amount of the exposures that occured in this time step
-    pub fn expose(
-        &mut self,
-        exposure_total: usize,
+    /// exposure_total: The effective amount of infectious contact that occurred in this time step,
+    /// i.e. the number of infected contacts, with any asymptomatic contacts weighted down by
+    /// `DiseaseModel::asymptomatic_infectiousness_multiplier`
+    pub fn exposure_probability(
+        &self,
+        exposure_total: f64,
         disease_model: &DiseaseModel,
         mask_status: &MaskStatus,
-        rng: &mut dyn RngCore,
-    ) -> bool {
+    ) -> f64 {
         let mask_status = if self.is_mask_compliant {
             &MaskStatus::None(0)
         } else {
             mask_status
         };
-        let exposure_chance = binomial(
+        let vaccinated_steps = match self.disease_status {
+            DiseaseStatus::Vaccinated(steps) => Some(steps),
+            _ => None,
+        };
+        binomial(
             disease_model.get_exposure_chance(
-                self.disease_status == DiseaseStatus::Vaccinated,
+                vaccinated_steps,
                 mask_status,
                 self.is_mask_compliant && self.on_public_transport.is_some(),
             ),
-            exposure_total as u8,
+            exposure_total,
+        )
+    }
+    /// Registers a new exposure to this citizen
+    ///
+    /// # Paramaters
+    /// exposure_total: The effective amount of infectious contact that occurred in this time step,
+    /// i.e. the number of infected contacts, with any asymptomatic contacts weighted down by
+    /// `DiseaseModel::asymptomatic_infectiousness_multiplier`
+    pub fn expose(
+        &mut self,
+        exposure_total: f64,
+        disease_model: &DiseaseModel,
+        mask_status: &MaskStatus,
+        rng: &mut dyn RngCore,
+    ) -> bool {
+        let exposure_chance = self.exposure_probability(exposure_total, disease_model, mask_status);
+        let can_be_exposed = matches!(
+            self.disease_status,
+            DiseaseStatus::Susceptible | DiseaseStatus::Vaccinated(_)
         );
-        if self.disease_status == DiseaseStatus::Susceptible
-            && RANDOM_DISTRUBUTION.sample(rng) < exposure_chance
-        {
+        if can_be_exposed && RANDOM_DISTRUBUTION.sample(rng) < exposure_chance {
             self.disease_status = DiseaseStatus::Exposed(0);
+            self.infection_count += 1;
+            if self.infection_count > 1 {
+                self.infectiousness_multiplier *= disease_model.reinfection_infectiousness_multiplier;
+            }
             return true;
         }
         false
@@ -249,6 +380,10 @@ impl Citizen {
     pub fn set_workplace_code(&mut self, workplace_code: BuildingID) {
         self.workplace_code = workplace_code;
     }
+    /// Sets the nearby Shop this Citizen makes a daily community/shopping trip to - see `shop_code`
+    pub fn set_shop_code(&mut self, shop_code: BuildingID) {
+        self.shop_code = Some(shop_code);
+    }
     /// Returns True if this Citizen is a student
     pub fn is_student(&self) -> bool {
         self.occupation == Occupation::Student
@@ -257,6 +392,13 @@ impl Citizen {
     pub fn occupation(&self) -> Occupation {
         self.occupation
     }
+    /// Whether this Citizen attends their workplace on a weekend day
+    ///
+    /// True for the "Essential" workforce (hospitals, shops, emergency services, ...), who work
+    /// every day, and false for everyone else, whose workplace only operates on weekdays
+    pub fn operates_on_weekends(&self) -> bool {
+        matches!(self.occupation, Occupation::Essential { .. })
+    }
     /// Attempts to return the detailed Occupation type, if it is available
     pub fn detailed_occupation(&self) -> Option<OccupationType> {
         match self.occupation() {
@@ -271,7 +413,10 @@ impl Citizen {
         self.disease_status == DiseaseStatus::Susceptible
     }
     pub fn is_infected(&self) -> bool {
-        matches!(self.disease_status, DiseaseStatus::Infected(_))
+        matches!(self.disease_status, DiseaseStatus::Infected { .. })
+    }
+    pub fn is_deceased(&self) -> bool {
+        self.disease_status == DiseaseStatus::Deceased
     }
 }
 
@@ -295,7 +440,7 @@ pub enum Occupation {
 }
 
 /// The detailed job type of a Citizen
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, EnumIter, Enum, Hash)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, EnumIter, Enum, Hash)]
 pub enum OccupationType {
     Manager,
     Professional,
@@ -306,6 +451,11 @@ pub enum OccupationType {
     Sales,
     MachineOperatives,
     Teaching,
+    /// A catch-all for occupation categories that don't map onto a more specific variant
+    ///
+    /// Used for `RawOccupationType::All`, so every parsed occupation record always yields a
+    /// detailed occupation and no worker is silently excluded from workplace assignment
+    Other,
 }
 
 impl OccupationType {
@@ -320,18 +470,15 @@ impl OccupationType {
             OccupationType::Sales => 6,
             OccupationType::MachineOperatives => 7,
             OccupationType::Teaching => 8,
+            OccupationType::Other => 9,
         }
     }
 }
 
-impl TryFrom<RawOccupationType> for OccupationType {
-    type Error = ();
-
-    fn try_from(raw_occupation: RawOccupationType) -> Result<Self, Self::Error> {
-        Ok(match raw_occupation {
-            RawOccupationType::All => {
-                return Err(());
-            }
+impl From<RawOccupationType> for OccupationType {
+    fn from(raw_occupation: RawOccupationType) -> Self {
+        match raw_occupation {
+            RawOccupationType::All => OccupationType::Other,
             RawOccupationType::Managers => OccupationType::Manager,
             RawOccupationType::Professional => OccupationType::Professional,
             RawOccupationType::Technical => OccupationType::Technical,
@@ -341,7 +488,7 @@ impl TryFrom<RawOccupationType> for OccupationType {
             RawOccupationType::Sales => OccupationType::Sales,
             RawOccupationType::MachineOperatives => OccupationType::MachineOperatives,
             RawOccupationType::Teaching => OccupationType::Teaching,
-        })
+        }
     }
 }
 /*
@@ -364,3 +511,534 @@ mod tests {
         }
     }
 }*/
+
+#[cfg(test)]
+mod occupation_type_tests {
+    use strum::IntoEnumIterator;
+
+    use load_census_data::tables::occupation_count::RawOccupationType;
+
+    use crate::models::citizen::OccupationType;
+
+    /// Every raw occupation category parsed from the census table, including `All`, should map to
+    /// a detailed `OccupationType`, so no occupation row is ever silently dropped
+    #[test]
+    fn every_raw_occupation_maps_to_a_detailed_occupation() {
+        for raw_occupation in RawOccupationType::iter() {
+            let _: OccupationType = raw_occupation.into();
+        }
+    }
+}
+
+#[cfg(test)]
+mod exposure_probability_tests {
+    use crate::disease::DiseaseModel;
+    use crate::interventions::MaskStatus;
+    use crate::models::building::{BuildingID, BuildingType};
+    use crate::models::citizen::{Citizen, CitizenID, Occupation};
+    use crate::models::output_area::OutputAreaID;
+
+    fn unmasked_citizen() -> Citizen {
+        let household_code = BuildingID::new(
+            OutputAreaID::from_code_and_index("area".to_string(), 0),
+            BuildingType::Household,
+            0,
+        );
+        Citizen::new(
+            CitizenID::from_indexes(0),
+            household_code.clone(),
+            household_code,
+            30,
+            Occupation::Unemployed,
+            false,
+            false,
+            false,
+            24,
+        )
+    }
+
+    /// With zero infectious contacts, the exposure probability must be exactly zero regardless of
+    /// the disease model or mask status, since `binomial` is `1 - (1 - p)^0`
+    #[test]
+    fn zero_contacts_give_zero_probability() {
+        let citizen = unmasked_citizen();
+        let disease = DiseaseModel::covid();
+        let probability = citizen.exposure_probability(0.0, &disease, &MaskStatus::None(0));
+        assert_eq!(probability, 0.0);
+    }
+
+    /// Under an `Everywhere` mask mandate, a mask-compliant Citizen and a non-compliant Citizen
+    /// should resolve to different exposure probabilities, pinning the current behaviour of the
+    /// `is_mask_compliant` override in `exposure_probability`
+    #[test]
+    fn mask_compliant_and_non_compliant_citizens_differ() {
+        let disease = DiseaseModel::covid();
+        let mask_status = MaskStatus::Everywhere(0);
+
+        let mut compliant = unmasked_citizen();
+        compliant.is_mask_compliant = true;
+        let compliant_probability = compliant.exposure_probability(5.0, &disease, &mask_status);
+
+        let non_compliant = unmasked_citizen();
+        let non_compliant_probability = non_compliant.exposure_probability(5.0, &disease, &mask_status);
+
+        assert_ne!(compliant_probability, non_compliant_probability);
+    }
+}
+
+#[cfg(test)]
+mod deceased_citizen_tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::disease::{DiseaseModel, DiseaseStatus};
+    use crate::models::building::{BuildingID, BuildingType};
+    use crate::models::citizen::{Citizen, CitizenID, Occupation};
+    use crate::models::output_area::OutputAreaID;
+    use crate::time::DayOfWeek;
+
+    /// Once a Citizen dies, their `current_building_position` must never change again, and they
+    /// must stop counting as infected (and so stop generating exposures), even across many
+    /// further time steps
+    #[test]
+    fn deceased_citizen_stays_in_place_and_stops_exposing() {
+        let household_code = BuildingID::new(
+            OutputAreaID::from_code_and_index("household".to_string(), 0),
+            BuildingType::Household,
+            0,
+        );
+        let workplace_code = BuildingID::new(
+            OutputAreaID::from_code_and_index("workplace".to_string(), 0),
+            BuildingType::Workplace,
+            0,
+        );
+        let mut disease = DiseaseModel::covid();
+        disease.death_rate = 1.0;
+        let mut citizen = Citizen::new(
+            CitizenID::from_indexes(0),
+            household_code,
+            workplace_code,
+            30,
+            Occupation::Unemployed,
+            false,
+            false,
+            false,
+            24,
+        );
+        citizen.disease_status =
+            DiseaseStatus::Infected { elapsed: disease.infected_time, duration: disease.infected_time };
+        let mut rng = StdRng::seed_from_u64(0);
+
+        citizen.execute_time_step(0, &disease, false, false, DayOfWeek::Monday, &mut rng);
+        assert!(citizen.is_deceased());
+        let position_at_death = citizen.current_building_position.clone();
+
+        for hour in 1..100 {
+            citizen.execute_time_step(hour, &disease, false, false, DayOfWeek::Monday, &mut rng);
+            assert_eq!(citizen.current_building_position, position_at_death);
+        }
+        assert!(!citizen.is_infected());
+    }
+}
+
+#[cfg(test)]
+mod commute_window_tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::disease::DiseaseModel;
+    use crate::models::building::{BuildingID, BuildingType};
+    use crate::models::citizen::{Citizen, CitizenID, Occupation};
+    use crate::models::output_area::OutputAreaID;
+    use crate::time::DayOfWeek;
+
+    /// Across a full day, a public transport commuter should only ever have an `on_public_transport`
+    /// session during the configured commute window either side of the working day, never mid-day
+    /// or overnight
+    #[test]
+    fn transport_sessions_are_confined_to_the_commute_windows() {
+        let household_code = BuildingID::new(
+            OutputAreaID::from_code_and_index("household".to_string(), 0),
+            BuildingType::Household,
+            0,
+        );
+        let workplace_code = BuildingID::new(
+            OutputAreaID::from_code_and_index("workplace".to_string(), 0),
+            BuildingType::Workplace,
+            0,
+        );
+        let disease = DiseaseModel::covid();
+        let mut citizen = Citizen::new(
+            CitizenID::from_indexes(0),
+            household_code,
+            workplace_code,
+            30,
+            Occupation::Unemployed,
+            false,
+            false,
+            true,
+            24,
+        );
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for hour in 0..disease.steps_per_day {
+            citizen.execute_time_step(hour, &disease, false, false, DayOfWeek::Monday, &mut rng);
+            let in_commute_window = hour == 8 || hour == 16;
+            assert_eq!(
+                citizen.on_public_transport.is_some(),
+                in_commute_window,
+                "Citizen was on public transport at hour {}, expected only at the commute windows",
+                hour
+            );
+        }
+    }
+
+    /// A Citizen whose `household_isolating` flag is set should stay put at `start_working_hour`,
+    /// rather than travelling to their workplace as they would otherwise - this is how
+    /// `HouseholdIsolation` keeps every co-resident of an Infected Citizen pinned home
+    #[test]
+    fn household_isolating_keeps_a_citizen_from_travelling_to_work() {
+        let household_code = BuildingID::new(
+            OutputAreaID::from_code_and_index("household".to_string(), 0),
+            BuildingType::Household,
+            0,
+        );
+        let workplace_code = BuildingID::new(
+            OutputAreaID::from_code_and_index("workplace".to_string(), 0),
+            BuildingType::Workplace,
+            0,
+        );
+        let disease = DiseaseModel::covid();
+        let mut citizen = Citizen::new(
+            CitizenID::from_indexes(0),
+            household_code.clone(),
+            workplace_code,
+            30,
+            Occupation::Unemployed,
+            false,
+            false,
+            false,
+            24,
+        );
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for hour in 0..disease.steps_per_day {
+            citizen.execute_time_step(hour, &disease, false, true, DayOfWeek::Monday, &mut rng);
+        }
+
+        assert_eq!(
+            citizen.current_building_position, household_code,
+            "An isolating Citizen should never leave their household, even at their usual start_working_hour"
+        );
+    }
+}
+
+#[cfg(test)]
+mod reinfection_tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::disease::{DiseaseModel, DiseaseStatus};
+    use crate::interventions::MaskStatus;
+    use crate::models::building::{BuildingID, BuildingType};
+    use crate::models::citizen::{Citizen, CitizenID, Occupation};
+    use crate::models::output_area::OutputAreaID;
+
+    fn susceptible_citizen(index: u32) -> Citizen {
+        let household_code = BuildingID::new(
+            OutputAreaID::from_code_and_index("area".to_string(), 0),
+            BuildingType::Household,
+            0,
+        );
+        Citizen::new(
+            CitizenID::from_indexes(index),
+            household_code.clone(),
+            household_code,
+            30,
+            Occupation::Unemployed,
+            false,
+            false,
+            false,
+            24,
+        )
+    }
+
+    /// A Citizen's second-or-later infection should reduce their `infectiousness_multiplier`, so
+    /// once they're infectious again they expose fewer of their contacts than they did on their
+    /// first infection, given the same exposure roll
+    #[test]
+    fn reinfected_citizen_exposes_fewer_contacts_than_a_first_time_infectee() {
+        let mut disease = DiseaseModel::covid();
+        disease.reinfection_infectiousness_multiplier = 0.5;
+
+        let mut first_time_infector = susceptible_citizen(0);
+        // An enormous exposure total saturates `binomial`'s result to 1.0, so this `expose` call
+        // succeeds deterministically rather than depending on the rng seed
+        first_time_infector.expose(1e7, &disease, &MaskStatus::None(0), &mut StdRng::seed_from_u64(1));
+        assert_eq!(first_time_infector.infection_count, 1);
+        assert_eq!(first_time_infector.infectiousness_multiplier, 1.0);
+
+        // Drive a second Citizen through a first infection and back to Susceptible, simulating
+        // waning immunity (not yet modelled by the disease state machine itself), so their second
+        // `expose` call is a genuine reinfection
+        let mut reinfected_infector = susceptible_citizen(1);
+        reinfected_infector.expose(1e7, &disease, &MaskStatus::None(0), &mut StdRng::seed_from_u64(1));
+        reinfected_infector.disease_status = DiseaseStatus::Susceptible;
+        reinfected_infector.expose(1e7, &disease, &MaskStatus::None(0), &mut StdRng::seed_from_u64(1));
+        assert_eq!(reinfected_infector.infection_count, 2);
+        assert_eq!(reinfected_infector.infectiousness_multiplier, 0.5);
+
+        // Use each infector's resulting `infectiousness_multiplier` exactly as
+        // `Simulator::apply_exposures` does: scaling the exposure total fed into a shared
+        // population of contacts' `expose` calls, with both runs sharing the same rng seed. The
+        // `* 500.0` scale keeps the resulting exposure probabilities high enough that the gap
+        // between a 1.0x and 0.5x multiplier is visible across only 100 contacts
+        let contacts_exposed = |infectiousness_multiplier: f64| -> usize {
+            let mut rng = StdRng::seed_from_u64(42);
+            (0u32..100)
+                .filter(|index| {
+                    let mut contact = susceptible_citizen(100 + *index);
+                    contact.expose(infectiousness_multiplier * 500.0, &disease, &MaskStatus::None(0), &mut rng)
+                })
+                .count()
+        };
+
+        let first_time_exposures = contacts_exposed(first_time_infector.infectiousness_multiplier);
+        let reinfected_exposures = contacts_exposed(reinfected_infector.infectiousness_multiplier);
+
+        assert!(
+            reinfected_exposures < first_time_exposures,
+            "Expected a reinfected Citizen ({} exposures) to expose fewer contacts than a \
+             first-time infectee ({} exposures)",
+            reinfected_exposures,
+            first_time_exposures
+        );
+    }
+}
+
+#[cfg(test)]
+mod weekend_operation_tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::disease::DiseaseModel;
+    use crate::models::building::{BuildingID, BuildingType};
+    use crate::models::citizen::{Citizen, CitizenID, Occupation, OccupationType};
+    use crate::models::output_area::OutputAreaID;
+    use crate::time::DayOfWeek;
+
+    fn citizen_with_occupation(occupation: Occupation) -> Citizen {
+        let household_code = BuildingID::new(
+            OutputAreaID::from_code_and_index("household".to_string(), 0),
+            BuildingType::Household,
+            0,
+        );
+        let workplace_code = BuildingID::new(
+            OutputAreaID::from_code_and_index("workplace".to_string(), 0),
+            BuildingType::Workplace,
+            0,
+        );
+        let mut citizen = Citizen::new(
+            CitizenID::from_indexes(0),
+            household_code,
+            workplace_code,
+            30,
+            occupation,
+            false,
+            false,
+            false,
+            24,
+        );
+        // Ensure work starts and ends somewhere in the middle of the simulated day, regardless of
+        // its default schedule, so asserting their position partway through the day is meaningful
+        citizen.start_working_hour = 8;
+        citizen.end_working_hour = 16;
+        citizen
+    }
+
+    /// On a weekend day, an Essential worker (e.g. hospital staff) should still travel to their
+    /// workplace, while a Normal office worker should stay at home
+    #[test]
+    fn essential_workers_attend_on_weekends_while_normal_workers_stay_home() {
+        let disease = DiseaseModel::covid();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let mut hospital_worker =
+            citizen_with_occupation(Occupation::Essential { occupation: OccupationType::Caring });
+        let mut office_worker =
+            citizen_with_occupation(Occupation::Normal { occupation: OccupationType::Administrative });
+
+        for hour in 0..=hospital_worker.start_working_hour {
+            hospital_worker.execute_time_step(hour, &disease, false, false, DayOfWeek::Saturday, &mut rng);
+            office_worker.execute_time_step(hour, &disease, false, false, DayOfWeek::Saturday, &mut rng);
+        }
+
+        assert_eq!(hospital_worker.current_building_position, hospital_worker.workplace_code);
+        assert_eq!(office_worker.current_building_position, office_worker.household_code);
+    }
+}
+
+#[cfg(test)]
+mod shopping_trip_tests {
+    use std::collections::HashMap;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use osm_data::{BuildingBoundaryID, RawBuilding, TagClassifiedBuilding};
+
+    use crate::disease::DiseaseModel;
+    use crate::models::building::{Building, BuildingID, BuildingType, Shop};
+    use crate::models::citizen::{Citizen, CitizenID, Occupation};
+    use crate::models::output_area::OutputAreaID;
+    use crate::time::DayOfWeek;
+
+    /// A non-working Citizen (retired/unemployed, so `workplace_code == household_code`) with a
+    /// `shop_code` assigned should move to the Shop at the shopping trip hour, and back home at
+    /// the shopping return hour, never lingering at the Shop outside that window
+    #[test]
+    fn retired_citizen_visits_their_shop_during_the_shopping_window() {
+        let household_code = BuildingID::new(
+            OutputAreaID::from_code_and_index("household".to_string(), 0),
+            BuildingType::Household,
+            0,
+        );
+        let shop_code = BuildingID::new(
+            OutputAreaID::from_code_and_index("household".to_string(), 0),
+            BuildingType::Shop,
+            0,
+        );
+        let disease = DiseaseModel::covid();
+        let mut citizen = Citizen::new(
+            CitizenID::from_indexes(0),
+            household_code.clone(),
+            household_code.clone(),
+            70,
+            Occupation::Unemployed,
+            false,
+            false,
+            false,
+            24,
+        );
+        citizen.set_shop_code(shop_code.clone());
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for hour in 0..disease.steps_per_day {
+            citizen.execute_time_step(hour, &disease, false, false, DayOfWeek::Monday, &mut rng);
+            let should_be_at_shop = hour == 13;
+            assert_eq!(
+                citizen.current_building_position == shop_code,
+                should_be_at_shop,
+                "Citizen was at position {:?} at hour {}, expected at shop: {}",
+                citizen.current_building_position,
+                hour,
+                should_be_at_shop
+            );
+        }
+        assert_eq!(citizen.current_building_position, household_code);
+    }
+
+    /// While sharing a Shop with an infected Citizen during the shopping window, a susceptible
+    /// Citizen must be a candidate for exposure there, the same way they would be in a Household
+    /// or Workplace
+    #[test]
+    fn susceptible_citizen_can_be_exposed_at_the_shop() {
+        let shop_building_id = BuildingID::new(
+            OutputAreaID::from_code_and_index("household".to_string(), 0),
+            BuildingType::Shop,
+            0,
+        );
+        let boundary = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(0, 0), (10, 0), (10, 10), (0, 10), (0, 0)]),
+            vec![],
+        );
+        let raw_building = RawBuilding::new(
+            TagClassifiedBuilding::Shop,
+            &boundary,
+            BuildingBoundaryID::default(),
+        )
+            .unwrap();
+        let mut shop = Shop::new(shop_building_id.clone(), raw_building);
+
+        let retired_citizen = CitizenID::from_indexes(0);
+        let infected_citizen = CitizenID::from_indexes(1);
+        shop.add_citizen(retired_citizen).unwrap();
+        shop.add_citizen(infected_citizen).unwrap();
+
+        let mut ages = HashMap::new();
+        ages.insert(retired_citizen, 70);
+        ages.insert(infected_citizen, 40);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let exposed = shop.find_exposures(&[infected_citizen], &ages, None, None, false, &mut rng);
+
+        assert!(
+            exposed.contains(&retired_citizen),
+            "Retired Citizen sharing a Shop with an infected Citizen should be a candidate for exposure"
+        );
+    }
+}
+
+#[cfg(test)]
+mod low_resolution_schedule_tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::disease::DiseaseModel;
+    use crate::models::building::{BuildingID, BuildingType};
+    use crate::models::citizen::{Citizen, CitizenID, Occupation, OccupationType};
+    use crate::models::output_area::OutputAreaID;
+    use crate::time::DayOfWeek;
+
+    /// At a `steps_per_day` of 1, `start_working_hour` and `end_working_hour` both collapse to step
+    /// 0 (see `Citizen::new`) - a working Citizen must still alternate between their workplace and
+    /// household each day they work, rather than moving to the workplace once and then staying stuck
+    /// there forever because the "starts work" arm matches every step
+    #[test]
+    fn working_citizen_alternates_home_and_work_at_day_level_resolution() {
+        let household_code = BuildingID::new(
+            OutputAreaID::from_code_and_index("household".to_string(), 0),
+            BuildingType::Household,
+            0,
+        );
+        let workplace_code = BuildingID::new(
+            OutputAreaID::from_code_and_index("household".to_string(), 0),
+            BuildingType::Workplace,
+            0,
+        );
+        let disease = DiseaseModel::covid_with_resolution(1);
+        let mut citizen = Citizen::new(
+            CitizenID::from_indexes(0),
+            household_code.clone(),
+            workplace_code.clone(),
+            30,
+            Occupation::Normal { occupation: OccupationType::Sales },
+            false,
+            false,
+            false,
+            1,
+        );
+        let mut rng = StdRng::seed_from_u64(0);
+
+        let mut positions = Vec::new();
+        for day in 0..10 {
+            citizen.execute_time_step(day, &disease, false, false, DayOfWeek::Monday, &mut rng);
+            positions.push(citizen.current_building_position.clone());
+        }
+
+        assert!(
+            positions.contains(&workplace_code),
+            "Citizen should reach their workplace at some point"
+        );
+        assert!(
+            positions.contains(&household_code),
+            "Citizen should return home at some point, rather than staying stuck at work"
+        );
+        for pair in positions.windows(2) {
+            assert_ne!(
+                pair[0], pair[1],
+                "Citizen should alternate between home and work every day, not stay in place"
+            );
+        }
+    }
+}
@@ -71,5 +71,6 @@ pub fn get_density_for_occupation(occupation: OccupationType) -> u32 {
         OccupationType::Sales => EmploymentDensities::RETAIL_HIGH_STREET,
         OccupationType::MachineOperatives => EmploymentDensities::INDUSTRIAL_GENERAL,
         OccupationType::Teaching => EmploymentDensities::RETAIL_HIGH_STREET,
+        OccupationType::Other => EmploymentDensities::OFFICE_GENERAL_OFFICE,
     }
 }
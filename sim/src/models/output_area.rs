@@ -18,22 +18,28 @@
  *
  */
 
-use std::collections::HashSet;
-use std::convert::TryFrom;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
 
 use anyhow::Context;
-use log::error;
+use enum_map::EnumMap;
+use geo::centroid::Centroid;
+use geo::prelude::BoundingRect;
+use geo_types::{Coordinate, Point, Rect};
+use log::{error, warn};
 use rand::distributions::{Bernoulli, Distribution};
-use rand::RngCore;
+use rand::{Rng, RngCore};
+use rand_distr::Gamma;
 use serde::{Deserialize, Serialize};
 
 use load_census_data::CensusDataEntry;
 use load_census_data::tables::population_and_density_per_output_area::PersonType;
-use osm_data::{RawBuilding, TagClassifiedBuilding};
+use osm_data::{convert_polygon_to_float, RawBuilding, TagClassifiedBuilding};
 
-use crate::config::MAX_STUDENT_AGE;
+use crate::config::{MAX_STUDENT_AGE, PUBLIC_TRANSPORT_PERCENTAGE};
+use crate::disease::DiseaseStatus;
 use crate::interventions::InterventionStatus;
 use crate::models::building::{Building, BuildingID, BuildingType, Household, Workplace};
 use crate::models::citizen::{Citizen, CitizenID, Occupation, OccupationType};
@@ -81,22 +87,70 @@ impl PartialEq for OutputAreaID {
 /// Has a given code corresponding to an area of the country, and a list of households and citizens
 ///
 /// The polygon and `draw()` function can be used for image representation
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct OutputArea {
     /// The Census Data Output Area Code
     pub output_area_id: OutputAreaID,
     pub citizens_eligible_for_vaccine: Option<HashSet<CitizenID>>,
     pub citizens: Vec<Citizen>,
+    /// Citizens pruned out of `citizens` by `prune_isolated_citizens` as epidemiologically inert -
+    /// they have no separate workplace/school, live alone, and don't use public transport, so they
+    /// can only catch the disease via importation, not local contact
+    pub isolated_citizens: Vec<Citizen>,
     /// A map of households, corresponding to what area they are in (Rural, Urban, Etc)
     pub buildings: Vec<Box<dyn Building + Sync + Send>>,
     /// A polygon for drawing this output area
     pub polygon: geo_types::Polygon<i32>,
     pub total_residents: u32,
+    /// The number of `citizens` currently `DiseaseStatus::Susceptible`, maintained incrementally
+    /// alongside `citizens` rather than recomputed by scanning it every time step
+    ///
+    /// Lets `Simulator::apply_exposures` skip a whole Output Area's exposure processing once this
+    /// reaches zero, rather than walking every building's exposure list only to find nobody left
+    /// who can actually catch the disease
+    pub susceptible_citizen_count: u32,
     pub interventions: InterventionStatus,
+    /// Household `BuildingID` -> number of time steps remaining before isolation is lifted
+    ///
+    /// Populated by `HouseholdIsolation` whenever a resident is detected transitioning into
+    /// `DiseaseStatus::Infected`, pinning every co-resident sharing that `household_code` (found via
+    /// `Citizen::execute_time_step`'s `household_isolating` check) to the household for the rest of
+    /// the newly-infected Citizen's infectious period
+    pub isolating_households: HashMap<BuildingID, u16>,
 
     /// The distribution to use to determine whether a Citizen is wearing a mask\
     /// Is stored as a distribution to increase speed
     mask_distribution: Bernoulli,
+    /// The distribution to use to determine whether a Citizen's infection is asymptomatic\
+    /// Is stored as a distribution to increase speed
+    asymptomatic_distribution: Bernoulli,
+    /// The distribution to use to determine whether a Citizen commutes by public transport\
+    /// Is stored as a distribution to increase speed
+    public_transport_distribution: Bernoulli,
+
+    centroid_cache: RefCell<Option<Point<f64>>>,
+    bounds_cache: RefCell<Option<Rect<f64>>>,
+}
+
+/// Scales an Output Area's census population down to the target used when generating Citizens, so
+/// e.g. a `population_scale` of `0.1` runs a 10%-sized version of the full region - households
+/// (and so workplaces, assigned per-citizen) shrink to match, since `generate_citizens_with_households`
+/// simply stops building households once this target is reached
+fn scale_population(total_population: u16, population_scale: f64) -> u32 {
+    (total_population as f64 * population_scale).round() as u32
+}
+
+/// Draws a per-Citizen infectiousness multiplier from a Gamma(`dispersion`, 1 / `dispersion`)
+/// distribution, which has a mean of `1.0` regardless of `dispersion` - so a low `dispersion` (`k`)
+/// widens the distribution, reproducing "superspreader" heterogeneity, while a high `dispersion`
+/// converges towards every Citizen sharing the same baseline infectiousness
+///
+/// Returns `1.0` (no heterogeneity) when `dispersion` is `None`, or when it's not a valid Gamma shape
+fn sample_infectiousness_multiplier(dispersion: Option<f64>, rng: &mut dyn RngCore) -> f64 {
+    dispersion
+        .and_then(|dispersion| Gamma::new(dispersion, 1.0 / dispersion).ok())
+        .map(|gamma| gamma.sample(rng))
+        .unwrap_or(1.0)
 }
 
 impl OutputArea {
@@ -107,17 +161,57 @@ impl OutputArea {
         output_area_id: OutputAreaID,
         polygon: geo_types::Polygon<i32>,
         mask_compliance_ratio: f64,
+    ) -> anyhow::Result<OutputArea> {
+        OutputArea::new_with_asymptomatic_chance(output_area_id, polygon, mask_compliance_ratio, 0.0)
+    }
+    /// Builds a new output area, for the given code, polygon for drawing and a census record of the population
+    ///
+    /// As with `new`, but also allows the proportion of Citizens whose infection will be
+    /// asymptomatic to be configured
+    pub fn new_with_asymptomatic_chance(
+        output_area_id: OutputAreaID,
+        polygon: geo_types::Polygon<i32>,
+        mask_compliance_ratio: f64,
+        asymptomatic_chance: f64,
+    ) -> anyhow::Result<OutputArea> {
+        OutputArea::new_with_commute_config(
+            output_area_id,
+            polygon,
+            mask_compliance_ratio,
+            asymptomatic_chance,
+            PUBLIC_TRANSPORT_PERCENTAGE,
+        )
+    }
+    /// Builds a new output area, for the given code, polygon for drawing and a census record of the population
+    ///
+    /// As with `new_with_asymptomatic_chance`, but also allows the proportion of commuters who use public
+    /// transport (rather than a car) to be configured
+    pub fn new_with_commute_config(
+        output_area_id: OutputAreaID,
+        polygon: geo_types::Polygon<i32>,
+        mask_compliance_ratio: f64,
+        asymptomatic_chance: f64,
+        public_transport_chance: f64,
     ) -> anyhow::Result<OutputArea> {
         Ok(OutputArea {
             output_area_id,
             citizens_eligible_for_vaccine: None,
             citizens: Default::default(),
+            isolated_citizens: Default::default(),
             buildings: Default::default(),
             polygon,
             total_residents: 0,
+            susceptible_citizen_count: 0,
             interventions: Default::default(),
+            isolating_households: Default::default(),
             mask_distribution: Bernoulli::new(mask_compliance_ratio)
                 .context("Failed to initialise the mask distribution")?,
+            asymptomatic_distribution: Bernoulli::new(asymptomatic_chance)
+                .context("Failed to initialise the asymptomatic distribution")?,
+            public_transport_distribution: Bernoulli::new(public_transport_chance)
+                .context("Failed to initialise the public transport distribution")?,
+            centroid_cache: RefCell::new(None),
+            bounds_cache: RefCell::new(None),
         })
     }
     /// Generates the Citizens for this Output Area, with households being the provided [`RawBuilding`]
@@ -131,9 +225,13 @@ impl OutputArea {
         rng: &mut dyn RngCore,
         census_data: CensusDataEntry,
         possible_buildings: Vec<RawBuilding>,
+        steps_per_day: u32,
+        population_scale: f64,
+        superspreading_dispersion: Option<f64>,
     ) -> anyhow::Result<u32> {
         self.citizens = Vec::with_capacity(census_data.total_population_size() as usize);
         let pop_count = &census_data.population_count.population_counts;
+        let target_population = scale_population(pop_count[PersonType::All], population_scale);
 
         // TODO Fix this
         let household_size = (pop_count[PersonType::All] as usize / possible_buildings.len()) + 1;
@@ -142,43 +240,61 @@ impl OutputArea {
         // Build households
         let mut possible_buildings = possible_buildings.iter();
         let possible_buildings_size = possible_buildings.len();
-        while generated_population <= pop_count[PersonType::All] {
+        while generated_population <= target_population {
             if let Some(location) = possible_buildings.next() {
                 assert_eq!(location.classification(), TagClassifiedBuilding::Household);
-                let household_building_id = BuildingID::new(
-                    self.output_area_id.clone(),
-                    BuildingType::Household,
-                    self.buildings.len() as u32,
-                );
-                let mut household =
-                    Household::new(household_building_id.clone(), location.center());
-                for _ in 0..household_size {
-                    let raw_occupation = census_data.occupation_count.get_random_occupation(rng);
-                    let age = census_data.age_population.get_random_age(rng);
-                    let occupation = if age < MAX_STUDENT_AGE {
-                        Occupation::Student
-                    } else {
-                        Occupation::Normal { occupation: OccupationType::try_from(raw_occupation).unwrap_or_else(|_| panic!("Couldn't convert Census Occupation ({:?}), to sim occupation", raw_occupation)) }
-                    };
-                    let citizen = Citizen::new(
-                        CitizenID::from_indexes(global_citizen_index),
-                        household_building_id.clone(),
-                        household_building_id.clone(),
-                        age,
-                        occupation,
-                        self.mask_distribution.sample(rng),
-                        rng,
+                // Denser building types (e.g. apartments) host multiple households in the same footprint,
+                // rather than consuming one household's worth of the remaining population per building
+                let households_in_building = location
+                    .household_building_type()
+                    .map(|building_type| building_type.household_density_multiplier())
+                    .unwrap_or(1);
+                let mut population_target_reached = false;
+                for _ in 0..households_in_building {
+                    let household_building_id = BuildingID::new(
+                        self.output_area_id.clone(),
+                        BuildingType::Household,
+                        self.buildings.len() as u32,
                     );
-                    household
-                        .add_citizen(citizen.id())
-                        .context("Failed to add Citizen to Household")?;
-                    self.citizens.push(citizen);
-                    self.total_residents += 1;
-                    generated_population += 1;
-                    global_citizen_index += 1;
+                    let mut household =
+                        Household::new(household_building_id.clone(), location.center());
+                    for _ in 0..household_size {
+                        let raw_occupation = census_data.occupation_count.get_random_occupation(rng);
+                        let age = census_data.age_population.get_random_age(rng);
+                        let occupation = if age < MAX_STUDENT_AGE {
+                            Occupation::Student
+                        } else {
+                            Occupation::Normal { occupation: OccupationType::from(raw_occupation) }
+                        };
+                        let mut citizen = Citizen::new(
+                            CitizenID::from_indexes(global_citizen_index),
+                            household_building_id.clone(),
+                            household_building_id.clone(),
+                            age,
+                            occupation,
+                            self.mask_distribution.sample(rng),
+                            self.asymptomatic_distribution.sample(rng),
+                            self.public_transport_distribution.sample(rng),
+                            steps_per_day,
+                        );
+                        citizen.infectiousness_multiplier =
+                            sample_infectiousness_multiplier(superspreading_dispersion, rng);
+                        household
+                            .add_citizen(citizen.id())
+                            .context("Failed to add Citizen to Household")?;
+                        self.citizens.push(citizen);
+                        self.total_residents += 1;
+                        self.susceptible_citizen_count += 1;
+                        generated_population += 1;
+                        global_citizen_index += 1;
+                    }
+                    self.buildings.push(Box::new(household));
+                    if generated_population >= target_population {
+                        population_target_reached = true;
+                        break;
+                    }
                 }
-                self.buildings.push(Box::new(household));
-                if generated_population >= pop_count[PersonType::All] {
+                if population_target_reached {
                     break;
                 }
             } else {
@@ -188,7 +304,7 @@ impl OutputArea {
                 possible_buildings_size,
                 household_size,
                     generated_population,
-                    pop_count[PersonType::All]
+                    target_population
                 );
                 return Ok(self.citizens.len() as u32);
             }
@@ -211,6 +327,16 @@ impl OutputArea {
     pub fn get_workers(&self) -> Vec<CitizenID> {
         self.extract_occupants_for_building_type::<Workplace>()
     }
+    /// Counts `self.buildings` by `BuildingType`, for validating population generation (e.g.
+    /// flagging an area with households but no workplaces) without needing to downcast each
+    /// building individually
+    pub fn building_counts(&self) -> EnumMap<BuildingType, usize> {
+        let mut counts = EnumMap::default();
+        for building in &self.buildings {
+            counts[*building.id().building_type()] += 1;
+        }
+        counts
+    }
     pub fn get_citizen(&self, local_index: &u32) -> Option<&Citizen> {
         self.citizens.get(*local_index as usize)
     }
@@ -223,32 +349,394 @@ impl OutputArea {
     pub fn decrement_index(&mut self) {
         self.output_area_id.index -= 1;
     }
-}
-
-impl Clone for OutputArea {
-    fn clone(&self) -> Self {
-        let mut buildings_copy: Vec<Box<dyn Building + Sync + Send>> =
-            Vec::with_capacity(self.buildings.len());
-        for current_building in &self.buildings {
-            let current_building = current_building.as_any();
-            if let Some(household) = current_building.downcast_ref::<Household>() {
-                buildings_copy.push(Box::new(household.clone()));
-            } else if let Some(workplace) = current_building.downcast_ref::<Workplace>() {
-                buildings_copy.push(Box::new(workplace.clone()));
+    /// Moves fully-isolated Citizens out of `citizens` and into `isolated_citizens`, so
+    /// `generate_exposures` no longer has to process them every time step
+    ///
+    /// A Citizen counts as fully isolated if they have no separate workplace or school (their
+    /// `workplace_code` is the same as their `household_code`), they are the sole occupant of that
+    /// household, and they don't use public transport - in other words, they never come into contact
+    /// with another Citizen, so they can only be infected via importation
+    ///
+    /// Returns the number of Citizens pruned
+    pub fn prune_isolated_citizens(&mut self) -> usize {
+        let mut household_occupant_counts: HashMap<BuildingID, usize> = HashMap::new();
+        for citizen in &self.citizens {
+            *household_occupant_counts
+                .entry(citizen.household_code.clone())
+                .or_insert(0) += 1;
+        }
+        let (isolated, active): (Vec<Citizen>, Vec<Citizen>) =
+            self.citizens.drain(..).partition(|citizen| {
+                let household_occupants = household_occupant_counts
+                    .get(&citizen.household_code)
+                    .copied()
+                    .unwrap_or(0);
+                citizen.disease_status == DiseaseStatus::Susceptible
+                    && citizen.workplace_code == citizen.household_code
+                    && !citizen.uses_public_transport
+                    && household_occupants <= 1
+            });
+        let pruned = isolated.len();
+        // Every pruned Citizen is Susceptible (checked above), so the active count drops by exactly
+        // the number pruned
+        self.susceptible_citizen_count = self.susceptible_citizen_count.saturating_sub(pruned as u32);
+        self.citizens = active;
+        self.isolated_citizens.extend(isolated);
+        pruned
+    }
+    /// Records a Citizen transitioning from `Susceptible` to `Exposed`, decrementing
+    /// `susceptible_citizen_count` to match
+    ///
+    /// Returns an error, rather than panicking or saturating, if the count is already zero - that
+    /// would mean a Citizen was exposed despite `apply_exposures` having already skipped this Area
+    /// as fully depleted, which points to the two falling out of sync somewhere
+    pub fn citizen_exposed(&mut self) -> Result<(), crate::error::SimError> {
+        match self.susceptible_citizen_count.checked_sub(1) {
+            Some(count) => {
+                self.susceptible_citizen_count = count;
+                Ok(())
+            }
+            None => {
+                warn!("Cannot log citizen being exposed, as no susceptible citizens left in Output Area {}", self.output_area_id);
+                Err(crate::error::SimError::new_simulation_error(String::from(
+                    "Cannot expose citizen as no citizens are susceptible!",
+                )))
+            }
+        }
+    }
+    /// Rolls each isolated Citizen against `importation_rate` using `rng`, moving any successful
+    /// rolls back into `citizens` as newly `Exposed` - this is the only way an isolated Citizen can
+    /// catch the disease, since they are otherwise skipped by `generate_exposures`
+    ///
+    /// Returns the id, new local index (their position in `citizens`) and age of each re-included
+    /// Citizen, so callers can keep any external Citizen -> local index lookup in sync, as well as
+    /// age-banded infection statistics
+    pub fn import_disease_into_isolated_citizens(
+        &mut self,
+        importation_rate: f64,
+        rng: &mut dyn RngCore,
+    ) -> Vec<(CitizenID, u32, u16)> {
+        let mut imported = Vec::new();
+        let mut index = 0;
+        while index < self.isolated_citizens.len() {
+            if rng.gen::<f64>() < importation_rate {
+                let mut citizen = self.isolated_citizens.remove(index);
+                citizen.disease_status = DiseaseStatus::Exposed(0);
+                let local_index = self.citizens.len() as u32;
+                imported.push((citizen.id(), local_index, citizen.age));
+                self.citizens.push(citizen);
             } else {
-                panic!("Unsupported building type, for cloning!")
+                index += 1;
             }
         }
+        imported
+    }
+    /// Starts (or restarts) `household`'s isolation period for `duration` time steps, called when
+    /// one of its residents is detected transitioning into `DiseaseStatus::Infected`
+    pub fn begin_household_isolation(&mut self, household: BuildingID, duration: u16) {
+        self.isolating_households.insert(household, duration);
+    }
+    /// Whether `household` is currently isolating, per `begin_household_isolation`
+    pub fn household_is_isolating(&self, household: &BuildingID) -> bool {
+        self.isolating_households.contains_key(household)
+    }
+    /// Counts down every currently isolating household by one time step, lifting isolation once its
+    /// remaining duration reaches zero
+    pub fn tick_household_isolation(&mut self) {
+        self.isolating_households
+            .retain(|_, remaining| {
+                *remaining = remaining.saturating_sub(1);
+                *remaining > 0
+            });
+    }
+    /// Returns the centroid of this Output Area's polygon, cached after the first computation
+    ///
+    /// This `polygon` field is a single ring (with optional holes), rather than a multipolygon, so there's
+    /// no ambiguity over which ring to use
+    pub fn centroid(&self) -> Point<f64> {
+        if let Some(centroid) = self.centroid_cache.borrow().clone() {
+            return centroid;
+        }
+        let centroid = convert_polygon_to_float::<i32, f64>(&self.polygon)
+            .centroid()
+            .unwrap_or_else(|| Point::new(0.0, 0.0));
+        *self.centroid_cache.borrow_mut() = Some(centroid);
+        centroid
+    }
+    /// Returns the bounding box of this Output Area's polygon, cached after the first computation
+    pub fn bounds(&self) -> Rect<f64> {
+        if let Some(bounds) = self.bounds_cache.borrow().clone() {
+            return bounds;
+        }
+        let bounds = convert_polygon_to_float::<i32, f64>(&self.polygon)
+            .bounding_rect()
+            .unwrap_or_else(|| Rect::new(Coordinate { x: 0.0, y: 0.0 }, Coordinate { x: 0.0, y: 0.0 }));
+        *self.bounds_cache.borrow_mut() = Some(bounds);
+        bounds
+    }
+}
 
+impl Clone for OutputArea {
+    fn clone(&self) -> Self {
         OutputArea {
             output_area_id: self.output_area_id.clone(),
             citizens_eligible_for_vaccine: self.citizens_eligible_for_vaccine.clone(),
             citizens: self.citizens.clone(),
-            buildings: buildings_copy,
+            isolated_citizens: self.isolated_citizens.clone(),
+            // `Box<dyn Building + Sync + Send>` clones itself via `Building::clone_box`, so this
+            // works for any `Building` implementation, not just the built-in types
+            buildings: self.buildings.clone(),
             polygon: self.polygon.clone(),
             total_residents: self.total_residents,
+            susceptible_citizen_count: self.susceptible_citizen_count,
             interventions: self.interventions.clone(),
+            isolating_households: self.isolating_households.clone(),
             mask_distribution: self.mask_distribution,
+            asymptomatic_distribution: self.asymptomatic_distribution,
+            public_transport_distribution: self.public_transport_distribution,
+            centroid_cache: self.centroid_cache.clone(),
+            bounds_cache: self.bounds_cache.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{Coordinate, LineString, Polygon};
+    use rand::distributions::Distribution;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    use osm_data::{BuildingBoundaryID, RawBuilding, TagClassifiedBuilding};
+
+    use crate::models::building::{BuildingID, BuildingType, Household, Workplace};
+    use crate::models::citizen::{Citizen, CitizenID, Occupation, OccupationType};
+    use crate::models::output_area::{
+        sample_infectiousness_multiplier, scale_population, OutputArea, OutputAreaID,
+    };
+    use crate::test_util::variance;
+
+    /// Builds a lone Citizen living by themselves, with no workplace/school and no public transport
+    /// use, so they should be picked up as fully isolated
+    fn lone_citizen(area_id: &OutputAreaID) -> Citizen {
+        let household_code = BuildingID::new(area_id.clone(), BuildingType::Household, 0);
+        Citizen::new(
+            CitizenID::from_indexes(0),
+            household_code.clone(),
+            household_code,
+            40,
+            Occupation::Unemployed,
+            false,
+            false,
+            false,
+            24,
+        )
+    }
+
+    /// A lone Citizen with no workplace/school, living alone and not using public transport, should
+    /// be pruned into `isolated_citizens` and removed from `citizens` - the list `generate_exposures`
+    /// drains from - so they are skipped by the per-step exposure loop entirely
+    #[test]
+    fn lone_citizen_is_pruned_out_of_the_exposure_loop() {
+        let area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(
+            area_id.clone(),
+            Polygon::new(
+                LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        area.citizens.push(lone_citizen(&area_id));
+
+        let pruned = area.prune_isolated_citizens();
+
+        assert_eq!(pruned, 1);
+        assert!(area.citizens.is_empty());
+        assert_eq!(area.isolated_citizens.len(), 1);
+    }
+
+    /// Once importation reaches an isolated Citizen's area, they should be re-included back into
+    /// `citizens` as `Exposed`, since that is otherwise the only way they can catch the disease
+    #[test]
+    fn importation_reintroduces_an_isolated_citizen() {
+        let area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(
+            area_id.clone(),
+            Polygon::new(
+                LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        area.citizens.push(lone_citizen(&area_id));
+        assert_eq!(area.prune_isolated_citizens(), 1);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        // An importation rate of 1.0 guarantees the isolated Citizen is rolled successfully
+        let imported = area.import_disease_into_isolated_citizens(1.0, &mut rng);
+
+        assert_eq!(imported.len(), 1);
+        assert!(area.isolated_citizens.is_empty());
+        assert_eq!(area.citizens.len(), 1);
+        assert!(!area.citizens[0].is_susceptible());
+    }
+
+    /// A household should report as isolating from the moment it's begun until its duration has
+    /// fully ticked down, at which point it should be lifted automatically
+    #[test]
+    fn household_isolation_lifts_once_its_duration_ticks_down() {
+        let area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(
+            area_id.clone(),
+            Polygon::new(
+                LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        let household_code = BuildingID::new(area_id, BuildingType::Household, 0);
+        area.begin_household_isolation(household_code.clone(), 2);
+
+        assert!(area.household_is_isolating(&household_code));
+        area.tick_household_isolation();
+        assert!(
+            area.household_is_isolating(&household_code),
+            "A 2 time step isolation shouldn't lift after only 1 tick"
+        );
+        area.tick_household_isolation();
+        assert!(
+            !area.household_is_isolating(&household_code),
+            "A 2 time step isolation should have lifted after 2 ticks"
+        );
+    }
+
+    /// With a 0% public transport modal share, every Citizen should be assigned to commute by car -
+    /// `generate_exposures` only builds bus sessions for Citizens who sample `true` here, so no
+    /// public transport exposures can be generated in an all-car area
+    #[test]
+    fn all_car_modal_share_never_assigns_public_transport() {
+        let area = OutputArea::new_with_commute_config(
+            OutputAreaID::from_code_and_index("test".to_string(), 0),
+            Polygon::new(
+                LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+            0.0,
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            assert!(!area.public_transport_distribution.sample(&mut rng));
         }
     }
+
+    #[test]
+    fn centroid_of_square_is_its_centre() {
+        let square = Polygon::new(
+            LineString::from(vec![(0, 0), (10, 0), (10, 10), (0, 10), (0, 0)]),
+            vec![],
+        );
+        let area = OutputArea::new(
+            OutputAreaID::from_code_and_index("test".to_string(), 0),
+            square,
+            0.5,
+        )
+            .expect("Failed to build test Output Area");
+        let centroid = area.centroid();
+        assert_eq!(centroid.x(), 5.0);
+        assert_eq!(centroid.y(), 5.0);
+        let bounds = area.bounds();
+        assert_eq!(bounds.min(), Coordinate { x: 0.0, y: 0.0 });
+        assert_eq!(bounds.max(), Coordinate { x: 10.0, y: 10.0 });
+    }
+
+    /// Scaling the population by 0.5 should produce roughly half the target population, so a
+    /// 0.1 scale (for example) runs a 10%-sized version of the full region
+    #[test]
+    fn half_population_scale_produces_half_the_target_population() {
+        assert_eq!(scale_population(1000, 0.5), 500);
+        assert_eq!(scale_population(1000, 1.0), 1000);
+        assert_eq!(scale_population(1000, 0.1), 100);
+    }
+
+    /// A low dispersion `k` should produce a far more spread out (higher variance) distribution of
+    /// infectiousness multipliers than a high `k`, on the same seed - reproducing the overdispersion
+    /// of secondary cases seen in real outbreaks, where a minority of Citizens cause most transmission
+    #[test]
+    fn low_dispersion_produces_higher_variance_than_high_dispersion() {
+        let sample = |dispersion: f64| -> Vec<f64> {
+            let mut rng = StdRng::seed_from_u64(42);
+            (0..1000)
+                .map(|_| sample_infectiousness_multiplier(Some(dispersion), &mut rng))
+                .collect()
+        };
+
+        let low_dispersion_samples = sample(0.1);
+        let high_dispersion_samples = sample(100.0);
+
+        assert!(variance(&low_dispersion_samples) > variance(&high_dispersion_samples));
+    }
+
+    /// With no dispersion configured, every sampled multiplier should be exactly the baseline `1.0`,
+    /// leaving infectiousness unchanged from the original (non-heterogeneous) behaviour
+    #[test]
+    fn no_dispersion_always_returns_the_baseline_multiplier() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..10 {
+            assert_eq!(sample_infectiousness_multiplier(None, &mut rng), 1.0);
+        }
+    }
+
+    /// `building_counts` should classify every building currently in `self.buildings` by its
+    /// `BuildingType`, and report zero for any type that isn't present
+    #[test]
+    fn building_counts_matches_the_buildings_actually_added() {
+        let area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(
+            area_id.clone(),
+            Polygon::new(
+                LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+
+        let location = geo_types::Point::new(0, 0);
+        area.buildings.push(Box::new(Household::new(
+            BuildingID::new(area_id.clone(), BuildingType::Household, 0),
+            location,
+        )));
+        area.buildings.push(Box::new(Household::new(
+            BuildingID::new(area_id.clone(), BuildingType::Household, 1),
+            location,
+        )));
+        let workplace_polygon = Polygon::new(
+            LineString::from(vec![(0, 0), (100, 0), (100, 100), (0, 100), (0, 0)]),
+            vec![],
+        );
+        let raw_workplace = RawBuilding::new(
+            TagClassifiedBuilding::WorkPlace,
+            &workplace_polygon,
+            BuildingBoundaryID::default(),
+        )
+            .expect("Failed to build a test RawBuilding");
+        area.buildings.push(Box::new(Workplace::new(
+            BuildingID::new(area_id, BuildingType::Workplace, 2),
+            raw_workplace,
+            OccupationType::Professional,
+            10,
+        )));
+
+        let counts = area.building_counts();
+        assert_eq!(counts[BuildingType::Household], 2);
+        assert_eq!(counts[BuildingType::Workplace], 1);
+        assert_eq!(counts[BuildingType::School], 0);
+    }
 }
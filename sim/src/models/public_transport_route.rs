@@ -63,18 +63,33 @@ pub struct PublicTransport {
     id: PublicTransportID,
     capacity: u32,
     citizens: Vec<CitizenID>,
-    pub exposure_count: usize,
+    /// The effective amount of infectious contact aboard this vehicle, i.e. the number of infected
+    /// occupants, with any asymptomatic occupants weighted down by
+    /// `DiseaseModel::asymptomatic_infectiousness_multiplier`
+    pub exposure_count: f64,
 }
 
 impl PublicTransport {
     pub fn new(source: OutputAreaID, destination: OutputAreaID) -> PublicTransport {
+        PublicTransport::with_capacity(source, destination, BUS_CAPACITY)
+    }
+    /// Creates a new vehicle for the given route, accepting at most `capacity` Citizens before
+    /// `add_citizen` starts returning `Err` - see `DiseaseModel::public_transport_capacity`
+    pub fn with_capacity(
+        source: OutputAreaID,
+        destination: OutputAreaID,
+        capacity: u32,
+    ) -> PublicTransport {
         PublicTransport {
             id: PublicTransportID::new(source, destination),
-            capacity: BUS_CAPACITY,
+            capacity,
             citizens: Default::default(),
-            exposure_count: 0,
+            exposure_count: 0.0,
         }
     }
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
     pub fn add_citizen(&mut self, citizen_id: CitizenID) -> Result<(), SimError> {
         if self.citizens.len() < self.capacity as usize {
             self.citizens.push(citizen_id);
@@ -116,3 +131,25 @@ impl Debug for PublicTransport {
         )
     }
 }
+
+/// A snapshot of a single public transport vehicle's route and current ridership, for exporting
+/// and visualising the transit network
+///
+/// Routes are the source/destination Output Area pairs `PublicTransport` vehicles are generated
+/// for, rather than a sequence of parsed OSM stops - individual stops aren't modelled
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublicTransportRoute {
+    pub id: PublicTransportID,
+    pub capacity: u32,
+    pub riders: Vec<CitizenID>,
+}
+
+impl From<&PublicTransport> for PublicTransportRoute {
+    fn from(transport: &PublicTransport) -> Self {
+        PublicTransportRoute {
+            id: transport.id().clone(),
+            capacity: transport.capacity(),
+            riders: transport.occupants().clone(),
+        }
+    }
+}
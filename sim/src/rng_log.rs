@@ -0,0 +1,210 @@
+/*
+ * Epidemic Simulation Using Census Data (ESUCD)
+ * Copyright (c)  2022. Sam Ralph
+ *
+ * This file is part of ESUCD.
+ *
+ * ESUCD is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * ESUCD is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with ESUCD.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+
+use anyhow::Context;
+use log::error;
+use rand::{thread_rng, Error, RngCore};
+
+/// Wraps another `RngCore`, recording every raw word it draws to a log file
+///
+/// `Rng::gen`/`gen_range` and friends are all built on top of `RngCore::next_u32`/`next_u64`, so
+/// recording at that level captures the full entropy stream behind every such call, without
+/// needing to instrument each call site individually. Replaying that log with `ReplayingRng`
+/// reproduces the exact same stream, forcing an identical sequence of downstream outcomes -
+/// invaluable for pinning nondeterminism bugs like the thread-count sensitivity, by diffing a
+/// recorded run's disease-status transitions against a replayed one
+///
+/// Kept behind `crate::config::RNG_LOG_ENABLED`, since writing a log line per draw has a real
+/// performance cost
+pub struct RecordingRng<R: RngCore> {
+    inner: R,
+    writer: BufWriter<File>,
+}
+
+impl<R: RngCore> RecordingRng<R> {
+    /// Wraps `inner`, appending every word it draws to a new log file at `log_path`
+    pub fn new(inner: R, log_path: &str) -> anyhow::Result<RecordingRng<R>> {
+        let file = File::create(log_path)
+            .context(format!("Failed to create RNG log: {}", log_path))?;
+        Ok(RecordingRng { inner, writer: BufWriter::new(file) })
+    }
+}
+
+impl<R: RngCore> RngCore for RecordingRng<R> {
+    fn next_u32(&mut self) -> u32 {
+        let value = self.inner.next_u32();
+        // A write failure here would silently truncate the log, but this wrapper is only a
+        // debugging aid, not part of the simulation's normal operation, so it's acceptable to just
+        // lose entries rather than propagate an error through every `RngCore` call site
+        let _ = writeln!(self.writer, "{}", value);
+        value
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let low = self.next_u32() as u64;
+        let high = self.next_u32() as u64;
+        (high << 32) | low
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let mut chunks = dest.chunks_exact_mut(4);
+        for chunk in &mut chunks {
+            chunk.copy_from_slice(&self.next_u32().to_le_bytes());
+        }
+        let remainder = chunks.into_remainder();
+        if !remainder.is_empty() {
+            let bytes = self.next_u32().to_le_bytes();
+            remainder.copy_from_slice(&bytes[..remainder.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Replays a log recorded by `RecordingRng`, returning the exact same sequence of raw words
+/// instead of generating new random ones - forcing whatever consumes this RNG down the identical
+/// code path as the recorded run
+pub struct ReplayingRng {
+    values: VecDeque<u32>,
+}
+
+impl ReplayingRng {
+    /// Loads a log written by `RecordingRng` from `log_path`
+    pub fn from_log(log_path: &str) -> anyhow::Result<ReplayingRng> {
+        let file = File::open(log_path).context(format!("Failed to open RNG log: {}", log_path))?;
+        let values = BufReader::new(file)
+            .lines()
+            .map(|line| -> anyhow::Result<u32> {
+                Ok(line.context("Failed to read RNG log line")?
+                    .parse()
+                    .context("Malformed RNG log line")?)
+            })
+            .collect::<anyhow::Result<VecDeque<u32>>>()?;
+        Ok(ReplayingRng { values })
+    }
+}
+
+impl RngCore for ReplayingRng {
+    fn next_u32(&mut self) -> u32 {
+        self.values.pop_front().expect(
+            "RNG log exhausted - this run has drawn more randomness than the recorded run did, \
+             so it has already diverged from it",
+        )
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let low = self.next_u32() as u64;
+        let high = self.next_u32() as u64;
+        (high << 32) | low
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let mut chunks = dest.chunks_exact_mut(4);
+        for chunk in &mut chunks {
+            chunk.copy_from_slice(&self.next_u32().to_le_bytes());
+        }
+        let remainder = chunks.into_remainder();
+        if !remainder.is_empty() {
+            let bytes = self.next_u32().to_le_bytes();
+            remainder.copy_from_slice(&bytes[..remainder.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Returns `Simulator`'s top level RNG: a plain `ThreadRng`, or one wrapped in a `RecordingRng`
+/// logging to `crate::config::RNG_LOG_PATH` when `crate::config::RNG_LOG_ENABLED` is set
+///
+/// Falls back to a plain, unlogged `ThreadRng` (with a logged error) if the log file can't be
+/// created, rather than failing the whole run over a debugging aid
+pub fn build_top_level_rng() -> Box<dyn RngCore> {
+    if !crate::config::RNG_LOG_ENABLED {
+        return Box::new(thread_rng());
+    }
+    match RecordingRng::new(thread_rng(), crate::config::RNG_LOG_PATH) {
+        Ok(recording) => Box::new(recording),
+        Err(e) => {
+            error!("Failed to start RNG logging, falling back to an unlogged RNG: {:#}", e);
+            Box::new(thread_rng())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::disease::{DiseaseModel, DiseaseStatus};
+    use crate::rng_log::{RecordingRng, ReplayingRng};
+
+    /// Drives `steps` time steps of `status` through `DiseaseStatus::execute_time_step`, returning
+    /// the status after each step
+    fn run_transitions(
+        mut status: DiseaseStatus,
+        disease: &DiseaseModel,
+        steps: usize,
+        rng: &mut dyn rand::RngCore,
+    ) -> Vec<DiseaseStatus> {
+        (0..steps)
+            .map(|_| {
+                status = DiseaseStatus::execute_time_step(&status, disease, 1, 30, rng);
+                status.clone()
+            })
+            .collect()
+    }
+
+    /// Replaying a log recorded while driving a Citizen through Exposed -> Infected -> Recovered/
+    /// Deceased should reproduce the exact same sequence of disease-status transitions as the
+    /// original, recorded run
+    #[test]
+    fn replayed_log_reproduces_the_same_disease_status_transitions() {
+        let disease = DiseaseModel::covid();
+        let steps = disease.exposed_time as usize + disease.infected_time as usize + 5;
+        let log_path = std::env::temp_dir().join(format!(
+            "esucd_rng_log_test_{}.txt",
+            std::process::id()
+        ));
+        let log_path = log_path.to_str().expect("Temp path should be valid UTF-8");
+
+        let mut recording = RecordingRng::new(StdRng::seed_from_u64(7), log_path)
+            .expect("Failed to create a test RNG log");
+        let recorded_transitions =
+            run_transitions(DiseaseStatus::Exposed(0), &disease, steps, &mut recording);
+        drop(recording);
+
+        let mut replaying = ReplayingRng::from_log(log_path).expect("Failed to load the test RNG log");
+        let replayed_transitions =
+            run_transitions(DiseaseStatus::Exposed(0), &disease, steps, &mut replaying);
+
+        std::fs::remove_file(log_path).ok();
+        assert_eq!(recorded_transitions, replayed_transitions);
+    }
+}
@@ -19,6 +19,11 @@
  */
 
 use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{BufWriter, Write};
 use std::ops::AddAssign;
 use std::sync::{Mutex, RwLock};
 use std::time::Instant;
@@ -26,31 +31,38 @@ use std::time::Instant;
 use anyhow::{Context, Error};
 use log::{debug, error, info, warn};
 use rand::prelude::{IteratorRandom, SliceRandom};
-use rand::rngs::ThreadRng;
+use rand::{Rng, RngCore};
 use rand::thread_rng;
 use rayon::prelude::{
     IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
     IntoParallelRefMutIterator, ParallelIterator,
 };
+use serde::{Deserialize, Serialize};
+use serde_json::to_writer;
 
-use crate::config::{DEBUG_ITERATION_PRINT, get_memory_usage};
+use crate::config::{deterministic_area_rng, DEBUG_ITERATION_PRINT, get_memory_usage, VALIDATE_INVARIANTS_AFTER_STEP};
 use crate::disease::{DiseaseModel, DiseaseStatus};
 use crate::disease::DiseaseStatus::Infected;
+use crate::error::SimError;
 use crate::interventions::{InterventionsEnabled, InterventionStatus};
-use crate::models::building::BuildingID;
+use crate::models::building::{BuildingID, BuildingType};
 use crate::models::citizen::{Citizen, CitizenID};
 use crate::models::ID;
 use crate::models::output_area::{OutputArea, OutputAreaID};
-use crate::models::public_transport_route::{PublicTransport, PublicTransportID};
+use crate::models::public_transport_route::{PublicTransport, PublicTransportID, PublicTransportRoute};
 use crate::simulator_builder::SimulatorBuilder;
+use crate::state_history::{dump_on_panic, StateHistory, StateSnapshot};
 use crate::statistics::{StatisticEntry, StatisticsRecorder};
+use crate::time::DayOfWeek;
 
 #[derive(Debug, Default, Clone)]
 struct GeneratedExposures {
     /// The list of Citizens on Public Transport, grouped by their origin and destination,
     ///
-    /// The bool represents whether a Citizen is infected
-    public_transport_pre_generated: HashMap<(OutputAreaID, OutputAreaID), Vec<(CitizenID, bool)>>,
+    /// The bools represent whether a Citizen is infected, and whether that infection is asymptomatic;
+    /// the `f64` is the Citizen's individual infectiousness multiplier
+    public_transport_pre_generated:
+        HashMap<(OutputAreaID, OutputAreaID), Vec<(CitizenID, bool, bool, f64)>>,
     /// The list of buildings, with the amount of exposures that occurred
     /// Output Area Index -> Building Exposure Index?
     building_exposure_list: Vec<HashMap<BuildingID, Vec<CitizenID>>>,
@@ -83,6 +95,19 @@ impl AddAssign for GeneratedExposures {
     }
 }
 
+/// The full effective configuration a run was started with, written to `config_used.json` so a
+/// result directory is self-describing - see `Simulator::export_config_used`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RunConfigExport {
+    area_code: String,
+    global_seed: u64,
+    /// The number of rayon worker threads the run used, read from the global thread pool rather
+    /// than threaded through as a parameter, since that's the pool every parallel step actually runs on
+    thread_count: usize,
+    disease_model: DiseaseModel,
+    interventions: InterventionStatus,
+}
+
 //#[derive(Clone)]
 pub struct Simulator {
     pub area_code: String,
@@ -99,30 +124,189 @@ pub struct Simulator {
     interventions: InterventionStatus,
     disease_model: DiseaseModel,
     pub public_transport: HashMap<PublicTransportID, PublicTransport>,
-    rng: ThreadRng,
+    rng: Box<dyn RngCore>,
+    /// The seed used to derive per Output Area Rng's, so `--threads` does not affect the outcome of a run
+    global_seed: u64,
+    /// The Day of the Week that `time_step` 0 falls on, so weekend effects stay correctly phased
+    /// regardless of which real calendar day a run is started on
+    start_day_of_week: DayOfWeek,
+    /// A ring buffer of recent `StateSnapshot`s, dumped to disk if `step` panics - see
+    /// `crate::state_history`
+    state_history: StateHistory,
 }
 
 /// Runtime Simulation Methods
 impl Simulator {
+    /// Returns the cumulative fraction of each Output Area's population that has ever been infected (the attack rate)
+    pub fn attack_rate_by_area(&self) -> HashMap<OutputAreaID, f64> {
+        self.statistics_recorder.attack_rate_by_area()
+    }
+    /// Returns every Output Area's current (not cumulative) infected fraction, sorted by descending
+    /// prevalence, for driving live "hotspot" dashboards and targeting interventions at the
+    /// worst-affected areas right now
+    ///
+    /// The population used is the Citizens currently present in the area, not the static
+    /// `total_residents` assigned at generation, so areas reflect who has actually moved there
+    ///
+    /// Output Areas with zero population are excluded, rather than being reported with a
+    /// meaningless (or NaN) prevalence
+    pub fn areas_by_prevalence(&self) -> Vec<(OutputAreaID, f64)> {
+        let mut areas: Vec<(OutputAreaID, f64)> = self
+            .output_areas
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|area| {
+                let area = area.lock().unwrap();
+                let population = area.citizens.len();
+                if population == 0 {
+                    return None;
+                }
+                let infected_count = area.citizens.iter().filter(|citizen| citizen.is_infected()).count();
+                Some((area.id(), infected_count as f64 / population as f64))
+            })
+            .collect();
+        areas.sort_by(|(_, a), (_, b)| b.partial_cmp(a).expect("Prevalence should never be NaN"));
+        areas
+    }
+    /// The number of Citizens currently mid-commute on public transport, for validating the
+    /// configured modal share against actual behaviour and for the live dashboard
+    pub fn citizens_in_transit(&self) -> usize {
+        self.output_areas
+            .read()
+            .unwrap()
+            .iter()
+            .map(|area| {
+                area.lock()
+                    .unwrap()
+                    .citizens
+                    .iter()
+                    .filter(|citizen| citizen.on_public_transport.is_some())
+                    .count()
+            })
+            .sum()
+    }
+    /// The current number of susceptible Citizens, across every Output Area
+    ///
+    /// Cheap relative to a full Citizen rescan - each Output Area already maintains its own
+    /// `susceptible_citizen_count` incrementally as Citizens move, are exposed or vaccinated (see
+    /// `OutputArea::citizen_exposed`), so this only sums those rather than checking every Citizen's
+    /// `DiseaseStatus` - see `validate_invariants` for the check that keeps the two in agreement
+    pub fn susceptible_count(&self) -> u32 {
+        self.output_areas
+            .read()
+            .unwrap()
+            .iter()
+            .map(|area| area.lock().unwrap().susceptible_citizen_count)
+            .sum()
+    }
+    /// The Day of the Week the current `time_step` falls on, so statistics and logs can be aligned
+    /// against real reported case dates
+    pub fn current_day_of_week(&self) -> DayOfWeek {
+        let elapsed_days = self.statistics_recorder.time_step() / self.disease_model.steps_per_day;
+        self.start_day_of_week.advance_by(elapsed_days)
+    }
+    /// Produces a deep copy of the current simulation state, reseeded with a fresh, independent RNG
+    /// seed, so scenarios can be branched from a shared starting point (e.g. "what if we'd locked
+    /// down at the peak") without rebuilding the population or perturbing the original run
+    ///
+    /// The fork shares none of its state with `self` - mutating one (e.g. via `disease_model` or
+    /// `interventions` in a later time step) has no effect on the other
+    pub fn fork(&self) -> Simulator {
+        let output_areas = self.output_areas.read().unwrap();
+        let citizen_output_area_lookup = self.citizen_output_area_lookup.read().unwrap();
+        Simulator {
+            area_code: self.area_code.clone(),
+            output_area_lookup: self.output_area_lookup.clone(),
+            current_population: self.current_population,
+            output_areas: RwLock::new(
+                output_areas
+                    .iter()
+                    .map(|area| Mutex::new(area.lock().unwrap().clone()))
+                    .collect(),
+            ),
+            citizen_output_area_lookup: RwLock::new(
+                citizen_output_area_lookup
+                    .iter()
+                    .map(|entry| Mutex::new(entry.lock().unwrap().clone()))
+                    .collect(),
+            ),
+            citizens_eligible_for_vaccine: self.citizens_eligible_for_vaccine.clone(),
+            statistics_recorder: self.statistics_recorder.clone(),
+            interventions: self.interventions.clone(),
+            disease_model: self.disease_model.clone(),
+            public_transport: self.public_transport.clone(),
+            rng: crate::rng_log::build_top_level_rng(),
+            global_seed: thread_rng().gen(),
+            start_day_of_week: self.start_day_of_week,
+            state_history: StateHistory::new(crate::config::STATE_HISTORY_CAPACITY),
+        }
+    }
+    /// Writes the full effective configuration this run was started with - disease model,
+    /// interventions, seed, region and thread count - to `config_used.json` in the output
+    /// directory, so a result directory is self-describing without needing to know which CLI
+    /// arguments or builder calls produced it
+    fn export_config_used(&self, output_name: &str) -> anyhow::Result<()> {
+        fs::create_dir_all(output_name)
+            .context("Failed to create output directory for config_used.json")?;
+        let export = RunConfigExport {
+            area_code: self.area_code.clone(),
+            global_seed: self.global_seed,
+            thread_count: rayon::current_num_threads(),
+            disease_model: self.disease_model.clone(),
+            interventions: self.interventions.clone(),
+        };
+        let file = File::create(output_name.to_owned() + "config_used.json")
+            .context("Failed to create config_used.json")?;
+        to_writer(BufWriter::new(file), &export).context("Failed to write config_used.json")?;
+        Ok(())
+    }
     /// Start the entire simulation process, until the disease is eradicated, or we reach teh max time step
     pub fn simulate(&mut self, output_name: String) -> anyhow::Result<()> {
+        self.simulate_with_step_hook(output_name, |_simulator| {})
+    }
+    /// Same as [`Simulator::simulate`], but calls `on_step` with a reference to `self` after every
+    /// completed time step, before checking for extinction
+    ///
+    /// Used by `visualisation::live_feed::run_with_live_feed` to render a frame of the current
+    /// state on every step, without duplicating this loop or losing the usual statistics export
+    pub fn simulate_with_step_hook(
+        &mut self,
+        output_name: String,
+        mut on_step: impl FnMut(&Simulator),
+    ) -> anyhow::Result<()> {
         let mut start_time = Instant::now();
+        self.export_config_used(&output_name)
+            .context("Failed to export the run's configuration")?;
         info!(
             "Starting simulation with {} areas",
             self.output_areas.read().unwrap().len()
         );
         for time_step in 0..self.disease_model.max_time_step {
-            if !self.step()? {
+            // Snapshotted before the step, so a panic inside it still leaves a dump of the
+            // trajectory leading up to the crash
+            let history_before_step = self.state_history.clone();
+            let panic_dump_path = format!("{}state_history_on_panic.json", output_name);
+            if !dump_on_panic(
+                &history_before_step,
+                &panic_dump_path,
+                std::panic::AssertUnwindSafe(|| self.step()),
+            )? {
                 debug!("{:?}", self.statistics_recorder.global_stats.last().expect("No data recorded!"));
                 break;
             }
+            on_step(self);
             if time_step % DEBUG_ITERATION_PRINT as u16 == 0 {
                 println!("Completed {: >3} time steps, in: {: >6} seconds  Statistics: {:?},   Memory usage: {}", DEBUG_ITERATION_PRINT, format!("{:.2}", start_time.elapsed().as_secs_f64()), self.statistics_recorder.global_stats.last().expect("No data recorded!"), get_memory_usage()?);
                 start_time = Instant::now();
             }
         }
         // TODO Change this to a cmd argument
-        self.statistics_recorder.dump_to_file(output_name);
+        self.statistics_recorder.dump_to_file(output_name.clone());
+        self.export_citizen_outcomes_csv(&(output_name.clone() + "citizen_outcomes.csv"))
+            .context("Failed to write citizen outcomes CSV")?;
+        self.export_transmission_flow_csv(&(output_name + "transmission_flows.csv"))
+            .context("Failed to write transmission flow CSV")?;
         Ok(())
     }
     /// Applies a single time step to the simulation
@@ -139,9 +323,26 @@ impl Simulator {
         self.apply_exposures(exposures)?;
         self.statistics_recorder.record_function_time("Apply Exposures".to_string());
 
+        self.apply_importations()?;
+        self.statistics_recorder.record_function_time("Apply Importations".to_string());
+
+        self.apply_community_transmission()?;
+        self.statistics_recorder.record_function_time("Apply Community Transmission".to_string());
+
         self.apply_interventions()?;
         self.statistics_recorder.record_function_time("Apply Interventions".to_string());
 
+        if cfg!(debug_assertions) && VALIDATE_INVARIANTS_AFTER_STEP {
+            self.validate_invariants()?;
+        }
+
+        if crate::config::STATE_HISTORY_CAPACITY > 0 {
+            self.state_history.record(StateSnapshot::new(
+                self.statistics_recorder.time_step(),
+                self.state_fingerprint(),
+                self.statistics_recorder.global_stats.last().expect("No data recorded!").clone(),
+            ));
+        }
 
         if !self.statistics_recorder.disease_exists() {
             info!("Disease finished as no one has the disease");
@@ -156,6 +357,9 @@ impl Simulator {
         let hour = self.statistics_recorder.time_step();
         let disease = &self.disease_model;
         let lockdown = self.interventions.lockdown_enabled();
+        let household_isolation_enabled = self.interventions.household_isolation_enabled();
+        let day_of_week = self.current_day_of_week();
+        let global_seed = self.global_seed;
         let mut output_areas = self.output_areas.write().unwrap();
         let mut citizen_lookup = self.citizen_output_area_lookup.write().unwrap();
 
@@ -164,18 +368,30 @@ impl Simulator {
         // Update the Position and Schedule of each Citizen
         // If a Citizen is changing area, then they are moved into `moved_citizens`
         // For any Citizens that are infected, build a list of infected buildings
-        let (statistics, exposures, moved_citizens) = output_areas.par_iter_mut().map(|area| {
+        let (statistics, exposures, moved_citizens) = output_areas.par_iter_mut().enumerate().map(|(area_index, area)| {
             let mut area = area.lock().unwrap();
+            let mut rng = deterministic_area_rng(global_seed, area_index as u32);
             let (mut statistics, mut exposures) = (StatisticEntry::with_time_step(hour), GeneratedExposures::default());
             // Apply timestep, and generate exposures
             let mut area_citizens = Vec::with_capacity(area.citizens.len());
             let mut moving_citizens: Vec<Vec<Citizen>> = vec![Vec::new(); output_area_count];
             let area_id = area.id();
+            if household_isolation_enabled {
+                area.tick_household_isolation();
+            }
             for mut citizen in area.citizens.drain(0..) {
+                let household_isolating = household_isolation_enabled
+                    && area.isolating_households.contains_key(&citizen.household_code);
                 let need_to_move = citizen.execute_time_step(
-                    hour, disease, lockdown,
+                    hour, disease, lockdown, household_isolating, day_of_week, &mut rng,
                 ).is_some();
-                statistics.add_citizen(&citizen.disease_status);
+                if household_isolation_enabled {
+                    if let Infected { elapsed: 0, duration } = citizen.disease_status {
+                        area.isolating_households
+                            .insert(citizen.household_code.clone(), duration);
+                    }
+                }
+                statistics.add_citizen(&citizen);
 
                 // Either generate public transport session, or add exposure for fixed building position
                 if let Some(travel) = &citizen.on_public_transport {
@@ -183,8 +399,13 @@ impl Simulator {
                         .entry(travel.clone())
                         .or_default();
 
-                    transport_session.push((citizen.id(), citizen.is_infected()));
-                } else if let Infected(_) = citizen.disease_status {
+                    transport_session.push((
+                        citizen.id(),
+                        citizen.is_infected(),
+                        citizen.is_asymptomatic,
+                        citizen.infectiousness_multiplier,
+                    ));
+                } else if let Infected { .. } = citizen.disease_status {
                     let area_index = citizen.current_building_position.output_area_code().index();
                     if exposures.building_exposure_list.len() <= area_index {
                         exposures.building_exposure_list.extend(vec![HashMap::new(); (area_index - exposures.building_exposure_list.len()) + 1]);
@@ -197,6 +418,12 @@ impl Simulator {
                     entry.push(citizen.id());
                 }
                 if need_to_move {
+                    // The Citizen is leaving this Area's `citizens` list (even if only for this
+                    // time step, e.g. commuting to a workplace Area), so this Area's susceptible
+                    // count needs to follow them
+                    if citizen.is_susceptible() {
+                        area.susceptible_citizen_count = area.susceptible_citizen_count.saturating_sub(1);
+                    }
                     let entry = moving_citizens.get_mut(citizen.current_building_position.output_area_code().index()).expect("Couldn't retrieve Output Area");
                     entry.push(citizen);
                 } else {
@@ -237,6 +464,9 @@ impl Simulator {
                     for mut citizen in citizens {
                         let local_index = area.citizens.len();
                         let id = citizen.id().clone();
+                        if citizen.is_susceptible() {
+                            area.susceptible_citizen_count += 1;
+                        }
                         area.citizens.push(citizen);
                         match citizen_lookup.get_mut(id.global_index()) {
                             Some(lookup_entry) => {
@@ -262,15 +492,23 @@ impl Simulator {
     fn apply_exposures(&mut self, exposures: GeneratedExposures) -> anyhow::Result<()> {
         let disease = &self.disease_model;
         let mask_status = &self.interventions.mask_status;
+        let school_bubbles = self.interventions.school_bubbles_enabled();
         let output_areas = &self.output_areas;
         let citizen_lookup = &self.citizen_output_area_lookup;
+        let global_seed = self.global_seed;
+        let hour = self.statistics_recorder.time_step() as u16;
         // Apply building exposures
-        let exposure_statistics: Vec<ID> = exposures
+        let (exposure_statistics, exposure_opportunities): (
+            Vec<Vec<(ID, OutputAreaID, CitizenID, u16, Option<CitizenID>)>>,
+            Vec<Vec<BuildingType>>,
+        ) = exposures
             .building_exposure_list
             .par_iter()
             .enumerate()
-            .map(|(area_index, building_exposures)| -> Vec<ID> {
+            .map(|(area_index, building_exposures)| -> (Vec<(ID, OutputAreaID, CitizenID, u16, Option<CitizenID>)>, Vec<BuildingType>) {
                 let mut exposures = Vec::new();
+                let mut opportunities = Vec::new();
+                let mut rng = deterministic_area_rng(global_seed, area_index as u32);
                 let output_areas = output_areas.read().unwrap();
                 let citizen_lookup = citizen_lookup
                     .read()
@@ -282,10 +520,29 @@ impl Simulator {
                     Ok(area) => area,
                     Err(e) => {
                         error!("{:?}", e);
-                        return exposures;
+                        return (exposures, opportunities);
                     }
                 };
                 let mut area = area.lock().unwrap();
+                // Nobody left in this Area can catch the disease, so there's no point building the
+                // per-Citizen lookups below or walking this Area's exposure list at all
+                if area.susceptible_citizen_count == 0 {
+                    return (exposures, opportunities);
+                }
+                let citizen_ages: HashMap<CitizenID, u16> =
+                    area.citizens.iter().map(|citizen| (citizen.id(), citizen.age)).collect();
+                let citizen_is_asymptomatic: HashMap<CitizenID, bool> = area
+                    .citizens
+                    .iter()
+                    .map(|citizen| (citizen.id(), citizen.is_asymptomatic))
+                    .collect();
+                let citizen_infectiousness: HashMap<CitizenID, f64> = area
+                    .citizens
+                    .iter()
+                    .map(|citizen| (citizen.id(), citizen.infectiousness_multiplier))
+                    .collect();
+                let contact_matrix = disease.contact_matrix.as_ref();
+                let child_transmission = disease.household_child_transmission.as_ref();
                 for (building_id, infected_citizens) in building_exposures {
                     let building =
                         &area
@@ -304,8 +561,28 @@ impl Simulator {
                     };
 
                     let building = building.as_ref();
-                    let exposure_count = infected_citizens.len();
-                    for citizen_id in building.find_exposures(infected_citizens) {
+                    let hospital_multiplier = if *building_id.building_type() == BuildingType::Hospital {
+                        disease.hospital_transmission_multiplier
+                    } else {
+                        1.0
+                    };
+                    let exposure_count: f64 = infected_citizens
+                        .iter()
+                        .map(|citizen_id| {
+                            let base_infectiousness =
+                                if citizen_is_asymptomatic.get(citizen_id).copied().unwrap_or(false) {
+                                    disease.asymptomatic_infectiousness_multiplier
+                                } else {
+                                    1.0
+                                };
+                            hospital_multiplier
+                                * base_infectiousness
+                                * citizen_infectiousness.get(citizen_id).copied().unwrap_or(1.0)
+                        })
+                        .sum();
+                    for citizen_id in
+                        building.find_exposures(infected_citizens, &citizen_ages, contact_matrix, child_transmission, school_bubbles, &mut rng)
+                    {
                         let lookup_ref = match citizen_lookup.get(citizen_id.global_index()) {
                             Some(lookup_ref) => lookup_ref,
                             None => {
@@ -334,66 +611,53 @@ impl Simulator {
                                 continue;
                             }
                         };
-                        if citizen.is_susceptible()
-                            && citizen.expose(
-                            exposure_count,
-                            disease,
-                            mask_status,
-                            &mut thread_rng(),
-                        )
-                        {
-                            exposures.push(ID::Building(building_id.clone()));
-                            if let Some(vaccine_list) = &mut area.citizens_eligible_for_vaccine {
-                                vaccine_list.remove(&citizen_id);
+                        if citizen.is_susceptible() {
+                            // This Citizen was actually at risk (as opposed to e.g. already immune),
+                            // so this is a genuine exposure opportunity regardless of whether the
+                            // chance roll below succeeds
+                            opportunities.push(*building_id.building_type());
+                            if citizen.expose(exposure_count, disease, mask_status, &mut rng) {
+                                // The model only tracks aggregate building-level exposure, not pairwise
+                                // contacts, so the first infected Citizen present is recorded as a
+                                // representative infector for the transmission log
+                                let infector = infected_citizens.first().copied();
+                                exposures.push((ID::Building(building_id.clone()), area.id(), citizen_id, citizen.age, infector));
+                                if let Some(vaccine_list) = &mut area.citizens_eligible_for_vaccine {
+                                    vaccine_list.remove(&citizen_id);
+                                }
+                                if let Err(e) = area.citizen_exposed() {
+                                    error!("{:?}", e);
+                                }
                             }
                         }
                     }
                 }
-                return exposures;
+                return (exposures, opportunities);
             })
-            .flatten()
-            .collect();
-        for id in exposure_statistics {
+            .unzip();
+        let exposure_statistics = exposure_statistics.into_iter().flatten();
+        for building_type in exposure_opportunities.into_iter().flatten() {
+            self.statistics_recorder
+                .record_exposure_opportunity(building_type);
+        }
+        for (id, area_id, citizen_id, age, infector) in exposure_statistics {
             self.statistics_recorder.add_exposure(id)?;
+            self.statistics_recorder
+                .record_ever_infected(area_id, citizen_id, age);
+            if let Some(infector) = infector {
+                self.statistics_recorder.record_transmission(infector, citizen_id, hour);
+            }
         }
         // Generate public transport routes
-        for (route, mut citizens) in exposures.public_transport_pre_generated {
-            // Shuffle to ensure randomness on bus
-            citizens.shuffle(&mut self.rng);
-            let mut current_bus = PublicTransport::new(route.0.clone(), route.1.clone());
-            while let Some((citizen, is_infected)) = citizens.pop() {
-                // If bus is full, generate a new one
-                if current_bus.add_citizen(citizen).is_err() {
-                    // Only need to save buses with exposures
-                    if current_bus.exposure_count > 0 {
-                        if let Err(e) = self
-                            .expose_citizens(
-                                current_bus.occupants().clone(),
-                                current_bus.exposure_count,
-                                ID::PublicTransport(current_bus.id().clone()),
-                            )
-                            .context(format!("Failed to expose bus: {}", current_bus.id()))
-                        {
-                            error!("{:?}", e);
-                        }
-                    }
-                    current_bus = PublicTransport::new(route.0.clone(), route.1.clone());
-                    current_bus
-                        .add_citizen(citizen)
-                        .context("Failed to add Citizen to new bus")?;
-                }
-                if is_infected {
-                    current_bus.exposure_count += 1;
-                }
-            }
-            if current_bus.exposure_count > 0 {
+        for (route, citizens) in exposures.public_transport_pre_generated {
+            for vehicle in self.split_route_into_vehicles(route, citizens) {
                 if let Err(e) = self
                     .expose_citizens(
-                        current_bus.occupants().clone(),
-                        current_bus.exposure_count,
-                        ID::PublicTransport(current_bus.id().clone()),
+                        vehicle.occupants().clone(),
+                        vehicle.exposure_count,
+                        ID::PublicTransport(vehicle.id().clone()),
                     )
-                    .context(format!("Failed to expose bus: {}", current_bus.id()))
+                    .context(format!("Failed to expose bus: {}", vehicle.id()))
                 {
                     error!("{:?}", e);
                 }
@@ -403,11 +667,66 @@ impl Simulator {
         //debug!("There are {} exposures", exposure_list.len());
         Ok(())
     }
+    /// Splits the commuters on a single route into vehicles of at most
+    /// `DiseaseModel::public_transport_capacity`, so a busy corridor is modelled as several
+    /// independently-exposing vehicles rather than one unrealistically large one
+    ///
+    /// Only vehicles that actually carried an infected occupant are returned, since an uninfected
+    /// vehicle has nothing to expose
+    ///
+    /// Ordered randomly unless `DiseaseModel::stable_public_transport_cohorts` is set, in which
+    /// case commuters are ordered by `CitizenID` instead - so, as long as the same set of Citizens
+    /// commute a route, they're bucketed into vehicles the same way every time, rather than
+    /// reshuffled into a fresh random mix
+    fn split_route_into_vehicles(
+        &mut self,
+        route: (OutputAreaID, OutputAreaID),
+        mut citizens: Vec<(CitizenID, bool, bool, f64)>,
+    ) -> Vec<PublicTransport> {
+        if self.disease_model.stable_public_transport_cohorts {
+            citizens.sort_by_key(|(citizen, ..)| citizen.global_index());
+        } else {
+            // Shuffle to ensure randomness on bus
+            citizens.shuffle(&mut self.rng);
+        }
+        let capacity = self.disease_model.public_transport_capacity;
+        let mut vehicles = Vec::new();
+        let mut current_bus =
+            PublicTransport::with_capacity(route.0.clone(), route.1.clone(), capacity);
+        while let Some((citizen, is_infected, is_asymptomatic, infectiousness_multiplier)) =
+            citizens.pop()
+        {
+            // If the vehicle is full, start a new one
+            if current_bus.add_citizen(citizen).is_err() {
+                // Only need to keep vehicles with exposures
+                if current_bus.exposure_count > 0.0 {
+                    vehicles.push(current_bus);
+                }
+                current_bus =
+                    PublicTransport::with_capacity(route.0.clone(), route.1.clone(), capacity);
+                current_bus
+                    .add_citizen(citizen)
+                    .expect("A freshly created vehicle should always have spare capacity");
+            }
+            if is_infected {
+                let base_infectiousness = if is_asymptomatic {
+                    self.disease_model.asymptomatic_infectiousness_multiplier
+                } else {
+                    1.0
+                };
+                current_bus.exposure_count += base_infectiousness * infectiousness_multiplier;
+            }
+        }
+        if current_bus.exposure_count > 0.0 {
+            vehicles.push(current_bus);
+        }
+        vehicles
+    }
     /// Applies the Exposure event to the given Citizens
     fn expose_citizens(
         &mut self,
         citizens: Vec<CitizenID>,
-        exposure_count: usize,
+        exposure_count: f64,
         location: ID,
     ) -> anyhow::Result<()> {
         let mut area_ref = self.output_areas.write().unwrap();
@@ -433,6 +752,7 @@ impl Simulator {
                 );
             }
             let citizen = citizen.unwrap();
+            let age = citizen.age;
             if citizen.is_susceptible()
                 & &citizen.expose(
                 exposure_count,
@@ -444,19 +764,129 @@ impl Simulator {
                 self.statistics_recorder
                     .add_exposure(location.clone())
                     .context(format!("Exposing citizen {}", citizen_id))?;
+                self.statistics_recorder
+                    .record_ever_infected(citizen_ref.0.clone(), citizen_id, age);
                 if let Some(vaccine_list) = &mut self.citizens_eligible_for_vaccine {
                     vaccine_list.remove(&citizen_id);
                 }
+                if let Err(e) = area.citizen_exposed() {
+                    error!("{:?}", e);
+                }
             }
         }
         Ok(())
     }
 
+    /// Exposes a small, random fraction of susceptible Citizens per Output Area to represent infections
+    /// brought in from outside the simulated region (e.g. travel), independent of any local contacts
+    fn apply_importations(&mut self) -> anyhow::Result<()> {
+        if self.disease_model.importation_rate <= 0.0 {
+            return Ok(());
+        }
+        let importation_rate = self.disease_model.importation_rate;
+        let seed = self.global_seed ^ (self.statistics_recorder.current_time_step() as u64);
+        let output_areas = self.output_areas.write().unwrap();
+        let citizen_lookup = self.citizen_output_area_lookup.read().unwrap();
+        let imported: Vec<(OutputAreaID, CitizenID, u16)> = output_areas
+            .par_iter()
+            .enumerate()
+            .flat_map(|(area_index, area)| {
+                let mut area = area.lock().unwrap();
+                let mut rng = deterministic_area_rng(seed, area_index as u32);
+                let mut imported = Vec::new();
+                let area_id = area.id();
+                for citizen in area.citizens.iter_mut() {
+                    if citizen.is_susceptible() && rng.gen::<f64>() < importation_rate {
+                        citizen.disease_status = DiseaseStatus::Exposed(0);
+                        imported.push((area_id.clone(), citizen.id(), citizen.age));
+                    }
+                }
+                area.susceptible_citizen_count =
+                    area.susceptible_citizen_count.saturating_sub(imported.len() as u32);
+                for (citizen_id, local_index, age) in
+                    area.import_disease_into_isolated_citizens(importation_rate, &mut rng)
+                {
+                    if let Some(lookup_entry) = citizen_lookup.get(citizen_id.global_index()) {
+                        *lookup_entry.lock().expect("Failed to retrieve citizen lock") =
+                            (area_id.clone(), local_index);
+                    }
+                    imported.push((area_id.clone(), citizen_id, age));
+                }
+                imported
+            })
+            .collect();
+        drop(output_areas);
+        for (area_id, citizen_id, age) in imported {
+            self.statistics_recorder
+                .record_ever_infected(area_id, citizen_id, age);
+        }
+        Ok(())
+    }
+    /// Exposes susceptible Citizens to a background community transmission hazard (e.g. shops,
+    /// streets), independent of their assigned buildings or public transport
+    ///
+    /// Each Citizen's chance is `community_transmission_rate` scaled by their own Output Area's
+    /// current prevalence, so Citizens isolated from every shared building (and so never reached by
+    /// `generate_exposures`) can still be infected if the local outbreak is severe enough
+    fn apply_community_transmission(&mut self) -> anyhow::Result<()> {
+        if self.disease_model.community_transmission_rate <= 0.0 {
+            return Ok(());
+        }
+        let community_transmission_rate = self.disease_model.community_transmission_rate;
+        let seed = self.global_seed ^ (self.statistics_recorder.current_time_step() as u64);
+        let output_areas = self.output_areas.write().unwrap();
+        let citizen_lookup = self.citizen_output_area_lookup.read().unwrap();
+        let exposed: Vec<(OutputAreaID, CitizenID, u16)> = output_areas
+            .par_iter()
+            .enumerate()
+            .flat_map(|(area_index, area)| {
+                let mut area = area.lock().unwrap();
+                let mut rng = deterministic_area_rng(seed, area_index as u32);
+                let area_id = area.id();
+                let population = area.citizens.len() + area.isolated_citizens.len();
+                if population == 0 {
+                    return Vec::new();
+                }
+                let prevalence = area.citizens.iter().filter(|citizen| citizen.is_infected()).count() as f64
+                    / population as f64;
+                let hazard = community_transmission_rate * prevalence;
+                let mut exposed = Vec::new();
+                for citizen in area.citizens.iter_mut() {
+                    if citizen.is_susceptible() && rng.gen::<f64>() < hazard {
+                        citizen.disease_status = DiseaseStatus::Exposed(0);
+                        exposed.push((area_id.clone(), citizen.id(), citizen.age));
+                    }
+                }
+                area.susceptible_citizen_count =
+                    area.susceptible_citizen_count.saturating_sub(exposed.len() as u32);
+                for (citizen_id, local_index, age) in
+                    area.import_disease_into_isolated_citizens(hazard, &mut rng)
+                {
+                    if let Some(lookup_entry) = citizen_lookup.get(citizen_id.global_index()) {
+                        *lookup_entry.lock().expect("Failed to retrieve citizen lock") =
+                            (area_id.clone(), local_index);
+                    }
+                    exposed.push((area_id.clone(), citizen_id, age));
+                }
+                exposed
+            })
+            .collect();
+        drop(output_areas);
+        for (area_id, citizen_id, age) in exposed {
+            self.statistics_recorder
+                .record_ever_infected(area_id, citizen_id, age);
+        }
+        Ok(())
+    }
     fn apply_interventions(&mut self) -> anyhow::Result<()> {
         // TODO Check vaccinations -> `citizens_eligible_for_vaccine` still works?
         let infected_percent = self.statistics_recorder.infected_percentage();
         //debug!("Infected percent: {}",infected_percent);
-        let new_interventions = self.interventions.update_status(infected_percent);
+        let current_day =
+            self.statistics_recorder.time_step() / self.disease_model.steps_per_day;
+        let new_interventions = self
+            .interventions
+            .update_status(infected_percent, current_day);
         for intervention in new_interventions {
             match intervention {
                 InterventionsEnabled::Lockdown => {
@@ -548,7 +978,267 @@ impl Simulator {
                     .get_mut(citizen_ref.1 as usize)
                     .context("Citizen '{}' due to be vaccinated, doesn't exist!")?;
 
-                citizen.disease_status = DiseaseStatus::Vaccinated;
+                let was_susceptible = citizen.is_susceptible();
+                citizen.disease_status = DiseaseStatus::Vaccinated(0);
+                if was_susceptible {
+                    output_area_ref.susceptible_citizen_count =
+                        output_area_ref.susceptible_citizen_count.saturating_sub(1);
+                }
+                drop(output_area_ref);
+                drop(areas_ref);
+                self.statistics_recorder.record_vaccination(citizen_id);
+            }
+        }
+
+        Ok(())
+    }
+    /// Returns every Citizen currently eligible for vaccination, annotated with their priority
+    /// score under the active `VaccinationStrategy`, sorted from highest to lowest priority
+    ///
+    /// Returns an empty `Vec` if no vaccination program has been enacted yet
+    pub fn citizens_eligible_for_vaccine_by_priority(&self) -> Vec<(CitizenID, f64)> {
+        let eligible = match &self.citizens_eligible_for_vaccine {
+            Some(eligible) => eligible,
+            None => return Vec::new(),
+        };
+        let citizen_lookup_ref = self.citizen_output_area_lookup.read().unwrap();
+        let areas_ref = self.output_areas.read().unwrap();
+        let mut scored: Vec<(CitizenID, f64)> = eligible
+            .iter()
+            .map(|citizen_id| {
+                let citizen_ref = citizen_lookup_ref
+                    .get(citizen_id.global_index())
+                    .expect("Eligible Citizen doesn't exist in the Citizen Output Area lookup!")
+                    .lock()
+                    .unwrap();
+                let output_area_ref = areas_ref
+                    .get(citizen_ref.0.index())
+                    .expect("Eligible Citizen's Output Area doesn't exist!")
+                    .lock()
+                    .unwrap();
+                let citizen = output_area_ref
+                    .citizens
+                    .get(citizen_ref.1 as usize)
+                    .expect("Eligible Citizen doesn't exist in their Output Area!");
+                (
+                    *citizen_id,
+                    self.disease_model
+                        .vaccination_strategy
+                        .priority_score(citizen),
+                )
+            })
+            .collect();
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        scored
+    }
+
+    /// Hashes this Simulator's current Citizen disease statuses, positions and building occupancies
+    /// into a single digest
+    ///
+    /// Two Simulators on an identical trajectory (e.g. the same scenario run before and after a
+    /// refactor, with the same seed) produce matching fingerprints at every time step; any divergence
+    /// changes the digest. Randomly generated unique IDs (`CitizenID`/`BuildingID`'s `Uuid` component)
+    /// are deliberately excluded, as they differ between runs even on an otherwise identical trajectory
+    pub fn state_fingerprint(&self) -> u64 {
+        let output_areas = self.output_areas.read().unwrap();
+        let mut citizen_entries = Vec::new();
+        let mut building_entries = Vec::new();
+        for area in output_areas.iter() {
+            let area = area.lock().unwrap();
+            let area_code = area.id().code().clone();
+            for citizen in &area.citizens {
+                citizen_entries.push((
+                    citizen.id().global_index(),
+                    citizen.disease_status.to_string(),
+                    citizen.current_building_position.output_area_code().code().clone(),
+                    citizen.current_building_position.building_index(),
+                ));
+            }
+            for building in &area.buildings {
+                building_entries.push((
+                    area_code.clone(),
+                    building.id().building_index(),
+                    building.occupants().len(),
+                ));
+            }
+        }
+        citizen_entries.sort();
+        building_entries.sort();
+
+        let mut hasher = DefaultHasher::new();
+        citizen_entries.hash(&mut hasher);
+        building_entries.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Writes one row per Citizen summarising their final disease trajectory - ever infected,
+    /// the time step they were first infected at, their final `DiseaseStatus`, and whether they
+    /// were ever vaccinated - as a CSV, for survival/hazard modelling (Cox regression,
+    /// Kaplan-Meier) externally
+    ///
+    /// Only CSV is supported - the workspace has no Parquet dependency to write that format with
+    pub fn export_citizen_outcomes_csv(&self, filename: &str) -> anyhow::Result<()> {
+        let file = File::create(filename)
+            .context(format!("Failed to create citizen outcomes CSV: {}", filename))?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "citizen_id,age,ever_infected,infection_time,final_status,ever_vaccinated")?;
+
+        let output_areas = self.output_areas.read().unwrap();
+        for area in output_areas.iter() {
+            let area = area.lock().unwrap();
+            for citizen in &area.citizens {
+                let citizen_id = citizen.id();
+                let infection_time = self.statistics_recorder.infection_onset(&citizen_id);
+                let final_status = match &citizen.disease_status {
+                    DiseaseStatus::Susceptible => "susceptible",
+                    DiseaseStatus::Exposed(_) => "exposed",
+                    DiseaseStatus::Infected { .. } => "infected",
+                    DiseaseStatus::Recovered => "recovered",
+                    DiseaseStatus::Vaccinated(_) => "vaccinated",
+                    DiseaseStatus::Deceased => "deceased",
+                };
+                writeln!(
+                    writer,
+                    "{},{},{},{},{},{}",
+                    citizen_id,
+                    citizen.age,
+                    infection_time.is_some(),
+                    infection_time
+                        .map(|onset| onset.to_string())
+                        .unwrap_or_default(),
+                    final_status,
+                    self.statistics_recorder.was_ever_vaccinated(&citizen_id),
+                )?;
+            }
+        }
+        writer.flush().context("Failed to flush citizen outcomes CSV")?;
+        Ok(())
+    }
+
+    /// Writes out the cross-Output-Area disease transmission flows recorded in
+    /// `StatisticsRecorder::transmission_log`, as a CSV of `origin,destination,count` rows, for
+    /// visualising spatial spread corridors (e.g. a flow/chord diagram)
+    ///
+    /// Only transmissions where the infector and infectee reside in different Output Areas are
+    /// included - transmissions within the same Output Area aren't a "flow" between areas
+    pub fn export_transmission_flow_csv(&self, filename: &str) -> anyhow::Result<()> {
+        let output_areas = self.output_areas.read().unwrap();
+        let mut citizen_residence = HashMap::new();
+        for area in output_areas.iter() {
+            let area = area.lock().unwrap();
+            for citizen in &area.citizens {
+                citizen_residence.insert(citizen.id(), area.id());
+            }
+        }
+        drop(output_areas);
+
+        let flows = self.statistics_recorder.transmission_log().flow_matrix(&citizen_residence);
+        let mut flows: Vec<((OutputAreaID, OutputAreaID), u32)> = flows.into_iter().collect();
+        flows.sort_by(|((a_origin, a_destination), _), ((b_origin, b_destination), _)| {
+            (a_origin.code(), a_destination.code()).cmp(&(b_origin.code(), b_destination.code()))
+        });
+
+        let file = File::create(filename)
+            .context(format!("Failed to create transmission flow CSV: {}", filename))?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "origin,destination,count")?;
+        for ((origin, destination), count) in flows {
+            writeln!(writer, "{},{},{}", origin.code(), destination.code(), count)?;
+        }
+        writer.flush().context("Failed to flush transmission flow CSV")?;
+        Ok(())
+    }
+
+    /// Enumerates every public transport vehicle currently generated, pairing its route with who's
+    /// currently riding it - see `PublicTransportRoute`
+    pub fn public_transport_routes(&self) -> Vec<PublicTransportRoute> {
+        self.public_transport.values().map(PublicTransportRoute::from).collect()
+    }
+
+    /// Writes `public_transport_routes` out as JSON, for visualising the transit network or
+    /// validating that commuters are assigned to plausible routes
+    pub fn export_public_transport_routes_json(&self, filename: &str) -> anyhow::Result<()> {
+        let file = File::create(filename)
+            .context(format!("Failed to create public transport routes JSON: {}", filename))?;
+        to_writer(BufWriter::new(file), &self.public_transport_routes())
+            .context("Failed to write public transport routes JSON")?;
+        Ok(())
+    }
+
+    /// Checks that `citizen_output_area_lookup` agrees with where every Citizen actually lives: each
+    /// Citizen must appear in exactly one Output Area's `citizens` vector, at the index its lookup
+    /// entry claims. Also checks that each Output Area's `susceptible_citizen_count` agrees with a
+    /// full rescan of its Citizens' `DiseaseStatus`
+    ///
+    /// Both of these are maintained by hand alongside the per-area Citizen vectors as Citizens move,
+    /// are exposed or vaccinated, so a mistake in any of those call sites silently desyncs them -
+    /// this is comparatively expensive, so it's only intended to run in debug builds (see
+    /// `VALIDATE_INVARIANTS_AFTER_STEP`), not on every step of a release build
+    pub fn validate_invariants(&self) -> anyhow::Result<()> {
+        let output_areas = self.output_areas.read().unwrap();
+        let citizen_lookup = self.citizen_output_area_lookup.read().unwrap();
+
+        let mut actual_location = vec![None; citizen_lookup.len()];
+        for area in output_areas.iter() {
+            let area = area.lock().unwrap();
+            for (local_index, citizen) in area.citizens.iter().enumerate() {
+                let global_index = citizen.id().global_index();
+                let slot = actual_location.get_mut(global_index).ok_or_else(|| {
+                    SimError::Simulation {
+                        message: format!(
+                            "Citizen {} has no lookup entry, but exists in Output Area {}",
+                            citizen.id(),
+                            area.id()
+                        ),
+                    }
+                })?;
+                if slot.is_some() {
+                    return Err(SimError::Simulation {
+                        message: format!("Citizen {} exists in more than one Output Area", citizen.id()),
+                    }
+                        .into());
+                }
+                *slot = Some((area.id(), local_index as u32));
+            }
+        }
+
+        for (global_index, lookup_entry) in citizen_lookup.iter().enumerate() {
+            let lookup_entry = lookup_entry.lock().expect("Failed to retrieve citizen lock").clone();
+            match &actual_location[global_index] {
+                None => {
+                    return Err(SimError::Simulation {
+                        message: format!(
+                            "Citizen {} has a lookup entry ({}, {}), but doesn't exist in any Output Area",
+                            global_index, lookup_entry.0, lookup_entry.1
+                        ),
+                    }
+                        .into());
+                }
+                Some(actual) if *actual != lookup_entry => {
+                    return Err(SimError::Simulation {
+                        message: format!(
+                            "Citizen {}'s lookup entry claims ({}, {}), but it actually exists at ({}, {})",
+                            global_index, lookup_entry.0, lookup_entry.1, actual.0, actual.1
+                        ),
+                    }
+                        .into());
+                }
+                _ => {}
+            }
+        }
+
+        for area in output_areas.iter() {
+            let area = area.lock().unwrap();
+            let area_rescanned_susceptible =
+                area.citizens.iter().filter(|citizen| citizen.is_susceptible()).count() as u32;
+            if area_rescanned_susceptible != area.susceptible_citizen_count {
+                return Err(SimError::Simulation {
+                    message: format!(
+                        "Output Area {}'s incrementally tracked susceptible count ({}) doesn't match a full rescan of its Citizens ({}) - susceptible_count sums these per-area counts, so this would also throw it off",
+                        area.id(), area.susceptible_citizen_count, area_rescanned_susceptible
+                    ),
+                }
+                    .into());
             }
         }
 
@@ -616,7 +1306,7 @@ impl From<SimulatorBuilder> for Simulator {
                 .collect(),
         );
 
-        let sim = Simulator {
+        let mut sim = Simulator {
             area_code: builder.area_code,
             output_area_lookup: builder.output_area_lookup,
             current_population,
@@ -627,8 +1317,18 @@ impl From<SimulatorBuilder> for Simulator {
             interventions: Default::default(),
             disease_model: builder.disease_model,
             public_transport: Default::default(),
-            rng: thread_rng(),
+            rng: crate::rng_log::build_top_level_rng(),
+            global_seed: builder.global_seed,
+            start_day_of_week: builder.start_day_of_week,
+            state_history: StateHistory::new(crate::config::STATE_HISTORY_CAPACITY),
         };
+        sim.statistics_recorder
+            .set_sampling_interval(builder.statistics_sampling_interval);
+        for area in sim.output_areas.read().unwrap().iter() {
+            let area = area.lock().unwrap();
+            sim.statistics_recorder
+                .set_area_population(area.id(), area.total_residents);
+        }
         for (_code, index) in &sim.output_area_lookup {
             assert!(
                 sim.output_areas
@@ -642,3 +1342,991 @@ impl From<SimulatorBuilder> for Simulator {
         sim
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use load_census_data::CensusData;
+    use load_census_data::tables::employment_densities::EmploymentDensities;
+    use osm_data::{BuildingBoundaryID, OSMRawBuildings, RawBuilding, TagClassifiedBuilding};
+    use osm_data::polygon_lookup::PolygonContainer;
+    use osm_data::voronoi_generator::Scaling;
+
+    use crate::disease::DiseaseStatus;
+    use crate::interventions::VaccinationStrategy;
+    use crate::models::building::{Building, BuildingID, BuildingType, Household, Workplace};
+    use crate::models::citizen::{Citizen, CitizenID, Occupation, OccupationType};
+    use crate::models::output_area::{OutputArea, OutputAreaID};
+    use crate::models::public_transport_route::{PublicTransport, PublicTransportRoute};
+    use crate::simulator::{GeneratedExposures, RunConfigExport, Simulator};
+    use crate::simulator_builder::SimulatorBuilder;
+    use crate::time::DayOfWeek;
+
+    /// Builds a `Simulator` with a single Output Area containing one Citizen per given age, none of
+    /// which are exposed/infected, suitable for testing vaccination eligibility/prioritisation
+    fn simulator_with_citizen_ages(ages: &[u16]) -> Simulator {
+        let census_data = CensusData {
+            valid_areas: Default::default(),
+            age_counts: Default::default(),
+            population_counts: Default::default(),
+            occupation_counts: Default::default(),
+            workplace_density: EmploymentDensities {},
+            residents_workplace: Default::default(),
+            imputed_areas: Default::default(),
+        };
+        let osm_data = OSMRawBuildings::from_building_locations(HashMap::new(), HashMap::new(), 100);
+        let output_areas_polygons =
+            PolygonContainer::new(HashMap::new(), Scaling::yorkshire_national_grid(100), 100)
+                .expect("Failed to build an empty polygon container");
+        let mut builder = SimulatorBuilder::new(
+            "test".to_string(),
+            census_data,
+            osm_data,
+            output_areas_polygons,
+        )
+            .expect("Failed to build an empty SimulatorBuilder");
+
+        let output_area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(
+            output_area_id.clone(),
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        let household_id = BuildingID::new(output_area_id.clone(), BuildingType::Household, 0);
+        for (index, age) in ages.iter().enumerate() {
+            area.citizens.push(Citizen::new(
+                CitizenID::from_indexes(index as u32),
+                household_id.clone(),
+                household_id.clone(),
+                *age,
+                Occupation::Student,
+                false,
+                false,
+                false,
+                24,
+            ));
+            area.susceptible_citizen_count += 1;
+            builder
+                .citizen_output_area_lookup
+                .push((output_area_id.clone(), index as u32));
+        }
+        builder.output_areas.push(area);
+        builder.output_area_lookup.insert("test".to_string(), 0);
+
+        Simulator::from(builder)
+    }
+
+    /// Builds a `Simulator` with a single Output Area containing one Citizen per given age, each in
+    /// their own Household with `workplace_code == household_code`, so they never come into contact
+    /// with another Citizen and can only catch the disease via importation - suitable for testing
+    /// `Simulator::apply_importations` without local transmission muddying the result
+    fn simulator_with_isolated_citizens(ages: &[u16]) -> Simulator {
+        let sim = simulator_with_citizen_ages(ages);
+        let output_area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        {
+            let areas = sim.output_areas.write().unwrap();
+            let mut area = areas[0].lock().unwrap();
+            for (index, citizen) in area.citizens.iter_mut().enumerate() {
+                let household_id =
+                    BuildingID::new(output_area_id.clone(), BuildingType::Household, index);
+                citizen.household_code = household_id.clone();
+                citizen.workplace_code = household_id.clone();
+                citizen.current_building_position = household_id;
+            }
+        }
+        sim
+    }
+
+    /// Builds a `Simulator` with a single Output Area containing `household_count` Households, each
+    /// of one working adult commuting to a shared Workplace and one homemaker who never leaves the
+    /// household, at the given `steps_per_day` resolution - suitable for comparing epidemic curves
+    /// across resolutions, since household transmission to the homemaker only happens if the worker
+    /// actually returns home between commutes
+    fn build_working_population_simulator(steps_per_day: u32, household_count: u32) -> Simulator {
+        let mut sim = simulator_with_citizen_ages(&[]);
+        let output_area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        // Households occupy indices 0..household_count, with the shared Workplace immediately
+        // after - `BuildingID::building_index` is used as a direct index into `area.buildings`, so
+        // every building in this Area needs a distinct, gapless index
+        let workplace_id =
+            BuildingID::new(output_area_id.clone(), BuildingType::Workplace, household_count);
+        let building_polygon = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(0, 0), (100, 0), (100, 100), (0, 100), (0, 0)]),
+            vec![],
+        );
+        let raw_building = RawBuilding::new(
+            TagClassifiedBuilding::WorkPlace,
+            &building_polygon,
+            BuildingBoundaryID::default(),
+        )
+            .expect("Failed to build test RawBuilding");
+        let mut workplace =
+            Workplace::new(workplace_id.clone(), raw_building, OccupationType::Sales, household_count);
+
+        {
+            let areas = sim.output_areas.write().unwrap();
+            let mut area = areas[0].lock().unwrap();
+            for index in 0..household_count {
+                let household_id =
+                    BuildingID::new(output_area_id.clone(), BuildingType::Household, index);
+                let mut household = Household::new(household_id.clone(), geo_types::Point::new(0, 0));
+
+                let worker_id = CitizenID::from_indexes(index * 2);
+                let worker = Citizen::new(
+                    worker_id,
+                    household_id.clone(),
+                    workplace_id.clone(),
+                    30,
+                    Occupation::Normal { occupation: OccupationType::Sales },
+                    false,
+                    false,
+                    false,
+                    steps_per_day,
+                );
+                household.add_citizen(worker_id).expect("Failed to add occupant");
+                workplace.add_citizen(worker_id).expect("Failed to add occupant");
+
+                let homemaker_id = CitizenID::from_indexes(index * 2 + 1);
+                let homemaker = Citizen::new(
+                    homemaker_id,
+                    household_id.clone(),
+                    household_id.clone(),
+                    30,
+                    Occupation::Unemployed,
+                    false,
+                    false,
+                    false,
+                    steps_per_day,
+                );
+                household.add_citizen(homemaker_id).expect("Failed to add occupant");
+
+                area.citizens.push(worker);
+                area.citizens.push(homemaker);
+                area.buildings.push(Box::new(household));
+                for offset in 0..2 {
+                    sim.citizen_output_area_lookup
+                        .write()
+                        .unwrap()
+                        .push(Mutex::new((output_area_id.clone(), index * 2 + offset)));
+                }
+            }
+            area.buildings.push(Box::new(workplace));
+            area.susceptible_citizen_count = household_count * 2;
+            // Seed the first household's worker - household transmission to their homemaker spouse
+            // only happens if the worker actually makes it home between commutes
+            area.citizens[0].disease_status = DiseaseStatus::Infected { elapsed: 0, duration: 336 };
+            area.susceptible_citizen_count -= 1;
+        }
+        sim
+    }
+
+    /// `public_transport_routes` should enumerate every vehicle currently in `public_transport`,
+    /// including which Citizens are currently riding it
+    #[test]
+    fn public_transport_routes_reports_riders_for_every_vehicle() {
+        let mut sim = simulator_with_citizen_ages(&[30]);
+        let source = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let destination = OutputAreaID::from_code_and_index("other".to_string(), 0);
+        let mut vehicle = PublicTransport::with_capacity(source, destination, 50);
+        vehicle
+            .add_citizen(CitizenID::from_indexes(0))
+            .expect("Failed to board test Citizen");
+        sim.public_transport.insert(vehicle.id().clone(), vehicle);
+
+        let routes = sim.public_transport_routes();
+
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].capacity, 50);
+        assert_eq!(routes[0].riders, vec![CitizenID::from_indexes(0)]);
+    }
+
+    /// `export_public_transport_routes_json` should write exactly the same routes `public_transport_routes`
+    /// returns, round-tripping through JSON with their riders intact
+    #[test]
+    fn exported_public_transport_routes_json_round_trips_to_the_same_routes() {
+        let mut sim = simulator_with_citizen_ages(&[30]);
+        let source = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let destination = OutputAreaID::from_code_and_index("other".to_string(), 0);
+        let mut vehicle = PublicTransport::with_capacity(source, destination, 50);
+        vehicle
+            .add_citizen(CitizenID::from_indexes(0))
+            .expect("Failed to board test Citizen");
+        sim.public_transport.insert(vehicle.id().clone(), vehicle);
+
+        let filename = std::env::temp_dir()
+            .join(format!("public_transport_routes_test_{}.json", std::process::id()))
+            .to_str()
+            .expect("Non-UTF8 temp path")
+            .to_string();
+        sim.export_public_transport_routes_json(&filename)
+            .expect("Failed to export public transport routes");
+
+        let contents =
+            std::fs::read_to_string(&filename).expect("Failed to read exported routes JSON");
+        std::fs::remove_file(&filename).ok();
+
+        let exported: Vec<PublicTransportRoute> =
+            serde_json::from_str(&contents).expect("routes JSON should deserialise");
+        assert_eq!(exported, sim.public_transport_routes());
+    }
+
+    /// Under `VaccinationStrategy::OldestFirst`, the eligible pool should be returned sorted by
+    /// descending age
+    #[test]
+    fn oldest_first_sorts_eligible_citizens_by_descending_age() {
+        let mut sim = simulator_with_citizen_ages(&[20, 65, 40, 80, 10]);
+        sim.disease_model.vaccination_strategy = VaccinationStrategy::OldestFirst;
+        sim.citizens_eligible_for_vaccine = Some(
+            sim.output_areas
+                .read()
+                .unwrap()
+                .iter()
+                .flat_map(|area| {
+                    area.lock()
+                        .unwrap()
+                        .citizens
+                        .iter()
+                        .map(|citizen| citizen.id())
+                        .collect::<Vec<_>>()
+                })
+                .collect(),
+        );
+
+        let scored = sim.citizens_eligible_for_vaccine_by_priority();
+        let scores: Vec<f64> = scored.iter().map(|(_, score)| *score).collect();
+        assert_eq!(scores, vec![80.0, 65.0, 40.0, 20.0, 10.0]);
+    }
+
+    /// Two Simulators built from the same inputs should fingerprint identically, and perturbing one
+    /// Citizen's disease status should change its Simulator's fingerprint
+    #[test]
+    fn state_fingerprint_matches_identical_runs_and_differs_after_perturbation() {
+        let sim_a = simulator_with_citizen_ages(&[20, 30, 40]);
+        let sim_b = simulator_with_citizen_ages(&[20, 30, 40]);
+        assert_eq!(sim_a.state_fingerprint(), sim_b.state_fingerprint());
+
+        sim_b.output_areas.write().unwrap()[0].lock().unwrap().citizens[0].disease_status =
+            DiseaseStatus::Infected { elapsed: 0, duration: 336 };
+        assert_ne!(sim_a.state_fingerprint(), sim_b.state_fingerprint());
+    }
+
+    /// An Output Area with a seeded infection should report a higher live prevalence than an
+    /// uninfected Output Area, and rank first in `areas_by_prevalence`
+    #[test]
+    fn areas_by_prevalence_ranks_the_infected_area_first() {
+        let sim = simulator_with_citizen_ages(&[20, 30, 40]);
+
+        let other_area_id = OutputAreaID::from_code_and_index("other".to_string(), 1);
+        let mut other_area = OutputArea::new(
+            other_area_id.clone(),
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        let household_id = BuildingID::new(other_area_id, BuildingType::Household, 0);
+        other_area.citizens.push(Citizen::new(
+            CitizenID::from_indexes(100),
+            household_id.clone(),
+            household_id,
+            25,
+            Occupation::Student,
+            false,
+            false,
+            false,
+            24,
+        ));
+        sim.output_areas.write().unwrap().push(Mutex::new(other_area));
+
+        sim.output_areas.write().unwrap()[0].lock().unwrap().citizens[0].disease_status =
+            DiseaseStatus::Infected { elapsed: 0, duration: 336 };
+
+        let ranked = sim.areas_by_prevalence();
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].0, OutputAreaID::from_code_and_index("test".to_string(), 0));
+        assert!(ranked[0].1 > 0.0);
+        assert_eq!(ranked[1].1, 0.0);
+    }
+
+    /// A freshly built Simulator's lookup should agree with where its Citizens actually are
+    #[test]
+    fn validate_invariants_passes_for_a_freshly_built_simulator() {
+        let sim = simulator_with_citizen_ages(&[20, 30, 40]);
+        sim.validate_invariants().expect("Freshly built Simulator should have consistent invariants");
+    }
+
+    /// Corrupting a lookup entry so it claims the wrong local index should be caught by
+    /// `validate_invariants`, rather than surfacing later as an inexplicable fingerprint mismatch
+    #[test]
+    fn validate_invariants_detects_a_corrupted_lookup_entry() {
+        let sim = simulator_with_citizen_ages(&[20, 30, 40]);
+        sim.citizen_output_area_lookup.read().unwrap()[0].lock().unwrap().1 = 999;
+
+        sim.validate_invariants()
+            .expect_err("A corrupted lookup entry should fail validation");
+    }
+
+    /// `susceptible_count` should always agree with a full rescan of every Citizen's
+    /// `DiseaseStatus`, even after many steps of the disease spreading, infecting and recovering
+    /// Citizens across Output Areas
+    #[test]
+    fn susceptible_count_matches_a_full_rescan_after_many_steps() {
+        let mut sim =
+            simulator_with_citizen_ages(&[20, 30, 40, 50, 60, 70, 25, 35, 45, 55]);
+        sim.output_areas.write().unwrap()[0].lock().unwrap().citizens[0].disease_status =
+            DiseaseStatus::Infected { elapsed: 0, duration: 336 };
+
+        for _ in 0..(sim.disease_model.steps_per_day * 10) {
+            sim.step().expect("Failed to execute a time step");
+        }
+
+        let rescanned_susceptible: u32 = sim
+            .output_areas
+            .read()
+            .unwrap()
+            .iter()
+            .map(|area| {
+                area.lock()
+                    .unwrap()
+                    .citizens
+                    .iter()
+                    .filter(|citizen| citizen.is_susceptible())
+                    .count() as u32
+            })
+            .sum();
+
+        assert_eq!(sim.susceptible_count(), rescanned_susceptible);
+        sim.validate_invariants()
+            .expect("Incremental susceptible counts should still agree with a rescan");
+    }
+
+    /// A fork taken mid-run should start out identical to its parent, then diverge once the two are
+    /// given different interventions, letting scenarios branch from a shared starting point
+    #[test]
+    fn forking_preserves_pre_fork_state_and_diverges_after_independent_interventions() {
+        let mut sim = simulator_with_citizen_ages(&[20, 30, 40]);
+        for _ in 0..sim.disease_model.steps_per_day {
+            sim.step().expect("Failed to execute a time step");
+        }
+
+        let forked = sim.fork();
+        assert_eq!(sim.state_fingerprint(), forked.state_fingerprint());
+        assert_ne!(sim.global_seed, forked.global_seed);
+
+        forked.output_areas.write().unwrap()[0].lock().unwrap().citizens[0].disease_status =
+            DiseaseStatus::Infected { elapsed: 0, duration: 336 };
+        assert_ne!(sim.state_fingerprint(), forked.state_fingerprint());
+    }
+
+    /// With the default `steps_per_day` of 24, a full day's worth of time steps should advance the
+    /// Day of the Week by exactly one day, regardless of how many hours within that day have passed
+    #[test]
+    fn one_day_of_steps_advances_the_day_of_week_once() {
+        let mut sim = simulator_with_citizen_ages(&[20, 30, 40]);
+        assert_eq!(sim.start_day_of_week, DayOfWeek::Monday);
+        assert_eq!(sim.current_day_of_week(), DayOfWeek::Monday);
+
+        for _ in 0..sim.disease_model.steps_per_day {
+            sim.step().expect("Failed to execute a time step");
+        }
+
+        assert_eq!(sim.current_day_of_week(), DayOfWeek::Tuesday);
+    }
+
+    /// A building-isolated Citizen (never reached by `generate_exposures`) should still be exposed
+    /// by `apply_community_transmission` when a nonzero `community_transmission_rate` is configured
+    /// and their Output Area has nonzero prevalence
+    #[test]
+    fn community_transmission_exposes_a_building_isolated_citizen() {
+        let mut sim = simulator_with_citizen_ages(&[20, 30]);
+        sim.disease_model.community_transmission_rate = 100.0;
+
+        {
+            let areas = sim.output_areas.write().unwrap();
+            let mut area = areas[0].lock().unwrap();
+            area.citizens[0].disease_status = DiseaseStatus::Infected { elapsed: 0, duration: 336 };
+            let isolated_citizen = area.citizens.remove(1);
+            area.isolated_citizens.push(isolated_citizen);
+        }
+
+        sim.apply_community_transmission()
+            .expect("Failed to apply community transmission");
+
+        let areas = sim.output_areas.read().unwrap();
+        let area = areas[0].lock().unwrap();
+        assert!(area.isolated_citizens.is_empty(), "Exposed Citizen should be moved out of isolation");
+        assert_eq!(area.citizens.len(), 2);
+        assert_eq!(area.citizens[1].disease_status, DiseaseStatus::Exposed(0));
+    }
+
+    /// With `importation_rate` at zero, a seeded infection among fully isolated Citizens (no shared
+    /// buildings, so no local transmission) should simply run its course and go extinct. With a
+    /// nonzero rate, the same scenario should instead keep reinfecting the susceptible pool well past
+    /// the point the zero-rate run went extinct
+    #[test]
+    fn importation_rate_prevents_extinction_after_local_transmission_dies_out() {
+        fn active_infections(sim: &Simulator) -> usize {
+            sim.output_areas
+                .read()
+                .unwrap()
+                .iter()
+                .map(|area| {
+                    area.lock()
+                        .unwrap()
+                        .citizens
+                        .iter()
+                        .filter(|citizen| {
+                            matches!(
+                                citizen.disease_status,
+                                DiseaseStatus::Exposed(_) | DiseaseStatus::Infected { .. }
+                            )
+                        })
+                        .count()
+                })
+                .sum()
+        }
+
+        let mut sim = simulator_with_isolated_citizens(&[20, 30, 40, 50, 60]);
+        sim.output_areas.write().unwrap()[0].lock().unwrap().citizens[0].disease_status =
+            DiseaseStatus::Infected { elapsed: 0, duration: 24 };
+        for _ in 0..48 {
+            sim.step().expect("Failed to execute a time step");
+        }
+        assert_eq!(
+            active_infections(&sim),
+            0,
+            "An isolated, zero-importation epidemic should have gone extinct by now"
+        );
+
+        let mut sim = simulator_with_isolated_citizens(&[20, 30, 40, 50, 60]);
+        sim.disease_model.importation_rate = 1.0;
+        sim.output_areas.write().unwrap()[0].lock().unwrap().citizens[0].disease_status =
+            DiseaseStatus::Infected { elapsed: 0, duration: 24 };
+        for _ in 0..48 {
+            sim.step().expect("Failed to execute a time step");
+        }
+        assert!(
+            active_infections(&sim) > 0,
+            "A nonzero importation rate should keep infections circulating past the zero-rate extinction point"
+        );
+    }
+
+    /// A Citizen transitioning into `Infected` ("testing positive") should mark their whole household
+    /// as isolating, not just flag the individual - so every co-resident (`Citizen::household_code
+    /// == household_code`, checked by `execute_time_step`'s `household_isolating` parameter) ends up
+    /// pinned home too, regardless of which resident was the one who tested positive
+    #[test]
+    fn household_isolation_is_triggered_by_any_residents_positive_test() {
+        let mut sim = simulator_with_citizen_ages(&[20, 30, 40]);
+        sim.interventions.set_household_isolation_enabled(true);
+        let household_code = sim.output_areas.read().unwrap()[0].lock().unwrap().citizens[1]
+            .household_code
+            .clone();
+        sim.output_areas.write().unwrap()[0].lock().unwrap().citizens[1].disease_status =
+            DiseaseStatus::Exposed(sim.disease_model.exposed_time);
+
+        sim.step().expect("Failed to execute a time step");
+
+        let areas = sim.output_areas.read().unwrap();
+        let area = areas[0].lock().unwrap();
+        assert!(
+            matches!(area.citizens[1].disease_status, DiseaseStatus::Infected { elapsed: 0, .. }),
+            "Citizen 1 should have just tested positive"
+        );
+        assert!(
+            area.household_is_isolating(&household_code),
+            "The whole household - shared by Citizens 0, 1 and 2 - should be isolating, not just Citizen 1"
+        );
+    }
+
+    /// Once every Citizen in an Output Area has left the Susceptible pool, `apply_exposures` should
+    /// skip that Area's exposure list entirely (via `susceptible_citizen_count`), rather than
+    /// processing a building whose only possible target is no longer catchable
+    #[test]
+    fn all_recovered_area_is_skipped_and_produces_no_exposures() {
+        let mut sim = simulator_with_citizen_ages(&[20, 30]);
+        let household_id = {
+            let areas = sim.output_areas.write().unwrap();
+            let mut area = areas[0].lock().unwrap();
+            area.citizens[0].disease_status = DiseaseStatus::Infected { elapsed: 0, duration: 336 };
+            area.citizens[1].disease_status = DiseaseStatus::Recovered;
+            area.susceptible_citizen_count = 0;
+            area.citizens[1].household_code.clone()
+        };
+
+        let mut exposures = GeneratedExposures::default();
+        exposures.building_exposure_list.push(HashMap::from([(
+            household_id,
+            vec![CitizenID::from_indexes(0)],
+        )]));
+
+        sim.apply_exposures(exposures)
+            .expect("Failed to apply exposures");
+
+        let areas = sim.output_areas.read().unwrap();
+        let area = areas[0].lock().unwrap();
+        assert_eq!(area.citizens[1].disease_status, DiseaseStatus::Recovered);
+    }
+
+    /// Across many independent households, each with exactly one infected occupant and one
+    /// susceptible occupant, the realised household attack rate reported by
+    /// `realised_attack_rate_by_building_type` should converge on the disease model's configured
+    /// per-contact `exposure_chance` - there's only ever one infected contact per household here,
+    /// so `binomial(exposure_chance, 1.0)` reduces to `exposure_chance` itself
+    #[test]
+    fn realised_household_attack_rate_approaches_the_configured_per_contact_probability() {
+        let mut sim = simulator_with_citizen_ages(&[]);
+        sim.disease_model.exposure_chance = 0.3;
+
+        const HOUSEHOLDS: u32 = 2000;
+        let mut building_exposures = HashMap::new();
+        {
+            let areas = sim.output_areas.write().unwrap();
+            let mut area = areas[0].lock().unwrap();
+            area.citizens.clear();
+            area.buildings.clear();
+            sim.citizen_output_area_lookup.write().unwrap().clear();
+
+            for index in 0..HOUSEHOLDS {
+                let household_id = BuildingID::new(
+                    OutputAreaID::from_code_and_index("test".to_string(), 0),
+                    BuildingType::Household,
+                    index,
+                );
+                let mut household =
+                    Household::new(household_id.clone(), geo_types::Point::new(0, 0));
+
+                let infected_id = CitizenID::from_indexes(index * 2);
+                let susceptible_id = CitizenID::from_indexes(index * 2 + 1);
+                let mut infected_citizen = Citizen::new(
+                    infected_id,
+                    household_id.clone(),
+                    household_id.clone(),
+                    30,
+                    Occupation::Student,
+                    false,
+                    false,
+                    false,
+                    24,
+                );
+                infected_citizen.disease_status =
+                    DiseaseStatus::Infected { elapsed: 0, duration: 336 };
+                let susceptible_citizen = Citizen::new(
+                    susceptible_id,
+                    household_id.clone(),
+                    household_id.clone(),
+                    30,
+                    Occupation::Student,
+                    false,
+                    false,
+                    false,
+                    24,
+                );
+                household.add_citizen(infected_id).expect("Failed to add occupant");
+                household.add_citizen(susceptible_id).expect("Failed to add occupant");
+
+                area.citizens.push(infected_citizen);
+                area.citizens.push(susceptible_citizen);
+                area.buildings.push(Box::new(household));
+                sim.citizen_output_area_lookup.write().unwrap().push(Mutex::new((
+                    OutputAreaID::from_code_and_index("test".to_string(), 0),
+                    index * 2,
+                )));
+                sim.citizen_output_area_lookup.write().unwrap().push(Mutex::new((
+                    OutputAreaID::from_code_and_index("test".to_string(), 0),
+                    index * 2 + 1,
+                )));
+
+                building_exposures.insert(household_id, vec![infected_id]);
+            }
+            area.susceptible_citizen_count = HOUSEHOLDS;
+        }
+
+        let mut exposures = GeneratedExposures::default();
+        exposures.building_exposure_list.push(building_exposures);
+
+        sim.statistics_recorder.next().expect("Failed to advance statistics recorder");
+        sim.apply_exposures(exposures)
+            .expect("Failed to apply exposures");
+
+        let attack_rates = sim.statistics_recorder.realised_attack_rate_by_building_type();
+        let realised_rate = attack_rates[BuildingType::Household];
+        assert!(
+            (realised_rate - sim.disease_model.exposure_chance).abs() < 0.04,
+            "Realised household attack rate {} should approach the configured exposure chance {}",
+            realised_rate,
+            sim.disease_model.exposure_chance
+        );
+    }
+
+    /// With a `hospital_transmission_multiplier` above `1.0`, otherwise identical workers in a
+    /// `BuildingType::Hospital` should converge on a higher realised attack rate than workers in a
+    /// `BuildingType::Workplace`, since only the Hospital occupants' infectiousness is multiplied
+    #[test]
+    fn hospital_staff_have_a_higher_realised_attack_rate_than_other_workers() {
+        let mut sim = simulator_with_citizen_ages(&[]);
+        sim.disease_model.exposure_chance = 0.1;
+        sim.disease_model.hospital_transmission_multiplier = 3.0;
+
+        const WORKPLACES_PER_TYPE: u32 = 2000;
+        let building_polygon = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(0, 0), (100, 0), (100, 100), (0, 100), (0, 0)]),
+            vec![],
+        );
+        let raw_building = RawBuilding::new(
+            TagClassifiedBuilding::WorkPlace,
+            &building_polygon,
+            BuildingBoundaryID::default(),
+        )
+            .expect("Failed to build test RawBuilding");
+
+        let mut building_exposures = HashMap::new();
+        {
+            let areas = sim.output_areas.write().unwrap();
+            let mut area = areas[0].lock().unwrap();
+            area.citizens.clear();
+            area.buildings.clear();
+            sim.citizen_output_area_lookup.write().unwrap().clear();
+
+            let mut next_building_index = 0;
+            let mut next_citizen_index = 0;
+            for building_type in [BuildingType::Hospital, BuildingType::Workplace] {
+                for _ in 0..WORKPLACES_PER_TYPE {
+                    let building_id = BuildingID::new(
+                        OutputAreaID::from_code_and_index("test".to_string(), 0),
+                        building_type,
+                        next_building_index,
+                    );
+                    next_building_index += 1;
+                    let mut workplace =
+                        Workplace::new(building_id.clone(), raw_building, OccupationType::Caring, 10);
+
+                    let infected_id = CitizenID::from_indexes(next_citizen_index);
+                    let susceptible_id = CitizenID::from_indexes(next_citizen_index + 1);
+                    next_citizen_index += 2;
+                    let mut infected_citizen = Citizen::new(
+                        infected_id,
+                        building_id.clone(),
+                        building_id.clone(),
+                        30,
+                        Occupation::Normal { occupation: OccupationType::Caring },
+                        false,
+                        false,
+                        false,
+                        24,
+                    );
+                    infected_citizen.disease_status =
+                        DiseaseStatus::Infected { elapsed: 0, duration: 336 };
+                    let susceptible_citizen = Citizen::new(
+                        susceptible_id,
+                        building_id.clone(),
+                        building_id.clone(),
+                        30,
+                        Occupation::Normal { occupation: OccupationType::Caring },
+                        false,
+                        false,
+                        false,
+                        24,
+                    );
+                    workplace.add_citizen(infected_id).expect("Failed to add occupant");
+                    workplace.add_citizen(susceptible_id).expect("Failed to add occupant");
+
+                    area.citizens.push(infected_citizen);
+                    area.citizens.push(susceptible_citizen);
+                    area.buildings.push(Box::new(workplace));
+                    sim.citizen_output_area_lookup.write().unwrap().push(Mutex::new((
+                        OutputAreaID::from_code_and_index("test".to_string(), 0),
+                        next_citizen_index - 2,
+                    )));
+                    sim.citizen_output_area_lookup.write().unwrap().push(Mutex::new((
+                        OutputAreaID::from_code_and_index("test".to_string(), 0),
+                        next_citizen_index - 1,
+                    )));
+
+                    building_exposures.insert(building_id, vec![infected_id]);
+                }
+            }
+            area.susceptible_citizen_count = WORKPLACES_PER_TYPE * 2;
+        }
+
+        let mut exposures = GeneratedExposures::default();
+        exposures.building_exposure_list.push(building_exposures);
+
+        sim.statistics_recorder.next().expect("Failed to advance statistics recorder");
+        sim.apply_exposures(exposures)
+            .expect("Failed to apply exposures");
+
+        let attack_rates = sim.statistics_recorder.realised_attack_rate_by_building_type();
+        let hospital_rate = attack_rates[BuildingType::Hospital];
+        let workplace_rate = attack_rates[BuildingType::Workplace];
+        assert!(
+            hospital_rate > workplace_rate,
+            "Hospital staff's realised attack rate {} should exceed other workers' {}",
+            hospital_rate,
+            workplace_rate
+        );
+    }
+
+    /// A route with 100 infected commuters and a capacity of 40 should be split across at least 3
+    /// vehicles, each tracking its own `exposure_count` independently of the others
+    #[test]
+    fn busy_route_is_split_across_multiple_vehicles_with_independent_exposure_counts() {
+        let mut sim = simulator_with_citizen_ages(&[20]);
+        sim.disease_model.public_transport_capacity = 40;
+        let area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let route = (area_id.clone(), area_id);
+        let commuters: Vec<(CitizenID, bool, bool, f64)> = (0..100)
+            .map(|index| (CitizenID::from_indexes(index), true, false, 1.0))
+            .collect();
+
+        let vehicles = sim.split_route_into_vehicles(route, commuters);
+
+        assert!(
+            vehicles.len() >= 3,
+            "100 commuters at capacity 40 should need at least 3 vehicles, got {}",
+            vehicles.len()
+        );
+        let total_occupants: usize = vehicles.iter().map(|vehicle| vehicle.occupants().len()).sum();
+        assert_eq!(total_occupants, 100);
+        for vehicle in &vehicles {
+            assert_eq!(vehicle.exposure_count, vehicle.occupants().len() as f64);
+        }
+    }
+
+    /// With `stable_public_transport_cohorts` enabled, the same route's commuters should be
+    /// bucketed into vehicles the same way across consecutive commute steps, so the same pairs of
+    /// Citizens keep sharing a vehicle
+    #[test]
+    fn stable_cohorts_keep_the_same_citizens_sharing_a_vehicle_across_steps() {
+        let mut sim = simulator_with_citizen_ages(&[20]);
+        sim.disease_model.stable_public_transport_cohorts = true;
+        sim.disease_model.public_transport_capacity = 10;
+        let area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let route = (area_id.clone(), area_id);
+        let commuters: Vec<(CitizenID, bool, bool, f64)> = (0..30)
+            .map(|index| (CitizenID::from_indexes(index), true, false, 1.0))
+            .collect();
+
+        let first_day = sim.split_route_into_vehicles(route.clone(), commuters.clone());
+        let second_day = sim.split_route_into_vehicles(route, commuters);
+
+        assert_eq!(first_day.len(), second_day.len());
+        for (first, second) in first_day.iter().zip(second_day.iter()) {
+            assert_eq!(first.occupants(), second.occupants());
+        }
+    }
+
+    /// `citizens_in_transit` should count exactly the Citizens with an active
+    /// `on_public_transport` commute, regardless of how many Citizens aren't travelling
+    #[test]
+    fn citizens_in_transit_counts_only_commuters_mid_journey() {
+        let sim = simulator_with_citizen_ages(&[20, 30, 40, 50]);
+        let area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        {
+            let areas = sim.output_areas.write().unwrap();
+            let mut area = areas[0].lock().unwrap();
+            area.citizens[0].on_public_transport = Some((area_id.clone(), area_id.clone()));
+            area.citizens[2].on_public_transport = Some((area_id.clone(), area_id));
+        }
+
+        assert_eq!(sim.citizens_in_transit(), 2);
+    }
+
+    /// `export_citizen_outcomes_csv` should write exactly one row per Citizen, and an infected
+    /// Citizen's recorded infection time should fall within the time steps the Simulator has
+    /// actually recorded
+    #[test]
+    fn citizen_outcomes_csv_has_one_row_per_citizen_with_infection_times_in_range() {
+        let mut sim = simulator_with_citizen_ages(&[20, 65, 40, 80, 10]);
+        let area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let infected_citizen = CitizenID::from_indexes(1);
+        let vaccinated_citizen = CitizenID::from_indexes(3);
+
+        sim.statistics_recorder.next().expect("Failed to start recording");
+        sim.statistics_recorder.next().expect("Failed to advance time step");
+        sim.statistics_recorder
+            .record_ever_infected(area_id, infected_citizen, 65);
+        sim.statistics_recorder.record_vaccination(vaccinated_citizen);
+
+        sim.output_areas.write().unwrap()[0].lock().unwrap().citizens[1].disease_status =
+            DiseaseStatus::Infected { elapsed: 0, duration: 336 };
+        sim.output_areas.write().unwrap()[0].lock().unwrap().citizens[3].disease_status =
+            DiseaseStatus::Vaccinated(0);
+
+        let filename = std::env::temp_dir()
+            .join(format!("citizen_outcomes_test_{}.csv", std::process::id()))
+            .to_str()
+            .expect("Non-UTF8 temp path")
+            .to_string();
+        sim.export_citizen_outcomes_csv(&filename)
+            .expect("Failed to export citizen outcomes");
+
+        let contents = std::fs::read_to_string(&filename).expect("Failed to read exported CSV");
+        std::fs::remove_file(&filename).ok();
+
+        let mut lines = contents.lines();
+        assert_eq!(
+            lines.next(),
+            Some("citizen_id,age,ever_infected,infection_time,final_status,ever_vaccinated")
+        );
+        let rows: Vec<&str> = lines.collect();
+        assert_eq!(rows.len(), 5, "Expected one row per Citizen");
+
+        let infected_row = rows
+            .iter()
+            .find(|row| row.contains(",infected,"))
+            .expect("Expected the infected Citizen's row");
+        let infection_time: u32 = infected_row
+            .split(',')
+            .nth(3)
+            .expect("Missing infection_time column")
+            .parse()
+            .expect("infection_time should be numeric");
+        assert!(
+            infection_time <= sim.statistics_recorder.current_time_step(),
+            "Infection time {} should fall within the simulated range (0..={})",
+            infection_time,
+            sim.statistics_recorder.current_time_step()
+        );
+        assert!(rows.iter().any(|row| row.contains(",vaccinated,true")));
+    }
+
+    /// In a two-area scenario where a Citizen resident in one area infects a Citizen resident in
+    /// another (as happens with cross-area commuting), the exported transmission flow CSV should
+    /// record that cross-area flow
+    #[test]
+    fn transmission_flow_csv_records_a_cross_area_transmission() {
+        let mut sim = simulator_with_citizen_ages(&[20, 30]);
+
+        let other_area_id = OutputAreaID::from_code_and_index("other".to_string(), 1);
+        let mut other_area = OutputArea::new(
+            other_area_id.clone(),
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        let household_id = BuildingID::new(other_area_id, BuildingType::Household, 0);
+        other_area.citizens.push(Citizen::new(
+            CitizenID::from_indexes(100),
+            household_id.clone(),
+            household_id,
+            25,
+            Occupation::Student,
+            false,
+            false,
+            false,
+            24,
+        ));
+        sim.output_areas.write().unwrap().push(Mutex::new(other_area));
+
+        let resident_of_test_area = CitizenID::from_indexes(0);
+        let resident_of_other_area = CitizenID::from_indexes(100);
+        sim.statistics_recorder
+            .record_transmission(resident_of_test_area, resident_of_other_area, 0);
+
+        let filename = std::env::temp_dir()
+            .join(format!("transmission_flow_test_{}.csv", std::process::id()))
+            .to_str()
+            .expect("Non-UTF8 temp path")
+            .to_string();
+        sim.export_transmission_flow_csv(&filename)
+            .expect("Failed to export transmission flows");
+
+        let contents = std::fs::read_to_string(&filename).expect("Failed to read exported CSV");
+        std::fs::remove_file(&filename).ok();
+
+        let mut lines = contents.lines();
+        assert_eq!(lines.next(), Some("origin,destination,count"));
+        let rows: Vec<&str> = lines.collect();
+        assert_eq!(rows.len(), 1, "Expected a single cross-area flow");
+        assert_eq!(rows[0], "test,other,1");
+    }
+
+    /// `export_config_used` should write a `config_used.json` whose `disease_model` and
+    /// `global_seed` read back as exactly equal to the ones the Simulator was run with
+    #[test]
+    fn exported_config_round_trips_to_an_equal_disease_model_and_seed() {
+        let mut sim = simulator_with_citizen_ages(&[20, 30]);
+        sim.disease_model.vaccination_strategy = VaccinationStrategy::OldestFirst;
+        sim.disease_model.seeding_strategy = crate::disease::SeedingStrategy::Fraction(0.02);
+        sim.global_seed = 123456789;
+
+        let output_directory = std::env::temp_dir()
+            .join(format!("config_used_test_{}/", std::process::id()))
+            .to_str()
+            .expect("Non-UTF8 temp path")
+            .to_string();
+        sim.export_config_used(&output_directory)
+            .expect("Failed to export run configuration");
+
+        let contents = std::fs::read_to_string(output_directory.clone() + "config_used.json")
+            .expect("Failed to read exported config_used.json");
+        std::fs::remove_dir_all(&output_directory).ok();
+
+        let exported: RunConfigExport =
+            serde_json::from_str(&contents).expect("config_used.json should deserialise");
+        assert_eq!(exported.disease_model, sim.disease_model);
+        assert_eq!(exported.global_seed, sim.global_seed);
+        assert_eq!(exported.area_code, sim.area_code);
+    }
+
+    /// A working population run at day-level resolution (`steps_per_day = 1`) should reach a
+    /// comparable total ever-infected count to the same population run at hour-level resolution
+    /// (`steps_per_day = 24`) over the same number of simulated days - regression test for the
+    /// "stuck at work forever" bug `Citizen::execute_time_step`'s schedule arms used to have at
+    /// low resolutions, which silently destroyed household-based transmission
+    ///
+    /// Drives the transmission pipeline directly (`generate_exposures`/`apply_exposures`/
+    /// `apply_importations`/`apply_community_transmission`), rather than `Simulator::step`,
+    /// to avoid `apply_interventions`' percentage-based lockdown/vaccination thresholds - those
+    /// would otherwise trigger at wildly different simulated hours for the two resolutions and
+    /// make the comparison meaningless for reasons unrelated to the bug under test
+    #[test]
+    fn day_and_hour_resolution_produce_comparable_epidemic_curves() {
+        const HOUSEHOLDS: u32 = 40;
+
+        fn ever_infected_after(steps_per_day: u32, days: u32) -> u32 {
+            let mut sim = build_working_population_simulator(steps_per_day, HOUSEHOLDS);
+            sim.disease_model.exposure_chance = 0.9;
+            for _ in 0..(days * steps_per_day) {
+                sim.statistics_recorder.next().expect("Failed to advance statistics recorder");
+                sim.public_transport = Default::default();
+                let exposures = sim.generate_exposures().expect("Failed to generate exposures");
+                sim.apply_exposures(exposures).expect("Failed to apply exposures");
+                sim.apply_importations().expect("Failed to apply importations");
+                sim.apply_community_transmission()
+                    .expect("Failed to apply community transmission");
+            }
+            HOUSEHOLDS * 2 - sim.susceptible_count()
+        }
+
+        let ever_infected_by_day = ever_infected_after(1, 60);
+        let ever_infected_by_hour = ever_infected_after(24, 60);
+
+        assert!(ever_infected_by_day > 0, "Day-resolution run should have some infections");
+        assert!(ever_infected_by_hour > 0, "Hour-resolution run should have some infections");
+        let (larger, smaller) = if ever_infected_by_day > ever_infected_by_hour {
+            (ever_infected_by_day, ever_infected_by_hour)
+        } else {
+            (ever_infected_by_hour, ever_infected_by_day)
+        };
+        assert!(
+            (larger - smaller) as f64 / larger as f64 < 0.2,
+            "Day-resolution ({}) and hour-resolution ({}) runs should reach a comparable total \
+            ever-infected count",
+            ever_infected_by_day,
+            ever_infected_by_hour
+        );
+    }
+}
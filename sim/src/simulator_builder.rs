@@ -27,11 +27,13 @@ use std::rc::Rc;
 
 use anyhow::Context;
 use enum_map::EnumMap;
+use geo::centroid::Centroid;
+use geo::prelude::{BoundingRect, Contains};
 use geo_types::{Coordinate, Point};
 use log::{debug, error, info, trace, warn};
 use num_format::ToFormattedString;
-use rand::{RngCore, thread_rng};
-use rand::prelude::{IteratorRandom, SliceRandom};
+use rand::{Rng, RngCore, thread_rng};
+use rand::prelude::SliceRandom;
 use rayon::prelude::{
     IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator,
     IntoParallelRefMutIterator, ParallelIterator,
@@ -40,20 +42,27 @@ use strum::IntoEnumIterator;
 
 use load_census_data::CensusData;
 use load_census_data::parsing_error::{DataLoadingError, ParseErrorType};
-use osm_data::{BuildingBoundaryID, OSMRawBuildings, RawBuilding, TagClassifiedBuilding};
+use osm_data::{
+    convert_polygon_to_float, BuildingBoundaryID, OSMRawBuildings, RawBuilding,
+    TagClassifiedBuilding,
+};
 use osm_data::polygon_lookup::PolygonContainer;
 
-use crate::config::{MAX_STUDENT_AGE, NUMBER_FORMATTING};
-use crate::config::STARTING_INFECTED_COUNT;
-use crate::disease::{DiseaseModel, DiseaseStatus};
+use crate::config::{deterministic_area_rng, get_available_system_memory_bytes, DEFAULT_AVERAGE_CLASS_SIZE, DEFAULT_AVERAGE_OFFICE_SIZE, DEFAULT_MAX_WORKPLACE_SEARCH_ATTEMPTS, DEFAULT_MIN_STUDENT_AGE, DEFAULT_POPULATION_SCALE, DEFAULT_PRUNE_ISOLATED_CITIZENS, DEFAULT_STATISTICS_SAMPLING_INTERVAL, DEFAULT_WORKPLACE_BUILDING_OVERCAPACITY, DEFAULT_WORKPLACE_ROOM_SIZE, MAX_STUDENT_AGE, NUMBER_FORMATTING};
+use crate::disease::{sample_infectious_period, DiseaseModel, DiseaseStatus, SeedingStrategy};
 use crate::error::SimError;
-use crate::models::building::{
-    AVERAGE_CLASS_SIZE, Building, BuildingID, BuildingType, School, Workplace,
-};
+use crate::models::building::{Building, BuildingID, BuildingType, School, Shop, Workplace};
 use crate::models::citizen::{Citizen, CitizenID, OccupationType};
 use crate::models::get_density_for_occupation;
 use crate::models::output_area::{OutputArea, OutputAreaID};
 use crate::statistics::Timer;
+use crate::time::DayOfWeek;
+use crate::warning_aggregator::WarningAggregator;
+
+/// A rough, unmeasured estimate of the heap size of a single `Box<dyn Building + Sync + Send>`,
+/// used by `SimulatorBuilder::estimated_memory_bytes` since the trait object's concrete size
+/// (`Household`/`Workplace`/`School`/`Shop`) isn't known without downcasting every building
+const AVERAGE_BUILDING_BYTES: usize = 256;
 
 pub struct SimulatorBuilder {
     pub area_code: String,
@@ -66,6 +75,119 @@ pub struct SimulatorBuilder {
     pub disease_model: DiseaseModel,
     /// The Output Area and Local Index a Citizen is located at
     pub citizen_output_area_lookup: Vec<(OutputAreaID, u32)>,
+    /// The seed used to derive per Output Area Rng's, so `--threads` does not affect the outcome of a run
+    pub global_seed: u64,
+    /// How much to inflate the assumed size of buildings when distributing them between
+    /// occupations, relative to the minimum required to fit every worker
+    ///
+    /// This is a blunt instrument - `assign_buildings_per_output_area` uses it as a single global
+    /// multiplier on every building's accounted size, so it should be tuned for the area's actual
+    /// building-size distribution rather than maximised: too low, and the "ran out of workplaces"
+    /// errors in `assign_workplaces_to_citizens_per_occupation` become more likely; too high, and
+    /// buildings can be prematurely considered to have met an occupation's quota and go unused
+    pub workplace_building_overcapacity: f64,
+    /// The Day of the Week that `time_step` 0 falls on, so weekend effects and logged statistics
+    /// stay correctly phased against the real calendar date the run represents
+    pub start_day_of_week: DayOfWeek,
+    /// The average number of students per class, used by `build_schools` to size classes and
+    /// therefore how many teachers a school requires
+    pub average_class_size: f64,
+    /// The average number of teachers sharing an office, for the leftover teaching staff not
+    /// assigned a class of their own
+    pub average_office_size: usize,
+    /// The maximum number of occupants sharing a room within a Workplace - once a Workplace's
+    /// occupants exceed this, `Workplace::find_exposures` contains exposures within a room rather
+    /// than mixing across the whole building, the same way `average_class_size` contains exposures
+    /// within a school class
+    pub workplace_room_size: u32,
+    /// If true, `build` prunes fully-isolated Citizens (no separate workplace/school, a sole
+    /// household occupant, and no public transport use) out of the per-step contact network, since
+    /// they can only catch the disease via importation - see `OutputArea::prune_isolated_citizens`
+    pub prune_isolated_citizens: bool,
+    /// Scales down each Output Area's generated population by this factor, so e.g. `0.1` runs a
+    /// 10%-sized version of the full region - households (and so workplaces, which are assigned
+    /// per-citizen) shrink to match, since `generate_citizens_with_households` simply stops
+    /// building households once the scaled population target is reached
+    pub population_scale: f64,
+    /// How many times `build_workplaces` will re-sample a Citizen's workplace Output Area before
+    /// giving up and leaving them without a workplace
+    ///
+    /// Raising this reduces how many Citizens go unassigned for pathological census distributions
+    /// where valid destination areas are rare, at the cost of more retries per Citizen affected
+    pub max_workplace_search_attempts: u32,
+    /// How many attempts each Citizen needed before a valid workplace Output Area was found, keyed
+    /// by attempt count, populated by the most recent `build_workplaces` call
+    ///
+    /// A long tail here indicates a pathological census distribution where valid workplace
+    /// destinations are rare, and `max_workplace_search_attempts` may need raising
+    pub workplace_search_attempt_histogram: HashMap<u32, u32>,
+    /// The youngest age `build_schools` will assign a Citizen to a school - below this, a Citizen
+    /// is left at their household, the same as when no schools exist in the OSM data
+    pub min_student_age: u16,
+    /// How many time steps apart `StatisticsRecorder::global_stats` entries are kept, to reduce
+    /// memory and output size on long runs - see `StatisticsRecorder::set_sampling_interval`
+    pub statistics_sampling_interval: u32,
+    /// How a Citizen's workplace Output Area is chosen in `build_workplaces`
+    pub workplace_assignment_strategy: WorkplaceAssignmentStrategy,
+    /// How `get_area_code_for_raw_building` resolves a building that matches more than one Output
+    /// Area, e.g. one straddling a border
+    pub building_area_assignment_policy: BuildingAreaAssignmentPolicy,
+    /// The percentage of Citizens whose workplace code is identical to their household code after
+    /// `build` has run, i.e. working from home - see `work_from_home_percentage`
+    ///
+    /// This counts every Citizen left at their household: those deliberately working remotely (see
+    /// `DiseaseModel::remote_work_probability`), those `build_workplaces` failed to find a
+    /// destination workplace for (see `max_workplace_search_attempts`), and those never given a
+    /// workplace or school at all - the percentage does not distinguish between them
+    work_from_home_percentage: Option<f64>,
+    /// If set, `build` loads/saves the building-to-output-area assignment from this directory via
+    /// `assign_buildings_to_output_areas_cached`, keyed on `building_assignment_cache_input_file_paths`,
+    /// instead of always recomputing it with the uncached `assign_buildings_to_output_areas` - see
+    /// `set_building_assignment_cache`
+    building_assignment_cache_dir: Option<String>,
+    /// The OSM and Output Area shapefile paths `build` hashes to key the building assignment cache -
+    /// see `building_assignment_cache_dir`
+    building_assignment_cache_input_file_paths: Vec<String>,
+}
+
+/// How a building that `find_polygons_containing_polygon` matches to more than one Output Area
+/// (since that match is only a bounding-box intersection, a building near a border can match
+/// several) is resolved down to the single Output Area it's actually assigned to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildingAreaAssignmentPolicy {
+    /// Assigns the building to whichever candidate Output Area's polygon contains its centroid
+    ///
+    /// Falls back to `LargestOverlap` if no candidate's polygon actually contains the centroid
+    Centroid,
+    /// Assigns the building to whichever candidate Output Area's bounding box overlaps the
+    /// building's bounding box by the largest area
+    LargestOverlap,
+}
+
+impl Default for BuildingAreaAssignmentPolicy {
+    fn default() -> Self {
+        BuildingAreaAssignmentPolicy::Centroid
+    }
+}
+
+/// How a Citizen's workplace Output Area is chosen in `build_workplaces`
+#[derive(Clone, Debug, PartialEq)]
+pub enum WorkplaceAssignmentStrategy {
+    /// Draws the workplace Output Area from the Census residence/workplace flow distribution for
+    /// the Citizen's home Output Area - the original, data-driven behaviour
+    CensusFlow,
+    /// Assigns the Output Area containing the WorkPlace building closest to the Citizen's home
+    /// Output Area, minimising commute distance instead of matching Census flows
+    NearestWorkplace,
+    /// Uses `NearestWorkplace` for a randomly chosen half of Citizens, and `CensusFlow` for the
+    /// rest, approximating a population with a mix of local and long-distance commuters
+    Hybrid,
+}
+
+impl Default for WorkplaceAssignmentStrategy {
+    fn default() -> Self {
+        WorkplaceAssignmentStrategy::CensusFlow
+    }
 }
 
 /// Initialisation Methods
@@ -75,7 +197,11 @@ impl SimulatorBuilder {
     /// And returns the starting population count
     pub fn initialise_output_areas(&mut self) -> anyhow::Result<()> {
         // Build the initial Output Areas and Households
-        for entry in &self.census_data.valid_areas {
+        // Sorted so the area code -> index mapping is deterministic, rather than depending on
+        // `HashSet`'s unspecified iteration order
+        let mut valid_areas: Vec<&String> = self.census_data.valid_areas.iter().collect();
+        valid_areas.sort_unstable();
+        for entry in valid_areas {
             let output_id = OutputAreaID::from_code_and_index(
                 entry.to_string(),
                 self.output_areas.len() as u32,
@@ -94,10 +220,12 @@ impl SimulatorBuilder {
                 .context(format!("Loading polygon shape for area: {}", output_id))?;
             self.output_area_lookup
                 .insert(output_id.code().clone(), output_id.index() as u32);
-            let new_area = OutputArea::new(
+            let new_area = OutputArea::new_with_commute_config(
                 output_id,
                 polygon.clone(),
                 self.disease_model.mask_percentage,
+                self.disease_model.asymptomatic_chance,
+                self.disease_model.public_transport_percentage,
             )
                 .context("Failed to create Output Area")?;
             self.output_areas.push(new_area);
@@ -125,7 +253,82 @@ impl SimulatorBuilder {
             &self.osm_data.building_boundaries,
             &self.osm_data.building_locations,
             &self.output_areas_polygons,
+            self.building_area_assignment_policy,
         );
+        self.apply_building_assignment(&possible_buildings_per_area)?;
+        Ok(possible_buildings_per_area)
+    }
+
+    /// Same as [`SimulatorBuilder::assign_buildings_to_output_areas`], but caches the (expensive, deterministic)
+    /// building-to-output-area assignment to disk, keyed by a hash of `input_file_paths`
+    ///
+    /// Subsequent builds of the same `input_file_paths` load the cached assignment instead of
+    /// recomputing it. `input_file_paths` should be the OSM `.osm.pbf` file and the Output Area
+    /// shapefile that were used to build `self.osm_data`/`self.output_areas_polygons` - passing
+    /// something else will not detect that the inputs have actually changed. Set `force_rebuild`
+    /// to ignore any existing cache entry and recompute it
+    pub fn assign_buildings_to_output_areas_cached(
+        &mut self,
+        cache_dir: &str,
+        input_file_paths: &[&str],
+        force_rebuild: bool,
+    ) -> anyhow::Result<HashMap<String, HashMap<TagClassifiedBuilding, Vec<RawBuilding>>>> {
+        let cache_path = format!(
+            "{}/building_assignment_{:x}.json",
+            cache_dir,
+            Self::building_assignment_input_hash(input_file_paths)?
+        );
+        if !force_rebuild {
+            if let Ok(bytes) = fs::read(&cache_path) {
+                match serde_json::from_slice(&bytes) {
+                    Ok(cached) => {
+                        info!("Loaded building-to-output-area assignment from cache: {}", cache_path);
+                        self.apply_building_assignment(&cached)?;
+                        return Ok(cached);
+                    }
+                    Err(e) => warn!("Failed to parse building assignment cache, rebuilding: {}", e),
+                }
+            }
+        }
+        let possible_buildings_per_area = self.assign_buildings_to_output_areas()?;
+        fs::create_dir_all(cache_dir).context("Failed to create building assignment cache directory")?;
+        fs::write(&cache_path, serde_json::to_vec(&possible_buildings_per_area)?)
+            .context("Failed to write building assignment cache")?;
+        Ok(possible_buildings_per_area)
+    }
+
+    /// A hash of `input_file_paths`' path, size, and last-modified time, used as the cache key for
+    /// [`SimulatorBuilder::assign_buildings_to_output_areas_cached`]
+    ///
+    /// Hashes each file's metadata rather than its full contents, so computing the cache key stays
+    /// cheap even for a multi-gigabyte `.osm.pbf` file - touching a file without actually changing
+    /// its content will still bust the cache, which is an acceptable trade-off for avoiding a full
+    /// re-read of the input on every build
+    fn building_assignment_input_hash(input_file_paths: &[&str]) -> anyhow::Result<u64> {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+        let mut hasher = DefaultHasher::new();
+        for path in input_file_paths {
+            path.hash(&mut hasher);
+            let metadata = fs::metadata(path)
+                .context(format!("Failed to read metadata for input file '{}'", path))?;
+            metadata.len().hash(&mut hasher);
+            metadata
+                .modified()
+                .context(format!("Failed to read modified time for input file '{}'", path))?
+                .duration_since(std::time::SystemTime::UNIX_EPOCH)
+                .context("Input file's modified time predates the UNIX epoch")?
+                .as_secs()
+                .hash(&mut hasher);
+        }
+        Ok(hasher.finish())
+    }
+
+    /// Removes Output Areas without any possible buildings, and rebuilds `output_area_lookup` to match
+    fn apply_building_assignment(
+        &mut self,
+        possible_buildings_per_area: &HashMap<String, HashMap<TagClassifiedBuilding, Vec<RawBuilding>>>,
+    ) -> anyhow::Result<()> {
         // Count the number of buildings generated
         let count: usize = possible_buildings_per_area
             .par_iter()
@@ -137,7 +340,7 @@ impl SimulatorBuilder {
             })
             .sum();
 
-        let mut output_areas = &mut self.output_areas;
+        let output_areas = &mut self.output_areas;
         // TODO This is broke
         // Remove any areas without any buildings
         let to_delete: Vec<usize> = output_areas
@@ -170,7 +373,7 @@ impl SimulatorBuilder {
             count,
             self.output_areas.len()
         );
-        Ok(possible_buildings_per_area)
+        Ok(())
     }
 
     /// Generates the Citizens for each Output Area
@@ -184,6 +387,9 @@ impl SimulatorBuilder {
     ) -> anyhow::Result<()> {
         let mut no_buildings = 0;
         let mut no_households = 0;
+        let steps_per_day = self.disease_model.steps_per_day;
+        let population_scale = self.population_scale;
+        let superspreading_dispersion = self.disease_model.superspreading_dispersion;
         // Generate Citizens
 
         // This ref self is needed, because we have a mut borrow (Output Areas) and an immutable borrow (Census Data)
@@ -233,6 +439,9 @@ impl SimulatorBuilder {
                     rng,
                     census_data_entry,
                     possible_households,
+                    steps_per_day,
+                    population_scale,
+                    superspreading_dispersion,
                 )?;
                 global_citizen_index += generated_count;
                 for (index, _citizen) in output_area.citizens.iter().enumerate() {
@@ -247,6 +456,12 @@ impl SimulatorBuilder {
             "Households and Citizen generation succeeded for {} Output Areas.",
             ref_output_areas.borrow().len()
         );
+        if population_scale != DEFAULT_POPULATION_SCALE {
+            info!(
+                "Population has been scaled to {:.1}% of the Census figures - all resulting counts and statistics are scaled accordingly",
+                population_scale * 100.0
+            );
+        }
         if no_households > 0 {
             warn!(
                 "Failed to generate households for {} Output Areas, as no homes exist!",
@@ -286,6 +501,7 @@ impl SimulatorBuilder {
                     (accum_citizens, accum_buildings, accum_ids)
                 },
             );
+        let min_student_age = self.min_student_age;
         let (students, teachers): (Vec<Vec<&mut Citizen>>, Vec<&mut Citizen>) =
             output_area_citizens
                 .par_iter_mut()
@@ -294,7 +510,7 @@ impl SimulatorBuilder {
                         .iter_mut()
                         .filter_map(|citizen| {
                             let age = citizen.age;
-                            if age < MAX_STUDENT_AGE {
+                            if (min_student_age..MAX_STUDENT_AGE).contains(&age) {
                                 Some((Some((citizen.age, citizen)), None))
                             } else if Some(OccupationType::Teaching)
                                 == citizen.detailed_occupation()
@@ -348,19 +564,25 @@ impl SimulatorBuilder {
             teachers.len()
         );
         // The OSM Voronoi School Lookup
-        let school_lookup = self
-            .osm_data
-            .voronoi()
-            .get(&TagClassifiedBuilding::School)
-            .expect("No schools exist!");
+        let school_lookup = match self.osm_data.voronoi().get(&TagClassifiedBuilding::School) {
+            Some(lookup) => lookup,
+            None => {
+                warn!("No schools exist in this region's OSM data - leaving students at home");
+                return Ok(());
+            }
+        };
         // Function to find the the closest school, to the given citizen
-        let building_locations = self
+        let building_locations = match self
             .osm_data
             .building_locations
             .get(&TagClassifiedBuilding::School)
-            .ok_or_else(|| SimError::InitializationError {
-                message: format!("Couldn't retrieve school buildings!"),
-            })?;
+        {
+            Some(buildings) => buildings,
+            None => {
+                warn!("No schools exist in this region's OSM data - leaving students at home");
+                return Ok(());
+            }
+        };
         debug!("There are {} raw schools", building_locations.len());
 
         let all_boundaries = &self.osm_data.building_boundaries;
@@ -387,6 +609,8 @@ impl SimulatorBuilder {
         let output_area_lookup = &self.output_area_lookup;
         let building_boundaries = &self.osm_data.building_boundaries;
         let output_areas_polygons = &self.output_areas_polygons;
+        let building_area_assignment_policy = self.building_area_assignment_policy;
+        let school_assignment_warnings = WarningAggregator::new(crate::config::VERBOSE_BUILD_WARNINGS);
         // Function to find the closest school to a given Citizen
         let finding_closest_school =
             |citizen: &Citizen, get_multiple: bool| -> Result<Vec<&RawBuilding>, SimError> {
@@ -412,15 +636,14 @@ impl SimulatorBuilder {
                         .into_iter()
                         .filter_map(|index| {
                             let school = building_locations.get(index)?;
-                            let area_codes = get_area_code_for_raw_building(
+                            let (output_area_id, _) = get_area_code_for_raw_building(
                                 school,
                                 output_areas_polygons,
                                 building_boundaries,
+                                building_area_assignment_policy,
                             )
                                 .expect("School building is not inside any Output areas!");
-                            let output_area_id = area_codes.keys().next()?;
-                            //.expect("School building is not inside any Output areas!");
-                            if output_area_lookup.contains_key(output_area_id) {
+                            if output_area_lookup.contains_key(&output_area_id) {
                                 Some(school)
                             } else {
                                 None
@@ -445,7 +668,7 @@ impl SimulatorBuilder {
                             }
                         }
                         Err(e) => {
-                            warn!("Failed to assign school to student: {}", e);
+                            school_assignment_warnings.record("Failed to assign school to student", e);
                             None
                         }
                     }
@@ -488,6 +711,7 @@ impl SimulatorBuilder {
 
         // The amount of teachers that fail to be assigned
         let mut failed_teacher_count = 0;
+        let average_class_size = self.average_class_size;
         // Take a two pronged approach to assigning teachers
         teachers.into_par_iter().filter_map(|teacher| {
             match finding_closest_school(teacher, true) {
@@ -495,7 +719,7 @@ impl SimulatorBuilder {
                     Some((teacher, schools))
                 }
                 Err(e) => {
-                    warn!("Failed to assign school to teacher: {}", e);
+                    school_assignment_warnings.record("Failed to assign school to teacher", e);
                     None
                 }
             }
@@ -512,7 +736,7 @@ impl SimulatorBuilder {
                     let total_students = students
                         .iter()
                         .map(|age_group| {
-                            ((age_group.len() as f64 / AVERAGE_CLASS_SIZE).ceil() as usize)
+                            ((age_group.len() as f64 / average_class_size).ceil() as usize)
                                 .max(1)
                         })
                         .sum::<usize>();
@@ -600,9 +824,11 @@ impl SimulatorBuilder {
         let mut schools_total = 0;
 
         let mut schools_missing_teachers = 0;
+        let mut schools_failed_to_build = 0;
 
         let mut debug_stats = HashMap::with_capacity(citizens_per_raw_school.len());
 
+        let average_office_size = self.average_office_size;
         citizens_per_raw_school.into_iter().for_each(
             |(_school_position, (students, teachers, building))| {
                 students_total += students
@@ -617,21 +843,17 @@ impl SimulatorBuilder {
                 }
                 // Retrieve the Output Area, and build the School building
                 // TODO Change to Let Else when `https://github.com/rust-lang/rust/issues/87335` is stabilised
-                let possible_output_area_ids = if let Some(area) = get_area_code_for_raw_building(
+                let output_area_code = if let Some((area, _)) = get_area_code_for_raw_building(
                     building,
                     output_areas_polygons,
                     building_boundaries,
+                    building_area_assignment_policy,
                 ) {
                     area
                 } else {
                     return;
                 };
-                let output_area_code = if let Some(area) = possible_output_area_ids.keys().next() {
-                    area
-                } else {
-                    return;
-                };
-                let index = if let Some(index) = output_area_lookup.get(output_area_code) {
+                let index = if let Some(index) = output_area_lookup.get(&output_area_code) {
                     index
                 } else {
                     return;
@@ -658,12 +880,21 @@ impl SimulatorBuilder {
 
                 let teacher_ids = teachers.iter().map(|citizen| citizen.id()).collect();
 
-                let (school, stats) = School::with_students_and_teachers(
+                let (school, stats) = match School::with_students_and_teachers(
                     building_id.clone(),
                     *building,
                     student_ids,
                     teacher_ids,
-                );
+                    average_class_size,
+                    average_office_size,
+                ) {
+                    Ok(school_and_stats) => school_and_stats,
+                    Err(e) => {
+                        warn!("Failed to build School {}: {}", building_id, e);
+                        schools_failed_to_build += 1;
+                        return;
+                    }
+                };
                 debug_stats.insert(
                     format!(
                         "({},{}",
@@ -705,10 +936,93 @@ impl SimulatorBuilder {
             serde_json::to_writer(file, &debug_stats).unwrap();
         }
         warn!("{} schools are missing teachers", schools_missing_teachers);
+        warn!("{} schools failed to build", schools_failed_to_build);
         info!("Generated {} schools, with {} teachers, {} students across {} classes, with avg class size {} and avg classes per school {}. With {} offices and {} misc staff",schools_total,teachers_total,students_total,class_total,(students_total/class_total),(class_total/schools_total),offices_total,misc_staff_total);
+        school_assignment_warnings.summarise();
         Ok(())
     }
 
+    /// Assigns a nearby Shop Building to every non-working Citizen (`workplace_code ==
+    /// household_code`, i.e. a retired or unemployed Citizen, or one too young/old for
+    /// `build_schools`/`build_workplaces` to have reassigned), so they make a daily
+    /// community/shopping trip instead of staying at their household all day - see
+    /// `Citizen::execute_time_step`
+    ///
+    /// Builds one `Shop` Building per Output Area, at the location of the raw Shop building
+    /// closest to that Output Area's centroid. An Output Area with no Shop found nearby is left
+    /// unchanged, so its Citizens simply keep the current stay-at-home behaviour
+    pub fn build_shops(&mut self) -> anyhow::Result<()> {
+        debug!("Building Shops");
+        let shop_lookup = match self.osm_data.voronoi().get(&TagClassifiedBuilding::Shop) {
+            Some(lookup) => lookup,
+            None => {
+                warn!("No shops exist in this region's OSM data - non-working Citizens will stay home");
+                return Ok(());
+            }
+        };
+        let building_locations = match self
+            .osm_data
+            .building_locations
+            .get(&TagClassifiedBuilding::Shop)
+        {
+            Some(buildings) => buildings,
+            None => {
+                warn!("No shops exist in this region's OSM data - non-working Citizens will stay home");
+                return Ok(());
+            }
+        };
+        let mut shops_built = 0;
+        for area in self.output_areas.iter_mut() {
+            let centroid = area.centroid();
+            let reference_point = Point::new(centroid.x() as i32, centroid.y() as i32);
+            let seed_index = match shop_lookup.find_seed_for_point(reference_point) {
+                Ok(seed_index) => seed_index,
+                Err(e) => {
+                    warn!("Failed to find a Shop for Output Area {}: {}", area.id(), e);
+                    continue;
+                }
+            };
+            let raw_shop = match building_locations.get(seed_index) {
+                Some(raw_shop) => *raw_shop,
+                None => continue,
+            };
+            let shop_id = BuildingID::new(area.id(), BuildingType::Shop, area.buildings.len() as u32);
+            area.buildings.push(Box::new(Shop::new(shop_id.clone(), raw_shop)));
+            shops_built += 1;
+            for citizen in area.citizens.iter_mut() {
+                if citizen.workplace_code == citizen.household_code {
+                    citizen.set_shop_code(shop_id.clone());
+                }
+            }
+        }
+        info!("Built {} shops, across {} Output Areas", shops_built, self.output_areas.len());
+        Ok(())
+    }
+
+    /// Finds the Output Area code containing the WorkPlace building closest to `household_area`'s
+    /// centroid, for `WorkplaceAssignmentStrategy::NearestWorkplace`/`Hybrid`
+    ///
+    /// Returns `None` if this region's OSM data has no WorkPlace buildings, or the nearest one
+    /// can't be resolved back to an Output Area
+    fn nearest_workplace_area_code(&self, household_area: &OutputArea) -> Option<String> {
+        let workplace_lookup = self.osm_data.voronoi().get(&TagClassifiedBuilding::WorkPlace)?;
+        let workplace_building_locations = self
+            .osm_data
+            .building_locations
+            .get(&TagClassifiedBuilding::WorkPlace)?;
+        let centroid = household_area.centroid();
+        let reference_point = Point::new(centroid.x() as i32, centroid.y() as i32);
+        let seed_index = workplace_lookup.find_seed_for_point(reference_point).ok()?;
+        let raw_workplace = workplace_building_locations.get(seed_index)?;
+        let (area_code, _) = get_area_code_for_raw_building(
+            raw_workplace,
+            &self.output_areas_polygons,
+            &self.osm_data.building_boundaries,
+            self.building_area_assignment_policy,
+        )?;
+        Some(area_code)
+    }
+
     /// Iterates through all Output Areas, and All Citizens in that Output Area
     ///
     /// Picks a Workplace Output Area, determined from Census Data Distribution
@@ -722,10 +1036,18 @@ impl SimulatorBuilder {
             "Assigning workplaces to {} output areas ",
             self.output_areas.len()
         );
-        // Shuffle the buildings
+        // Shuffle the buildings, using a Rng seeded per Output Area so the result doesn't depend on
+        // how rayon happens to schedule areas across threads
+        let output_area_lookup = &self.output_area_lookup;
+        let global_seed = self.global_seed;
+        let workplace_assignment_warnings = WarningAggregator::new(crate::config::VERBOSE_BUILD_WARNINGS);
         possible_buildings_per_area
             .par_iter_mut()
-            .for_each(|(_, buildings)| buildings.shuffle(&mut thread_rng()));
+            .for_each(|(area_code, buildings)| {
+                let area_index = output_area_lookup.get(area_code).copied().unwrap_or(0);
+                let mut rng = deterministic_area_rng(global_seed, area_index);
+                buildings.shuffle(&mut rng)
+            });
 
         // Group Citizens by their workplace output area
         // NOTE This is achieved by removing citizens from self.citizens, because we cannot pass references through
@@ -733,6 +1055,8 @@ impl SimulatorBuilder {
         let mut citizens_to_allocate: Vec<Vec<CitizenID>> =
             vec![Vec::new(); self.output_areas.len()];
         let mut citizens_allocated_count = 0;
+        let mut remote_worker_count = 0;
+        self.workplace_search_attempt_histogram.clear();
         // Assign workplace areas to each Citizen, per Output area
         for household_output_area in &self.output_areas {
             // Retrieve the census data for the household output area
@@ -745,38 +1069,100 @@ impl SimulatorBuilder {
                         key: household_output_area.id().to_string(),
                     },
                 })?;
+            // Resolved once per household Output Area, since it doesn't depend on the Citizen -
+            // only computed when actually needed, as it walks the WorkPlace Voronoi diagram
+            let nearest_workplace_area_code = if self.workplace_assignment_strategy
+                != WorkplaceAssignmentStrategy::CensusFlow
+            {
+                self.nearest_workplace_area_code(household_output_area)
+            } else {
+                None
+            };
 
             // For each Citizen, assign a workplace area
             'citizens: for citizen in &household_output_area.citizens {
-                let mut index = 0;
                 if citizen.is_student()
                     || citizen.detailed_occupation() == Some(OccupationType::Teaching)
                 {
                     continue 'citizens;
                 }
+                // Deliberate remote work, separate from a Citizen who simply fails to be assigned
+                // a Workplace below - see `DiseaseModel::remote_work_probability`
+                if let Some(occupation) = citizen.detailed_occupation() {
+                    let remote_work_probability =
+                        self.disease_model.remote_work_probability[occupation];
+                    if remote_work_probability > 0.0
+                        && thread_rng().gen_bool(remote_work_probability)
+                    {
+                        remote_worker_count += 1;
+                        continue 'citizens;
+                    }
+                }
+                // Whether to use the nearest WorkPlace area for this specific Citizen - under
+                // `Hybrid`, each Citizen independently gets one of the two strategies, rather than
+                // every Citizen in an Output Area sharing the same choice
+                let use_nearest_workplace = match self.workplace_assignment_strategy {
+                    WorkplaceAssignmentStrategy::CensusFlow => false,
+                    WorkplaceAssignmentStrategy::NearestWorkplace => true,
+                    WorkplaceAssignmentStrategy::Hybrid => thread_rng().gen_bool(0.5),
+                };
                 // Loop until we find a Valid area, otherwise skip this Citizen
+                let mut attempts = 0;
                 let workplace_output_area_index: u32 = loop {
-                    let code = household_census_data
-                        .get_random_workplace_area(&mut thread_rng())
-                        .context("Failed to retrieve random workplace area")?;
+                    attempts += 1;
+                    let code = if use_nearest_workplace {
+                        // Deterministic given the household Output Area, so retrying wouldn't
+                        // change the result - give up immediately rather than looping
+                        match nearest_workplace_area_code.clone() {
+                            Some(code) => code,
+                            None => {
+                                workplace_assignment_warnings
+                                    .record("Failed to assign workplace", citizen.id());
+                                *self
+                                    .workplace_search_attempt_histogram
+                                    .entry(attempts)
+                                    .or_insert(0) += 1;
+                                continue 'citizens;
+                            }
+                        }
+                    } else {
+                        household_census_data
+                            .get_random_workplace_area(&mut thread_rng())
+                            .context("Failed to retrieve random workplace area")?
+                    };
                     if let Some(id) = self.output_area_lookup.get(&code) {
                         if possible_buildings_per_area.get(&code).is_some() {
                             break *id;
                         }
                     }
-                    index += 1;
-                    if index > 50 {
-                        error!("Failed to generate code for Citizen: {}", citizen.id());
+                    if use_nearest_workplace || attempts >= self.max_workplace_search_attempts {
+                        workplace_assignment_warnings
+                            .record("Failed to assign workplace", citizen.id());
+                        *self
+                            .workplace_search_attempt_histogram
+                            .entry(attempts)
+                            .or_insert(0) += 1;
                         continue 'citizens;
                     }
                 };
+                *self
+                    .workplace_search_attempt_histogram
+                    .entry(attempts)
+                    .or_insert(0) += 1;
                 let citizens_to_add = citizens_to_allocate
                     .get_mut(workplace_output_area_index as usize)
-                    .expect(&format!("Output area {} doesn't exist", index));
+                    .expect(&format!(
+                        "Output area {} doesn't exist",
+                        workplace_output_area_index
+                    ));
                 citizens_to_add.push(citizen.id());
                 citizens_allocated_count += 1;
             }
         }
+        debug!(
+            "{} Citizens deliberately assigned to work from home",
+            remote_worker_count
+        );
         debug!(
             "Creating workplace buildings for: {:?} Citizens and {} Output Areas",
             citizens_allocated_count,
@@ -807,6 +1193,17 @@ impl SimulatorBuilder {
                     (accum_citizens, accum_buildings, accum_ids)
                 },
             );
+        // Redirect Citizens whose chosen workplace Output Area no longer exists (e.g. removed by
+        // `apply_building_assignment` after they were assigned) onto a still-valid Output Area,
+        // rather than silently dropping them in the loop below
+        let reassigned_worker_count = SimulatorBuilder::reassign_orphaned_workplace_citizens(
+            &mut citizens_to_allocate,
+            |index| output_area_buildings.get(index).is_some(),
+        );
+        debug!(
+            "{} Citizens reassigned away from a deleted workplace Output Area",
+            reassigned_worker_count
+        );
         // Create buildings for each Workplace output area
         'citizen_allocation_loop: for ((workplace_area_index, mut _citizen_ids), citizens) in
         citizens_to_allocate
@@ -846,6 +1243,8 @@ impl SimulatorBuilder {
                 citizens,
                 possible_buildings,
                 workplace_output_area_buildings.len() as u32,
+                self.workplace_building_overcapacity,
+                self.workplace_room_size,
             ) {
                 Ok(buildings) => workplace_output_area_buildings.extend(buildings),
                 Err(e) => {
@@ -856,17 +1255,65 @@ impl SimulatorBuilder {
                 }
             }
         }
+        debug!(
+            "Workplace search attempt histogram: {:?}",
+            self.workplace_search_attempt_histogram
+        );
+        if crate::config::CREATE_DEBUG_DUMPS {
+            let debug_directory = crate::config::DEBUG_DUMP_DIRECTORY.to_owned();
+            fs::create_dir_all(debug_directory.clone())
+                .context("Failed to create debug dump directory")?;
+
+            let filename = debug_directory + "workplace_search_attempts.json";
+            let file = File::create(filename.clone())
+                .context(format!("Failed to create file: '{}'", filename))?;
+            serde_json::to_writer(file, &self.workplace_search_attempt_histogram).unwrap();
+        }
+        workplace_assignment_warnings.summarise();
         Ok(())
     }
 
+    /// For each `citizens_to_allocate` index that `area_exists` reports as no longer valid, moves
+    /// its queued Citizens onto a randomly chosen surviving index instead of leaving them to be
+    /// dropped - an Output Area can stop existing between a Citizen being assigned to it and
+    /// `build_workplaces` creating its buildings, since `apply_building_assignment` removes Output
+    /// Areas without any possible buildings
+    ///
+    /// Returns the number of Citizens reassigned. If every index is invalid, no reassignment is
+    /// possible and the Citizens are left in place, to be reported as a normal assignment failure
+    fn reassign_orphaned_workplace_citizens(
+        citizens_to_allocate: &mut [Vec<CitizenID>],
+        area_exists: impl Fn(usize) -> bool,
+    ) -> u32 {
+        let valid_indices: Vec<usize> =
+            (0..citizens_to_allocate.len()).filter(|&index| area_exists(index)).collect();
+        let mut reassigned_count = 0;
+        for index in 0..citizens_to_allocate.len() {
+            if area_exists(index) || citizens_to_allocate[index].is_empty() {
+                continue;
+            }
+            if let Some(&fallback_index) = valid_indices.choose(&mut thread_rng()) {
+                let orphaned = std::mem::take(&mut citizens_to_allocate[index]);
+                reassigned_count += orphaned.len() as u32;
+                citizens_to_allocate[fallback_index].extend(orphaned);
+            }
+        }
+        reassigned_count
+    }
+
     /// Calculates which buildings should be assigned to what occupation, and scales the floor space, to ensure every Citizen can have a workplace
     ///
     /// `next_building_index` is the index to start assigning indexes to new buildings
+    ///
+    /// `building_overcapacity` inflates the scaled floor space beyond the minimum required to fit
+    /// every worker - see `SimulatorBuilder::workplace_building_overcapacity` for the trade-off
     fn assign_buildings_per_output_area(
         workplace_area_code: OutputAreaID,
         mut citizen_ids: &mut Vec<Citizen>,
         possible_buildings: &mut Vec<RawBuilding>,
         mut next_building_index: u32,
+        building_overcapacity: f64,
+        workplace_room_size: u32,
     ) -> anyhow::Result<Vec<Box<dyn Building + Sync + Send>>> {
         if citizen_ids.len() == 0 {
             warn!(
@@ -888,12 +1335,11 @@ impl SimulatorBuilder {
         }
 
         let mut rng = thread_rng();
-        // This is the amount to increase bin capacity to ensure it meets the minimum required size
-        const BUILDING_PER_OCCUPATION_OVERCAPACITY: f64 = 1.1;
 
         // Randomise the order of the citizens, to reduce the number of Citizens sharing household and Workplace output areas
         citizen_ids.shuffle(&mut rng);
 
+        let total_citizens = citizen_ids.len();
         // Group by occupation
         let mut citizen_ids_per_occupation: EnumMap<OccupationType, Vec<&mut Citizen>> =
             citizen_ids
@@ -903,6 +1349,13 @@ impl SimulatorBuilder {
                     a[b.0].push(b.1);
                     a
                 });
+        let assigned_citizens: usize = citizen_ids_per_occupation.values().map(Vec::len).sum();
+        if assigned_citizens < total_citizens {
+            debug!(
+                "{} of {} Citizens in Output Area {} have no detailed occupation (Unemployed/Student) and were not assigned a workplace",
+                total_citizens - assigned_citizens, total_citizens, workplace_area_code
+            );
+        }
 
         // Calculate how much space we have
         let available_space: usize = possible_buildings
@@ -931,7 +1384,7 @@ impl SimulatorBuilder {
 
         // Calculate how much we need to scale buildings to meet the targets
         let scale = (((required_space as f64) / (available_space as f64))
-            * BUILDING_PER_OCCUPATION_OVERCAPACITY)
+            * building_overcapacity)
             .ceil() as usize;
         //trace!("Scale for Output Area: {} is {} with {} buildings and {} Workers",workplace_area_code,scale,possible_buildings.len(),total_workers);
         // Allocate buildings using first fit
@@ -1021,6 +1474,7 @@ impl SimulatorBuilder {
                 selected_citizen_ids,
                 &buildings.1,
                 next_building_index,
+                workplace_room_size,
             ) {
                 Ok(workplaces) => {
                     next_building_index += workplaces.len() as u32;
@@ -1045,20 +1499,30 @@ impl SimulatorBuilder {
         citizens: &mut Vec<&mut Citizen>,
         buildings: &Vec<RawBuilding>,
         mut next_building_index: u32,
+        workplace_room_size: u32,
     ) -> anyhow::Result<Vec<Box<dyn Building + Sync + Send>>> {
         let total_building_count = buildings.len();
         let total_workers = citizens.len();
         let mut workplace_buildings: Vec<Box<dyn Building + Sync + Send>> = Vec::new();
         let mut buildings = buildings.iter();
+        // Caring is the census occupation category healthcare workers fall under - tagging their
+        // workplaces as Hospital, rather than the generic Workplace, lets nosocomial transmission be
+        // modelled distinctly (see DiseaseModel::hospital_transmission_multiplier)
+        let building_type = if occupation == OccupationType::Caring {
+            BuildingType::Hospital
+        } else {
+            BuildingType::Workplace
+        };
 
         let mut current_workplace: Workplace = Workplace::new(
             BuildingID::new(
                 workplace_area_code.clone(),
-                BuildingType::Workplace,
+                building_type,
                 next_building_index,
             ),
             *buildings.next().ok_or_else(|| SimError::InitializationError { message: format!("Ran out of Workplaces ({}) to assign workers ({}/{}) to in Output Area: {}", total_building_count, 0, total_workers, workplace_area_code) })?,
-            occupation);
+            occupation,
+            workplace_room_size);
         next_building_index += 1;
         for (index, citizen) in citizens.iter_mut().enumerate() {
             assert_eq!(
@@ -1089,11 +1553,12 @@ impl SimulatorBuilder {
                     let mut new_workplace = Workplace::new(
                         BuildingID::new(
                             workplace_area_code.clone(),
-                            BuildingType::Workplace,
+                            building_type,
                             next_building_index,
                         ),
                         new_raw_building,
                         occupation,
+                        workplace_room_size,
                     );
                     next_building_index += 1;
                     new_workplace
@@ -1108,35 +1573,158 @@ impl SimulatorBuilder {
         Ok(workplace_buildings)
     }
 
+    /// Returns how many other Citizens the Citizen at `(area_index, local_index)` is in contact with,
+    /// approximated as the combined occupancy of their household and workplace/school buildings -
+    /// this crate has no explicit contact graph, so building co-occupancy stands in for it
+    fn contact_degree(&self, area_index: usize, local_index: usize) -> f64 {
+        let citizen = &self.output_areas[area_index].citizens[local_index];
+        let household_occupants = self.building_occupant_count(&citizen.household_code);
+        let workplace_occupants = self.building_occupant_count(&citizen.workplace_code);
+        (household_occupants + workplace_occupants) as f64
+    }
+
+    /// Returns how many Citizens occupy the given building, or `0` if it can't be found
+    fn building_occupant_count(&self, building_id: &BuildingID) -> usize {
+        self.output_areas
+            .get(building_id.output_area_code().index())
+            .and_then(|area| area.buildings.get(building_id.building_index()))
+            .map(|building| building.occupants().len())
+            .unwrap_or(0)
+    }
+
+    /// Infects the number of distinct Citizens resolved by `disease_model.seeding_strategy`, to seed
+    /// the epidemic - chosen uniformly at random across all Output Areas, unless the strategy is
+    /// `SeedingStrategy::WeightedByContactDegree`, in which case seeds are weighted towards
+    /// higher-contact Citizens (see `contact_degree`)
+    ///
+    /// If the resolved count exceeds the total population, it is clamped to the population size
     pub fn apply_initial_infections(&mut self, rng: &mut dyn RngCore) -> anyhow::Result<()> {
-        for _ in 0..STARTING_INFECTED_COUNT {
-            let output_area: &mut OutputArea = match self.output_areas.iter_mut().choose(rng) {
-                Some(area) => area,
-                None => {
-                    let error = DataLoadingError::ValueParsingError {
-                        source: ParseErrorType::IsEmpty {
-                            message: "No Output Areas exist infor seeding the disease".to_string(),
-                        },
-                    };
-                    error!("{:?}", error);
-                    continue;
-                }
+        let population_size: usize =
+            self.output_areas.iter().map(|area| area.citizens.len()).sum();
+        if self.output_areas.is_empty() || population_size == 0 {
+            let error = DataLoadingError::ValueParsingError {
+                source: ParseErrorType::IsEmpty {
+                    message: "No citizens exist in the output areas for seeding the disease"
+                        .to_string(),
+                },
             };
-            let citizen: &mut Citizen = match output_area.citizens.iter_mut().choose(rng) {
-                Some(citizen) => citizen,
-                None => {
-                    let error = DataLoadingError::ValueParsingError {
-                        source: ParseErrorType::IsEmpty {
-                            message:
-                            "No citizens exist in the output areas for seeding the disease"
-                                .to_string(),
-                        },
-                    };
-                    error!("{:?}", error);
-                    continue;
-                }
+            error!("{:?}", error);
+            return Ok(());
+        }
+        let requested_infected_count = self.disease_model.seeding_strategy.resolve(population_size);
+        let initial_infected_count = if population_size < requested_infected_count as usize {
+            error!(
+                "Requested {} initial infections, but the population is only {} - clamping to the population size",
+                requested_infected_count, population_size
+            );
+            population_size as u32
+        } else {
+            requested_infected_count
+        };
+        // Picked as (Output Area index, local Citizen index) pairs, so that no Citizen can be chosen twice
+        let mut citizen_locations: Vec<(usize, usize)> = self
+            .output_areas
+            .iter()
+            .enumerate()
+            .flat_map(|(area_index, area)| {
+                (0..area.citizens.len()).map(move |local_index| (area_index, local_index))
+            })
+            .collect();
+        let selected_locations: Vec<(usize, usize)> =
+            if matches!(self.disease_model.seeding_strategy, SeedingStrategy::WeightedByContactDegree(_)) {
+                citizen_locations
+                    .choose_multiple_weighted(rng, initial_infected_count as usize, |&(area_index, local_index)| {
+                        // A `+ 1.0` baseline weight, so isolated Citizens still have some (small)
+                        // chance of being seeded, rather than being entirely excluded
+                        self.contact_degree(area_index, local_index) + 1.0
+                    })
+                    .context("Failed to select Citizens weighted by contact degree")?
+                    .copied()
+                    .collect()
+            } else {
+                citizen_locations.shuffle(rng);
+                citizen_locations.into_iter().take(initial_infected_count as usize).collect()
             };
-            citizen.disease_status = DiseaseStatus::Infected(0);
+        for (area_index, local_index) in selected_locations {
+            let duration = sample_infectious_period(
+                self.disease_model.infected_time,
+                self.disease_model.infectious_period_dispersion,
+                rng,
+            );
+            let area = &mut self.output_areas[area_index];
+            let citizen = &mut area.citizens[local_index];
+            if citizen.is_susceptible() {
+                area.susceptible_citizen_count = area.susceptible_citizen_count.saturating_sub(1);
+            }
+            citizen.disease_status = DiseaseStatus::Infected { elapsed: 0, duration };
+        }
+        Ok(())
+    }
+
+    /// Returns a mutable reference to the building with the given ID, for constructing targeted scenarios
+    /// (e.g. seeding a meat-packing plant outbreak) before the simulation starts
+    pub fn building_mut(&mut self, id: &BuildingID) -> Option<&mut dyn Building> {
+        let output_area = self.output_areas.get_mut(id.output_area_code().index())?;
+        let building = output_area.buildings.get_mut(id.building_index())?;
+        Some(building.as_mut())
+    }
+
+    /// Moves a Citizen to a new workplace, keeping `Citizen::workplace_code` and the old/new
+    /// Workplace's occupant lists consistent
+    ///
+    /// Fails without making any change if the Citizen or new workplace do not exist, the new
+    /// workplace is at capacity, or the Citizen's current workplace is a `School` (a School's
+    /// occupants are fixed at creation - see `Building::remove_citizen`'s `School` impl)
+    pub fn move_citizen_to_workplace(
+        &mut self,
+        citizen_id: CitizenID,
+        new_workplace: BuildingID,
+    ) -> anyhow::Result<()> {
+        let (area_id, local_index) = self
+            .citizen_output_area_lookup
+            .get(citizen_id.global_index())
+            .context(format!(
+                "Citizen {} does not exist in the Output Area lookup",
+                citizen_id
+            ))?
+            .clone();
+        // Resolve the current state read-only first, so a later failure can't leave the Citizen
+        // half-moved
+        let old_workplace = self
+            .output_areas
+            .get(area_id.index())
+            .context(format!("Output Area {} does not exist", area_id))?
+            .citizens
+            .get(local_index as usize)
+            .context(format!("Citizen {} does not exist", citizen_id))?
+            .workplace_code
+            .clone();
+        if *old_workplace.building_type() == BuildingType::School {
+            return Err(anyhow::anyhow!(
+                "Citizen {} cannot be moved out of School {}, as a School's occupants are fixed at creation",
+                citizen_id,
+                old_workplace
+            ));
+        }
+
+        // Guard against capacity violations before mutating anything
+        self.building_mut(&new_workplace)
+            .context(format!("Workplace {} does not exist", new_workplace))?
+            .add_citizen(citizen_id)?;
+        let output_area = self
+            .output_areas
+            .get_mut(area_id.index())
+            .context(format!("Output Area {} does not exist", area_id))?;
+        let citizen = output_area
+            .citizens
+            .get_mut(local_index as usize)
+            .context(format!("Citizen {} does not exist", citizen_id))?;
+        citizen.set_workplace_code(new_workplace);
+        if let Some(old_building) = output_area
+            .buildings
+            .get_mut(old_workplace.building_index())
+        {
+            old_building.remove_citizen(citizen_id)?;
         }
         Ok(())
     }
@@ -1156,9 +1744,176 @@ impl SimulatorBuilder {
             output_areas_polygons,
             disease_model: DiseaseModel::covid(),
             citizen_output_area_lookup: Default::default(),
+            global_seed: 0,
+            workplace_building_overcapacity: DEFAULT_WORKPLACE_BUILDING_OVERCAPACITY,
+            start_day_of_week: DayOfWeek::default(),
+            average_class_size: DEFAULT_AVERAGE_CLASS_SIZE,
+            average_office_size: DEFAULT_AVERAGE_OFFICE_SIZE,
+            workplace_room_size: DEFAULT_WORKPLACE_ROOM_SIZE,
+            prune_isolated_citizens: DEFAULT_PRUNE_ISOLATED_CITIZENS,
+            population_scale: DEFAULT_POPULATION_SCALE,
+            max_workplace_search_attempts: DEFAULT_MAX_WORKPLACE_SEARCH_ATTEMPTS,
+            workplace_search_attempt_histogram: Default::default(),
+            min_student_age: DEFAULT_MIN_STUDENT_AGE,
+            statistics_sampling_interval: DEFAULT_STATISTICS_SAMPLING_INTERVAL,
+            workplace_assignment_strategy: WorkplaceAssignmentStrategy::default(),
+            building_area_assignment_policy: BuildingAreaAssignmentPolicy::default(),
+            work_from_home_percentage: None,
+            building_assignment_cache_dir: None,
+            building_assignment_cache_input_file_paths: Vec::new(),
         })
     }
+    /// The percentage of Citizens working from home, populated by the most recent `build` call -
+    /// `None` until `build` has run
+    pub fn work_from_home_percentage(&self) -> Option<f64> {
+        self.work_from_home_percentage
+    }
+    /// Makes `build` load/save the building-to-output-area assignment from `cache_dir`, keyed on a
+    /// hash of `input_file_paths`, rather than always recomputing it - see
+    /// `assign_buildings_to_output_areas_cached`. `input_file_paths` should be the OSM `.osm.pbf`
+    /// file and the Output Area shapefile actually used to build this `SimulatorBuilder`
+    pub fn set_building_assignment_cache(&mut self, cache_dir: String, input_file_paths: Vec<String>) {
+        self.building_assignment_cache_dir = Some(cache_dir);
+        self.building_assignment_cache_input_file_paths = input_file_paths;
+    }
+    /// Sets the seed used to derive deterministic per Output Area Rng's
+    ///
+    /// With a fixed seed, a build is reproducible regardless of the number of threads rayon uses
+    pub fn set_seed(&mut self, seed: u64) {
+        self.global_seed = seed;
+    }
+    /// Sets how much to inflate the assumed size of buildings when distributing them between
+    /// occupations - see `workplace_building_overcapacity` for the trade-off this controls
+    pub fn set_workplace_building_overcapacity(&mut self, overcapacity: f64) {
+        self.workplace_building_overcapacity = overcapacity;
+    }
+    /// Sets the Day of the Week that `time_step` 0 should fall on
+    pub fn set_start_day_of_week(&mut self, start_day_of_week: DayOfWeek) {
+        self.start_day_of_week = start_day_of_week;
+    }
+    /// Sets how many time steps apart recorded statistics entries should be kept - see
+    /// `StatisticsRecorder::set_sampling_interval`
+    pub fn set_statistics_sampling_interval(&mut self, statistics_sampling_interval: u32) {
+        self.statistics_sampling_interval = statistics_sampling_interval;
+    }
+    /// Sets the average number of students per class, used when sizing classes and the number of
+    /// teachers a school requires
+    pub fn set_average_class_size(&mut self, average_class_size: f64) {
+        self.average_class_size = average_class_size;
+    }
+    /// Sets the average number of teachers sharing an office, for leftover staff not assigned a
+    /// class of their own
+    pub fn set_average_office_size(&mut self, average_office_size: usize) {
+        self.average_office_size = average_office_size;
+    }
+    /// Sets the maximum number of occupants sharing a room within a Workplace, before further
+    /// occupants are split into another room - see `workplace_room_size`
+    pub fn set_workplace_room_size(&mut self, workplace_room_size: u32) {
+        self.workplace_room_size = workplace_room_size;
+    }
+    /// Sets whether `build` should prune fully-isolated Citizens out of the per-step contact network
+    pub fn set_prune_isolated_citizens(&mut self, prune_isolated_citizens: bool) {
+        self.prune_isolated_citizens = prune_isolated_citizens;
+    }
+    /// Sets the factor each Output Area's generated population is scaled down by, e.g. `0.1` for a
+    /// 10%-sized region
+    pub fn set_population_scale(&mut self, population_scale: f64) {
+        self.population_scale = population_scale;
+    }
+    /// Sets how many times `build_workplaces` will re-sample a Citizen's workplace Output Area
+    /// before giving up and leaving them without a workplace
+    pub fn set_max_workplace_search_attempts(&mut self, max_workplace_search_attempts: u32) {
+        self.max_workplace_search_attempts = max_workplace_search_attempts;
+    }
+    /// Sets the youngest age `build_schools` will assign a Citizen to a school - younger Citizens
+    /// are left at their household instead
+    pub fn set_min_student_age(&mut self, min_student_age: u16) {
+        self.min_student_age = min_student_age;
+    }
+    /// Sets how a Citizen's workplace Output Area is chosen in `build_workplaces` - see
+    /// `WorkplaceAssignmentStrategy`
+    pub fn set_workplace_assignment_strategy(&mut self, workplace_assignment_strategy: WorkplaceAssignmentStrategy) {
+        self.workplace_assignment_strategy = workplace_assignment_strategy;
+    }
+    /// Sets how `get_area_code_for_raw_building` resolves a building that matches more than one
+    /// Output Area - see `BuildingAreaAssignmentPolicy`
+    pub fn set_building_area_assignment_policy(
+        &mut self,
+        building_area_assignment_policy: BuildingAreaAssignmentPolicy,
+    ) {
+        self.building_area_assignment_policy = building_area_assignment_policy;
+    }
+
+    /// Estimates how much memory the Citizens, Buildings and lookups built so far occupy, in
+    /// bytes, so a region's footprint can be judged before committing to running it
+    ///
+    /// This is necessarily approximate: `Building` is stored as a `Box<dyn Building + Sync +
+    /// Send>` of unknown concrete size, so its fixed cost is approximated by
+    /// `AVERAGE_BUILDING_BYTES` rather than measured per concrete type, and the Output Area
+    /// polygons are counted by their exterior ring's point count, ignoring any interior rings
+    pub fn estimated_memory_bytes(&self) -> usize {
+        let citizen_count: usize = self
+            .output_areas
+            .iter()
+            .map(|area| area.citizens.len())
+            .sum();
+        let citizens_bytes = citizen_count * std::mem::size_of::<Citizen>();
 
+        let building_count: usize = self
+            .output_areas
+            .iter()
+            .map(|area| area.buildings.len())
+            .sum();
+        let occupant_entries: usize = self
+            .output_areas
+            .iter()
+            .flat_map(|area| area.buildings.iter())
+            .map(|building| building.occupants().len())
+            .sum();
+        let buildings_bytes = building_count * AVERAGE_BUILDING_BYTES
+            + occupant_entries * std::mem::size_of::<CitizenID>();
+
+        let output_area_lookup_bytes = self.output_area_lookup.len()
+            * (std::mem::size_of::<String>() + std::mem::size_of::<u32>());
+        let citizen_output_area_lookup_bytes =
+            self.citizen_output_area_lookup.len() * std::mem::size_of::<(OutputAreaID, u32)>();
+
+        let polygon_point_count: usize = self
+            .output_areas_polygons
+            .polygons
+            .values()
+            .map(|polygon| polygon.exterior().0.len())
+            .sum();
+        let polygons_bytes = self.output_areas_polygons.polygons.len()
+            * std::mem::size_of::<String>()
+            + polygon_point_count * std::mem::size_of::<Coordinate<i32>>();
+
+        citizens_bytes
+            + buildings_bytes
+            + output_area_lookup_bytes
+            + citizen_output_area_lookup_bytes
+            + polygons_bytes
+    }
+
+    /// Counts Citizens whose workplace code is identical to their household code, i.e. working
+    /// from home - see `work_from_home_percentage`
+    fn count_citizens_working_from_home(&self) -> u32 {
+        self.output_areas
+            .par_iter()
+            .map(|area| {
+                area.citizens
+                    .par_iter()
+                    .map(|citizen| {
+                        if citizen.household_code.eq(&citizen.workplace_code) {
+                            1
+                        } else {
+                            0
+                        }
+                    })
+                    .sum::<u32>()
+            })
+            .sum()
+    }
     pub fn build(&mut self) -> anyhow::Result<()> {
         let mut timer = Timer::default();
         let mut rng = thread_rng();
@@ -1169,9 +1924,15 @@ impl SimulatorBuilder {
             "Initialised {} Output Areas",
             self.output_areas.len()
         ))?;
-        let mut possible_buildings_per_area = self
-            .assign_buildings_to_output_areas()
-            .context("Failed to assign buildings to output areas")?;
+        let mut possible_buildings_per_area = if let Some(cache_dir) = self.building_assignment_cache_dir.clone() {
+            let input_file_paths = self.building_assignment_cache_input_file_paths.clone();
+            let input_file_paths: Vec<&str> = input_file_paths.iter().map(String::as_str).collect();
+            self.assign_buildings_to_output_areas_cached(&cache_dir, &input_file_paths, false)
+                .context("Failed to assign buildings to output areas")?
+        } else {
+            self.assign_buildings_to_output_areas()
+                .context("Failed to assign buildings to output areas")?
+        };
         timer.code_block_finished_with_print("Assigned Possible Buildings to Output Areas".to_string())?;
         self.generate_citizens(&mut rng, &mut possible_buildings_per_area)
             .context("Failed to generate Citizens")?;
@@ -1181,6 +1942,16 @@ impl SimulatorBuilder {
             self.output_areas.len()
         ))?;
 
+        if let Some(curve) = &self.disease_model.age_mortality_curve {
+            let max_population_age = self
+                .output_areas
+                .par_iter()
+                .filter_map(|area| area.citizens.iter().map(|citizen| citizen.age).max())
+                .max()
+                .unwrap_or(0);
+            curve.validate_covers_population(max_population_age);
+        }
+
         self.build_schools().context("Failed to build schools")?;
 
         // Check all Citizens with a workplace are actually meant to be in a school
@@ -1249,35 +2020,52 @@ impl SimulatorBuilder {
             .context("Failed to build workplaces")?;
         timer.code_block_finished_with_print("Generated workplaces for {} Output Areas".to_string())?;
 
-        let work_from_home_count: u32 = self
-            .output_areas
-            .par_iter()
-            .map(|area| {
-                area.citizens
-                    .par_iter()
-                    .map(|citizen| {
-                        if citizen.household_code.eq(&citizen.workplace_code) {
-                            1
-                        } else {
-                            0
-                        }
-                    })
-                    .sum::<u32>()
-            })
-            .sum();
+        self.build_shops().context("Failed to build shops")?;
+        timer.code_block_finished_with_print("Built shops for non-working Citizens".to_string())?;
+
+        let work_from_home_count = self.count_citizens_working_from_home();
+        let work_from_home_percentage =
+            (work_from_home_count as f64 / self.citizen_output_area_lookup.len() as f64) * 100.0;
+        self.work_from_home_percentage = Some(work_from_home_percentage);
         debug!(
             "{} out of {} Citizens {:.1}%, are working from home.",
             work_from_home_count.to_formatted_string(&NUMBER_FORMATTING),
             self.citizen_output_area_lookup
                 .len()
                 .to_formatted_string(&NUMBER_FORMATTING),
-            (work_from_home_count as f64 / self.citizen_output_area_lookup.len() as f64) * 100.0
+            work_from_home_percentage
         );
         // Infect random citizens
         self.apply_initial_infections(&mut rng)
             .context("Failed to create initial infections")?;
 
         timer.code_block_finished_with_print("Applied initial infections".to_string())?;
+
+        if self.prune_isolated_citizens {
+            let mut pruned = 0;
+            for area in self.output_areas.iter_mut() {
+                pruned += area.prune_isolated_citizens();
+                // Pruning shifts the remaining Citizens down to fill the gaps left in `citizens`,
+                // so their recorded local index needs to be refreshed to match
+                for (local_index, citizen) in area.citizens.iter().enumerate() {
+                    self.citizen_output_area_lookup[citizen.id().global_index()] =
+                        (area.id(), local_index as u32);
+                }
+            }
+            info!(
+                "Pruned {} fully-isolated Citizens from the contact network",
+                pruned.to_formatted_string(&NUMBER_FORMATTING)
+            );
+        }
+        let estimated_memory_bytes = self.estimated_memory_bytes();
+        match get_available_system_memory_bytes() {
+            Ok(available_bytes) => info!(
+                "Estimated memory footprint: {:.2} GB, out of {:.2} GB available on this machine",
+                estimated_memory_bytes as f64 / 1024.0 / 1024.0 / 1024.0,
+                available_bytes as f64 / 1024.0 / 1024.0 / 1024.0
+            ),
+            Err(e) => warn!("Failed to determine available system memory: {}", e),
+        }
         debug!(
             "Starting Statistics: There are {} total Citizens, {} Output Areas",
             self.citizen_output_area_lookup
@@ -1292,22 +2080,22 @@ impl SimulatorBuilder {
     }
 }
 
-/// Returns a list of Output Areas that the given building is inside
+/// Returns the single Output Area the given building is inside
 ///
-/// If the building is in multiple Areas, it is duplicated
+/// If the building's boundary bounding-box matches more than one Output Area (e.g. one straddling
+/// a border), `policy` decides which Area it's actually assigned to - see
+/// `BuildingAreaAssignmentPolicy`
 fn get_area_code_for_raw_building(
     building: &RawBuilding,
     output_area_lookup: &PolygonContainer<String>,
     building_boundaries: &HashMap<BuildingBoundaryID, geo_types::Polygon<i32>>,
-) -> Option<HashMap<String, Vec<RawBuilding>>> {
+    policy: BuildingAreaAssignmentPolicy,
+) -> Option<(String, RawBuilding)> {
     let boundary = building_boundaries.get(&building.boundary_id());
     if let Some(boundary) = boundary {
         if let Ok(areas) = output_area_lookup.find_polygons_containing_polygon(boundary) {
-            let area_locations = areas
-                .map(|area| area.to_string())
-                .zip(std::iter::repeat(vec![*building]))
-                .collect::<HashMap<String, Vec<RawBuilding>>>();
-            return Some(area_locations);
+            let area_code = resolve_building_area(areas, boundary, output_area_lookup, policy)?;
+            return Some((area_code, *building));
         }
     } else {
         warn!(
@@ -1318,23 +2106,104 @@ fn get_area_code_for_raw_building(
     None
 }
 
+/// Resolves `candidate_areas` (Output Area codes whose bounding box `building_boundary` matches)
+/// down to the single Area the building is assigned to, according to `policy`
+fn resolve_building_area<'a>(
+    candidate_areas: impl Iterator<Item = &'a String>,
+    building_boundary: &geo_types::Polygon<i32>,
+    output_area_lookup: &PolygonContainer<String>,
+    policy: BuildingAreaAssignmentPolicy,
+) -> Option<String> {
+    let candidate_areas: Vec<String> = candidate_areas.cloned().collect();
+    if candidate_areas.len() <= 1 {
+        return candidate_areas.into_iter().next();
+    }
+    if let BuildingAreaAssignmentPolicy::Centroid = policy {
+        let building_centroid = convert_polygon_to_float::<i32, f64>(building_boundary).centroid();
+        if let Some(building_centroid) = building_centroid {
+            let building_centroid = geo_types::Point::new(
+                building_centroid.x() as i32,
+                building_centroid.y() as i32,
+            );
+            let containing_area = candidate_areas.iter().find(|area_code| {
+                output_area_lookup
+                    .polygons
+                    .get(*area_code)
+                    .map(|polygon| polygon.contains(&building_centroid))
+                    .unwrap_or(false)
+            });
+            if let Some(containing_area) = containing_area {
+                return Some(containing_area.clone());
+            }
+        }
+    }
+    // Either the policy is `LargestOverlap`, or `Centroid` found no Area actually containing the
+    // centroid (e.g. it falls in a gap between Areas) - fall back to the Area whose bounding box
+    // overlaps the building's bounding box the most
+    candidate_areas
+        .into_iter()
+        .max_by(|a, b| {
+            let overlap_a = largest_overlap_area(a, building_boundary, output_area_lookup);
+            let overlap_b = largest_overlap_area(b, building_boundary, output_area_lookup);
+            overlap_a
+                .partial_cmp(&overlap_b)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+}
+
+/// The area, in the same units as the Output Area polygons, that `area_code`'s bounding box
+/// overlaps `building_boundary`'s bounding box by, or `0.0` if either polygon is missing
+fn largest_overlap_area(
+    area_code: &str,
+    building_boundary: &geo_types::Polygon<i32>,
+    output_area_lookup: &PolygonContainer<String>,
+) -> f64 {
+    let area_polygon = match output_area_lookup.polygons.get(area_code) {
+        Some(polygon) => polygon,
+        None => return 0.0,
+    };
+    bounding_box_overlap_area(area_polygon, building_boundary)
+}
+
+/// Approximates overlap area via each polygon's bounding box, rather than true polygon-clip
+/// intersection area, consistent with this module's other bounding-box-based matching
+fn bounding_box_overlap_area(
+    a: &geo_types::Polygon<i32>,
+    b: &geo_types::Polygon<i32>,
+) -> f64 {
+    let (Some(a_rect), Some(b_rect)) = (a.bounding_rect(), b.bounding_rect()) else {
+        return 0.0;
+    };
+    let overlap_width = a_rect.max().x.min(b_rect.max().x) - a_rect.min().x.max(b_rect.min().x);
+    let overlap_height = a_rect.max().y.min(b_rect.max().y) - a_rect.min().y.max(b_rect.min().y);
+    if overlap_width <= 0 || overlap_height <= 0 {
+        0.0
+    } else {
+        overlap_width as f64 * overlap_height as f64
+    }
+}
+
 /// On csgpu2 with 20? threads took 11 seconds as oppose to 57 seconds for single threaded version
 pub fn parallel_assign_buildings_to_output_areas(
     building_boundaries: &HashMap<BuildingBoundaryID, geo_types::Polygon<i32>>,
     building_locations: &HashMap<TagClassifiedBuilding, Vec<RawBuilding>>,
     output_area_lookup: &PolygonContainer<String>,
+    policy: BuildingAreaAssignmentPolicy,
 ) -> HashMap<String, HashMap<TagClassifiedBuilding, Vec<RawBuilding>>> {
     building_locations.into_par_iter().filter_map(|(building_type, possible_building_locations)|
         {
             if TagClassifiedBuilding::School == *building_type {
                 return None;
             }
-            // Try find Area Codes for the given building
+            // Try find the Area Code for each given building
             let area_codes = possible_building_locations.into_par_iter().filter_map(|building| {
-                get_area_code_for_raw_building(building, output_area_lookup, building_boundaries)
+                get_area_code_for_raw_building(building, output_area_lookup, building_boundaries, policy)
             });
             // Group By Area Code
-            let area_codes = area_codes.reduce(HashMap::new, |mut a, b| {
+            let area_codes = area_codes.fold(HashMap::new, |mut a: HashMap<String, Vec<RawBuilding>>, (area, building)| {
+                a.entry(area).or_default().push(building);
+                a
+            }).reduce(HashMap::new, |mut a, b| {
                 for (area, area_buildings) in b {
                     let area_entry = a.entry(area).or_default();
                     area_entry.extend(area_buildings)
@@ -1364,3 +2233,1298 @@ pub fn parallel_assign_buildings_to_output_areas(
             a
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rand::thread_rng;
+
+    use load_census_data::CensusData;
+    use load_census_data::tables::age_structure::AgePopulationRecord;
+    use load_census_data::tables::employment_densities::EmploymentDensities;
+    use load_census_data::tables::occupation_count::{OccupationCountRecord, RawOccupationType};
+    use load_census_data::tables::population_and_density_per_output_area::PopulationRecord;
+    use load_census_data::tables::resides_vs_workplace::WorkplaceResidentialRecord;
+    use osm_data::{BuildingBoundaryID, OSMRawBuildings, RawBuilding, TagClassifiedBuilding};
+    use osm_data::polygon_lookup::PolygonContainer;
+    use osm_data::voronoi_generator::Scaling;
+
+    use crate::disease::{DiseaseStatus, SeedingStrategy};
+    use crate::models::building::{Building, BuildingID, BuildingType, Household, Workplace};
+    use crate::models::citizen::{Citizen, CitizenID, Occupation, OccupationType};
+    use crate::models::output_area::{OutputArea, OutputAreaID};
+    use crate::simulator_builder::{
+        BuildingAreaAssignmentPolicy, SimulatorBuilder, WorkplaceAssignmentStrategy,
+    };
+
+    /// Builds a `SimulatorBuilder` with no Census/OSM data loaded, suitable for tests that only
+    /// exercise logic operating on `output_areas` and `disease_model`
+    fn empty_simulator_builder() -> SimulatorBuilder {
+        let census_data = CensusData {
+            valid_areas: Default::default(),
+            age_counts: Default::default(),
+            population_counts: Default::default(),
+            occupation_counts: Default::default(),
+            workplace_density: EmploymentDensities {},
+            residents_workplace: Default::default(),
+            imputed_areas: Default::default(),
+        };
+        let osm_data = OSMRawBuildings::from_building_locations(HashMap::new(), HashMap::new(), 100);
+        let output_areas_polygons =
+            PolygonContainer::new(HashMap::new(), Scaling::yorkshire_national_grid(100), 100)
+                .expect("Failed to build an empty polygon container");
+        SimulatorBuilder::new(
+            "test".to_string(),
+            census_data,
+            osm_data,
+            output_areas_polygons,
+        )
+            .expect("Failed to build an empty SimulatorBuilder")
+    }
+
+    #[test]
+    fn initial_infections_matches_configured_count_for_larger_population() {
+        let mut builder = empty_simulator_builder();
+        builder.disease_model.seeding_strategy = SeedingStrategy::Count(5);
+
+        let mut rng = thread_rng();
+        let output_area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(
+            output_area_id.clone(),
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        let household_id = BuildingID::new(output_area_id, BuildingType::Household, 0);
+        for index in 0..20 {
+            area.citizens.push(Citizen::new(
+                CitizenID::from_indexes(index),
+                household_id.clone(),
+                household_id.clone(),
+                30,
+                Occupation::Student,
+                false,
+                false,
+                false,
+                24,
+            ));
+        }
+        builder.output_areas.push(area);
+
+        builder
+            .apply_initial_infections(&mut rng)
+            .expect("Failed to apply initial infections");
+
+        let infected_count = builder.output_areas[0]
+            .citizens
+            .iter()
+            .filter(|citizen| matches!(citizen.disease_status, DiseaseStatus::Infected { .. }))
+            .count();
+        assert_eq!(infected_count, 5);
+    }
+
+    /// Moving a Citizen to a new Workplace should update both `Citizen::workplace_code` and the
+    /// old/new Workplace's occupant lists
+    #[test]
+    fn move_citizen_to_workplace_updates_citizen_and_both_buildings() {
+        let mut builder = empty_simulator_builder();
+        let output_area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(
+            output_area_id.clone(),
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        let household_id = BuildingID::new(output_area_id.clone(), BuildingType::Household, 0);
+        let old_workplace_id = BuildingID::new(output_area_id.clone(), BuildingType::Workplace, 0);
+        let new_workplace_id = BuildingID::new(output_area_id, BuildingType::Workplace, 1);
+
+        let building_polygon = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(0, 0), (100, 0), (100, 100), (0, 100), (0, 0)]),
+            vec![],
+        );
+        let raw_building = RawBuilding::new(
+            TagClassifiedBuilding::WorkPlace,
+            &building_polygon,
+            BuildingBoundaryID::default(),
+        )
+            .expect("Failed to build test RawBuilding");
+
+        let citizen_id = CitizenID::from_indexes(0);
+        let mut old_workplace = Workplace::new(
+            old_workplace_id.clone(),
+            raw_building,
+            OccupationType::Sales,
+            10,
+        );
+        old_workplace
+            .add_citizen(citizen_id)
+            .expect("Failed to add occupant to old workplace");
+        let new_workplace = Workplace::new(new_workplace_id.clone(), raw_building, OccupationType::Sales, 10);
+
+        area.citizens.push(Citizen::new(
+            citizen_id,
+            household_id.clone(),
+            old_workplace_id,
+            30,
+            Occupation::Normal { occupation: OccupationType::Sales },
+            false,
+            false,
+            false,
+            24,
+        ));
+        area.buildings.push(Box::new(old_workplace));
+        area.buildings.push(Box::new(new_workplace));
+        builder.output_areas.push(area);
+        builder.citizen_output_area_lookup = vec![(
+            OutputAreaID::from_code_and_index("test".to_string(), 0),
+            0,
+        )];
+
+        builder
+            .move_citizen_to_workplace(citizen_id, new_workplace_id.clone())
+            .expect("Failed to move citizen to new workplace");
+
+        assert_eq!(
+            builder.output_areas[0].citizens[0].workplace_code,
+            new_workplace_id
+        );
+        assert!(!builder.output_areas[0].buildings[0]
+            .occupants()
+            .contains(&citizen_id));
+        assert!(builder.output_areas[0].buildings[1]
+            .occupants()
+            .contains(&citizen_id));
+    }
+
+    /// A Student's workplace code points at a `School`, whose occupants are fixed at creation -
+    /// moving them should return an error rather than panicking inside `School::remove_citizen`
+    #[test]
+    fn move_citizen_to_workplace_errors_without_panicking_for_a_school_citizen() {
+        let mut builder = empty_simulator_builder();
+        let output_area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(
+            output_area_id.clone(),
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        let household_id = BuildingID::new(output_area_id.clone(), BuildingType::Household, 0);
+        let school_id = BuildingID::new(output_area_id.clone(), BuildingType::School, 1);
+        let new_workplace_id = BuildingID::new(output_area_id, BuildingType::Workplace, 0);
+
+        let building_polygon = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(0, 0), (100, 0), (100, 100), (0, 100), (0, 0)]),
+            vec![],
+        );
+        let raw_building = RawBuilding::new(
+            TagClassifiedBuilding::WorkPlace,
+            &building_polygon,
+            BuildingBoundaryID::default(),
+        )
+            .expect("Failed to build test RawBuilding");
+        let new_workplace = Workplace::new(new_workplace_id.clone(), raw_building, OccupationType::Sales, 10);
+
+        let citizen_id = CitizenID::from_indexes(0);
+        area.citizens.push(Citizen::new(
+            citizen_id,
+            household_id,
+            school_id,
+            10,
+            Occupation::Student,
+            false,
+            false,
+            false,
+            24,
+        ));
+        area.buildings.push(Box::new(new_workplace));
+        builder.output_areas.push(area);
+        builder.citizen_output_area_lookup = vec![(
+            OutputAreaID::from_code_and_index("test".to_string(), 0),
+            0,
+        )];
+
+        let result = builder.move_citizen_to_workplace(citizen_id, new_workplace_id);
+        assert!(
+            result.is_err(),
+            "Moving a Student out of a School should fail, rather than panicking"
+        );
+    }
+
+    /// Moving a Citizen that doesn't exist in `citizen_output_area_lookup` should return an error
+    #[test]
+    fn move_citizen_to_workplace_errors_for_a_citizen_that_does_not_exist() {
+        let mut builder = empty_simulator_builder();
+        let new_workplace_id = BuildingID::new(
+            OutputAreaID::from_code_and_index("test".to_string(), 0),
+            BuildingType::Workplace,
+            0,
+        );
+
+        let result =
+            builder.move_citizen_to_workplace(CitizenID::from_indexes(0), new_workplace_id);
+        assert!(
+            result.is_err(),
+            "Moving a Citizen that doesn't exist should fail, rather than panicking"
+        );
+    }
+
+    /// `work_from_home_percentage` should match a manual count of Citizens whose household and
+    /// workplace codes are equal, out of every Citizen recorded in `citizen_output_area_lookup`
+    #[test]
+    fn work_from_home_percentage_matches_a_manual_count() {
+        let mut builder = empty_simulator_builder();
+        let output_area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(
+            output_area_id.clone(),
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        let household_id = BuildingID::new(output_area_id, BuildingType::Household, 0);
+        let workplace_id = BuildingID::new(
+            OutputAreaID::from_code_and_index("test".to_string(), 0),
+            BuildingType::Workplace,
+            1,
+        );
+        // Three Citizens working from home (workplace code equals household code), two commuting
+        // to a separate Workplace
+        for index in 0..5 {
+            let workplace_code = if index < 3 { household_id.clone() } else { workplace_id.clone() };
+            area.citizens.push(Citizen::new(
+                CitizenID::from_indexes(index),
+                household_id.clone(),
+                workplace_code,
+                30,
+                Occupation::Normal { occupation: OccupationType::Sales },
+                false,
+                false,
+                false,
+                24,
+            ));
+        }
+        builder.output_areas.push(area);
+        builder.citizen_output_area_lookup = (0..5)
+            .map(|index| (OutputAreaID::from_code_and_index("test".to_string(), 0), index))
+            .collect();
+
+        let manual_count = builder.output_areas[0]
+            .citizens
+            .iter()
+            .filter(|citizen| citizen.household_code == citizen.workplace_code)
+            .count();
+        assert_eq!(
+            builder.count_citizens_working_from_home() as usize,
+            manual_count
+        );
+        assert_eq!(
+            (builder.count_citizens_working_from_home() as f64
+                / builder.citizen_output_area_lookup.len() as f64)
+                * 100.0,
+            60.0
+        );
+    }
+
+    /// A `SeedingStrategy::Fraction` should scale the initial infected count with the population,
+    /// rather than seeding a fixed number of Citizens
+    #[test]
+    fn fractional_seeding_strategy_scales_with_population() {
+        let mut builder = empty_simulator_builder();
+        builder.disease_model.seeding_strategy = SeedingStrategy::Fraction(0.01);
+
+        let mut rng = thread_rng();
+        let output_area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(
+            output_area_id.clone(),
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        let household_id = BuildingID::new(output_area_id, BuildingType::Household, 0);
+        for index in 0..1000 {
+            area.citizens.push(Citizen::new(
+                CitizenID::from_indexes(index),
+                household_id.clone(),
+                household_id.clone(),
+                30,
+                Occupation::Student,
+                false,
+                false,
+                false,
+                24,
+            ));
+        }
+        builder.output_areas.push(area);
+
+        builder
+            .apply_initial_infections(&mut rng)
+            .expect("Failed to apply initial infections");
+
+        let infected_count = builder.output_areas[0]
+            .citizens
+            .iter()
+            .filter(|citizen| matches!(citizen.disease_status, DiseaseStatus::Infected { .. }))
+            .count();
+        assert_eq!(infected_count, 10);
+    }
+
+    /// With `SeedingStrategy::WeightedByContactDegree`, seeds should land disproportionately on the
+    /// single high-occupancy "hub" household rather than the many single-occupant households,
+    /// pulling the mean contact degree of the seed cases above the population mean
+    #[test]
+    fn weighted_seeding_strategy_favours_high_contact_degree_citizens() {
+        let mut builder = empty_simulator_builder();
+        builder.disease_model.seeding_strategy = SeedingStrategy::WeightedByContactDegree(5);
+
+        let output_area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(
+            output_area_id.clone(),
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+
+        let mut next_building_index = 0;
+        let mut next_citizen_index = 0;
+        let mut spawn_household = |area: &mut OutputArea, occupant_count: u32| {
+            let building_id = BuildingID::new(
+                output_area_id.clone(),
+                BuildingType::Household,
+                next_building_index,
+            );
+            next_building_index += 1;
+            let mut household = Household::new(building_id.clone(), geo_types::Point::new(0, 0));
+            for _ in 0..occupant_count {
+                let citizen_id = CitizenID::from_indexes(next_citizen_index);
+                next_citizen_index += 1;
+                household
+                    .add_citizen(citizen_id)
+                    .expect("Failed to add test citizen to household");
+                area.citizens.push(Citizen::new(
+                    citizen_id,
+                    building_id.clone(),
+                    building_id.clone(),
+                    30,
+                    Occupation::Student,
+                    false,
+                    false,
+                    false,
+                    24,
+                ));
+            }
+            area.buildings.push(Box::new(household));
+        };
+
+        // One hub household where everyone lives (and "works", since workplace == household here)
+        // together, plus many single-occupant households - so the hub's Citizens have a much higher
+        // contact degree than everyone else's
+        spawn_household(&mut area, 40);
+        for _ in 0..40 {
+            spawn_household(&mut area, 1);
+        }
+
+        builder.output_areas.push(area);
+
+        let citizen_count = builder.output_areas[0].citizens.len();
+        let population_mean_degree: f64 = (0..citizen_count)
+            .map(|local_index| builder.contact_degree(0, local_index))
+            .sum::<f64>()
+            / citizen_count as f64;
+
+        let mut rng = thread_rng();
+        builder
+            .apply_initial_infections(&mut rng)
+            .expect("Failed to apply initial infections");
+
+        let seed_degrees: Vec<f64> = builder.output_areas[0]
+            .citizens
+            .iter()
+            .enumerate()
+            .filter(|(_, citizen)| matches!(citizen.disease_status, DiseaseStatus::Infected { .. }))
+            .map(|(local_index, _)| builder.contact_degree(0, local_index))
+            .collect();
+        let seed_mean_degree = seed_degrees.iter().sum::<f64>() / seed_degrees.len() as f64;
+
+        assert!(
+            seed_mean_degree > population_mean_degree,
+            "Expected seeds weighted by contact degree ({}) to exceed the population mean ({})",
+            seed_mean_degree,
+            population_mean_degree
+        );
+    }
+
+    /// `workplace_building_overcapacity` is a single global multiplier applied to every building's
+    /// accounted size while distributing a scarce shared pool of buildings between occupations, so
+    /// changing it can shift which occupation's quota is considered "met" first - and, with it, how
+    /// many buildings are left over to assign to the other occupation's workers
+    #[test]
+    fn workplace_building_overcapacity_changes_the_building_distribution_between_occupations() {
+        let building_polygon = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(0, 0), (50, 0), (50, 30), (0, 30), (0, 0)]),
+            vec![],
+        );
+        let mut possible_buildings: Vec<RawBuilding> = (0..3)
+            .map(|_| {
+                RawBuilding::new(
+                    TagClassifiedBuilding::WorkPlace,
+                    &building_polygon,
+                    BuildingBoundaryID::default(),
+                )
+                    .expect("Failed to build a test RawBuilding")
+            })
+            .collect();
+
+        let household_id = BuildingID::new(
+            OutputAreaID::from_code_and_index("test".to_string(), 0),
+            BuildingType::Household,
+            0,
+        );
+        let make_citizens = |occupation: OccupationType, count: u32, id_offset: u32| -> Vec<Citizen> {
+            (0..count)
+                .map(|index| {
+                    Citizen::new(
+                        CitizenID::from_indexes(id_offset + index),
+                        household_id.clone(),
+                        household_id.clone(),
+                        30,
+                        Occupation::Normal { occupation },
+                        false,
+                        false,
+                        false,
+                        24,
+                    )
+                })
+                .collect()
+        };
+        let unassigned_workplace_count = |citizens: &[Citizen]| -> usize {
+            citizens
+                .iter()
+                .filter(|citizen| citizen.workplace_code == household_id)
+                .count()
+        };
+
+        let run_with_overcapacity = |overcapacity: f64| -> usize {
+            let mut citizens: Vec<Citizen> = make_citizens(OccupationType::Manager, 50, 0)
+                .into_iter()
+                .chain(make_citizens(OccupationType::Sales, 220, 50))
+                .collect();
+            let mut buildings = possible_buildings.clone();
+            SimulatorBuilder::assign_buildings_per_output_area(
+                OutputAreaID::from_code_and_index("test".to_string(), 0),
+                &mut citizens,
+                &mut buildings,
+                0,
+                overcapacity,
+                crate::config::DEFAULT_WORKPLACE_ROOM_SIZE,
+            )
+                .expect("Failed to assign buildings per output area");
+            unassigned_workplace_count(&citizens)
+        };
+
+        // With only 3 generic buildings shared between 50 Managers and 220 Sales workers, a modest
+        // overcapacity of 1.0 leaves 10 Sales workers unassigned; pushing it to 2.0 inflates every
+        // building's accounted size enough that the bin packing considers both occupations' quotas
+        // met after only 2 buildings, discarding the 3rd rather than handing it to Sales, so the
+        // shortfall gets worse rather than better
+        let unassigned_at_low_overcapacity = run_with_overcapacity(1.0);
+        let unassigned_at_high_overcapacity = run_with_overcapacity(2.0);
+        assert_eq!(unassigned_at_low_overcapacity, 10);
+        assert_eq!(unassigned_at_high_overcapacity, 115);
+    }
+
+    /// A region with no Schools in its OSM data shouldn't panic while building schools - students should
+    /// simply be left assigned to their household, rather than aborting the whole build
+    #[test]
+    fn build_schools_tolerates_a_region_with_no_schools() {
+        let mut builder = empty_simulator_builder();
+
+        let output_area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(
+            output_area_id.clone(),
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        let household_id = BuildingID::new(output_area_id, BuildingType::Household, 0);
+        area.citizens.push(Citizen::new(
+            CitizenID::from_indexes(0),
+            household_id.clone(),
+            household_id.clone(),
+            10,
+            Occupation::Student,
+            false,
+            false,
+            false,
+            24,
+        ));
+        builder.output_areas.push(area);
+        builder.output_area_lookup.insert("test".to_string(), 0);
+
+        builder
+            .build_schools()
+            .expect("build_schools should tolerate a region with no schools");
+
+        let student = &builder.output_areas[0].citizens[0];
+        assert_eq!(student.workplace_code, student.household_code);
+    }
+
+    /// `min_student_age` should keep younger children at their household, the same as the
+    /// no-schools path, while still assigning older children a School building
+    #[test]
+    fn build_schools_leaves_citizens_below_the_minimum_age_at_home() {
+        let area_polygon = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(0, 0), (100, 0), (100, 100), (0, 100), (0, 0)]),
+            vec![],
+        );
+        let boundary_id = BuildingBoundaryID::default();
+        let school_raw_building = RawBuilding::new(
+            TagClassifiedBuilding::School,
+            &area_polygon,
+            boundary_id,
+        )
+            .expect("Failed to build a test school RawBuilding");
+
+        let mut building_boundaries = HashMap::new();
+        building_boundaries.insert(boundary_id, area_polygon.clone());
+        let mut building_locations = HashMap::new();
+        building_locations.insert(TagClassifiedBuilding::School, vec![school_raw_building]);
+        let osm_data = OSMRawBuildings::from_building_locations(building_boundaries, building_locations, 100);
+
+        let mut output_area_polygons = HashMap::new();
+        output_area_polygons.insert("test".to_string(), area_polygon.clone());
+        let output_areas_polygons =
+            PolygonContainer::new(output_area_polygons, Scaling::yorkshire_national_grid(100), 100)
+                .expect("Failed to build a test polygon container");
+
+        let census_data = CensusData {
+            valid_areas: Default::default(),
+            age_counts: Default::default(),
+            population_counts: Default::default(),
+            occupation_counts: Default::default(),
+            workplace_density: EmploymentDensities {},
+            residents_workplace: Default::default(),
+            imputed_areas: Default::default(),
+        };
+        let mut builder = SimulatorBuilder::new(
+            "test".to_string(),
+            census_data,
+            osm_data,
+            output_areas_polygons,
+        )
+            .expect("Failed to build a test SimulatorBuilder");
+        builder.set_min_student_age(10);
+
+        let output_area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(output_area_id.clone(), area_polygon.clone(), 0.0)
+            .expect("Failed to build test Output Area");
+        let household_id = BuildingID::new(output_area_id, BuildingType::Household, 0);
+        area.buildings.push(Box::new(Household::new(
+            household_id.clone(),
+            geo_types::Point::new(50, 50),
+        )));
+        area.citizens.push(Citizen::new(
+            CitizenID::from_indexes(0),
+            household_id.clone(),
+            household_id.clone(),
+            3,
+            Occupation::Student,
+            false,
+            false,
+            false,
+            24,
+        ));
+        area.citizens.push(Citizen::new(
+            CitizenID::from_indexes(1),
+            household_id.clone(),
+            household_id.clone(),
+            12,
+            Occupation::Student,
+            false,
+            false,
+            false,
+            24,
+        ));
+        area.citizens.push(Citizen::new(
+            CitizenID::from_indexes(2),
+            household_id.clone(),
+            household_id.clone(),
+            30,
+            Occupation::Normal { occupation: OccupationType::Teaching },
+            false,
+            false,
+            false,
+            24,
+        ));
+        builder.output_areas.push(area);
+        builder.output_area_lookup.insert("test".to_string(), 0);
+
+        builder
+            .build_schools()
+            .expect("build_schools should succeed with a single school and two students");
+
+        let toddler = &builder.output_areas[0].citizens[0];
+        assert_eq!(
+            toddler.workplace_code, toddler.household_code,
+            "A Citizen below the minimum school age should be left at their household"
+        );
+        let schoolchild = &builder.output_areas[0].citizens[1];
+        assert_eq!(
+            *schoolchild.workplace_code.building_type(),
+            BuildingType::School,
+            "A Citizen at or above the minimum school age should be assigned a School"
+        );
+    }
+
+    /// `build_workplaces` should respect a configured `max_workplace_search_attempts`, and record
+    /// how many attempts each Citizen needed in `workplace_search_attempt_histogram` - a
+    /// pathological census distribution where the only reachable workplace area doesn't actually
+    /// exist should show up as every Citizen maxing out their attempts, rather than looping forever
+    #[test]
+    fn workplace_search_attempt_histogram_is_populated_and_respects_the_configured_max() {
+        let mut builder = empty_simulator_builder();
+        builder.set_max_workplace_search_attempts(5);
+        // No deliberate remote work, so every Citizen enters the Workplace search loop
+        builder.disease_model.remote_work_probability[OccupationType::Sales] = 0.0;
+
+        let output_area_id = OutputAreaID::from_code_and_index("home".to_string(), 0);
+        let mut area = OutputArea::new(
+            output_area_id.clone(),
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        let household_id = BuildingID::new(output_area_id, BuildingType::Household, 0);
+        for index in 0..3 {
+            area.citizens.push(Citizen::new(
+                CitizenID::from_indexes(index),
+                household_id.clone(),
+                household_id.clone(),
+                30,
+                Occupation::Normal { occupation: OccupationType::Sales },
+                false,
+                false,
+                false,
+                24,
+            ));
+        }
+        builder.output_areas.push(area);
+        builder.output_area_lookup.insert("home".to_string(), 0);
+
+        // Every Citizen is only ever sampled into a workplace area that doesn't exist in
+        // `output_area_lookup`, so every Citizen should exhaust the configured attempt budget
+        builder.census_data = CensusData {
+            valid_areas: Default::default(),
+            age_counts: HashMap::from([(
+                "home".to_string(),
+                AgePopulationRecord::new([10; 101], 1010).unwrap(),
+            )]),
+            population_counts: HashMap::from([(
+                "home".to_string(),
+                PopulationRecord {
+                    area_size: 1.0,
+                    density: 1.0,
+                    population_counts: Default::default(),
+                    population_size: 100,
+                },
+            )]),
+            occupation_counts: HashMap::from([(
+                "home".to_string(),
+                OccupationCountRecord::new(vec![RawOccupationType::Managers], vec![10]).unwrap(),
+            )]),
+            workplace_density: EmploymentDensities {},
+            residents_workplace: HashMap::from([(
+                "home".to_string(),
+                WorkplaceResidentialRecord {
+                    workplace_count: HashMap::from([("nonexistent".to_string(), 100)]),
+                    total_workplace_count: 100,
+                },
+            )]),
+            imputed_areas: Default::default(),
+        };
+
+        builder
+            .build_workplaces(HashMap::new())
+            .expect("build_workplaces should tolerate an unreachable workplace area");
+
+        assert_eq!(builder.workplace_search_attempt_histogram.get(&5), Some(&3));
+        assert!(builder
+            .workplace_search_attempt_histogram
+            .keys()
+            .all(|attempts| *attempts <= 5));
+    }
+
+    /// Setting a 100% remote-work probability for an occupation should leave every Citizen of that
+    /// occupation at their household, without ever attempting a Workplace search for them
+    #[test]
+    fn full_remote_work_probability_leaves_all_of_that_occupation_home_based() {
+        let mut builder = empty_simulator_builder();
+        builder.disease_model.remote_work_probability[OccupationType::Sales] = 1.0;
+
+        let output_area_id = OutputAreaID::from_code_and_index("home".to_string(), 0);
+        let mut area = OutputArea::new(
+            output_area_id.clone(),
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        let household_id = BuildingID::new(output_area_id, BuildingType::Household, 0);
+        for index in 0..10 {
+            area.citizens.push(Citizen::new(
+                CitizenID::from_indexes(index),
+                household_id.clone(),
+                household_id.clone(),
+                30,
+                Occupation::Normal { occupation: OccupationType::Sales },
+                false,
+                false,
+                false,
+                24,
+            ));
+        }
+        builder.output_areas.push(area);
+        builder.output_area_lookup.insert("home".to_string(), 0);
+
+        builder.census_data = CensusData {
+            valid_areas: Default::default(),
+            age_counts: HashMap::from([(
+                "home".to_string(),
+                AgePopulationRecord::new([10; 101], 1010).unwrap(),
+            )]),
+            population_counts: HashMap::from([(
+                "home".to_string(),
+                PopulationRecord {
+                    area_size: 1.0,
+                    density: 1.0,
+                    population_counts: Default::default(),
+                    population_size: 100,
+                },
+            )]),
+            occupation_counts: HashMap::from([(
+                "home".to_string(),
+                OccupationCountRecord::new(vec![RawOccupationType::Managers], vec![10]).unwrap(),
+            )]),
+            workplace_density: EmploymentDensities {},
+            residents_workplace: HashMap::from([(
+                "home".to_string(),
+                WorkplaceResidentialRecord {
+                    workplace_count: HashMap::from([("nonexistent".to_string(), 100)]),
+                    total_workplace_count: 100,
+                },
+            )]),
+            imputed_areas: Default::default(),
+        };
+
+        builder
+            .build_workplaces(HashMap::new())
+            .expect("build_workplaces should succeed with every Citizen working remotely");
+
+        assert!(
+            builder.workplace_search_attempt_histogram.is_empty(),
+            "A remote Citizen should never enter the Workplace search loop"
+        );
+        assert!(builder.output_areas[0]
+            .citizens
+            .iter()
+            .all(|citizen| citizen.workplace_code == citizen.household_code));
+    }
+
+    /// `nearest_workplace_area_code` should resolve a household Output Area's centroid to the
+    /// Output Area actually containing the nearby WorkPlace building, rather than failing or
+    /// returning the household's own (WorkPlace-less) area
+    #[test]
+    fn nearest_workplace_area_code_resolves_to_the_workplaces_enclosing_area() {
+        let workplace_building_polygon = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(0, 0), (5, 0), (5, 5), (0, 5), (0, 0)]),
+            vec![],
+        );
+        let boundary_id = BuildingBoundaryID::default();
+        let workplace = RawBuilding::new(
+            TagClassifiedBuilding::WorkPlace,
+            &workplace_building_polygon,
+            boundary_id,
+        )
+            .expect("Failed to build a test WorkPlace RawBuilding");
+
+        let mut building_boundaries = HashMap::new();
+        building_boundaries.insert(boundary_id, workplace_building_polygon.clone());
+        let mut building_locations = HashMap::new();
+        building_locations.insert(TagClassifiedBuilding::WorkPlace, vec![workplace]);
+        let osm_data = OSMRawBuildings::from_building_locations(building_boundaries, building_locations, 100);
+
+        // Kept well clear of the WorkPlace's bounding box - `find_polygons_containing_polygon`
+        // matches on bounding-box intersection, so an overlapping household Output Area could
+        // spuriously "contain" the WorkPlace building too and make the resolved area ambiguous
+        let home_polygon = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(50, 50), (51, 50), (51, 51), (50, 51), (50, 50)]),
+            vec![],
+        );
+        let mut output_area_polygons = HashMap::new();
+        output_area_polygons.insert("home".to_string(), home_polygon.clone());
+        output_area_polygons.insert("work".to_string(), workplace_building_polygon);
+        let output_areas_polygons =
+            PolygonContainer::new(output_area_polygons, Scaling::yorkshire_national_grid(100), 100)
+                .expect("Failed to build a test polygon container");
+
+        let census_data = CensusData {
+            valid_areas: Default::default(),
+            age_counts: Default::default(),
+            population_counts: Default::default(),
+            occupation_counts: Default::default(),
+            workplace_density: EmploymentDensities {},
+            residents_workplace: Default::default(),
+            imputed_areas: Default::default(),
+        };
+        let builder = SimulatorBuilder::new(
+            "test".to_string(),
+            census_data,
+            osm_data,
+            output_areas_polygons,
+        )
+            .expect("Failed to build a test SimulatorBuilder");
+
+        let home_area_id = OutputAreaID::from_code_and_index("home".to_string(), 0);
+        let home_area = OutputArea::new(home_area_id, home_polygon, 0.0)
+            .expect("Failed to build test Output Area");
+
+        let nearest_area_code = builder
+            .nearest_workplace_area_code(&home_area)
+            .expect("The one WorkPlace in the region should have been found");
+        assert_eq!(nearest_area_code, "work");
+    }
+
+    /// With `BuildingAreaAssignmentPolicy::Centroid`, a building whose bounding box matches two
+    /// Output Areas should be assigned to whichever Area's polygon actually contains its centroid,
+    /// rather than an arbitrary one of the two
+    #[test]
+    fn centroid_policy_resolves_a_straddling_building_to_its_containing_area() {
+        let boundary_id = BuildingBoundaryID::default();
+        // Sits mostly inside "east", but its bounding box also clips the edge of "west"
+        let building_polygon = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(9, 0), (20, 0), (20, 10), (9, 10), (9, 0)]),
+            vec![],
+        );
+        let building = RawBuilding::new(TagClassifiedBuilding::Household, &building_polygon, boundary_id)
+            .expect("Failed to build a test RawBuilding");
+
+        let mut building_boundaries = HashMap::new();
+        building_boundaries.insert(boundary_id, building_polygon);
+
+        let mut output_area_polygons = HashMap::new();
+        output_area_polygons.insert(
+            "west".to_string(),
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(0, 0), (10, 0), (10, 10), (0, 10), (0, 0)]),
+                vec![],
+            ),
+        );
+        output_area_polygons.insert(
+            "east".to_string(),
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(10, 0), (25, 0), (25, 10), (10, 10), (10, 0)]),
+                vec![],
+            ),
+        );
+        let output_areas_polygons =
+            PolygonContainer::new(output_area_polygons, Scaling::yorkshire_national_grid(100), 100)
+                .expect("Failed to build a test polygon container");
+
+        let area_code = super::get_area_code_for_raw_building(
+            &building,
+            &output_areas_polygons,
+            &building_boundaries,
+            BuildingAreaAssignmentPolicy::Centroid,
+        )
+            .expect("Building should have resolved to an Output Area")
+            .0;
+        assert_eq!(area_code, "east");
+    }
+
+    /// A second call to `assign_buildings_to_output_areas_cached` with unchanged `input_file_paths`
+    /// should load the assignment from the cache written by the first call, rather than
+    /// recomputing it - proven by mutating `osm_data` in between the two calls and asserting the
+    /// second call still returns the first call's (now otherwise unreproducible) result
+    #[test]
+    fn assign_buildings_to_output_areas_cached_reuses_an_unchanged_input_files_cache_entry() {
+        let workplace_building_polygon = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(0, 0), (5, 0), (5, 5), (0, 5), (0, 0)]),
+            vec![],
+        );
+        let boundary_id = BuildingBoundaryID::default();
+        let workplace = RawBuilding::new(
+            TagClassifiedBuilding::WorkPlace,
+            &workplace_building_polygon,
+            boundary_id,
+        )
+            .expect("Failed to build a test WorkPlace RawBuilding");
+
+        let mut building_boundaries = HashMap::new();
+        building_boundaries.insert(boundary_id, workplace_building_polygon.clone());
+        let mut building_locations = HashMap::new();
+        building_locations.insert(TagClassifiedBuilding::WorkPlace, vec![workplace]);
+        let osm_data =
+            OSMRawBuildings::from_building_locations(building_boundaries, building_locations, 100);
+
+        let mut output_area_polygons = HashMap::new();
+        output_area_polygons.insert("work".to_string(), workplace_building_polygon);
+        let output_areas_polygons =
+            PolygonContainer::new(output_area_polygons, Scaling::yorkshire_national_grid(100), 100)
+                .expect("Failed to build a test polygon container");
+
+        let census_data = CensusData {
+            valid_areas: Default::default(),
+            age_counts: Default::default(),
+            population_counts: Default::default(),
+            occupation_counts: Default::default(),
+            workplace_density: EmploymentDensities {},
+            residents_workplace: Default::default(),
+            imputed_areas: Default::default(),
+        };
+        let mut builder = SimulatorBuilder::new(
+            "test".to_string(),
+            census_data,
+            osm_data,
+            output_areas_polygons,
+        )
+            .expect("Failed to build a test SimulatorBuilder");
+
+        let cache_dir = std::env::temp_dir()
+            .join(format!(
+                "building_assignment_cache_test_{}",
+                std::process::id()
+            ))
+            .to_str()
+            .unwrap()
+            .to_string();
+        let input_file_path = std::env::temp_dir()
+            .join(format!("building_assignment_cache_test_input_{}", std::process::id()))
+            .to_str()
+            .unwrap()
+            .to_string();
+        std::fs::write(&input_file_path, "unchanged osm data").expect("Failed to write test input file");
+
+        let first_result = builder
+            .assign_buildings_to_output_areas_cached(&cache_dir, &[input_file_path.as_str()], false)
+            .expect("First call should compute and cache the assignment");
+        assert!(
+            !first_result.is_empty(),
+            "The WorkPlace should have been assigned to the \"work\" Output Area"
+        );
+
+        // Remove the only building, so a second, genuinely recomputed assignment would be empty -
+        // if the second call below still returns `first_result`, it must have come from the cache
+        builder.osm_data.building_locations.clear();
+
+        let second_result = builder
+            .assign_buildings_to_output_areas_cached(&cache_dir, &[input_file_path.as_str()], false)
+            .expect("Second call should load the assignment from the cache");
+
+        std::fs::remove_dir_all(&cache_dir).ok();
+        std::fs::remove_file(&input_file_path).ok();
+
+        // `RawBuilding` doesn't implement `PartialEq`, so compare via its `Serialize` impl instead
+        assert_eq!(
+            serde_json::to_vec(&first_result).expect("Failed to serialize first result"),
+            serde_json::to_vec(&second_result).expect("Failed to serialize second result")
+        );
+    }
+
+    /// Under `WorkplaceAssignmentStrategy::NearestWorkplace`, every eligible Citizen should be sent
+    /// straight to the one nearby workplace area in a single attempt, rather than sampling the
+    /// Census residence/workplace flow distribution the way `CensusFlow` does - this is what gives
+    /// `NearestWorkplace` its shorter commutes: it always lands on the nearest area instead of
+    /// occasionally being sent to a distant one that merely happens to appear in the flow data
+    #[test]
+    fn nearest_workplace_strategy_assigns_every_citizen_to_the_nearby_area_in_one_attempt() {
+        let workplace_building_polygon = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(0, 0), (5, 0), (5, 5), (0, 5), (0, 0)]),
+            vec![],
+        );
+        let boundary_id = BuildingBoundaryID::default();
+        let workplace = RawBuilding::new(
+            TagClassifiedBuilding::WorkPlace,
+            &workplace_building_polygon,
+            boundary_id,
+        )
+            .expect("Failed to build a test WorkPlace RawBuilding");
+
+        let mut building_boundaries = HashMap::new();
+        building_boundaries.insert(boundary_id, workplace_building_polygon.clone());
+        let mut building_locations = HashMap::new();
+        building_locations.insert(TagClassifiedBuilding::WorkPlace, vec![workplace]);
+        let osm_data = OSMRawBuildings::from_building_locations(building_boundaries, building_locations, 100);
+
+        // Kept well clear of the WorkPlace's bounding box - `find_polygons_containing_polygon`
+        // matches on bounding-box intersection, so an overlapping household Output Area could
+        // spuriously "contain" the WorkPlace building too and make the resolved area ambiguous
+        let home_polygon = geo_types::Polygon::new(
+            geo_types::LineString::from(vec![(50, 50), (51, 50), (51, 51), (50, 51), (50, 50)]),
+            vec![],
+        );
+        let mut output_area_polygons = HashMap::new();
+        output_area_polygons.insert("home".to_string(), home_polygon.clone());
+        output_area_polygons.insert("work".to_string(), workplace_building_polygon);
+        let output_areas_polygons =
+            PolygonContainer::new(output_area_polygons, Scaling::yorkshire_national_grid(100), 100)
+                .expect("Failed to build a test polygon container");
+
+        let census_data = CensusData {
+            valid_areas: Default::default(),
+            age_counts: Default::default(),
+            population_counts: Default::default(),
+            occupation_counts: Default::default(),
+            workplace_density: EmploymentDensities {},
+            // Deliberately never points at "work" - if `NearestWorkplace` fell back to sampling
+            // this distribution like `CensusFlow` does, every Citizen would exhaust their attempts
+            // against "nonexistent" instead of landing on the nearby area
+            residents_workplace: HashMap::from([(
+                "home".to_string(),
+                WorkplaceResidentialRecord {
+                    workplace_count: HashMap::from([("nonexistent".to_string(), 100)]),
+                    total_workplace_count: 100,
+                },
+            )]),
+            imputed_areas: Default::default(),
+        };
+        let mut builder = SimulatorBuilder::new(
+            "test".to_string(),
+            census_data,
+            osm_data,
+            output_areas_polygons,
+        )
+            .expect("Failed to build a test SimulatorBuilder");
+        builder.set_workplace_assignment_strategy(WorkplaceAssignmentStrategy::NearestWorkplace);
+        // No deliberate remote work, so every Citizen enters the Workplace search loop
+        builder.disease_model.remote_work_probability[OccupationType::Sales] = 0.0;
+
+        let home_area_id = OutputAreaID::from_code_and_index("home".to_string(), 0);
+        let mut home_area = OutputArea::new(home_area_id.clone(), home_polygon, 0.0)
+            .expect("Failed to build test Output Area");
+        let household_id = BuildingID::new(home_area_id, BuildingType::Household, 0);
+        for index in 0..5 {
+            home_area.citizens.push(Citizen::new(
+                CitizenID::from_indexes(index),
+                household_id.clone(),
+                household_id.clone(),
+                30,
+                Occupation::Normal { occupation: OccupationType::Sales },
+                false,
+                false,
+                false,
+                24,
+            ));
+        }
+        builder.output_areas.push(home_area);
+        builder.output_area_lookup.insert("home".to_string(), 0);
+
+        let work_area_id = OutputAreaID::from_code_and_index("work".to_string(), 1);
+        let work_area = OutputArea::new(
+            work_area_id,
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(0, 0), (5, 0), (5, 5), (0, 5), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        builder.output_areas.push(work_area);
+
+        let mut possible_buildings_per_area = HashMap::new();
+        possible_buildings_per_area.insert(
+            "work".to_string(),
+            vec![RawBuilding::new(
+                TagClassifiedBuilding::WorkPlace,
+                &geo_types::Polygon::new(
+                    geo_types::LineString::from(vec![(0, 0), (5, 0), (5, 5), (0, 5), (0, 0)]),
+                    vec![],
+                ),
+                BuildingBoundaryID::default(),
+            )
+                .expect("Failed to build a test WorkPlace RawBuilding")],
+        );
+        builder.output_area_lookup.insert("work".to_string(), 1);
+
+        builder
+            .build_workplaces(possible_buildings_per_area)
+            .expect("build_workplaces should succeed when every Citizen lands on the nearby area");
+
+        assert_eq!(
+            builder.workplace_search_attempt_histogram.get(&1),
+            Some(&5),
+            "Every Citizen should have found the nearby workplace area on their first attempt"
+        );
+    }
+
+    /// Citizens queued against a destination Output Area that no longer exists should be moved onto
+    /// a surviving Output Area, rather than dropped
+    #[test]
+    fn orphaned_citizens_are_reassigned_to_a_surviving_workplace_area_not_dropped() {
+        let citizen_a = CitizenID::from_indexes(0);
+        let citizen_b = CitizenID::from_indexes(1);
+        let mut citizens_to_allocate = vec![
+            vec![citizen_a, citizen_b],
+            Vec::new(),
+        ];
+
+        let reassigned_count = SimulatorBuilder::reassign_orphaned_workplace_citizens(
+            &mut citizens_to_allocate,
+            |index| index != 0,
+        );
+
+        assert_eq!(reassigned_count, 2, "Both orphaned Citizens should have been reassigned");
+        assert!(citizens_to_allocate[0].is_empty());
+        assert_eq!(citizens_to_allocate[1].len(), 2);
+        assert!(citizens_to_allocate[1].contains(&citizen_a));
+        assert!(citizens_to_allocate[1].contains(&citizen_b));
+    }
+
+    /// Builds a `SimulatorBuilder` over the given area codes, with a matching trivial polygon for
+    /// each, but with `initialise_output_areas` not yet called
+    fn simulator_builder_with_valid_areas(area_codes: &[&str]) -> SimulatorBuilder {
+        let mut valid_areas = std::collections::HashSet::new();
+        let mut polygons = HashMap::new();
+        for code in area_codes {
+            valid_areas.insert(code.to_string());
+            polygons.insert(
+                code.to_string(),
+                geo_types::Polygon::new(
+                    geo_types::LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                    vec![],
+                ),
+            );
+        }
+        let census_data = CensusData {
+            valid_areas,
+            age_counts: Default::default(),
+            population_counts: Default::default(),
+            occupation_counts: Default::default(),
+            workplace_density: EmploymentDensities {},
+            residents_workplace: Default::default(),
+            imputed_areas: Default::default(),
+        };
+        let osm_data = OSMRawBuildings::from_building_locations(HashMap::new(), HashMap::new(), 100);
+        let output_areas_polygons = PolygonContainer::new(
+            polygons,
+            Scaling::yorkshire_national_grid(100),
+            100,
+        )
+            .expect("Failed to build a polygon container");
+        SimulatorBuilder::new(
+            "test".to_string(),
+            census_data,
+            osm_data,
+            output_areas_polygons,
+        )
+            .expect("Failed to build a SimulatorBuilder")
+    }
+
+    /// Two builders over the same census data should assign the same area code -> index mapping,
+    /// rather than depending on the unspecified iteration order of the underlying `HashSet`
+    #[test]
+    fn output_area_index_assignment_is_deterministic_across_builds() {
+        let area_codes = ["area_c", "area_a", "area_b", "area_e", "area_d"];
+
+        let mut first = simulator_builder_with_valid_areas(&area_codes);
+        first
+            .initialise_output_areas()
+            .expect("Failed to initialise output areas");
+
+        let mut second = simulator_builder_with_valid_areas(&area_codes);
+        second
+            .initialise_output_areas()
+            .expect("Failed to initialise output areas");
+
+        assert_eq!(first.output_area_lookup, second.output_area_lookup);
+    }
+
+    /// Fills a single Output Area of an otherwise-empty `SimulatorBuilder` with `citizen_count`
+    /// household-only Citizens, for `estimated_memory_bytes` scaling tests
+    fn simulator_builder_with_citizens(citizen_count: u32) -> SimulatorBuilder {
+        let mut builder = empty_simulator_builder();
+        let output_area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(
+            output_area_id.clone(),
+            geo_types::Polygon::new(
+                geo_types::LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+                vec![],
+            ),
+            0.0,
+        )
+            .expect("Failed to build test Output Area");
+        let household_id = BuildingID::new(output_area_id, BuildingType::Household, 0);
+        let mut household = Household::new(household_id.clone(), geo_types::Point::new(0, 0));
+        for index in 0..citizen_count {
+            let citizen = Citizen::new(
+                CitizenID::from_indexes(index),
+                household_id.clone(),
+                household_id.clone(),
+                30,
+                Occupation::Unemployed,
+                false,
+                false,
+                false,
+                24,
+            );
+            household
+                .add_citizen(citizen.id())
+                .expect("Failed to add Citizen to test Household");
+            area.citizens.push(citizen);
+        }
+        area.buildings.push(Box::new(household));
+        builder.output_areas.push(area);
+        builder
+    }
+
+    /// Doubling the Citizen count (with everything else held constant) should roughly double the
+    /// estimated memory footprint, since `Citizen`s dominate a typical region's population - this
+    /// pins `estimated_memory_bytes` against regressing into a constant or sub-linear estimate
+    #[test]
+    fn estimated_memory_scales_roughly_linearly_with_citizen_count() {
+        let small = simulator_builder_with_citizens(1_000);
+        let large = simulator_builder_with_citizens(10_000);
+
+        let small_bytes = small.estimated_memory_bytes();
+        let large_bytes = large.estimated_memory_bytes();
+
+        assert!(
+            small_bytes > 0,
+            "A non-empty population should have a non-zero estimate"
+        );
+        let ratio = large_bytes as f64 / small_bytes as f64;
+        assert!(
+            (9.0..=11.0).contains(&ratio),
+            "Expected roughly a 10x estimate for a 10x Citizen count, got a ratio of {:.2} ({} vs {})",
+            ratio,
+            small_bytes,
+            large_bytes
+        );
+    }
+}
+}
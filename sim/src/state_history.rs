@@ -0,0 +1,152 @@
+/*
+ * Epidemic Simulation Using Census Data (ESUCD)
+ * Copyright (c)  2022. Sam Ralph
+ *
+ * This file is part of ESUCD.
+ *
+ * ESUCD is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * ESUCD is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with ESUCD.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+use std::panic::UnwindSafe;
+
+use anyhow::Context;
+use log::error;
+use serde::Serialize;
+
+use crate::statistics::StatisticEntry;
+
+/// A single recorded point in a Simulator's trajectory, kept by `StateHistory` for diagnosing a
+/// crash mid-simulation
+#[derive(Debug, Clone, Serialize)]
+pub struct StateSnapshot {
+    time_step: u32,
+    fingerprint: u64,
+    statistics: StatisticEntry,
+}
+
+impl StateSnapshot {
+    pub fn new(time_step: u32, fingerprint: u64, statistics: StatisticEntry) -> StateSnapshot {
+        StateSnapshot { time_step, fingerprint, statistics }
+    }
+}
+
+/// A fixed-size ring buffer of the most recent `StateSnapshot`s, so a panic mid-simulation can be
+/// diagnosed from the trajectory leading up to it, rather than just the final state
+///
+/// A `capacity` of `0` disables history retention entirely - `record` becomes a no-op
+#[derive(Debug, Clone, Default)]
+pub struct StateHistory {
+    capacity: usize,
+    entries: VecDeque<StateSnapshot>,
+}
+
+impl StateHistory {
+    pub fn new(capacity: usize) -> StateHistory {
+        StateHistory { capacity, entries: VecDeque::with_capacity(capacity) }
+    }
+
+    pub fn record(&mut self, snapshot: StateSnapshot) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(snapshot);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Writes the retained history to `dump_path` as JSON, oldest entry first
+    pub fn dump_to_file(&self, dump_path: &str) -> anyhow::Result<()> {
+        let file = File::create(dump_path)
+            .context(format!("Failed to create state history dump: {}", dump_path))?;
+        let writer = BufWriter::new(file);
+        serde_json::to_writer(writer, &self.entries).context("Failed to serialise state history")?;
+        Ok(())
+    }
+}
+
+/// Runs `f`, and if it panics, dumps `history` to `dump_path` before resuming the unwind - so a
+/// crash mid-simulation leaves a trail of the states leading up to it on disk, instead of vanishing
+/// with the process
+///
+/// A failure to write the dump itself only logs an error; it never suppresses the original panic
+pub fn dump_on_panic<F, R>(history: &StateHistory, dump_path: &str, f: F) -> R
+where
+    F: FnOnce() -> R + UnwindSafe,
+{
+    match std::panic::catch_unwind(f) {
+        Ok(result) => result,
+        Err(panic_payload) => {
+            match history.dump_to_file(dump_path) {
+                Ok(()) => error!("Dumped {} recent states to {} after a panic", history.len(), dump_path),
+                Err(e) => error!("Failed to dump state history after a panic: {}", e),
+            }
+            std::panic::resume_unwind(panic_payload);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::state_history::{dump_on_panic, StateHistory, StateSnapshot};
+    use crate::statistics::StatisticEntry;
+
+    /// A ring buffer at capacity should evict its oldest entry to make room for a new one
+    #[test]
+    fn recording_past_capacity_evicts_the_oldest_entry() {
+        let mut history = StateHistory::new(2);
+        history.record(StateSnapshot::new(0, 1, StatisticEntry::with_time_step(0)));
+        history.record(StateSnapshot::new(1, 2, StatisticEntry::with_time_step(1)));
+        history.record(StateSnapshot::new(2, 3, StatisticEntry::with_time_step(2)));
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.entries[0].time_step, 1);
+        assert_eq!(history.entries[1].time_step, 2);
+    }
+
+    /// A panic raised while `dump_on_panic` is running the given closure should dump the recent
+    /// state history to disk before the panic is allowed to propagate
+    #[test]
+    fn forced_panic_dumps_the_recent_state_history() {
+        let mut history = StateHistory::new(5);
+        history.record(StateSnapshot::new(0, 111, StatisticEntry::with_time_step(0)));
+        history.record(StateSnapshot::new(1, 222, StatisticEntry::with_time_step(1)));
+
+        let dump_path = std::env::temp_dir()
+            .join(format!("state_history_panic_test_{}.json", std::process::id()))
+            .to_str()
+            .expect("Non-UTF8 temp path")
+            .to_string();
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            dump_on_panic(&history, &dump_path, || panic!("forced panic for testing"));
+        }));
+        assert!(result.is_err(), "The forced panic should still propagate out of dump_on_panic");
+
+        let contents = std::fs::read_to_string(&dump_path).expect("Expected a state history dump file");
+        std::fs::remove_file(&dump_path).ok();
+        assert!(contents.contains("111"), "Dump should contain the first recorded fingerprint");
+        assert!(contents.contains("222"), "Dump should contain the second recorded fingerprint");
+    }
+}
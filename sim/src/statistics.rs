@@ -22,7 +22,7 @@ use std::collections::HashMap;
 use std::fmt::{Display, format, Formatter};
 use std::fs;
 use std::fs::File;
-use std::io::BufWriter;
+use std::io::{BufWriter, Write};
 use std::ops::AddAssign;
 use std::time::Instant;
 
@@ -33,17 +33,22 @@ use num_format::ToFormattedString;
 use serde::{Deserialize, Serialize};
 use serde_json::to_writer;
 
+use enum_map::EnumMap;
+
 use crate::config::{get_memory_usage, NUMBER_FORMATTING};
+use crate::contact_matrix::{AgeContactMatrix, AGE_GROUP_WIDTH};
 use crate::disease::DiseaseStatus;
 use crate::error::SimError;
-use crate::models::building::BuildingID;
-use crate::models::citizen::Citizen;
+use crate::models::building::{BuildingID, BuildingType};
+use crate::models::citizen::{Citizen, CitizenID};
 use crate::models::ID;
 use crate::models::output_area::OutputAreaID;
 use crate::models::public_transport_route::PublicTransportID;
+use crate::surveillance::SurveillanceModel;
+use std::collections::HashSet;
 
 /// A simple struct for benchmarking how long a block of code takes
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Timer {
     function_timer: Instant,
     code_block_timer: Instant,
@@ -94,7 +99,7 @@ impl Default for Timer {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
 pub struct StatisticsRecorder {
     #[serde(skip)]
     timer: Timer,
@@ -104,8 +109,53 @@ pub struct StatisticsRecorder {
     pub global_stats: Vec<StatisticEntry>,
     /// The amount of exposures that occured in this building
     exposures_per_building_per_time_step: HashMap<ID, Vec<u32>>,
+    /// The number of Susceptible Citizens who were ever actually rolled against the exposure
+    /// chance in a building of each type, regardless of whether the roll succeeded - the
+    /// denominator for `realised_attack_rate_by_building_type`
+    exposure_opportunities_by_building_type: EnumMap<BuildingType, u32>,
+    /// The number of those opportunities (see `exposure_opportunities_by_building_type`) that
+    /// actually resulted in an infection - the numerator for `realised_attack_rate_by_building_type`
+    exposure_successes_by_building_type: EnumMap<BuildingType, u32>,
     /// The time steps currently being altered
     pub current_entry: HashMap<ID, u32>,
+    /// The distinct Citizens that have ever been infected, grouped by the Output Area they were infected in
+    #[serde(skip)]
+    ever_infected_by_area: HashMap<OutputAreaID, HashSet<CitizenID>>,
+    /// The starting population of each Output Area, used to compute the attack rate
+    area_population: HashMap<OutputAreaID, u32>,
+    /// The time step each Citizen was first exposed at, used to compute the serial interval of the
+    /// transmissions they go on to cause
+    #[serde(skip)]
+    infection_onset: HashMap<CitizenID, u16>,
+    /// The recorded infector -> infectee transmission events, for epidemiological validation
+    #[serde(skip)]
+    transmission_log: TransmissionLog,
+    /// New (first-ever) infections recorded so far this time step, grouped by
+    /// `AgeContactMatrix::age_group` - flushed into `infections_by_age_timeseries` by `next()`
+    #[serde(skip)]
+    current_infections_by_age: HashMap<usize, u32>,
+    /// One entry per time step, recording how many distinct Citizens were newly infected that
+    /// step, grouped by age band - so users can see which age groups are driving a given wave
+    infections_by_age_timeseries: Vec<HashMap<usize, u32>>,
+    /// The distinct Citizens who have received a vaccine dose at any point in the simulation -
+    /// kept separately from `DiseaseStatus`, since a vaccinated Citizen who is later infected
+    /// moves out of `DiseaseStatus::Vaccinated` and would otherwise be indistinguishable from one
+    /// who was never vaccinated at all
+    #[serde(skip)]
+    ever_vaccinated: HashSet<CitizenID>,
+    /// How many time steps apart `global_stats` entries are kept, to reduce memory/output size on
+    /// long runs - `0` (the `Default` value) is treated the same as `1`, recording every step
+    ///
+    /// The disease-exists termination check in `disease_exists` always reflects the current time
+    /// step regardless of this setting, since `next()` keeps the full per-step counts up to date
+    /// in the most recent entry even on steps that aren't permanently recorded
+    sampling_interval: u32,
+    /// An optional surveillance layer converting `infections_by_age_timeseries`'s true new
+    /// infection counts into the delayed, under-ascertained "reported cases" a real surveillance
+    /// system would have observed - see `reported_cases_timeseries`
+    ///
+    /// Defaults to `None`, meaning `dump_to_file` exports only the true counts
+    surveillance_model: Option<SurveillanceModel>,
 }
 
 
@@ -114,6 +164,13 @@ impl StatisticsRecorder {
         // Flush the recordings
         self.next();
         fs::create_dir_all(directory.clone()).expect(&format!("Failed to create statistics directory: '{}'", directory));
+
+        let file = File::create(directory.to_owned() + "top_exposure_buildings.json")
+            .expect("Failed to create top exposure buildings results file!");
+        let file_writer = BufWriter::new(file);
+        to_writer(file_writer, &self.top_exposure_buildings(usize::MAX))
+            .expect("Failed to write to file!");
+
         let file = File::create(directory.to_owned() + "exposures.json").expect("Failed to create results file!");
         let file_writer = BufWriter::new(file);
         let mut exposure_counts: HashMap<&str, HashMap<String, Vec<u32>>> = HashMap::new();
@@ -121,7 +178,10 @@ impl StatisticsRecorder {
             let mut entry = exposure_counts.entry("All").or_default();
             entry.insert("All".to_string(), records.clone());
             match place {
-                ID::Building(id) => {}
+                ID::Building(id) => {
+                    let mut entry = exposure_counts.entry("Building").or_default();
+                    entry.insert(id.to_string(), records);
+                }
                 ID::OutputArea(code) => {
                     let mut entry = exposure_counts.entry("OutputArea").or_default();
                     entry.insert(code.code().to_string(), records);
@@ -146,12 +206,38 @@ impl StatisticsRecorder {
         let file = File::create(directory.to_owned() + "global_stats.json").expect("Failed to create global stats results file!");
         let file_writer = BufWriter::new(file);
         to_writer(file_writer, &self.global_stats).expect("Failed to write to file!");
+
+        if let Some(reported_cases) = self.reported_cases_timeseries() {
+            let file = File::create(directory.to_owned() + "reported_cases.json")
+                .expect("Failed to create reported cases results file!");
+            let file_writer = BufWriter::new(file);
+            to_writer(file_writer, &reported_cases).expect("Failed to write to file!");
+        }
+
+        self.export_infections_by_age_csv(&(directory.to_owned() + "infections_by_age.csv"))
+            .expect("Failed to write infections by age CSV!");
         info!("Dumped data to file: {}",directory);
     }
     pub fn current_time_step(&self) -> u32 {
         self.current_time_step
     }
 
+    /// Sets how many time steps apart `global_stats` entries are kept - e.g. `6` keeps one entry
+    /// out of every six steps, rather than every single one
+    ///
+    /// The termination check in `disease_exists` is unaffected, since it always reads the most
+    /// recently computed counts regardless of whether this step's entry is being kept
+    pub fn set_sampling_interval(&mut self, sampling_interval: u32) {
+        self.sampling_interval = sampling_interval;
+    }
+
+    /// Configures a surveillance layer so `reported_cases_timeseries` (and `dump_to_file`'s
+    /// `reported_cases.json`) reflect delayed, under-ascertained "reported cases" alongside the
+    /// model's true counts
+    pub fn set_surveillance_model(&mut self, surveillance_model: SurveillanceModel) {
+        self.surveillance_model = Some(surveillance_model);
+    }
+
     /// Prepares for recording the next step
     pub fn next(&mut self) -> anyhow::Result<()> {
         // If we have started recording, update the previous data
@@ -162,10 +248,21 @@ impl StatisticsRecorder {
                 let mut recording_entry = self.exposures_per_building_per_time_step.entry(area).or_default();//tatisticEntry::with_time_step(self.current_time_step));
                 recording_entry.push(entry);
             }
+            self.infections_by_age_timeseries
+                .push(std::mem::take(&mut self.current_infections_by_age));
         }
         self.timer = Timer::default();
         self.current_time_step += 1;
-        self.global_stats.push(StatisticEntry::with_time_step(self.current_time_step()));
+        let interval = self.sampling_interval.max(1);
+        // Keep an entry for every `interval`'th step, starting with the first - on the steps in
+        // between, overwrite the last entry in place so `update_global_stats_entry`/`disease_exists`
+        // still see this step's own counts, without growing `global_stats`
+        if self.global_stats.is_empty() || (self.current_time_step - 1) % interval == 0 {
+            self.global_stats.push(StatisticEntry::with_time_step(self.current_time_step()));
+        } else {
+            *self.global_stats.last_mut().expect("Checked non-empty above") =
+                StatisticEntry::with_time_step(self.current_time_step());
+        }
         self.current_entry = HashMap::new();
         Ok(())
     }
@@ -178,21 +275,167 @@ impl StatisticsRecorder {
         let mut current = self.global_stats.last_mut().expect("Need to call next() to start a recording!");
         *current += entry;
     }
+    /// Records a Susceptible Citizen having been rolled against the exposure chance in a building
+    /// of the given type, whether or not that roll went on to succeed - see
+    /// `exposure_opportunities_by_building_type`
+    pub fn record_exposure_opportunity(&mut self, building_type: BuildingType) {
+        self.exposure_opportunities_by_building_type[building_type] += 1;
+    }
+    /// Returns the realised secondary attack rate for each `BuildingType` - the proportion of
+    /// exposure opportunities (see `record_exposure_opportunity`) that actually resulted in an
+    /// infection - for validating that building-specific transmission parameters produce the
+    /// intended attack rates
+    ///
+    /// A `BuildingType` with no recorded opportunities reports `0.0`, rather than `NaN`
+    pub fn realised_attack_rate_by_building_type(&self) -> EnumMap<BuildingType, f64> {
+        let mut attack_rates = EnumMap::default();
+        for (building_type, opportunities) in &self.exposure_opportunities_by_building_type {
+            attack_rates[building_type] = if *opportunities == 0 {
+                0.0
+            } else {
+                self.exposure_successes_by_building_type[building_type] as f64 / *opportunities as f64
+            };
+        }
+        attack_rates
+    }
     pub fn add_exposure(&mut self, location: ID) -> Result<(), SimError> {
         self.global_stats.last_mut().expect("No global data recorded").citizen_exposed()?;
         // If building, expose the Output Area as well
         let time_step = self.current_time_step;
         let current_entry = &mut self.current_entry;
         if let ID::Building(building) = &location {
+            self.exposure_successes_by_building_type[*building.building_type()] += 1;
             let area_id = ID::OutputArea(building.output_area_code());
             let mut stat_entry = current_entry.entry(area_id).or_default();
             *stat_entry += 1;
-            ;
         }
         let mut stat_entry = current_entry.entry(location).or_default();
         *stat_entry += 1;
         Ok(())
     }
+    /// Records that the given Citizen has been infected (at least once) while resident of the given Output Area
+    ///
+    /// Reinfections of an already recorded Citizen do not count twice, so `attack_rate_by_area` always
+    /// reflects the fraction of distinct individuals ever infected, and `infections_by_age_timeseries`
+    /// only counts a Citizen's first-ever infection against their age band
+    pub fn record_ever_infected(&mut self, area: OutputAreaID, citizen: CitizenID, age: u16) {
+        let newly_infected = self.ever_infected_by_area.entry(area).or_default().insert(citizen);
+        if newly_infected {
+            let age_band = AgeContactMatrix::age_group(age);
+            *self.current_infections_by_age.entry(age_band).or_insert(0) += 1;
+            self.infection_onset
+                .entry(citizen)
+                .or_insert(self.current_time_step as u16);
+        }
+    }
+    /// The time step the given Citizen was first ever infected at, or `None` if they have never
+    /// been infected
+    pub fn infection_onset(&self, citizen: &CitizenID) -> Option<u16> {
+        self.infection_onset.get(citizen).copied()
+    }
+    /// Records that the given Citizen has received a vaccine dose, so `was_ever_vaccinated`
+    /// remains true even after their `DiseaseStatus` later moves on to `Exposed`/`Infected`
+    pub fn record_vaccination(&mut self, citizen: CitizenID) {
+        self.ever_vaccinated.insert(citizen);
+    }
+    /// Whether the given Citizen has received a vaccine dose at any point in the simulation
+    pub fn was_ever_vaccinated(&self, citizen: &CitizenID) -> bool {
+        self.ever_vaccinated.contains(citizen)
+    }
+    /// Returns how many distinct Citizens were newly infected on each time step, grouped by age
+    /// band (see `AgeContactMatrix::age_group`), so age groups driving a given wave can be compared
+    pub fn infections_by_age_timeseries(&self) -> &Vec<HashMap<usize, u32>> {
+        &self.infections_by_age_timeseries
+    }
+    /// Writes `infections_by_age_timeseries` out as a CSV, with one row per time step and one
+    /// column per age band (named by the lowest age in that `AGE_GROUP_WIDTH`-year bracket)
+    pub fn export_infections_by_age_csv(&self, filename: &str) -> anyhow::Result<()> {
+        let mut age_bands: Vec<usize> = self
+            .infections_by_age_timeseries
+            .iter()
+            .flat_map(|entry| entry.keys().copied())
+            .collect();
+        age_bands.sort_unstable();
+        age_bands.dedup();
+
+        let file = File::create(filename)
+            .context(format!("Failed to create infections by age CSV: {}", filename))?;
+        let mut writer = BufWriter::new(file);
+
+        write!(writer, "time_step")?;
+        for age_band in &age_bands {
+            write!(writer, ",age_{}", age_band * AGE_GROUP_WIDTH as usize)?;
+        }
+        writeln!(writer)?;
+
+        for (time_step, entry) in self.infections_by_age_timeseries.iter().enumerate() {
+            write!(writer, "{}", time_step)?;
+            for age_band in &age_bands {
+                write!(writer, ",{}", entry.get(age_band).copied().unwrap_or(0))?;
+            }
+            writeln!(writer)?;
+        }
+        writer.flush().context("Failed to flush infections by age CSV")?;
+        Ok(())
+    }
+    /// Records a disease transmission from `infector` to `infectee`, occurring at `onset` (the
+    /// current time step), for later computing the serial interval via `transmission_log`
+    ///
+    /// If `infector`'s own onset hasn't been recorded (e.g. they were part of the initial seeded
+    /// infections), `onset` is used for both, giving a serial interval of zero for that event
+    pub fn record_transmission(&mut self, infector: CitizenID, infectee: CitizenID, onset: u16) {
+        let infector_onset = self.infection_onset.get(&infector).copied().unwrap_or(onset);
+        self.infection_onset.entry(infectee).or_insert(onset);
+        self.transmission_log.record(infector, infector_onset, infectee, onset);
+    }
+    /// The recorded infector -> infectee transmission events, for epidemiological validation
+    pub fn transmission_log(&self) -> &TransmissionLog {
+        &self.transmission_log
+    }
+    /// Records the starting population of an Output Area, so the attack rate can be expressed as a fraction
+    pub fn set_area_population(&mut self, area: OutputAreaID, population: u32) {
+        self.area_population.insert(area, population);
+    }
+    /// Returns the cumulative fraction of each Output Area's population that has ever been infected (the attack rate)
+    ///
+    /// Areas with no recorded population default to a rate of 0.0
+    pub fn attack_rate_by_area(&self) -> HashMap<OutputAreaID, f64> {
+        self.area_population
+            .iter()
+            .map(|(area, population)| {
+                let infected = self
+                    .ever_infected_by_area
+                    .get(area)
+                    .map(|citizens| citizens.len())
+                    .unwrap_or(0);
+                let rate = if *population == 0 {
+                    0.0
+                } else {
+                    infected as f64 / *population as f64
+                };
+                (area.clone(), rate)
+            })
+            .collect()
+    }
+    /// Returns the `n` Buildings responsible for the most cumulative exposures recorded so far,
+    /// sorted from most to least, alongside their total exposure count
+    ///
+    /// Only exposures from time steps already passed to `next()` are counted - the current,
+    /// in-progress time step is not yet reflected
+    pub fn top_exposure_buildings(&self, n: usize) -> Vec<(BuildingID, u32)> {
+        let mut totals: Vec<(BuildingID, u32)> = self
+            .exposures_per_building_per_time_step
+            .iter()
+            .filter_map(|(location, counts)| match location {
+                ID::Building(building_id) => Some((building_id.clone(), counts.iter().sum())),
+                _ => None,
+            })
+            .collect();
+        totals.sort_by(|a, b| b.1.cmp(&a.1));
+        totals.truncate(n);
+        totals
+    }
+
     pub fn disease_exists(&self) -> bool {
         self.global_stats.last().expect("No data recorded").disease_exists()
     }
@@ -201,6 +444,186 @@ impl StatisticsRecorder {
         self.global_stats.last().expect("No data recorded").infected_percentage()
     }
     pub fn time_step(&self) -> u32 { self.current_time_step }
+
+    /// Returns the time step and infected count at the point of maximum prevalence (the epidemic peak)
+    ///
+    /// Returns `None` if no recorded time step has ever had an infected Citizen
+    pub fn peak(&self) -> Option<(u16, u32)> {
+        self.global_stats
+            .iter()
+            .map(|entry| (entry.time_step() as u16, entry.infected()))
+            .max_by_key(|(_, infected)| *infected)
+            .filter(|(_, infected)| *infected > 0)
+    }
+    /// The time step at which the epidemic peaked, or `None` if it never infected anyone - see `peak`
+    pub fn time_to_peak(&self) -> Option<u16> {
+        self.peak().map(|(time_step, _)| time_step)
+    }
+    /// The total number of distinct Citizens ever infected across the whole simulation, summed
+    /// across every Output Area - the "final size" of the epidemic
+    pub fn final_size(&self) -> u32 {
+        self.ever_infected_by_area
+            .values()
+            .map(|citizens| citizens.len() as u32)
+            .sum()
+    }
+    /// Estimates the local doubling time (in time steps) of the epidemic at each recorded time
+    /// step, from the growth in infected count over the preceding `window` steps - a metric
+    /// widely reported in public health situation reports alongside the epidemic peak
+    ///
+    /// Each entry aligns with the corresponding `global_stats` time step. The first `window`
+    /// entries are `None`, since there isn't yet a full window of history to compare against. An
+    /// entry is also `None` when the infected count is declining, since doubling time isn't a
+    /// meaningful concept for a shrinking epidemic, and `Some(f64::INFINITY)` when the infected
+    /// count is flat (zero growth never doubles)
+    pub fn doubling_time_series(&self, window: usize) -> Vec<Option<f64>> {
+        self.global_stats
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                if index < window {
+                    return None;
+                }
+                let previous = self.global_stats[index - window].infected();
+                let current = entry.infected();
+                if previous == 0 {
+                    return None;
+                }
+                match current.cmp(&previous) {
+                    std::cmp::Ordering::Less => None,
+                    std::cmp::Ordering::Equal => Some(f64::INFINITY),
+                    std::cmp::Ordering::Greater => {
+                        let growth_ratio = current as f64 / previous as f64;
+                        Some(window as f64 * std::f64::consts::LN_2 / growth_ratio.ln())
+                    }
+                }
+            })
+            .collect()
+    }
+    /// The cumulative number of distinct Citizens ever infected, by the end of each time step
+    ///
+    /// Derived from `infections_by_age_timeseries`'s per-step new infection counts, which (unlike
+    /// `global_stats`) are recorded on every time step regardless of `sampling_interval` - see
+    /// `cumulative_incidence_bands` for combining this across an ensemble of runs
+    pub fn cumulative_incidence_timeseries(&self) -> Vec<u32> {
+        let mut cumulative = 0;
+        self.infections_by_age_timeseries
+            .iter()
+            .map(|new_infections_by_age| {
+                cumulative += new_infections_by_age.values().sum::<u32>();
+                cumulative
+            })
+            .collect()
+    }
+    /// The expected "reported cases" time series a real surveillance system would have observed,
+    /// derived from the true new-infection counts in `infections_by_age_timeseries` via
+    /// `set_surveillance_model`'s configured delay and ascertainment fraction
+    ///
+    /// Returns `None` if no surveillance model has been configured
+    pub fn reported_cases_timeseries(&self) -> Option<Vec<f64>> {
+        let surveillance_model = self.surveillance_model.as_ref()?;
+        let true_new_infections: Vec<u32> = self
+            .infections_by_age_timeseries
+            .iter()
+            .map(|new_infections_by_age| new_infections_by_age.values().sum())
+            .collect();
+        Some(surveillance_model.apply(&true_new_infections))
+    }
+}
+
+/// A single recorded disease transmission, from an infector to the infectee they exposed
+#[derive(Debug, Clone, Serialize)]
+struct TransmissionEvent {
+    infector: CitizenID,
+    infectee: CitizenID,
+    /// The time step `infector` was themselves first exposed at
+    infector_onset: u16,
+    /// The time step `infectee` was exposed at
+    infectee_onset: u16,
+}
+
+/// Records every infector -> infectee disease transmission, so epidemiological summary statistics
+/// like the serial interval can be computed for validating the simulated disease against real data
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct TransmissionLog {
+    events: Vec<TransmissionEvent>,
+}
+
+impl TransmissionLog {
+    fn record(&mut self, infector: CitizenID, infector_onset: u16, infectee: CitizenID, infectee_onset: u16) {
+        self.events.push(TransmissionEvent { infector, infectee, infector_onset, infectee_onset });
+    }
+    /// Returns the serial interval (in time steps) of every recorded transmission - the time between
+    /// the infector's and the infectee's infection onset
+    pub fn serial_intervals(&self) -> Vec<u16> {
+        self.events
+            .iter()
+            .map(|event| event.infectee_onset.saturating_sub(event.infector_onset))
+            .collect()
+    }
+    /// The mean serial interval across all recorded transmissions, or `None` if none have been recorded
+    pub fn mean_serial_interval(&self) -> Option<f64> {
+        let intervals = self.serial_intervals();
+        if intervals.is_empty() {
+            return None;
+        }
+        Some(intervals.iter().map(|interval| *interval as f64).sum::<f64>() / intervals.len() as f64)
+    }
+    /// The median serial interval across all recorded transmissions, or `None` if none have been recorded
+    pub fn median_serial_interval(&self) -> Option<f64> {
+        let mut intervals = self.serial_intervals();
+        if intervals.is_empty() {
+            return None;
+        }
+        intervals.sort_unstable();
+        let midpoint = intervals.len() / 2;
+        Some(if intervals.len() % 2 == 0 {
+            (intervals[midpoint - 1] as f64 + intervals[midpoint] as f64) / 2.0
+        } else {
+            intervals[midpoint] as f64
+        })
+    }
+    /// Returns the generation interval (in time steps) of every recorded transmission - the time
+    /// between the infector's own infection and the moment they infect the named infectee
+    ///
+    /// `infector_onset`/`infectee_onset` already record the time step each Citizen was exposed at,
+    /// not a separate symptom onset time, so this is numerically identical to `serial_intervals` in
+    /// this model. It's exposed under its own name since it's the infectiousness-based figure
+    /// real-time R estimation actually wants, and a future symptom-onset tracking feature should
+    /// change this implementation rather than `serial_intervals`'s
+    pub fn generation_intervals(&self) -> Vec<u16> {
+        self.serial_intervals()
+    }
+    /// The mean generation interval across all recorded transmissions, or `None` if none have been recorded
+    pub fn mean_generation_interval(&self) -> Option<f64> {
+        let intervals = self.generation_intervals();
+        if intervals.is_empty() {
+            return None;
+        }
+        Some(intervals.iter().map(|interval| *interval as f64).sum::<f64>() / intervals.len() as f64)
+    }
+    /// Counts recorded transmissions between Citizens who reside in different Output Areas, keyed by
+    /// `(infector's Output Area, infectee's Output Area)`, for visualising spatial spread corridors
+    /// (e.g. a flow/chord diagram)
+    ///
+    /// `citizen_residence` maps a Citizen to the Output Area they reside in; a transmission where
+    /// either party is missing from it, or where both reside in the same Output Area, is excluded
+    pub fn flow_matrix(
+        &self,
+        citizen_residence: &HashMap<CitizenID, OutputAreaID>,
+    ) -> HashMap<(OutputAreaID, OutputAreaID), u32> {
+        let mut flows: HashMap<(OutputAreaID, OutputAreaID), u32> = HashMap::new();
+        for event in &self.events {
+            let infector_area = citizen_residence.get(&event.infector);
+            let infectee_area = citizen_residence.get(&event.infectee);
+            if let (Some(origin), Some(destination)) = (infector_area, infectee_area) {
+                if origin != destination {
+                    *flows.entry((origin.clone(), destination.clone())).or_insert(0) += 1;
+                }
+            }
+        }
+        flows
+    }
 }
 
 /// A snapshot of the disease per time step
@@ -212,6 +635,9 @@ pub struct StatisticEntry {
     infected: u32,
     recovered: u32,
     pub vaccinated: u32,
+    /// The number of Exposed/Infected/Recovered Citizens whose infection is/was asymptomatic
+    asymptomatic: u32,
+    deceased: u32,
 }
 
 impl StatisticEntry {
@@ -223,6 +649,8 @@ impl StatisticEntry {
             infected: 0,
             recovered: 0,
             vaccinated: 0,
+            asymptomatic: 0,
+            deceased: 0,
         }
     }
     pub fn time_step(&self) -> u32 {
@@ -243,31 +671,56 @@ impl StatisticEntry {
     pub fn vaccinated(&self) -> u32 {
         self.vaccinated
     }
+    pub fn asymptomatic(&self) -> u32 {
+        self.asymptomatic
+    }
+    pub fn deceased(&self) -> u32 {
+        self.deceased
+    }
+    /// The proportion of Exposed/Infected/Recovered Citizens whose infection is/was asymptomatic
+    pub fn asymptomatic_fraction(&self) -> f64 {
+        let ever_infected = self.exposed + self.infected + self.recovered;
+        if ever_infected == 0 {
+            0.0
+        } else {
+            self.asymptomatic as f64 / ever_infected as f64
+        }
+    }
 
     #[inline]
     pub fn total(&self) -> u32 {
-        self.susceptible() + self.exposed() + self.infected() + self.recovered() + self.vaccinated()
+        self.susceptible() + self.exposed() + self.infected() + self.recovered() + self.vaccinated() + self.deceased()
     }
 
     pub fn infected_percentage(&self) -> f64 {
         self.infected as f64 / (self.total() as f64)
     }
     /// Adds a new Citizen to the log, and increments the stage the citizen is at by one
-    pub fn add_citizen(&mut self, disease_status: &DiseaseStatus) {
-        match disease_status {
+    pub fn add_citizen(&mut self, citizen: &Citizen) {
+        match citizen.disease_status {
             DiseaseStatus::Susceptible => {
                 self.susceptible += 1;
             }
             DiseaseStatus::Exposed(_) => {
                 self.exposed += 1;
+                if citizen.is_asymptomatic {
+                    self.asymptomatic += 1;
+                }
             }
-            DiseaseStatus::Infected(_) => {
+            DiseaseStatus::Infected { .. } => {
                 self.infected += 1;
+                if citizen.is_asymptomatic {
+                    self.asymptomatic += 1;
+                }
             }
             DiseaseStatus::Recovered => {
                 self.recovered += 1;
+                if citizen.is_asymptomatic {
+                    self.asymptomatic += 1;
+                }
             }
-            DiseaseStatus::Vaccinated => self.vaccinated += 1,
+            DiseaseStatus::Vaccinated(_) => self.vaccinated += 1,
+            DiseaseStatus::Deceased => self.deceased += 1,
         }
     }
     /// When a citizen has been exposed, the susceptible count drops by one, and exposure count increases by 1
@@ -298,6 +751,8 @@ impl AddAssign for StatisticEntry {
         self.infected += rhs.infected;
         self.recovered += rhs.recovered;
         self.vaccinated += rhs.vaccinated;
+        self.asymptomatic += rhs.asymptomatic;
+        self.deceased += rhs.deceased;
     }
 }
 
@@ -310,6 +765,8 @@ impl Default for StatisticEntry {
             infected: 0,
             recovered: 0,
             vaccinated: 0,
+            asymptomatic: 0,
+            deceased: 0,
         }
     }
 }
@@ -318,8 +775,464 @@ impl Display for StatisticEntry {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Hour: {: >4}, Total: {: >10}, Susceptible: {: >10}, {:.2}%, Exposed: {: >10}, {:.2}%, Infected: {: >10}, {:.2}%, Recovered: {: >10}, {:.2}% Vaccinated: {: >10}, {:.2}%",
-            self.time_step, self.total().to_formatted_string(&NUMBER_FORMATTING), self.susceptible().to_formatted_string(&NUMBER_FORMATTING), (self.susceptible() as f64 / self.total() as f64) * 100.0, self.exposed().to_formatted_string(&NUMBER_FORMATTING), (self.exposed() as f64 / self.total() as f64) * 100.0, self.infected().to_formatted_string(&NUMBER_FORMATTING), (self.infected() as f64 / self.total() as f64) * 100.0, self.recovered().to_formatted_string(&NUMBER_FORMATTING), (self.recovered() as f64 / self.total() as f64) * 100.0, self.vaccinated().to_formatted_string(&NUMBER_FORMATTING), (self.vaccinated() as f64 / self.total() as f64) * 100.0,
+            "Hour: {: >4}, Total: {: >10}, Susceptible: {: >10}, {:.2}%, Exposed: {: >10}, {:.2}%, Infected: {: >10}, {:.2}%, Recovered: {: >10}, {:.2}% Vaccinated: {: >10}, {:.2}% Deceased: {: >10}, {:.2}%",
+            self.time_step, self.total().to_formatted_string(&NUMBER_FORMATTING), self.susceptible().to_formatted_string(&NUMBER_FORMATTING), (self.susceptible() as f64 / self.total() as f64) * 100.0, self.exposed().to_formatted_string(&NUMBER_FORMATTING), (self.exposed() as f64 / self.total() as f64) * 100.0, self.infected().to_formatted_string(&NUMBER_FORMATTING), (self.infected() as f64 / self.total() as f64) * 100.0, self.recovered().to_formatted_string(&NUMBER_FORMATTING), (self.recovered() as f64 / self.total() as f64) * 100.0, self.vaccinated().to_formatted_string(&NUMBER_FORMATTING), (self.vaccinated() as f64 / self.total() as f64) * 100.0, self.deceased().to_formatted_string(&NUMBER_FORMATTING), (self.deceased() as f64 / self.total() as f64) * 100.0,
         )
     }
 }
+
+/// One time step's cumulative-incidence summary across an ensemble of runs - the mean, and the
+/// requested percentile values, so a calling crate can plot a mean line with confidence-interval
+/// shading around it without re-deriving the statistics itself
+#[derive(Debug, Clone, Serialize)]
+pub struct CumulativeIncidenceBand {
+    pub time_step: u32,
+    pub mean: f64,
+    /// Parallel to the `percentiles` slice passed to `cumulative_incidence_bands`
+    pub percentiles: Vec<f64>,
+}
+
+/// Computes the mean and requested percentile bands of cumulative incidence, per time step, across
+/// an ensemble of independent simulation runs
+///
+/// Runs that finish early (e.g. the disease dies out) are padded by repeating their final
+/// cumulative incidence value for the remaining time steps, rather than excluded, so a run that
+/// burns out early doesn't bias the ensemble average against runs that kept going
+///
+/// `percentiles` are fractions in `[0.0, 1.0]` - e.g. `&[0.1, 0.5, 0.9]` for the median with an 80%
+/// band either side
+pub fn cumulative_incidence_bands(
+    runs: &[StatisticsRecorder],
+    percentiles: &[f64],
+) -> Vec<CumulativeIncidenceBand> {
+    let series: Vec<Vec<u32>> = runs.iter().map(StatisticsRecorder::cumulative_incidence_timeseries).collect();
+    let step_count = series.iter().map(|run| run.len()).max().unwrap_or(0);
+    (0..step_count)
+        .map(|step| {
+            let mut values: Vec<f64> = series
+                .iter()
+                .map(|run| run.get(step).or_else(|| run.last()).copied().unwrap_or(0) as f64)
+                .collect();
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            values.sort_by(|a, b| a.partial_cmp(b).expect("Cumulative incidence is never NaN"));
+            CumulativeIncidenceBand {
+                time_step: step as u32,
+                mean,
+                percentiles: percentiles.iter().map(|p| percentile(&values, *p)).collect(),
+            }
+        })
+        .collect()
+}
+
+/// Linearly interpolates the given `percentile` (a fraction in `[0.0, 1.0]`) out of `sorted_values`
+fn percentile(sorted_values: &[f64], percentile: f64) -> f64 {
+    if sorted_values.is_empty() {
+        return 0.0;
+    }
+    let rank = percentile * (sorted_values.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    if lower == upper {
+        sorted_values[lower]
+    } else {
+        let weight = rank - lower as f64;
+        sorted_values[lower] * (1.0 - weight) + sorted_values[upper] * weight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::contact_matrix::AgeContactMatrix;
+    use crate::disease::DiseaseStatus;
+    use crate::models::building::{BuildingID, BuildingType};
+    use crate::models::citizen::{Citizen, CitizenID, Occupation};
+    use crate::models::ID;
+    use crate::models::output_area::OutputAreaID;
+    use crate::statistics::{cumulative_incidence_bands, StatisticEntry, StatisticsRecorder};
+    use crate::surveillance::SurveillanceModel;
+
+    /// A closed area where every resident is eventually infected should report an attack rate of 1.0
+    #[test]
+    fn attack_rate_is_one_when_everyone_infected() {
+        let mut recorder = StatisticsRecorder::default();
+        let area = OutputAreaID::from_code_and_index("area".to_string(), 0);
+        recorder.set_area_population(area.clone(), 3);
+        for index in 0..3 {
+            recorder.record_ever_infected(area.clone(), CitizenID::from_indexes(index), 30);
+        }
+        let attack_rate = recorder.attack_rate_by_area();
+        assert_eq!(Some(&1.0), attack_rate.get(&area));
+    }
+
+    /// Reinfecting the same Citizen must not be double-counted towards the attack rate
+    #[test]
+    fn reinfections_do_not_double_count() {
+        let mut recorder = StatisticsRecorder::default();
+        let area = OutputAreaID::from_code_and_index("area".to_string(), 0);
+        recorder.set_area_population(area.clone(), 4);
+        let citizen = CitizenID::from_indexes(0);
+        recorder.record_ever_infected(area.clone(), citizen, 30);
+        recorder.record_ever_infected(area.clone(), citizen, 30);
+        let attack_rate = recorder.attack_rate_by_area();
+        assert_eq!(Some(&0.25), attack_rate.get(&area));
+    }
+
+    /// A two generation transmission chain (A infects B, B infects C, each after the same number of
+    /// time steps) should report that fixed gap as the serial interval for both recorded events
+    #[test]
+    fn serial_interval_matches_the_gap_between_transmission_generations() {
+        let mut recorder = StatisticsRecorder::default();
+        let seed_citizen = CitizenID::from_indexes(0);
+        let citizen_a = CitizenID::from_indexes(1);
+        let citizen_b = CitizenID::from_indexes(2);
+        let citizen_c = CitizenID::from_indexes(3);
+        let generation_time = 5;
+
+        // Seed Citizen A's own onset at time step 0, so the A -> B transmission below has a
+        // non-zero serial interval to measure
+        recorder.record_transmission(seed_citizen, citizen_a, 0);
+        recorder.record_transmission(citizen_a, citizen_b, generation_time);
+        recorder.record_transmission(citizen_b, citizen_c, 2 * generation_time);
+
+        let serial_intervals = recorder.transmission_log().serial_intervals();
+        assert_eq!(&serial_intervals[1..], [generation_time, generation_time]);
+        assert_eq!(
+            recorder.transmission_log().mean_serial_interval(),
+            Some((2 * generation_time) as f64 / 3.0)
+        );
+    }
+
+    /// With a sampling interval of 6, stepping 13 times should keep exactly `ceil(13 / 6) = 3`
+    /// entries - one starting at each of steps 1, 7 and 13
+    #[test]
+    fn sampling_interval_keeps_one_entry_per_interval() {
+        let mut recorder = StatisticsRecorder::default();
+        recorder.set_sampling_interval(6);
+        let steps: usize = 13;
+        for _ in 0..steps {
+            recorder.next().expect("Failed to advance time step");
+        }
+        let expected_entries = (steps + 6 - 1) / 6;
+        assert_eq!(recorder.global_stats.len(), expected_entries);
+    }
+
+    /// The generation interval of a recorded transmission should equal the gap between the
+    /// infector's own infection (exposure) and the secondary exposure they caused
+    #[test]
+    fn generation_interval_matches_the_gap_to_the_secondary_exposure() {
+        let mut recorder = StatisticsRecorder::default();
+        let infector = CitizenID::from_indexes(0);
+        let infectee = CitizenID::from_indexes(1);
+        let infector_infected_at = 3;
+        let secondary_exposure_at = 10;
+
+        recorder.record_transmission(CitizenID::from_indexes(99), infector, infector_infected_at);
+        recorder.record_transmission(infector, infectee, secondary_exposure_at);
+
+        let generation_intervals = recorder.transmission_log().generation_intervals();
+        assert_eq!(
+            generation_intervals[1],
+            secondary_exposure_at - infector_infected_at
+        );
+        assert_eq!(
+            recorder.transmission_log().mean_generation_interval(),
+            recorder.transmission_log().mean_serial_interval()
+        );
+    }
+
+    /// `asymptomatic_fraction` should reflect the proportion of Exposed/Infected/Recovered Citizens
+    /// whose infection is asymptomatic
+    #[test]
+    fn asymptomatic_fraction_reflects_infected_citizens() {
+        let mut entry = StatisticEntry::with_time_step(0);
+        let output_area_id = OutputAreaID::from_code_and_index("area".to_string(), 0);
+        let household_id = BuildingID::new(output_area_id, BuildingType::Household, 0);
+        for (index, is_asymptomatic) in [(0, true), (1, false), (2, false), (3, true)] {
+            let mut citizen = Citizen::new(
+                CitizenID::from_indexes(index),
+                household_id.clone(),
+                household_id.clone(),
+                30,
+                Occupation::Student,
+                false,
+                is_asymptomatic,
+                false,
+                24,
+            );
+            citizen.disease_status = DiseaseStatus::Infected { elapsed: 0, duration: 336 };
+            entry.add_citizen(&citizen);
+        }
+        assert_eq!(entry.asymptomatic_fraction(), 0.5);
+    }
+
+    /// In a two building scenario where one building hosts an infected cluster, `top_exposure_buildings`
+    /// should rank that building above one with only a single exposure
+    #[test]
+    fn top_exposure_buildings_ranks_the_infected_cluster_highest() {
+        let mut recorder = StatisticsRecorder::default();
+        recorder.next().expect("Failed to start recording");
+        let mut susceptible_pool = StatisticEntry::with_time_step(0);
+        for index in 0..20 {
+            let citizen = Citizen::new(
+                CitizenID::from_indexes(index),
+                BuildingID::new(
+                    OutputAreaID::from_code_and_index("area".to_string(), 0),
+                    BuildingType::Household,
+                    0,
+                ),
+                BuildingID::new(
+                    OutputAreaID::from_code_and_index("area".to_string(), 0),
+                    BuildingType::Household,
+                    0,
+                ),
+                30,
+                Occupation::Student,
+                false,
+                false,
+                false,
+                24,
+            );
+            susceptible_pool.add_citizen(&citizen);
+        }
+        recorder.update_global_stats_entry(susceptible_pool);
+
+        let output_area_id = OutputAreaID::from_code_and_index("area".to_string(), 0);
+        let cluster_building = BuildingID::new(output_area_id.clone(), BuildingType::Household, 0);
+        let quiet_building = BuildingID::new(output_area_id, BuildingType::Household, 1);
+        for _ in 0..10 {
+            recorder
+                .add_exposure(ID::Building(cluster_building.clone()))
+                .expect("Failed to record exposure");
+        }
+        recorder
+            .add_exposure(ID::Building(quiet_building))
+            .expect("Failed to record exposure");
+        recorder.next().expect("Failed to advance time step");
+
+        let top = recorder.top_exposure_buildings(1);
+        assert_eq!(top, vec![(cluster_building, 10)]);
+    }
+
+    fn infected_citizens(count: u32) -> StatisticEntry {
+        let mut entry = StatisticEntry::with_time_step(0);
+        for index in 0..count {
+            let household_id = BuildingID::new(
+                OutputAreaID::from_code_and_index("area".to_string(), 0),
+                BuildingType::Household,
+                0,
+            );
+            let mut citizen = Citizen::new(
+                CitizenID::from_indexes(index),
+                household_id.clone(),
+                household_id,
+                30,
+                Occupation::Student,
+                false,
+                false,
+                false,
+                24,
+            );
+            citizen.disease_status = DiseaseStatus::Infected { elapsed: 0, duration: 336 };
+            entry.add_citizen(&citizen);
+        }
+        entry
+    }
+
+    /// On a synthetic curve that rises then falls, `peak` and `time_to_peak` should report the
+    /// step with the highest infected count, not simply the last or first recorded step
+    #[test]
+    fn peak_reports_the_step_with_the_highest_infected_count() {
+        let mut recorder = StatisticsRecorder::default();
+        for count in [2, 5, 9, 4, 1] {
+            recorder.next().expect("Failed to advance time step");
+            recorder.update_global_stats_entry(infected_citizens(count));
+        }
+        assert_eq!(recorder.peak(), Some((3, 9)));
+        assert_eq!(recorder.time_to_peak(), Some(3));
+    }
+
+    /// With no recorded time steps at all, there is no peak to report
+    #[test]
+    fn peak_is_none_when_nothing_has_been_recorded() {
+        let recorder = StatisticsRecorder::default();
+        assert_eq!(recorder.peak(), None);
+        assert_eq!(recorder.time_to_peak(), None);
+    }
+
+    /// If nobody is ever infected, there is no peak to report, even once time steps have been recorded
+    #[test]
+    fn peak_is_none_when_nobody_is_ever_infected() {
+        let mut recorder = StatisticsRecorder::default();
+        recorder.next().expect("Failed to advance time step");
+        recorder.next().expect("Failed to advance time step");
+        assert_eq!(recorder.peak(), None);
+        assert_eq!(recorder.time_to_peak(), None);
+    }
+
+    /// `final_size` should total the distinct Citizens ever recorded as infected, across every
+    /// Output Area, not just the currently infected count
+    #[test]
+    fn final_size_sums_ever_infected_citizens_across_areas() {
+        let mut recorder = StatisticsRecorder::default();
+        let first_area = OutputAreaID::from_code_and_index("area".to_string(), 0);
+        let second_area = OutputAreaID::from_code_and_index("area".to_string(), 1);
+        recorder.record_ever_infected(first_area.clone(), CitizenID::from_indexes(0), 30);
+        recorder.record_ever_infected(first_area, CitizenID::from_indexes(1), 30);
+        recorder.record_ever_infected(second_area, CitizenID::from_indexes(2), 30);
+        assert_eq!(recorder.final_size(), 3);
+    }
+
+    /// Seeding most of a step's infections among a specific age band should make that band
+    /// dominate `infections_by_age_timeseries`'s entry for that step
+    #[test]
+    fn infections_by_age_timeseries_is_dominated_by_the_seeded_age_band() {
+        let mut recorder = StatisticsRecorder::default();
+        let area = OutputAreaID::from_code_and_index("area".to_string(), 0);
+
+        recorder.next().expect("Failed to advance time step");
+        for index in 0..8 {
+            recorder.record_ever_infected(area.clone(), CitizenID::from_indexes(index), 75);
+        }
+        recorder.record_ever_infected(area.clone(), CitizenID::from_indexes(8), 20);
+        recorder.record_ever_infected(area.clone(), CitizenID::from_indexes(9), 5);
+        // Flushes the step above into `infections_by_age_timeseries`
+        recorder.next().expect("Failed to advance time step");
+
+        let timeseries = recorder.infections_by_age_timeseries();
+        let first_step = &timeseries[0];
+        let seeded_band = AgeContactMatrix::age_group(75);
+        let dominant_band = first_step
+            .iter()
+            .max_by_key(|(_, count)| **count)
+            .map(|(band, _)| *band)
+            .expect("Expected at least one age band recorded");
+
+        assert_eq!(dominant_band, seeded_band);
+        assert_eq!(first_step.get(&seeded_band), Some(&8));
+    }
+
+    /// On a synthetic curve whose infected count doubles every 3 steps, the estimated doubling
+    /// time over a window of 3 should be close to 3 at every step with enough history
+    #[test]
+    fn doubling_time_series_matches_a_synthetic_doubling_curve() {
+        let mut recorder = StatisticsRecorder::default();
+        let factor = 2f64.powf(1.0 / 3.0);
+        let counts: Vec<u32> = (0..10)
+            .map(|step| (100.0 * factor.powi(step)).round() as u32)
+            .collect();
+        for count in &counts {
+            recorder.next().expect("Failed to advance time step");
+            recorder.update_global_stats_entry(infected_citizens(*count));
+        }
+
+        let doubling_times = recorder.doubling_time_series(3);
+        for entry in &doubling_times[..3] {
+            assert_eq!(*entry, None);
+        }
+        for entry in &doubling_times[3..] {
+            let doubling_time = entry.expect("Expected a doubling time estimate for a growing curve");
+            assert!((doubling_time - 3.0).abs() < 0.1, "Expected ~3, got {}", doubling_time);
+        }
+    }
+
+    /// A flat infected count should report an infinite doubling time, and a declining one should
+    /// report no doubling time at all, rather than a misleading finite negative estimate
+    #[test]
+    fn doubling_time_series_handles_flat_and_declining_counts() {
+        let mut recorder = StatisticsRecorder::default();
+        for count in [50, 50, 50, 50, 30, 10] {
+            recorder.next().expect("Failed to advance time step");
+            recorder.update_global_stats_entry(infected_citizens(count));
+        }
+
+        let doubling_times = recorder.doubling_time_series(3);
+        assert_eq!(doubling_times[3], Some(f64::INFINITY));
+        assert_eq!(doubling_times[4], None);
+        assert_eq!(doubling_times[5], None);
+    }
+
+    /// With no surveillance model configured, `reported_cases_timeseries` should report `None`
+    /// rather than silently falling back to the true counts
+    #[test]
+    fn reported_cases_timeseries_is_none_without_a_configured_surveillance_model() {
+        let mut recorder = StatisticsRecorder::default();
+        recorder.next().expect("Failed to initialise the first time step");
+        assert_eq!(recorder.reported_cases_timeseries(), None);
+    }
+
+    /// With an instantaneous surveillance model (no delay, full ascertainment), reported cases
+    /// should exactly equal the true new infections recorded each time step; with a delay, they
+    /// should be shifted later instead
+    #[test]
+    fn reported_cases_match_true_infections_without_delay_and_shift_with_it() {
+        let mut recorder = synthetic_run(&[0, 5, 0]);
+
+        recorder.set_surveillance_model(SurveillanceModel::instantaneous());
+        assert_eq!(recorder.reported_cases_timeseries(), Some(vec![0.0, 5.0, 0.0]));
+
+        recorder.set_surveillance_model(SurveillanceModel::new(1.0, vec![0.0, 1.0]));
+        assert_eq!(recorder.reported_cases_timeseries(), Some(vec![0.0, 0.0, 5.0, 0.0]));
+    }
+
+    /// Builds a synthetic run recording `infections_per_step[i]` newly-infected Citizens at step `i`
+    fn synthetic_run(infections_per_step: &[u32]) -> StatisticsRecorder {
+        let mut recorder = StatisticsRecorder::default();
+        let area = OutputAreaID::from_code_and_index("area".to_string(), 0);
+        let mut next_citizen_index = 0;
+        // The initial `next()` only starts the first time step's recording - it doesn't flush
+        // anything, since there's nothing recorded yet to flush
+        recorder.next().expect("Failed to initialise the first time step");
+        for count in infections_per_step {
+            for _ in 0..*count {
+                recorder.record_ever_infected(area.clone(), CitizenID::from_indexes(next_citizen_index), 30);
+                next_citizen_index += 1;
+            }
+            // Flushes the infections just recorded into `infections_by_age_timeseries`, and starts
+            // the next time step
+            recorder.next().expect("Failed to advance time step");
+        }
+        recorder
+    }
+
+    /// Across three runs with consistently low/medium/high infection counts, the median band
+    /// should lie between the min and max run's cumulative incidence at every time step
+    #[test]
+    fn median_band_lies_between_the_min_and_max_runs_at_each_step() {
+        let low_run = synthetic_run(&[1, 1, 1, 1]);
+        let mid_run = synthetic_run(&[2, 2, 2, 2]);
+        let high_run = synthetic_run(&[5, 5, 5, 5]);
+
+        let low_series = low_run.cumulative_incidence_timeseries();
+        let high_series = high_run.cumulative_incidence_timeseries();
+        let bands = cumulative_incidence_bands(&[low_run, mid_run, high_run], &[0.5]);
+
+        for (step, band) in bands.iter().enumerate() {
+            let low = low_series[step] as f64;
+            let high = high_series[step] as f64;
+            let median = band.percentiles[0];
+            assert!(
+                median >= low && median <= high,
+                "Expected the median ({}) at step {} to lie between the min ({}) and max ({}) runs",
+                median,
+                step,
+                low,
+                high
+            );
+        }
+    }
+
+    /// A run that ends early should have its final cumulative incidence padded forward, rather
+    /// than excluded, so it still contributes to later time steps' bands
+    #[test]
+    fn short_runs_are_padded_with_their_final_value() {
+        let short_run = synthetic_run(&[3]);
+        let long_run = synthetic_run(&[3, 0, 0]);
+
+        let bands = cumulative_incidence_bands(&[short_run, long_run], &[0.5]);
+
+        assert_eq!(bands.len(), 3);
+        for band in &bands {
+            assert_eq!(band.mean, 3.0);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+/*
+ * Epidemic Simulation Using Census Data (ESUCD)
+ * Copyright (c)  2022. Sam Ralph
+ *
+ * This file is part of ESUCD.
+ *
+ * ESUCD is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * ESUCD is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with ESUCD.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Converts a time series of true new infections into the "reported cases" a real surveillance
+/// system would have observed, by spreading each time step's true count forward according to a
+/// reporting-delay distribution and scaling it down by an ascertainment fraction
+///
+/// Real case data is never instantaneous or complete - it lags behind true infections by however
+/// long testing and reporting take, and it misses cases that are never tested at all. Applying this
+/// to the model's true new-infection counts lets them be compared against real reported-case time
+/// series on equal footing
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SurveillanceModel {
+    /// The fraction of true infections that are ever reported at all
+    pub ascertainment_fraction: f64,
+    /// A discrete probability distribution over how many time steps elapse between a true infection
+    /// and it being reported - `reporting_delay[0]` is same-step reporting, `reporting_delay[1]` is
+    /// reported one step later, and so on
+    ///
+    /// Doesn't need to sum to exactly `1.0` - a lower sum just reports fewer of the eventual cases,
+    /// which has the same effect as lowering `ascertainment_fraction` further
+    reporting_delay: Vec<f64>,
+}
+
+impl SurveillanceModel {
+    /// Builds a surveillance model from an explicit ascertainment fraction and reporting-delay
+    /// distribution
+    ///
+    /// Panics if `reporting_delay` is empty, since there would be nowhere to place a reported case
+    pub fn new(ascertainment_fraction: f64, reporting_delay: Vec<f64>) -> SurveillanceModel {
+        assert!(
+            !reporting_delay.is_empty(),
+            "SurveillanceModel requires a non-empty reporting delay distribution"
+        );
+        SurveillanceModel { ascertainment_fraction, reporting_delay }
+    }
+
+    /// A surveillance model with no delay and perfect ascertainment - reported cases exactly equal
+    /// true new infections, on the same time step they occur
+    pub fn instantaneous() -> SurveillanceModel {
+        SurveillanceModel { ascertainment_fraction: 1.0, reporting_delay: vec![1.0] }
+    }
+
+    /// Converts a time series of true new infections per time step into expected reported cases per
+    /// time step
+    ///
+    /// The returned series is longer than `true_new_infections` by `reporting_delay.len() - 1`
+    /// entries, since delayed reports from the final time steps continue to arrive after the
+    /// observed period ends
+    pub fn apply(&self, true_new_infections: &[u32]) -> Vec<f64> {
+        let max_delay = self.reporting_delay.len();
+        let mut reported = vec![0.0; true_new_infections.len() + max_delay - 1];
+        for (time_step, &true_count) in true_new_infections.iter().enumerate() {
+            let ascertained = true_count as f64 * self.ascertainment_fraction;
+            for (delay, weight) in self.reporting_delay.iter().enumerate() {
+                reported[time_step + delay] += ascertained * weight;
+            }
+        }
+        reported
+    }
+}
+
+impl Default for SurveillanceModel {
+    fn default() -> Self {
+        SurveillanceModel::instantaneous()
+    }
+}
+
+/// Deserialized separately from the derive macro so that a config file can't smuggle in an empty
+/// `reporting_delay`, which would otherwise panic later in `apply()` instead of failing at load time
+impl<'de> Deserialize<'de> for SurveillanceModel {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Raw {
+            ascertainment_fraction: f64,
+            reporting_delay: Vec<f64>,
+        }
+        let raw = Raw::deserialize(deserializer)?;
+        if raw.reporting_delay.is_empty() {
+            return Err(serde::de::Error::custom(
+                "SurveillanceModel requires a non-empty reporting delay distribution",
+            ));
+        }
+        Ok(SurveillanceModel {
+            ascertainment_fraction: raw.ascertainment_fraction,
+            reporting_delay: raw.reporting_delay,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::surveillance::SurveillanceModel;
+
+    /// With zero delay and full ascertainment, reported cases should exactly equal true new
+    /// infections, time step for time step
+    #[test]
+    fn instantaneous_model_reports_true_infections_unchanged() {
+        let model = SurveillanceModel::instantaneous();
+        let true_new_infections = vec![0, 5, 20, 15, 3, 0];
+
+        let reported = model.apply(&true_new_infections);
+
+        let expected: Vec<f64> = true_new_infections.iter().map(|&count| count as f64).collect();
+        assert_eq!(reported, expected);
+    }
+
+    /// A one-step reporting delay should shift every true infection count one time step later,
+    /// rather than reporting it on the step it actually occurred
+    #[test]
+    fn delayed_model_shifts_reported_cases_later() {
+        let model = SurveillanceModel::new(1.0, vec![0.0, 1.0]);
+        let true_new_infections = vec![10, 20, 30];
+
+        let reported = model.apply(&true_new_infections);
+
+        assert_eq!(reported, vec![0.0, 10.0, 20.0, 30.0]);
+    }
+
+    /// Under-ascertainment should scale down every reported count by the configured fraction,
+    /// without affecting how the delay distribution spreads it over time
+    #[test]
+    fn partial_ascertainment_scales_down_reported_cases() {
+        let model = SurveillanceModel::new(0.5, vec![1.0]);
+        let true_new_infections = vec![10, 20];
+
+        let reported = model.apply(&true_new_infections);
+
+        assert_eq!(reported, vec![5.0, 10.0]);
+    }
+
+    /// Deserializing a config with an empty `reporting_delay` must fail cleanly rather than
+    /// producing a value that panics later in `apply()`
+    #[test]
+    fn deserializing_empty_reporting_delay_fails() {
+        let result: Result<SurveillanceModel, _> =
+            serde_json::from_str(r#"{"ascertainment_fraction":1.0,"reporting_delay":[]}"#);
+
+        assert!(result.is_err());
+    }
+}
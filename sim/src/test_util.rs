@@ -0,0 +1,29 @@
+/*
+ * Epidemic Simulation Using Census Data (ESUCD)
+ * Copyright (c)  2022. Sam Ralph
+ *
+ * This file is part of ESUCD.
+ *
+ * ESUCD is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * ESUCD is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with ESUCD.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+
+//! Shared helpers for `#[cfg(test)]` modules across the crate, so fixtures used by more than one
+//! test module aren't pasted into each of them separately
+
+/// The population variance of a sample - used by tests that assert one distribution is more
+/// spread out than another, rather than pinning exact sampled values
+pub(crate) fn variance(values: &[f64]) -> f64 {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / values.len() as f64
+}
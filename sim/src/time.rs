@@ -0,0 +1,86 @@
+/*
+ * Epidemic Simulation Using Census Data (ESUCD)
+ * Copyright (c)  2022. Sam Ralph
+ *
+ * This file is part of ESUCD.
+ *
+ * ESUCD is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * ESUCD is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with ESUCD.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+
+/// A day of the week, used to phase weekend-dependent effects (e.g. reduced commuting) against the
+/// simulation's `time_step`, rather than assuming every run starts on a Monday
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DayOfWeek {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl DayOfWeek {
+    /// The day after this one, wrapping from Sunday back to Monday
+    pub fn next_day(&self) -> DayOfWeek {
+        match self {
+            DayOfWeek::Monday => DayOfWeek::Tuesday,
+            DayOfWeek::Tuesday => DayOfWeek::Wednesday,
+            DayOfWeek::Wednesday => DayOfWeek::Thursday,
+            DayOfWeek::Thursday => DayOfWeek::Friday,
+            DayOfWeek::Friday => DayOfWeek::Saturday,
+            DayOfWeek::Saturday => DayOfWeek::Sunday,
+            DayOfWeek::Sunday => DayOfWeek::Monday,
+        }
+    }
+    pub fn is_weekend(&self) -> bool {
+        matches!(self, DayOfWeek::Saturday | DayOfWeek::Sunday)
+    }
+    /// Advances this day by `days` whole days, wrapping around the week as many times as needed
+    pub fn advance_by(&self, days: u32) -> DayOfWeek {
+        let mut day = *self;
+        for _ in 0..(days % 7) {
+            day = day.next_day();
+        }
+        day
+    }
+}
+
+impl Default for DayOfWeek {
+    fn default() -> Self {
+        DayOfWeek::Monday
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_day_wraps_from_sunday_to_monday() {
+        assert_eq!(DayOfWeek::Sunday.next_day(), DayOfWeek::Monday);
+    }
+
+    #[test]
+    fn only_saturday_and_sunday_are_weekend() {
+        assert!(!DayOfWeek::Friday.is_weekend());
+        assert!(DayOfWeek::Saturday.is_weekend());
+        assert!(DayOfWeek::Sunday.is_weekend());
+    }
+
+    #[test]
+    fn advance_by_wraps_around_multiple_weeks() {
+        assert_eq!(DayOfWeek::Monday.advance_by(8), DayOfWeek::Tuesday);
+    }
+}
@@ -0,0 +1,103 @@
+/*
+ * Epidemic Simulation Using Census Data (ESUCD)
+ * Copyright (c)  2022. Sam Ralph
+ *
+ * This file is part of ESUCD.
+ *
+ * ESUCD is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * ESUCD is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with ESUCD.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::Mutex;
+
+use log::{info, warn};
+use num_format::ToFormattedString;
+
+use crate::config::NUMBER_FORMATTING;
+
+/// Counts occurrences of per-citizen/per-area failures during a build phase (`generate_citizens`,
+/// `build_schools`, `build_workplaces`), so an England-scale run logs one summary line per failure
+/// category instead of a `warn!`/`error!` for every failing Citizen or Output Area
+///
+/// Backed by a `Mutex`, since the build phases record failures from rayon worker threads
+pub struct WarningAggregator {
+    /// If true, `record` also logs the individual failure immediately, in addition to it
+    /// contributing to the aggregated summary - see `config::VERBOSE_BUILD_WARNINGS`
+    verbose: bool,
+    counts: Mutex<HashMap<String, u32>>,
+}
+
+impl WarningAggregator {
+    pub fn new(verbose: bool) -> WarningAggregator {
+        WarningAggregator {
+            verbose,
+            counts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records a single occurrence of the given failure `category`
+    ///
+    /// `detail` describes this specific occurrence (e.g. the failing Citizen's ID), and is only
+    /// logged when `verbose` is enabled - otherwise it is discarded once counted, to avoid
+    /// retaining a detail string per failure on an England-scale run
+    pub fn record(&self, category: &str, detail: impl Display) {
+        if self.verbose {
+            warn!("{}: {}", category, detail);
+        }
+        let mut counts = self.counts.lock().unwrap();
+        *counts.entry(category.to_string()).or_insert(0) += 1;
+    }
+
+    /// Logs one aggregated summary line per distinct failure category recorded since this
+    /// `WarningAggregator` was created
+    pub fn summarise(&self) {
+        let counts = self.counts.lock().unwrap();
+        for (category, count) in counts.iter() {
+            info!(
+                "{}: {} citizens",
+                category,
+                count.to_formatted_string(&NUMBER_FORMATTING)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::warning_aggregator::WarningAggregator;
+
+    #[test]
+    fn identical_failures_aggregate_into_a_single_category() {
+        let aggregator = WarningAggregator::new(false);
+        for index in 0..100 {
+            aggregator.record("Failed to assign workplace", format!("citizen {}", index));
+        }
+        let counts = aggregator.counts.lock().unwrap();
+        assert_eq!(counts.len(), 1, "Expected a single aggregated category");
+        assert_eq!(counts.get("Failed to assign workplace"), Some(&100));
+    }
+
+    #[test]
+    fn distinct_categories_are_counted_separately() {
+        let aggregator = WarningAggregator::new(false);
+        aggregator.record("Failed to assign school to student", "citizen 1");
+        aggregator.record("Failed to assign school to teacher", "citizen 2");
+        aggregator.record("Failed to assign school to student", "citizen 3");
+
+        let counts = aggregator.counts.lock().unwrap();
+        assert_eq!(counts.get("Failed to assign school to student"), Some(&2));
+        assert_eq!(counts.get("Failed to assign school to teacher"), Some(&1));
+    }
+}
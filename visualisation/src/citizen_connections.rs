@@ -126,6 +126,74 @@ pub fn connected_groups(graph: &GraphMap<u128, u8, Undirected>) -> usize {
     petgraph::algo::connected_components(graph)
 }
 
+/// Streams the citizen contact graph (Citizens linked by sharing a building, as in
+/// `build_citizen_graph`) out as GraphML, for analysis in external network tools such as Gephi or
+/// NetworkX
+///
+/// Each node carries `age`, `occupation`, and `area` attributes, and each edge a `building_type`
+/// attribute identifying the kind of building (household, workplace, etc.) that connects the pair.
+/// Unlike `build_citizen_graph`, a pair sharing several buildings gets one edge per building, and
+/// no Citizen is linked to themselves
+///
+/// Writes directly to `filename` as it walks the Output Areas, rather than building the document
+/// in memory first, so exporting a full-scale population doesn't require holding the whole graph
+/// as a string
+pub fn export_citizen_graphml(
+    simulation: &sim::simulator::Simulator,
+    filename: String,
+) -> anyhow::Result<()> {
+    let area_ref = simulation.output_areas.read().unwrap();
+    info!("Creaeting file: {}", filename);
+    let file = File::create(filename.to_string())
+        .context(format!("Failed to create file: {}", filename))?;
+    let mut writer = BufWriter::new(file);
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+    writeln!(writer, r#"<key id="age" for="node" attr.name="age" attr.type="int"/>"#)?;
+    writeln!(writer, r#"<key id="occupation" for="node" attr.name="occupation" attr.type="string"/>"#)?;
+    writeln!(writer, r#"<key id="area" for="node" attr.name="area" attr.type="string"/>"#)?;
+    writeln!(writer, r#"<key id="building_type" for="edge" attr.name="building_type" attr.type="string"/>"#)?;
+    writeln!(writer, r#"<graph id="citizens" edgedefault="undirected">"#)?;
+
+    let mut edge_id = 0;
+    for area in area_ref.iter() {
+        let area = area.lock().unwrap();
+        for citizen in &area.citizens {
+            writeln!(
+                writer,
+                r#"<node id="{}"><data key="age">{}</data><data key="occupation">{:?}</data><data key="area">{}</data></node>"#,
+                citizen.id().uuid_id(),
+                citizen.age,
+                citizen.occupation(),
+                area.id(),
+            )?;
+        }
+        for building in &area.buildings {
+            let occupants = building.occupants();
+            for (index, outer_citizen) in occupants.iter().enumerate() {
+                for inner_citizen in occupants.iter().skip(index + 1) {
+                    writeln!(
+                        writer,
+                        r#"<edge id="e{}" source="{}" target="{}"><data key="building_type">{:?}</data></edge>"#,
+                        edge_id,
+                        outer_citizen.uuid_id(),
+                        inner_citizen.uuid_id(),
+                        building.id().building_type(),
+                    )?;
+                    edge_id += 1;
+                }
+            }
+        }
+    }
+
+    writeln!(writer, "</graph>")?;
+    writeln!(writer, "</graphml>")?;
+    writer.flush().context("Failed to flush GraphML export")?;
+    info!("Dumped to fikle");
+    Ok(())
+}
+
 pub fn draw_graph<T: Copy + Ord + Hash + Debug, U: Copy + Ord + Hash + Debug, V: EdgeType>(
     filename: String,
     graph: GraphMap<T, U, V>,
@@ -141,3 +209,116 @@ pub fn draw_graph<T: Copy + Ord + Hash + Debug, U: Copy + Ord + Hash + Debug, V:
     writer.flush().expect("Failed to flush to file");
     Ok(())
 }
+
+#[cfg(test)]
+mod graphml_export_tests {
+    use std::collections::HashMap;
+    use std::fs;
+
+    use geo_types::{LineString, Polygon};
+    use osm_data::{BuildingBoundaryID, OSMRawBuildings, RawBuilding, TagClassifiedBuilding};
+    use osm_data::polygon_lookup::PolygonContainer;
+    use osm_data::voronoi_generator::Scaling;
+
+    use load_census_data::CensusData;
+    use load_census_data::tables::employment_densities::EmploymentDensities;
+    use sim::models::building::{Building, BuildingID, BuildingType, Workplace};
+    use sim::models::citizen::{Citizen, CitizenID, Occupation, OccupationType};
+    use sim::models::output_area::{OutputArea, OutputAreaID};
+    use sim::simulator::Simulator;
+    use sim::simulator_builder::SimulatorBuilder;
+
+    use crate::citizen_connections::export_citizen_graphml;
+
+    fn square_polygon() -> Polygon<i32> {
+        Polygon::new(
+            LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+            vec![],
+        )
+    }
+
+    /// Builds a `Simulator` with a single Output Area holding two Citizens who share a Workplace,
+    /// suitable for exercising the GraphML exporter against a tiny, known population
+    fn tiny_simulator() -> Simulator {
+        let census_data = CensusData {
+            valid_areas: Default::default(),
+            age_counts: Default::default(),
+            population_counts: Default::default(),
+            occupation_counts: Default::default(),
+            workplace_density: EmploymentDensities {},
+            residents_workplace: Default::default(),
+            imputed_areas: Default::default(),
+        };
+        let osm_data = OSMRawBuildings::from_building_locations(HashMap::new(), HashMap::new(), 100);
+        let output_areas_polygons =
+            PolygonContainer::new(HashMap::new(), Scaling::yorkshire_national_grid(100), 100)
+                .expect("Failed to build an empty polygon container");
+        let mut builder = SimulatorBuilder::new(
+            "test".to_string(),
+            census_data,
+            osm_data,
+            output_areas_polygons,
+        )
+            .expect("Failed to build an empty SimulatorBuilder");
+
+        let output_area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(output_area_id.clone(), square_polygon(), 0.0)
+            .expect("Failed to build test Output Area");
+
+        let workplace_id = BuildingID::new(output_area_id.clone(), BuildingType::Workplace, 0);
+        let raw_building = RawBuilding::new(
+            TagClassifiedBuilding::WorkPlace,
+            &square_polygon(),
+            BuildingBoundaryID::default(),
+        )
+            .expect("Failed to build test workplace");
+        let mut workplace =
+            Workplace::new(workplace_id.clone(), raw_building, OccupationType::Manager, 10);
+
+        for index in 0..2 {
+            let citizen = Citizen::new(
+                CitizenID::from_indexes(index),
+                workplace_id.clone(),
+                workplace_id.clone(),
+                30,
+                Occupation::Normal { occupation: OccupationType::Manager },
+                false,
+                false,
+                false,
+                24,
+            );
+            workplace
+                .add_citizen(citizen.id())
+                .expect("Failed to add Citizen to Workplace");
+            area.citizens.push(citizen);
+            builder
+                .citizen_output_area_lookup
+                .push((output_area_id.clone(), index));
+        }
+        area.buildings.push(Box::new(workplace));
+        builder.output_areas.push(area);
+        builder.output_area_lookup.insert("test".to_string(), 0);
+
+        Simulator::from(builder)
+    }
+
+    /// Exporting a tiny two-Citizen, one-Workplace population should produce a GraphML file with
+    /// exactly two nodes and one edge between them
+    #[test]
+    fn export_produces_expected_node_and_edge_counts() {
+        let simulation = tiny_simulator();
+        let filename = std::env::temp_dir()
+            .join("citizen_connections_graphml_export_test.graphml")
+            .to_str()
+            .expect("Non-UTF8 temp path")
+            .to_string();
+
+        export_citizen_graphml(&simulation, filename.clone()).expect("Failed to export GraphML");
+
+        let contents = fs::read_to_string(&filename).expect("Failed to read exported GraphML");
+        fs::remove_file(&filename).ok();
+
+        assert_eq!(contents.matches("<node ").count(), 2);
+        assert_eq!(contents.matches("<edge ").count(), 1);
+    }
+}
@@ -0,0 +1,151 @@
+/*
+ * Epidemic Simulation Using Census Data (ESUCD)
+ * Copyright (c)  2022. Sam Ralph
+ *
+ * This file is part of ESUCD.
+ *
+ * ESUCD is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * ESUCD is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with ESUCD.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+use std::fs::File;
+
+use serde_json::{json, Value};
+
+use osm_data::convert::northing_and_eastings_to_decimal_latitude_and_longitude;
+use sim::models::building::Building;
+use sim::models::output_area::OutputArea;
+
+use crate::error::{DrawingResult, MyDrawingError};
+
+/// Builds a GeoJSON Point `Feature` for a single Building, with its type, occupant count and
+/// Output Area code as properties
+///
+/// The Building's location is stored as OSGB36 Eastings/Northings, so is converted back to a
+/// WGS84 lat/lon before being written out, as that's what GeoJSON consumers (QGIS, Leaflet) expect
+fn building_to_feature(
+    output_area_code: &str,
+    building: &(dyn Building + Sync + Send),
+) -> Value {
+    let location = building.get_location();
+    let (latitude, longitude) =
+        northing_and_eastings_to_decimal_latitude_and_longitude(location.x(), location.y());
+    json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "Point",
+            "coordinates": [longitude, latitude],
+        },
+        "properties": {
+            "building_type": format!("{:?}", building.id().building_type()),
+            "occupant_count": building.occupants().len(),
+            "output_area": output_area_code,
+        },
+    })
+}
+
+/// Writes every Building across the given Output Areas to `filename`, as a GeoJSON
+/// FeatureCollection of Points - intended for a quick look at the generated synthetic population
+/// in a tool like QGIS or Leaflet, without needing to render a full choropleth
+pub fn export_buildings_as_geojson(
+    filename: &str,
+    output_areas: &[OutputArea],
+) -> DrawingResult<()> {
+    let features: Vec<Value> = output_areas
+        .iter()
+        .flat_map(|output_area| {
+            let output_area_code = output_area.id().code().clone();
+            output_area
+                .buildings
+                .iter()
+                .map(move |building| building_to_feature(&output_area_code, building.as_ref()))
+        })
+        .collect();
+    let feature_collection = json!({
+        "type": "FeatureCollection",
+        "features": features,
+    });
+
+    let file = File::create(filename).map_err(|e| MyDrawingError::Default {
+        message: format!("Failed to create GeoJSON file '{}': {}", filename, e),
+    })?;
+    serde_json::to_writer(file, &feature_collection).map_err(|e| MyDrawingError::Default {
+        message: format!("Failed to write GeoJSON to '{}': {}", filename, e),
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use geo_types::{LineString, Polygon};
+    use osm_data::{BuildingBoundaryID, RawBuilding, TagClassifiedBuilding};
+    use sim::models::building::{Building, BuildingID, BuildingType, Workplace};
+    use sim::models::citizen::{CitizenID, OccupationType};
+    use sim::models::output_area::{OutputArea, OutputAreaID};
+
+    use crate::geojson_export::export_buildings_as_geojson;
+
+    fn square_polygon() -> Polygon<i32> {
+        Polygon::new(
+            LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+            vec![],
+        )
+    }
+
+    /// Exporting a single Output Area holding one occupied Workplace should produce a
+    /// FeatureCollection with exactly one Point Feature, carrying that Workplace's type, occupant
+    /// count and Output Area code
+    #[test]
+    fn export_produces_one_feature_per_building_with_expected_properties() {
+        let output_area_id = OutputAreaID::from_code_and_index("test".to_string(), 0);
+        let mut area = OutputArea::new(output_area_id.clone(), square_polygon(), 0.0)
+            .expect("Failed to build test Output Area");
+
+        let workplace_id = BuildingID::new(output_area_id, BuildingType::Workplace, 0);
+        let raw_building = RawBuilding::new(
+            TagClassifiedBuilding::WorkPlace,
+            &square_polygon(),
+            BuildingBoundaryID::default(),
+        )
+            .expect("Failed to build test workplace");
+        let mut workplace =
+            Workplace::new(workplace_id, raw_building, OccupationType::Manager, 10);
+        workplace
+            .add_citizen(CitizenID::from_indexes(0))
+            .expect("Failed to add Citizen to Workplace");
+        area.buildings.push(Box::new(workplace));
+
+        let filename = std::env::temp_dir()
+            .join("geojson_export_test.geojson")
+            .to_str()
+            .expect("Non-UTF8 temp path")
+            .to_string();
+
+        export_buildings_as_geojson(&filename, &[area]).expect("Failed to export GeoJSON");
+
+        let contents = fs::read_to_string(&filename).expect("Failed to read exported GeoJSON");
+        fs::remove_file(&filename).ok();
+        let geojson: serde_json::Value =
+            serde_json::from_str(&contents).expect("Exported file wasn't valid JSON");
+
+        let features = geojson["features"]
+            .as_array()
+            .expect("Missing features array");
+        assert_eq!(features.len(), 1);
+        assert_eq!(features[0]["geometry"]["type"], "Point");
+        assert_eq!(features[0]["properties"]["building_type"], "Workplace");
+        assert_eq!(features[0]["properties"]["occupant_count"], 1);
+        assert_eq!(features[0]["properties"]["output_area"], "test");
+    }
+}
@@ -22,7 +22,7 @@ use std::collections::HashMap;
 use std::time::Instant;
 
 use geo_types::{Coordinate, Polygon};
-use log::{debug, info};
+use log::{debug, info, warn};
 use plotters::chart::ChartContext;
 use plotters::coord::Shift;
 use plotters::coord::types::RangedCoordi32;
@@ -34,7 +34,10 @@ use plotters::prelude::{
 use plotters::style::TextStyle;
 use polylabel::polylabel;
 
+use osm_data::polygon_lookup::PolygonContainer;
 use osm_data::TagClassifiedBuilding;
+use sim::models::output_area::OutputAreaID;
+use sim::simulator::Simulator;
 
 use crate::{convert_geo_point_to_pixel, GRID_SIZE, PIXEL_SIZE, SCALE};
 use crate::error::DrawingResult;
@@ -172,6 +175,40 @@ impl From<(&String, &Polygon<f64>)> for DrawingRecord {
     }
 }
 
+/// Builds one `DrawingRecord` per entry in `attack_rate_by_area`, with `percentage_highlighting` set
+/// to the attack rate and the polygon looked up from `polygons`
+///
+/// Turns a sim state straight into something `draw_output_areas` can render, rather than the caller
+/// having to zip attack rates and polygons up into `DrawingRecord`s by hand. An Output Area missing
+/// from `polygons` is skipped, with a warning, rather than panicking
+pub fn drawing_records_from_attack_rates(
+    attack_rate_by_area: &HashMap<OutputAreaID, f64>,
+    polygons: &PolygonContainer<String>,
+) -> Vec<DrawingRecord> {
+    attack_rate_by_area
+        .iter()
+        .filter_map(|(area_id, attack_rate)| {
+            let polygon = polygons.polygons.get(area_id.code());
+            if polygon.is_none() {
+                warn!("Output Area {} has no polygon in the lookup, skipping", area_id);
+            }
+            polygon.map(|polygon| {
+                DrawingRecord::from((area_id.code().to_string(), polygon, Some(*attack_rate)))
+            })
+        })
+        .collect()
+}
+
+/// Builds one `DrawingRecord` per Output Area `simulator` knows about - see
+/// `drawing_records_from_attack_rates`, which this delegates to using `simulator`'s current attack
+/// rate by area (the cumulative fraction of each Output Area's population ever infected)
+pub fn drawing_records_from_simulator(
+    simulator: &Simulator,
+    polygons: &PolygonContainer<String>,
+) -> Vec<DrawingRecord> {
+    drawing_records_from_attack_rates(&simulator.attack_rate_by_area(), polygons)
+}
+
 #[allow(dead_code)]
 fn draw_polygon_ring_filled(
     chart: &mut ChartContext<BitMapBackend, Cartesian2d<RangedCoordi32, RangedCoordi32>>,
@@ -363,3 +400,49 @@ pub fn draw_buildings_and_output_areas(
     info!("Finished drawing in {:?}", start_time.elapsed());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use geo_types::LineString;
+
+    use osm_data::polygon_lookup::PolygonContainer;
+    use osm_data::voronoi_generator::Scaling;
+    use sim::models::output_area::OutputAreaID;
+
+    use super::*;
+
+    fn square_polygon() -> Polygon<i32> {
+        Polygon::new(
+            LineString::from(vec![(0, 0), (1, 0), (1, 1), (0, 1), (0, 0)]),
+            vec![],
+        )
+    }
+
+    /// The produced records should cover every Output Area with an attack rate, each carrying its
+    /// polygon from the container and its attack rate as a percentage within `[0, 1]`
+    #[test]
+    fn produced_records_cover_all_areas_with_percentages_in_range() {
+        let areas = [
+            OutputAreaID::from_code_and_index("a".to_string(), 0),
+            OutputAreaID::from_code_and_index("b".to_string(), 0),
+        ];
+        let mut attack_rate_by_area = HashMap::new();
+        attack_rate_by_area.insert(areas[0].clone(), 0.0);
+        attack_rate_by_area.insert(areas[1].clone(), 0.5);
+        let mut polygons = HashMap::new();
+        polygons.insert(areas[0].code().clone(), square_polygon());
+        polygons.insert(areas[1].code().clone(), square_polygon());
+        let polygons = PolygonContainer::new(polygons, Scaling::yorkshire_national_grid(1), 1)
+            .expect("Failed to build a test polygon container");
+
+        let records = drawing_records_from_attack_rates(&attack_rate_by_area, &polygons);
+
+        assert_eq!(records.len(), areas.len());
+        for record in &records {
+            let percentage = record
+                .percentage_highlighting
+                .expect("Every record should have a percentage set");
+            assert!((0.0..=1.0).contains(&percentage));
+        }
+    }
+}
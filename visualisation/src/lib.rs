@@ -24,8 +24,11 @@ use crate::error::{DrawingResult, MyDrawingError};
 
 pub mod citizen_connections;
 pub mod error;
+pub mod geojson_export;
 pub mod image_export;
 #[cfg(feature = "webp")]
+pub mod live_feed;
+#[cfg(feature = "webp")]
 pub mod live_render;
 
 pub const GRID_SIZE: u32 = 700000;
@@ -71,3 +74,85 @@ fn convert_geo_point_to_pixel(coords: Coordinate<f64>) -> DrawingResult<(i32, i3
 
     Ok((coords.0 as i32, coords.1 as i32))
 }
+
+/// The extent of a set of pixels, used to sanity check a projection before drawing it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PixelBoundingBox {
+    pub min: (i32, i32),
+    pub max: (i32, i32),
+}
+
+impl PixelBoundingBox {
+    fn from_points(points: &[(i32, i32)]) -> Option<PixelBoundingBox> {
+        let mut points = points.iter();
+        let first = *points.next()?;
+        let (min, max) = points.fold((first, first), |(min, max), &(x, y)| {
+            ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+        });
+        Some(PixelBoundingBox { min, max })
+    }
+    /// The width and height this bounding box spans, in pixels
+    pub fn spread(&self) -> (i32, i32) {
+        (self.max.0 - self.min.0, self.max.1 - self.min.1)
+    }
+}
+
+/// Converts a slice of `geo_types::Coordinate`s to Pixel Mappings on the GRID
+///
+/// If `minimum_spread` is given, this additionally checks that the converted pixels don't all
+/// collapse into a bounding box narrower than it on either axis - a tell-tale sign of a
+/// mis-configured projection or scaling factor silently mapping distinct real-world points onto
+/// (near-)identical pixels, rather than an outright out-of-range coordinate
+pub fn convert_geo_points_to_pixels(
+    coords: &[Coordinate<f64>],
+    minimum_spread: Option<i32>,
+) -> DrawingResult<Vec<(i32, i32)>> {
+    let points = coords
+        .iter()
+        .map(|coord| convert_geo_point_to_pixel(*coord))
+        .collect::<DrawingResult<Vec<(i32, i32)>>>()?;
+    if let Some(minimum_spread) = minimum_spread {
+        if let Some(bounding_box) = PixelBoundingBox::from_points(&points) {
+            let spread = bounding_box.spread();
+            if spread.0 < minimum_spread || spread.1 < minimum_spread {
+                return Err(MyDrawingError::ConversionError {
+                    message: format!(
+                        "Projected points collapsed to a {}x{} pixel bounding box ({:?} to {:?}), below the minimum expected spread of {} - check the projection/scaling is configured correctly",
+                        spread.0, spread.1, bounding_box.min, bounding_box.max, minimum_spread
+                    ),
+                    value: None,
+                });
+            }
+        }
+    }
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Widely spaced points should pass a strict spread check comfortably
+    #[test]
+    fn well_spread_points_pass_the_minimum_spread_check() {
+        let points = vec![
+            Coordinate::from((0.0, 0.0)),
+            Coordinate::from((100000.0, 100000.0)),
+        ];
+        assert!(convert_geo_points_to_pixels(&points, Some(100)).is_ok());
+    }
+
+    /// Points that are real-world distinct but close enough together to round to the same pixel
+    /// should fail a strict spread check, flagging the collapsed bounding box
+    #[test]
+    fn clustered_points_fail_the_minimum_spread_check() {
+        let points = vec![
+            Coordinate::from((0.0, 0.0)),
+            Coordinate::from((5.0, 5.0)),
+            Coordinate::from((10.0, 10.0)),
+        ];
+        let error = convert_geo_points_to_pixels(&points, Some(5))
+            .expect_err("Clustered points should fail the minimum spread check");
+        assert!(error.to_string().contains("0x0 pixel bounding box"));
+    }
+}
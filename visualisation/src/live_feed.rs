@@ -0,0 +1,203 @@
+/*
+ * Epidemic Simulation Using Census Data (ESUCD)
+ * Copyright (c)  2022. Sam Ralph
+ *
+ * This file is part of ESUCD.
+ *
+ * ESUCD is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, version 3 of the License.
+ *
+ * ESUCD is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with ESUCD.  If not, see <https://www.gnu.org/licenses/>.
+ *
+ */
+
+//! Streams the current infection choropleth as WebP frames over a minimal HTTP endpoint, so the
+//! epidemic can be watched live in a browser during `simulate`, rather than only replayed from
+//! exported timeseries afterwards
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use log::{error, info};
+use plotters::prelude::{BitMapBackend, Color, IntoDrawingArea, WHITE};
+use tiny_http::{Header, Response, Server};
+
+use sim::simulator::Simulator;
+
+use crate::image_export::DrawingRecord;
+use crate::GRID_SIZE;
+
+/// The pixel dimensions of a single live feed frame
+pub const FRAME_WIDTH: u32 = 800;
+pub const FRAME_HEIGHT: u32 = 800;
+
+/// The most recently rendered frame, shared between the simulation loop and the HTTP server thread
+#[derive(Clone, Default)]
+pub struct LatestFrame(Arc<Mutex<Vec<u8>>>);
+
+impl LatestFrame {
+    pub fn new() -> LatestFrame {
+        LatestFrame::default()
+    }
+    fn set(&self, frame: Vec<u8>) {
+        *self.0.lock().expect("Latest frame lock was poisoned") = frame;
+    }
+    fn get(&self) -> Vec<u8> {
+        self.0.lock().expect("Latest frame lock was poisoned").clone()
+    }
+}
+
+/// Renders a set of coloured Output Area outlines into an in-memory RGB pixel buffer of
+/// `FRAME_WIDTH` x `FRAME_HEIGHT`, using `percentage_highlighting` as the infected fraction
+fn render_frame_pixels(data: &[DrawingRecord]) -> anyhow::Result<Vec<u8>> {
+    let mut pixels = vec![255u8; (FRAME_WIDTH * FRAME_HEIGHT * 3) as usize];
+    {
+        let draw_backend =
+            BitMapBackend::with_buffer(&mut pixels, (FRAME_WIDTH, FRAME_HEIGHT)).into_drawing_area();
+        draw_backend.fill(&WHITE)?;
+        for area in data {
+            let infected_fraction = area.percentage_highlighting.unwrap_or(0.0).clamp(0.0, 1.0);
+            let colour = plotters::style::RGBColor(
+                (infected_fraction * 255.0) as u8,
+                ((1.0 - infected_fraction) * 255.0) as u8,
+                0,
+            );
+            let points = area
+                .polygon
+                .exterior()
+                .0
+                .iter()
+                .map(|p| {
+                    (
+                        (p.x / GRID_SIZE as f64 * FRAME_WIDTH as f64) as i32,
+                        (p.y / GRID_SIZE as f64 * FRAME_HEIGHT as f64) as i32,
+                    )
+                })
+                .collect::<Vec<(i32, i32)>>();
+            draw_backend.draw(&plotters::element::Polygon::new(points, colour))?;
+        }
+        draw_backend.present()?;
+    }
+    Ok(pixels)
+}
+
+/// Encodes an RGB pixel buffer of `FRAME_WIDTH` x `FRAME_HEIGHT` as a lossy WebP frame
+fn encode_webp(pixels: &[u8]) -> Vec<u8> {
+    webp_encoder::Encoder::from_rgb(pixels, FRAME_WIDTH, FRAME_HEIGHT)
+        .encode(80.0)
+        .to_vec()
+}
+
+/// Renders the current infection choropleth for `simulator` into an in-memory WebP frame, colouring
+/// each Output Area by its attack rate (the cumulative fraction of its population ever infected)
+pub fn render_frame(simulator: &Simulator) -> anyhow::Result<Vec<u8>> {
+    let attack_rate_by_area = simulator.attack_rate_by_area();
+    let data: Vec<DrawingRecord> = simulator
+        .output_areas
+        .read()
+        .expect("Output Areas lock was poisoned")
+        .iter()
+        .map(|area| {
+            let area = area.lock().expect("Output Area lock was poisoned");
+            let attack_rate = attack_rate_by_area
+                .get(&area.output_area_id)
+                .copied()
+                .unwrap_or(0.0);
+            DrawingRecord::from((
+                area.output_area_id.to_string(),
+                area.polygon.clone(),
+                Some(attack_rate),
+            ))
+        })
+        .collect();
+    let pixels = render_frame_pixels(&data)?;
+    Ok(encode_webp(&pixels))
+}
+
+/// Serves the latest rendered frame at `GET /frame.webp`, for a browser to poll during `simulate`
+///
+/// Runs until the process exits - intended to be spawned on its own thread by `run_with_live_feed`
+fn serve(address: &str, latest_frame: LatestFrame) -> anyhow::Result<()> {
+    let server = Server::http(address).map_err(|error| {
+        anyhow::anyhow!("Failed to bind the live feed server to {}: {}", address, error)
+    })?;
+    info!("Serving the live infection feed at http://{}/frame.webp", address);
+    for request in server.incoming_requests() {
+        let header = Header::from_bytes(&b"Content-Type"[..], &b"image/webp"[..])
+            .expect("Failed to build the Content-Type header");
+        let response = Response::from_data(latest_frame.get()).with_header(header);
+        if let Err(error) = request.respond(response) {
+            error!("Failed to respond to a live feed request: {}", error);
+        }
+    }
+    Ok(())
+}
+
+/// Runs `simulator` to completion via `Simulator::simulate_with_step_hook`, rendering a WebP frame
+/// of the infection choropleth after every time step and serving the latest one over HTTP at
+/// `address` (e.g. `"127.0.0.1:8080"`), so it can be watched live in a browser
+///
+/// A browser polling `GET /frame.webp` is used instead of a push-based WebSocket feed, to keep the
+/// server side of this feature to a single blocking thread. `output_name` is forwarded to
+/// `simulate_with_step_hook` so the usual statistics export still happens once the run finishes
+pub fn run_with_live_feed(
+    mut simulator: Simulator,
+    output_name: String,
+    address: &str,
+) -> anyhow::Result<Simulator> {
+    let latest_frame = LatestFrame::new();
+    {
+        let latest_frame = latest_frame.clone();
+        let address = address.to_string();
+        std::thread::spawn(move || {
+            if let Err(error) = serve(&address, latest_frame) {
+                error!("Live feed server stopped: {:?}", error);
+            }
+        });
+    }
+    simulator
+        .simulate_with_step_hook(output_name, |simulator| match render_frame(simulator) {
+            Ok(frame) => latest_frame.set(frame),
+            Err(error) => error!("Failed to render a live feed frame: {:?}", error),
+        })
+        .context("Failed to run the simulation with the live feed enabled")?;
+    Ok(simulator)
+}
+
+#[cfg(test)]
+mod tests {
+    use geo_types::{LineString, Polygon};
+
+    use super::*;
+
+    /// Rendering a sample Output Area polygon and encoding it should produce a buffer with a valid
+    /// WebP header (`RIFF....WEBP`)
+    #[test]
+    fn render_and_encode_produces_a_valid_webp_frame() {
+        let data = vec![DrawingRecord::from((
+            "test".to_string(),
+            Polygon::new(
+                LineString::from(vec![
+                    (0, 0),
+                    (100000, 0),
+                    (100000, 100000),
+                    (0, 100000),
+                    (0, 0),
+                ]),
+                vec![],
+            ),
+            Some(0.5),
+        ))];
+        let pixels = render_frame_pixels(&data).expect("Failed to render frame");
+        let frame = encode_webp(&pixels);
+        assert_eq!(&frame[0..4], b"RIFF");
+        assert_eq!(&frame[8..12], b"WEBP");
+    }
+}